@@ -0,0 +1,210 @@
+//! Queue that runs configuration changes one at a time.
+//!
+//! Hotkeys, the tray, the scheduler and the UI can all ask for a new layout at
+//! once. Every apply is sent to a single worker task, so displayplacer never
+//! runs twice concurrently. When several applies pile up behind a slow one,
+//! only the newest runs and the others are skipped, since their layouts would
+//! be overwritten immediately anyway. Progress is reported with `apply-status`
+//! events.
+//!
+//! Smaller changes such as toggling or rotating a single display don't go
+//! through the queue, but every displayplacer run that changes the layout takes
+//! the same lock, so they still never overlap a queued apply.
+
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::displayplacer::{apply_layout_now, ApplyVerification};
+use crate::history::HistorySource;
+use crate::system_tray::set_last_apply_failed;
+
+/// Stage of a queued apply, as reported in `apply-status` events.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyStatus {
+    Queued,
+    Running,
+    Applied,
+    Failed,
+    /// Superseded by a newer apply before it started
+    Skipped,
+}
+
+/// What happened to an apply that didn't fail.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyOutcome {
+    Applied {
+        verification: ApplyVerification,
+        /// Undo snapshot of the layout it replaced, if that could be read
+        undo_snapshot: Option<u64>,
+    },
+    Skipped,
+}
+
+/// Payload of the `apply-status` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplyStatusEvent {
+    pub id: String,
+    pub source: HistorySource,
+    pub status: ApplyStatus,
+    pub error: Option<String>,
+}
+
+struct ApplyJob {
+    id: String,
+    config: String,
+    source: HistorySource,
+    reply: oneshot::Sender<Result<ApplyOutcome, String>>,
+}
+
+static QUEUE: OnceLock<mpsc::UnboundedSender<ApplyJob>> = OnceLock::new();
+
+/// Held while displayplacer changes the layout
+static DISPLAYPLACER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Run `f` while no other layout change is in progress.
+pub(crate) fn exclusive<T>(f: impl FnOnce() -> T) -> T {
+    // A panic mid-apply leaves nothing to clean up, so a poisoned lock is still usable
+    let _guard = DISPLAYPLACER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    f()
+}
+
+fn emit_status(app: &AppHandle, job: &ApplyJob, status: ApplyStatus, error: Option<String>) {
+    let _ = app.emit(
+        "apply-status",
+        ApplyStatusEvent {
+            id: job.id.clone(),
+            source: job.source,
+            status,
+            error,
+        },
+    );
+}
+
+/// Start the worker that runs queued applies.
+pub fn start(app: AppHandle) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    if QUEUE.set(tx).is_err() {
+        return;
+    }
+
+    tauri::async_runtime::spawn(run_worker(app, rx));
+}
+
+/// The newest of `job` and the jobs already waiting behind it, and the ones it supersedes.
+fn newest_waiting(
+    mut job: ApplyJob,
+    rx: &mut mpsc::UnboundedReceiver<ApplyJob>,
+) -> (ApplyJob, Vec<ApplyJob>) {
+    let mut superseded = Vec::new();
+    while let Ok(newer) = rx.try_recv() {
+        superseded.push(std::mem::replace(&mut job, newer));
+    }
+    (job, superseded)
+}
+
+/// Run jobs in order, skipping all but the newest of those waiting.
+async fn run_worker(app: AppHandle, mut rx: mpsc::UnboundedReceiver<ApplyJob>) {
+    while let Some(job) = rx.recv().await {
+        let (job, superseded) = newest_waiting(job, &mut rx);
+        for old in superseded {
+            log::info!("[ApplyQueue] Skipping apply {} in favour of {}", old.id, job.id);
+            emit_status(&app, &old, ApplyStatus::Skipped, None);
+            let _ = old.reply.send(Ok(ApplyOutcome::Skipped));
+        }
+
+        emit_status(&app, &job, ApplyStatus::Running, None);
+        let result = apply_layout_now(&app, job.config.clone(), job.source).await;
+        match &result {
+            Ok(_) => emit_status(&app, &job, ApplyStatus::Applied, None),
+            Err(e) => emit_status(&app, &job, ApplyStatus::Failed, Some(e.clone())),
+        }
+        set_last_apply_failed(&app, result.is_err());
+        let _ = job.reply.send(result.map(|(verification, undo_snapshot)| ApplyOutcome::Applied {
+            verification,
+            undo_snapshot,
+        }));
+    }
+}
+
+/// Queue a configuration and wait until it has been applied or skipped.
+///
+/// # Returns
+/// * `Ok(ApplyOutcome)` - The configuration was applied, or skipped for a newer one
+/// * `Err(String)` - Error message if the apply failed or the queue isn't running
+pub(crate) async fn enqueue(app: &AppHandle, config: String, source: HistorySource) -> Result<ApplyOutcome, String> {
+    let queue = QUEUE.get().ok_or("Apply queue is not running")?;
+
+    let (reply, done) = oneshot::channel();
+    let job = ApplyJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        config,
+        source,
+        reply,
+    };
+    emit_status(app, &job, ApplyStatus::Queued, None);
+    queue.send(job).map_err(|_| "Apply queue has stopped".to_string())?;
+
+    done.await.map_err(|_| "Apply queue dropped the request".to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    fn job(id: &str) -> (ApplyJob, oneshot::Receiver<Result<ApplyOutcome, String>>) {
+        let (reply, done) = oneshot::channel();
+        let job = ApplyJob {
+            id: id.to_string(),
+            config: format!("id:{} res:1920x1080 origin:(0,0) degree:0", id),
+            source: HistorySource::Manual,
+            reply,
+        };
+        (job, done)
+    }
+
+    #[test]
+    fn test_newest_waiting() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (first, _) = job("A");
+
+        // Nothing waiting: the job runs as-is
+        let (next, superseded) = newest_waiting(first, &mut rx);
+        assert_eq!(next.id, "A");
+        assert!(superseded.is_empty());
+
+        let (b, _) = job("B");
+        let (c, _) = job("C");
+        tx.send(b).unwrap();
+        tx.send(c).unwrap();
+        let (next, superseded) = newest_waiting(next, &mut rx);
+        assert_eq!(next.id, "C");
+        assert_eq!(superseded.iter().map(|j| j.id.as_str()).collect::<Vec<_>>(), ["A", "B"]);
+    }
+
+    #[test]
+    fn test_exclusive() {
+        let running = Arc::new(AtomicBool::new(false));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let running = running.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        exclusive(|| {
+                            assert!(!running.swap(true, Ordering::SeqCst), "changes overlapped");
+                            std::thread::sleep(std::time::Duration::from_micros(200));
+                            running.store(false, Ordering::SeqCst);
+                        });
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+}
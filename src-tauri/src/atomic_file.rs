@@ -0,0 +1,78 @@
+//! Crash-safe file writes.
+//!
+//! Files are written to a temporary sibling, flushed to disk and then renamed
+//! over the destination, so readers only ever see the old or the new contents.
+//! The previous contents are kept as a `.bak` generation for recovery.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Path of the backup generation kept next to `path` (e.g. `settings.json.bak`).
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Atomically replace `path` with `contents`, keeping the old file as a backup.
+///
+/// # Arguments
+/// * `path` - Destination file
+/// * `contents` - New file contents
+///
+/// # Returns
+/// * `Ok(())` - File replaced successfully
+/// * `Err(String)` - Error message if any step fails; the original file is left intact
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = File::create(&tmp_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+
+    if path.exists() {
+        fs::copy(path, backup_path(path)).map_err(|e| format!("Failed to create backup: {}", e))?;
+    }
+
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace file: {}", e))
+}
+
+/// Move a corrupt file aside so it is preserved for inspection.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Where the corrupt file was moved to
+/// * `Err(String)` - Error message if the file could not be moved
+pub fn quarantine(path: &Path) -> Result<PathBuf, String> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupt-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+    let target = path.with_file_name(name);
+
+    fs::rename(path, &target).map_err(|e| format!("Failed to move corrupt file: {}", e))?;
+
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_keeps_backup() {
+        let dir = std::env::temp_dir().join(format!("dpui-atomic-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+        assert!(!dir.join("settings.json.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
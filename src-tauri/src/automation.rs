@@ -0,0 +1,417 @@
+//! Automation rules: conditions on the desk setup mapped to actions.
+//!
+//! A rule pairs one or more conditions ("3 displays connected", "on AC
+//! power", "lid closed", "Keynote in front") with an action (apply a preset
+//! or workspace, turn off external displays). Rules are evaluated whenever
+//! the display topology, the power source or the frontmost application
+//! changes and fire when all of their conditions become true, not on every
+//! evaluation while they stay true. A rule that already matches when DPUI
+//! starts, or when it is created, waits for the next change.
+//!
+//! Rules are stored in `automation.json` next to the presets file.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::atomic_file::write_atomic;
+use crate::displayplacer::{
+    change_resolution, find_display, get_displays, parse_resolution, turn_off_external_displays, DisplayConfig,
+};
+use crate::frontmost::{self, FrontmostApp};
+use crate::history::HistorySource;
+use crate::kvm;
+use crate::power;
+use crate::presets::{apply_preset_config, get_config_dir};
+use crate::state::AppState;
+use crate::system_tray::show_tray_notification;
+use crate::workspaces::apply_workspace;
+
+/// How a measured value is compared with a rule's value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn compare(self, actual: usize, expected: usize) -> bool {
+        match self {
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+            Self::Lt => actual < expected,
+            Self::Le => actual <= expected,
+            Self::Gt => actual > expected,
+            Self::Ge => actual >= expected,
+        }
+    }
+}
+
+/// Something a rule waits for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// Number of connected displays, enabled or not
+    DisplayCount { op: Comparison, count: usize },
+    /// A display is connected, matched by ID, persistent ID or name
+    DisplayConnected { display: String },
+    /// Running on AC power (`true`) or battery (`false`)
+    PowerSource { ac: bool },
+    /// The lid is closed (`true`) or open (`false`), e.g. to switch to an
+    /// external-only layout when closing the lid at a dock
+    Lid { closed: bool },
+    /// An application is frontmost, matched by bundle ID or name
+    FrontmostApp { app: String },
+}
+
+/// What a rule does when it fires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    ApplyPreset { preset_id: String },
+    ApplyWorkspace { workspace_id: String },
+    DisableExternalDisplays,
+    /// Switch a display to another refresh rate at its current resolution,
+    /// e.g. to save power on battery
+    SetRefreshRate { display_id: String, hz: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// All of these have to hold for the rule to fire
+    pub conditions: Vec<Condition>,
+    pub action: Action,
+    pub created_at: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleStore {
+    pub version: String,
+    pub rules: Vec<Rule>,
+}
+
+impl Default for RuleStore {
+    fn default() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Facts about the current setup that conditions are checked against.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub display_count: usize,
+    /// IDs, persistent IDs and names of the connected displays
+    pub display_keys: Vec<String>,
+    /// `None` if the power source is unknown
+    pub on_ac_power: Option<bool>,
+    /// `None` if the Mac has no lid
+    pub lid_closed: Option<bool>,
+    /// `None` if not known yet
+    pub frontmost_app: Option<FrontmostApp>,
+}
+
+impl Context {
+    fn gather(config: &DisplayConfig) -> Self {
+        let display_keys = config
+            .displays
+            .iter()
+            .flat_map(|d| [Some(&d.id), d.persistent_id.as_ref(), d.name.as_ref()])
+            .flatten()
+            .cloned()
+            .collect();
+
+        Self {
+            display_count: config.displays.len(),
+            display_keys,
+            on_ac_power: power::on_ac_power(),
+            lid_closed: config.lid_closed,
+            frontmost_app: frontmost::current(),
+        }
+    }
+}
+
+impl Condition {
+    fn matches(&self, context: &Context) -> bool {
+        match self {
+            Self::DisplayCount { op, count } => op.compare(context.display_count, *count),
+            Self::DisplayConnected { display } => {
+                context.display_keys.iter().any(|key| key.eq_ignore_ascii_case(display))
+            }
+            Self::PowerSource { ac } => context.on_ac_power == Some(*ac),
+            Self::Lid { closed } => context.lid_closed == Some(*closed),
+            Self::FrontmostApp { app } => context.frontmost_app.as_ref().is_some_and(|f| f.is(app)),
+        }
+    }
+}
+
+impl Rule {
+    fn matches(&self, context: &Context) -> bool {
+        !self.conditions.is_empty() && self.conditions.iter().all(|c| c.matches(context))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Rule name is required".to_string());
+        }
+        if self.conditions.is_empty() {
+            return Err("A rule needs at least one condition".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Whether each rule matched at the last evaluation, keyed by rule ID.
+/// Rules without an entry are recorded without firing.
+static MATCHED: Mutex<Option<HashMap<String, bool>>> = Mutex::new(None);
+
+fn get_rules_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("automation.json"))
+}
+
+/// Read rules from disk.
+pub fn read_rules() -> Result<RuleStore, String> {
+    let path = get_rules_path()?;
+
+    if !path.exists() {
+        return Ok(RuleStore::default());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read rules: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse rules: {}", e))
+}
+
+fn write_rules(store: &RuleStore) -> Result<(), String> {
+    let path = get_rules_path()?;
+
+    let content = serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize rules: {}", e))?;
+
+    write_atomic(&path, content.as_bytes()).map_err(|e| format!("Failed to write rules: {}", e))
+}
+
+/// Forget whether a rule matched, so it waits for the next change before firing.
+fn reset_rule(id: &str) {
+    if let Ok(mut matched) = MATCHED.lock() {
+        if let Some(matched) = matched.as_mut() {
+            matched.remove(id);
+        }
+    }
+}
+
+/// Load automation rules from file
+#[tauri::command]
+pub async fn load_rules() -> Result<RuleStore, String> {
+    read_rules()
+}
+
+/// Add a new automation rule
+///
+/// # Arguments
+/// * `rule` - Rule to add; its ID and creation time are assigned here
+///
+/// # Returns
+/// * `Ok(Rule)` - The stored rule
+/// * `Err(String)` - Error message if the rule is invalid or can't be saved
+#[tauri::command]
+pub async fn add_rule(mut rule: Rule) -> Result<Rule, String> {
+    rule.validate()?;
+    let mut store = read_rules()?;
+
+    rule.id = uuid::Uuid::new_v4().to_string();
+    rule.created_at = chrono::Utc::now().to_rfc3339();
+
+    store.rules.push(rule.clone());
+    write_rules(&store)?;
+
+    log::info!("[Automation] Added rule {}", rule.name);
+    Ok(rule)
+}
+
+/// Replace an existing automation rule
+///
+/// # Arguments
+/// * `rule` - Rule with the ID of the one to replace
+///
+/// # Returns
+/// * `Ok(())` - Rule updated
+/// * `Err(String)` - Error message if the rule is invalid, doesn't exist or can't be saved
+#[tauri::command]
+pub async fn update_rule(rule: Rule) -> Result<(), String> {
+    rule.validate()?;
+    let mut store = read_rules()?;
+    let id = rule.id.clone();
+
+    let existing = store.rules.iter_mut().find(|r| r.id == rule.id).ok_or("Rule not found")?;
+    *existing = Rule {
+        created_at: existing.created_at.clone(),
+        ..rule
+    };
+    write_rules(&store)?;
+
+    reset_rule(&id);
+    Ok(())
+}
+
+/// Delete an automation rule
+#[tauri::command]
+pub async fn delete_rule(id: String) -> Result<(), String> {
+    let mut store = read_rules()?;
+
+    store.rules.retain(|r| r.id != id);
+    write_rules(&store)?;
+
+    reset_rule(&id);
+    Ok(())
+}
+
+/// Run a rule's action.
+async fn run_action(app: &AppHandle, action: &Action) -> Result<String, String> {
+    match action {
+        Action::ApplyPreset { preset_id } => {
+            let preset = app
+                .state::<AppState>()
+                .find_preset(preset_id)
+                .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+            apply_preset_config(app, &preset, HistorySource::Automation).await?;
+            Ok(format!("Applied: {}", preset.name))
+        }
+        Action::ApplyWorkspace { workspace_id } => {
+            let report = apply_workspace(app.clone(), workspace_id.clone()).await?;
+            if !report.ok() {
+                return Err("Workspace applied with errors".to_string());
+            }
+            Ok("Applied workspace".to_string())
+        }
+        Action::DisableExternalDisplays => {
+            let count = turn_off_external_displays(HistorySource::Automation).await?;
+            Ok(format!("Turned off {} external displays", count))
+        }
+        Action::SetRefreshRate { display_id, hz } => {
+            let current = get_displays().await?;
+            let display = find_display(&current.displays, display_id)
+                .ok_or_else(|| format!("Display {} not found", display_id))?;
+            let (width, height) = parse_resolution(&display.resolution)
+                .ok_or_else(|| format!("Unknown resolution of display {}", display_id))?;
+            change_resolution(&display.id, width, height, Some(*hz), HistorySource::Automation).await?;
+            Ok(format!("Set refresh rate to {} Hz", hz))
+        }
+    }
+}
+
+/// Evaluate every enabled rule and run the actions of those that just started matching.
+///
+/// Called after the display topology or power source changed. Held off while
+/// KVM mode expects displays back.
+///
+/// # Arguments
+/// * `app` - Application handle
+/// * `config` - Displays connected now
+pub async fn evaluate(app: &AppHandle, config: &DisplayConfig) {
+    if kvm::is_suppressing() {
+        return;
+    }
+    let store = match read_rules() {
+        Ok(store) => store,
+        Err(e) => {
+            log::error!("[Automation] {}", e);
+            return;
+        }
+    };
+
+    let context = Context::gather(config);
+    let fired: Vec<Rule> = {
+        let Ok(mut matched) = MATCHED.lock() else {
+            return;
+        };
+        let matched = matched.get_or_insert_with(HashMap::new);
+        store
+            .rules
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .filter(|rule| {
+                let now = rule.matches(&context);
+                let before = matched.insert(rule.id.clone(), now);
+                now && before == Some(false)
+            })
+            .collect()
+    };
+
+    // Rules run in the order they are listed, so later ones win
+    for rule in fired {
+        log::info!("[Automation] Rule {} matched, running {:?}", rule.name, rule.action);
+        match run_action(app, &rule.action).await {
+            Ok(message) => show_tray_notification(&rule.name, &message),
+            Err(e) => {
+                log::error!("[Automation] Rule {} failed: {}", rule.name, e);
+                show_tray_notification("DPUI", &format!("Rule {} failed: {}", rule.name, e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_matches() {
+        let context = Context {
+            display_count: 3,
+            display_keys: vec!["AAAA-1".to_string(), "DELL U2720Q".to_string()],
+            on_ac_power: Some(true),
+            lid_closed: Some(true),
+            frontmost_app: Some(FrontmostApp {
+                bundle_id: Some("com.apple.iWork.Keynote".to_string()),
+                name: Some("Keynote".to_string()),
+            }),
+        };
+        let rule = |conditions: Vec<Condition>| Rule {
+            id: "r".to_string(),
+            name: "Rule".to_string(),
+            enabled: true,
+            conditions,
+            action: Action::DisableExternalDisplays,
+            created_at: String::new(),
+        };
+
+        assert!(rule(vec![Condition::DisplayCount { op: Comparison::Eq, count: 3 }]).matches(&context));
+        assert!(!rule(vec![Condition::DisplayCount { op: Comparison::Lt, count: 3 }]).matches(&context));
+        assert!(rule(vec![
+            Condition::DisplayConnected { display: "dell u2720q".to_string() },
+            Condition::PowerSource { ac: true },
+        ])
+        .matches(&context));
+        assert!(!rule(vec![
+            Condition::DisplayCount { op: Comparison::Ge, count: 2 },
+            Condition::PowerSource { ac: false },
+        ])
+        .matches(&context));
+        assert!(rule(vec![Condition::Lid { closed: true }]).matches(&context));
+        assert!(rule(vec![Condition::FrontmostApp { app: "keynote".to_string() }]).matches(&context));
+        assert!(!rule(vec![Condition::FrontmostApp { app: "com.apple.Safari".to_string() }]).matches(&context));
+        assert!(!rule(Vec::new()).matches(&context));
+
+        let json = r#"{"type":"display_count","op":"ge","count":2}"#;
+        let condition: Condition = serde_json::from_str(json).unwrap();
+        assert_eq!(condition, Condition::DisplayCount { op: Comparison::Ge, count: 2 });
+    }
+}
@@ -0,0 +1,141 @@
+//! Brightness of the built-in panel.
+//!
+//! The built-in display has no DDC bus; its backlight is read and set through
+//! the private DisplayServices framework, as the brightness keys do. The
+//! framework is loaded at runtime so DPUI still starts if it ever goes away.
+
+/// A brightness between 0.0 and 1.0 as a percentage.
+fn to_percent(level: f32) -> u16 {
+    (level.clamp(0.0, 1.0) * 100.0).round() as u16
+}
+
+/// Read the built-in display's brightness as a percentage.
+pub(crate) fn get() -> Result<u16, String> {
+    native::brightness().map(to_percent)
+}
+
+/// Set the built-in display's brightness as a percentage.
+pub(crate) fn set(percent: u16) -> Result<(), String> {
+    if percent > 100 {
+        return Err(format!("Invalid value {}; expected 0-100", percent));
+    }
+    native::set_brightness(f32::from(percent) / 100.0)?;
+
+    log::info!("[Backlight] Set built-in brightness to {}%", percent);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::ffi::{c_char, c_void};
+    use std::sync::OnceLock;
+
+    type DisplayId = u32;
+    type GetBrightness = unsafe extern "C" fn(display: DisplayId, brightness: *mut f32) -> i32;
+    type SetBrightness = unsafe extern "C" fn(display: DisplayId, brightness: f32) -> i32;
+
+    const FRAMEWORK: &std::ffi::CStr = c"/System/Library/PrivateFrameworks/DisplayServices.framework/DisplayServices";
+
+    /// `RTLD_LAZY`
+    const LAZY: i32 = 0x1;
+
+    extern "C" {
+        fn dlopen(path: *const c_char, mode: i32) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGGetOnlineDisplayList(max: u32, displays: *mut DisplayId, count: *mut u32) -> i32;
+        fn CGDisplayIsBuiltin(display: DisplayId) -> u32;
+    }
+
+    struct DisplayServices {
+        get: GetBrightness,
+        set: SetBrightness,
+    }
+
+    static SERVICES: OnceLock<Option<DisplayServices>> = OnceLock::new();
+
+    fn services() -> Result<&'static DisplayServices, String> {
+        SERVICES
+            .get_or_init(|| unsafe {
+                let handle = dlopen(FRAMEWORK.as_ptr(), LAZY);
+                if handle.is_null() {
+                    return None;
+                }
+                let get = dlsym(handle, c"DisplayServicesGetBrightness".as_ptr());
+                let set = dlsym(handle, c"DisplayServicesSetBrightness".as_ptr());
+                if get.is_null() || set.is_null() {
+                    return None;
+                }
+                Some(DisplayServices {
+                    get: std::mem::transmute::<*mut c_void, GetBrightness>(get),
+                    set: std::mem::transmute::<*mut c_void, SetBrightness>(set),
+                })
+            })
+            .as_ref()
+            .ok_or_else(|| "DisplayServices is not available".to_string())
+    }
+
+    /// The built-in display's CoreGraphics ID, if it is on.
+    fn builtin_display() -> Result<DisplayId, String> {
+        let mut displays = [0 as DisplayId; 16];
+        let mut count = 0;
+        unsafe {
+            if CGGetOnlineDisplayList(displays.len() as u32, displays.as_mut_ptr(), &mut count) != 0 {
+                return Err("Failed to list displays".to_string());
+            }
+            displays[..count as usize]
+                .iter()
+                .copied()
+                .find(|d| CGDisplayIsBuiltin(*d) != 0)
+                .ok_or_else(|| "The built-in display is not on".to_string())
+        }
+    }
+
+    pub fn brightness() -> Result<f32, String> {
+        let services = services()?;
+        let display = builtin_display()?;
+        let mut level = 0.0;
+        let status = unsafe { (services.get)(display, &mut level) };
+        if status != 0 {
+            return Err(format!("Failed to read brightness ({})", status));
+        }
+        Ok(level)
+    }
+
+    pub fn set_brightness(level: f32) -> Result<(), String> {
+        let services = services()?;
+        let display = builtin_display()?;
+        let status = unsafe { (services.set)(display, level) };
+        if status != 0 {
+            return Err(format!("Failed to set brightness ({})", status));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod native {
+    pub fn brightness() -> Result<f32, String> {
+        Err("Built-in brightness is only available on macOS".to_string())
+    }
+
+    pub fn set_brightness(_level: f32) -> Result<(), String> {
+        Err("Built-in brightness is only available on macOS".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_percent() {
+        assert_eq!(to_percent(0.0), 0);
+        assert_eq!(to_percent(0.666), 67);
+        assert_eq!(to_percent(1.2), 100);
+        assert!(set(101).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Timestamped backups of the presets file.
+//!
+//! Every time presets are saved, the previous file is copied into `backups/`
+//! in the config directory as `presets-<timestamp>.json`, keeping the newest
+//! `MAX_BACKUPS`. Backups stay local even when presets are stored in a synced
+//! folder, and any of them can be restored from the UI.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::presets::{get_config_dir, parse_store, PresetStore};
+use crate::state::AppState;
+
+/// Number of backups kept; older ones are deleted
+const MAX_BACKUPS: usize = 10;
+
+const BACKUP_PREFIX: &str = "presets-";
+const BACKUP_EXTENSION: &str = ".json";
+
+/// A backup of the presets file.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetBackup {
+    /// File name, used to restore the backup
+    pub name: String,
+    /// When the backup was taken (RFC 3339)
+    pub created_at: Option<String>,
+    /// Number of presets in the backup, if it can be read
+    pub preset_count: Option<usize>,
+}
+
+/// Get the backups directory, creating it if needed
+pub(crate) fn get_backups_dir() -> Result<PathBuf, String> {
+    let dir = get_config_dir()?.join("backups");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Names of all backups, newest first.
+fn backup_names(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read backups directory: {}", e))?;
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_EXTENSION))
+        .collect();
+    // Timestamps are zero-padded, so names sort chronologically
+    names.sort_by(|a, b| b.cmp(a));
+
+    Ok(names)
+}
+
+/// Copy the current presets file into the backups directory.
+///
+/// Does nothing if `path` doesn't exist yet. Old backups beyond `MAX_BACKUPS`
+/// are removed.
+pub(crate) fn backup_presets(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let dir = get_backups_dir()?;
+    let name = format!(
+        "{}{}{}",
+        BACKUP_PREFIX,
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+        BACKUP_EXTENSION
+    );
+    fs::copy(path, dir.join(&name)).map_err(|e| format!("Failed to back up presets: {}", e))?;
+
+    for old in backup_names(&dir)?.iter().skip(MAX_BACKUPS) {
+        if let Err(e) = fs::remove_file(dir.join(old)) {
+            log::error!("[Backups] Failed to remove {}: {}", old, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the timestamp out of a backup file name.
+fn backup_time(name: &str) -> Option<String> {
+    let stamp = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(BACKUP_EXTENSION)?;
+    chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%S%.3fZ")
+        .ok()
+        .map(|t| t.and_utc().to_rfc3339())
+}
+
+/// List backups of the presets file
+///
+/// # Returns
+/// * `Ok(Vec<PresetBackup>)` - Available backups, newest first
+/// * `Err(String)` - Error message if the backups directory cannot be read
+#[tauri::command]
+pub async fn list_presets_backups() -> Result<Vec<PresetBackup>, String> {
+    let dir = get_backups_dir()?;
+
+    Ok(backup_names(&dir)?
+        .into_iter()
+        .map(|name| {
+            let preset_count = fs::read_to_string(dir.join(&name))
+                .ok()
+                .and_then(|content| parse_store(&content).ok())
+                .map(|(store, _)| store.presets.len());
+            PresetBackup {
+                created_at: backup_time(&name),
+                preset_count,
+                name,
+            }
+        })
+        .collect())
+}
+
+/// Replace the presets with a backup
+///
+/// The presets being replaced are backed up first, so a restore can itself be undone.
+///
+/// # Arguments
+/// * `name` - File name of the backup, as returned by `list_presets_backups`
+///
+/// # Returns
+/// * `Ok(PresetStore)` - The restored presets
+/// * `Err(String)` - Error message if the backup doesn't exist, can't be parsed or can't be written
+#[tauri::command]
+pub async fn restore_presets_backup(state: State<'_, AppState>, name: String) -> Result<PresetStore, String> {
+    let is_backup_name = name.starts_with(BACKUP_PREFIX)
+        && name.ends_with(BACKUP_EXTENSION)
+        && !name.contains(['/', '\\'])
+        && !name.contains("..");
+    if !is_backup_name {
+        return Err(format!("Invalid backup name: {}", name));
+    }
+
+    let path = get_backups_dir()?.join(&name);
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read backup {}: {}", name, e))?;
+    let (store, _) = parse_store(&content)?;
+
+    state.update_presets(|presets| {
+        *presets = store.clone();
+        Ok(())
+    })?;
+    log::info!("[Backups] Restored presets from {}", name);
+
+    Ok(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_time() {
+        assert_eq!(
+            backup_time("presets-20261015T181500.123Z.json").as_deref(),
+            Some("2026-10-15T18:15:00.123+00:00")
+        );
+        assert_eq!(backup_time("presets.json"), None);
+    }
+}
@@ -0,0 +1,5 @@
+//! Command-line companion to DPUI; see `tauri_app_lib::cli`.
+
+fn main() {
+    std::process::exit(tauri_app_lib::cli::main())
+}
@@ -0,0 +1,423 @@
+//! `dpui-cli`: DPUI from the terminal.
+//!
+//! ```text
+//! dpui-cli list                 Connected displays and saved presets
+//! dpui-cli list --json          The same as JSON, with `dpui://` links
+//! dpui-cli list --alfred        The same as Alfred Script Filter items
+//! dpui-cli layout               Current layout as a displayplacer command
+//! dpui-cli apply <preset>       Apply a preset by ID or name
+//! dpui-cli snapshot <name>      Save the current layout as a preset
+//! dpui-cli toggle <display>     Turn a display on or off
+//! dpui-cli agent install        Run DPUI headless at login
+//! dpui-cli agent uninstall      Stop running DPUI headless at login
+//! ```
+//!
+//! While DPUI is running, commands go through its control socket if that is
+//! enabled, or else are handed to it as `dpui://` URLs, so its history, tray
+//! and in-memory presets stay in step. Otherwise the CLI works on its own
+//! with the same backend code and files. Without the socket, `list`
+//! and `layout` only read and so always run standalone.
+//!
+//! The JSON formats are meant for launchers such as Raycast and Alfred: each
+//! preset and display carries the `dpui://` URL that triggers it, so an
+//! extension can list, fuzzy-search and run them without calling the CLI again.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+
+use crate::displayplacer::{
+    execute_displayplacer, layout_command, read_displays_uncached, toggle_display, tokenize_config, Display,
+};
+use crate::headless;
+use crate::history::{self, HistorySource};
+use crate::paths;
+use crate::presets::{active_preset, find_preset_by_key, Preset};
+use crate::socket::{self, SocketMethod};
+use crate::storage;
+use crate::url_scheme::UrlCommand;
+
+const USAGE: &str = "Usage: dpui-cli <command>
+
+Commands:
+  list [--json|--alfred]
+                       Connected displays and saved presets
+  layout               Current layout as a displayplacer command
+  apply <preset>       Apply a preset by ID or name
+  snapshot <name>      Save the current layout as a preset
+  toggle <display>     Turn a display on or off
+  agent install        Run DPUI headless at login
+  agent uninstall      Stop running DPUI headless at login";
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq)]
+enum CliCommand {
+    List(ListFormat),
+    Layout,
+    Help,
+    /// Install (`true`) or remove the headless launchd agent
+    Agent(bool),
+    /// A change that the running app can carry out
+    Change(UrlCommand),
+}
+
+/// How `list` prints its result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListFormat {
+    Text,
+    /// Displays and presets with their `dpui://` URLs
+    Json,
+    /// Alfred Script Filter items
+    Alfred,
+}
+
+fn parse_args(args: &[String]) -> Result<CliCommand, String> {
+    let target = || {
+        let target = args[1..].join(" ");
+        if target.trim().is_empty() {
+            Err(format!("{} needs an argument\n\n{}", args[0], USAGE))
+        } else {
+            Ok(target)
+        }
+    };
+
+    match args.first().map(String::as_str) {
+        None | Some("help" | "-h" | "--help") => Ok(CliCommand::Help),
+        Some("list" | "ls") => match args[1..].iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [] => Ok(CliCommand::List(ListFormat::Text)),
+            ["--json"] => Ok(CliCommand::List(ListFormat::Json)),
+            ["--alfred"] => Ok(CliCommand::List(ListFormat::Alfred)),
+            _ => Err(format!("list takes --json or --alfred\n\n{}", USAGE)),
+        },
+        Some("layout") => Ok(CliCommand::Layout),
+        Some("apply") => Ok(CliCommand::Change(UrlCommand::Apply(target()?))),
+        Some("snapshot") => Ok(CliCommand::Change(UrlCommand::Snapshot(target()?))),
+        Some("toggle") => Ok(CliCommand::Change(UrlCommand::Toggle(target()?))),
+        Some("agent") => match args.get(1).map(String::as_str) {
+            Some("install") => Ok(CliCommand::Agent(true)),
+            Some("uninstall") => Ok(CliCommand::Agent(false)),
+            _ => Err(format!("agent takes install or uninstall\n\n{}", USAGE)),
+        },
+        Some(other) => Err(format!("Unknown command \"{}\"\n\n{}", other, USAGE)),
+    }
+}
+
+/// Whether the DPUI app is running, asked without launching it.
+fn app_is_running() -> bool {
+    let script = format!("application id \"{}\" is running", paths::BUNDLE_IDENTIFIER);
+    Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+        .unwrap_or(false)
+}
+
+/// Hand a change to the running app in the background.
+fn send_to_app(command: &UrlCommand) -> Result<(), String> {
+    let status = Command::new("open")
+        .args(["-g", &command.to_url()])
+        .status()
+        .map_err(|e| format!("Failed to execute open: {}", e))?;
+    if !status.success() {
+        return Err("DPUI did not accept the command".to_string());
+    }
+    Ok(())
+}
+
+/// A saved preset as `list` shows it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PresetEntry {
+    id: String,
+    name: String,
+    #[serde(default)]
+    folder: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// What `list` shows, read from the app or from disk.
+struct Listing {
+    displays: Vec<Display>,
+    presets: Vec<PresetEntry>,
+    active_preset_id: Option<String>,
+}
+
+impl Listing {
+    fn print_text(&self) {
+        println!("Displays:");
+        for display in &self.displays {
+            println!(
+                "  {}  {}  {}{}  origin:({},{})  {}",
+                display.id,
+                display.name.as_deref().unwrap_or("Unknown display"),
+                display.resolution,
+                display.hz.map(|hz| format!("@{}Hz", hz)).unwrap_or_default(),
+                display.origin.0,
+                display.origin.1,
+                if display.enabled { "on" } else { "off" }
+            );
+        }
+
+        println!("Presets:");
+        for preset in &self.presets {
+            let active = if self.is_active(preset) { "  (active)" } else { "" };
+            println!("  {}  {}{}", preset.id, preset.name, active);
+        }
+    }
+
+    fn is_active(&self, preset: &PresetEntry) -> bool {
+        self.active_preset_id.as_deref() == Some(preset.id.as_str())
+    }
+
+    fn to_json(&self) -> Value {
+        let displays: Vec<Value> = self
+            .displays
+            .iter()
+            .map(|d| {
+                json!({
+                    "id": d.id,
+                    "name": d.name,
+                    "resolution": d.resolution,
+                    "hz": d.hz,
+                    "origin": [d.origin.0, d.origin.1],
+                    "enabled": d.enabled,
+                    "url": UrlCommand::Toggle(d.id.clone()).to_url(),
+                })
+            })
+            .collect();
+        let presets: Vec<Value> = self
+            .presets
+            .iter()
+            .map(|p| {
+                json!({
+                    "id": p.id,
+                    "name": p.name,
+                    "folder": p.folder,
+                    "tags": p.tags,
+                    "active": self.is_active(p),
+                    "url": UrlCommand::Apply(p.id.clone()).to_url(),
+                })
+            })
+            .collect();
+        json!({ "displays": displays, "presets": presets, "active_preset_id": self.active_preset_id })
+    }
+
+    /// Script Filter items: presets to apply, then displays to toggle.
+    ///
+    /// `match` lets Alfred's own fuzzy filter search folders and tags too.
+    fn to_alfred(&self) -> Value {
+        let presets = self.presets.iter().map(|p| {
+            let mut words = vec![p.name.clone()];
+            words.extend(p.folder.clone());
+            words.extend(p.tags.iter().cloned());
+            json!({
+                "uid": format!("preset:{}", p.id),
+                "title": p.name,
+                "subtitle": if self.is_active(p) { "Active preset" } else { "Apply preset" },
+                "arg": UrlCommand::Apply(p.id.clone()).to_url(),
+                "match": words.join(" "),
+                "autocomplete": p.name,
+            })
+        });
+        let displays = self.displays.iter().map(|d| {
+            let name = d.name.clone().unwrap_or_else(|| d.id.clone());
+            json!({
+                "uid": format!("display:{}", d.id),
+                "title": name,
+                "subtitle": if d.enabled { "Turn display off" } else { "Turn display on" },
+                "arg": UrlCommand::Toggle(d.id.clone()).to_url(),
+                "match": name,
+            })
+        });
+        json!({ "items": presets.chain(displays).collect::<Vec<_>>() })
+    }
+
+    fn print(&self, format: ListFormat) {
+        match format {
+            ListFormat::Text => self.print_text(),
+            ListFormat::Json => println!("{}", self.to_json()),
+            ListFormat::Alfred => println!("{}", self.to_alfred()),
+        }
+    }
+}
+
+fn list() -> Result<Listing, String> {
+    let config = read_displays_uncached()?;
+    let store = storage::backend().load_presets()?;
+
+    Ok(Listing {
+        active_preset_id: active_preset(&store, &config).map(|p| p.id.clone()),
+        displays: config.displays,
+        presets: store
+            .presets
+            .into_iter()
+            .map(|p| PresetEntry {
+                id: p.id,
+                name: p.name,
+                folder: p.folder,
+                tags: p.tags,
+            })
+            .collect(),
+    })
+}
+
+fn layout() -> Result<(), String> {
+    println!("{}", layout_command(&read_displays_uncached()?));
+    Ok(())
+}
+
+fn apply(key: &str) -> Result<(), String> {
+    let store = storage::backend().load_presets()?;
+    let preset = find_preset_by_key(&store, key).ok_or_else(|| format!("Preset {} not found", key))?;
+
+    let result = execute_displayplacer(tokenize_config(&preset.config)?).map(|_| ());
+    history::record(HistorySource::External, &preset.config, &result);
+    result?;
+
+    println!("Applied {}", preset.name);
+    Ok(())
+}
+
+fn snapshot(name: &str) -> Result<(), String> {
+    let current = read_displays_uncached()?;
+    if current.displays.is_empty() {
+        return Err("No displays found".to_string());
+    }
+
+    let mut store = storage::backend().load_presets()?;
+    let mut preset = Preset::new(name.to_string(), layout_command(&current));
+    preset.sort_order = store.presets.iter().map(|p| p.sort_order + 1).max().unwrap_or(0);
+    store.presets.push(preset.clone());
+    storage::backend().save_presets(&store, true)?;
+
+    println!("Saved {} ({})", preset.name, preset.id);
+    Ok(())
+}
+
+fn toggle(display_id: &str) -> Result<(), String> {
+    let enabled = tauri::async_runtime::block_on(toggle_display(display_id, HistorySource::External))?;
+    println!("Turned display {} {}", display_id, if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/// Install or remove the launchd agent.
+///
+/// A freshly installed agent starts DPUI right away unless it is already running.
+fn agent(install: bool) -> Result<(), String> {
+    if install {
+        let path = headless::install_agent(&headless::app_executable()?, !app_is_running())?;
+        println!("Installed {}", path.display());
+    } else {
+        headless::uninstall_agent(true)?;
+        println!("Removed the launch agent");
+    }
+    Ok(())
+}
+
+/// Run a command in the app through its control socket.
+fn run_via_socket(stream: &mut UnixStream, command: CliCommand) -> Result<(), String> {
+    match command {
+        CliCommand::Help | CliCommand::Agent(_) => {}
+        CliCommand::List(format) => {
+            let displays = socket::call(stream, SocketMethod::ListDisplays, Value::Null)?;
+            let presets = socket::call(stream, SocketMethod::ListPresets, Value::Null)?;
+            let status = socket::call(stream, SocketMethod::GetStatus, Value::Null)?;
+
+            let listing = Listing {
+                displays: serde_json::from_value(displays).map_err(|e| format!("Invalid display list: {}", e))?,
+                presets: serde_json::from_value(presets).map_err(|e| format!("Invalid preset list: {}", e))?,
+                active_preset_id: status["active_preset"]["id"].as_str().map(str::to_string),
+            };
+            listing.print(format);
+        }
+        CliCommand::Layout => {
+            let layout = socket::call(stream, SocketMethod::GetLayout, Value::Null)?;
+            println!("{}", layout["command"].as_str().unwrap_or_default());
+        }
+        CliCommand::Change(UrlCommand::Apply(key)) => {
+            socket::call(stream, SocketMethod::ApplyPreset, json!({ "preset": key }))?;
+            println!("Applied {}", key);
+        }
+        CliCommand::Change(UrlCommand::Snapshot(name)) => {
+            let preset = socket::call(stream, SocketMethod::Snapshot, json!({ "name": name }))?;
+            println!("Saved {} ({})", name, preset["id"].as_str().unwrap_or_default());
+        }
+        CliCommand::Change(UrlCommand::Toggle(display_id)) => {
+            let result = socket::call(stream, SocketMethod::ToggleDisplay, json!({ "display": display_id }))?;
+            let enabled = result["enabled"].as_bool().unwrap_or_default();
+            println!("Turned display {} {}", display_id, if enabled { "on" } else { "off" });
+        }
+    }
+    Ok(())
+}
+
+fn run(command: CliCommand) -> Result<(), String> {
+    if command == CliCommand::Help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    // The agent is managed from outside the app
+    if let CliCommand::Agent(install) = command {
+        return agent(install);
+    }
+    if let Some(mut stream) = socket::connect() {
+        return run_via_socket(&mut stream, command);
+    }
+
+    match command {
+        CliCommand::Help | CliCommand::Agent(_) => Ok(()),
+        CliCommand::List(format) => {
+            list()?.print(format);
+            Ok(())
+        }
+        CliCommand::Layout => layout(),
+        CliCommand::Change(change) if app_is_running() => {
+            send_to_app(&change)?;
+            println!("Sent to DPUI");
+            Ok(())
+        }
+        CliCommand::Change(UrlCommand::Apply(key)) => apply(&key),
+        CliCommand::Change(UrlCommand::Snapshot(name)) => snapshot(&name),
+        CliCommand::Change(UrlCommand::Toggle(display_id)) => toggle(&display_id),
+    }
+}
+
+/// Entry point of `dpui-cli`; returns the process exit code.
+pub fn main() -> i32 {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = parse_args(&args).and_then(|command| {
+        paths::init_standalone()?;
+        run(command)
+    });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("dpui-cli: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(parse_args(&args(&[])), Ok(CliCommand::Help));
+        assert_eq!(parse_args(&args(&["list"])), Ok(CliCommand::List(ListFormat::Text)));
+        assert_eq!(parse_args(&args(&["ls", "--alfred"])), Ok(CliCommand::List(ListFormat::Alfred)));
+        assert!(parse_args(&args(&["list", "--yaml"])).is_err());
+        assert_eq!(
+            parse_args(&args(&["apply", "Home", "Office"])),
+            Ok(CliCommand::Change(UrlCommand::Apply("Home Office".to_string())))
+        );
+        assert_eq!(parse_args(&args(&["agent", "install"])), Ok(CliCommand::Agent(true)));
+        assert!(parse_args(&args(&["toggle"])).is_err());
+        assert!(parse_args(&args(&["rotate", "1"])).is_err());
+    }
+}
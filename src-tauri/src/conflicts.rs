@@ -0,0 +1,211 @@
+//! Pre-flight conflict checks for network and IPC integrations.
+//!
+//! Before an integration binds a port or socket, these checks look for the
+//! usual culprits — another process on the port, a stale or live socket file,
+//! or a second DPUI instance — and turn them into actionable diagnostics
+//! instead of a generic bind error buried in stderr.
+
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::process::Command;
+
+/// Integration that is about to be enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationKind {
+    /// Local HTTP server (needs a free TCP port)
+    Http,
+    /// Unix domain socket server (needs a free socket path)
+    Socket,
+    /// MQTT bridge (needs a unique client, i.e. no second instance)
+    Mqtt,
+    /// Remote control server (needs a free TCP port)
+    Remote,
+}
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The integration cannot start until this is fixed
+    Error,
+    /// The integration can start but something looks off
+    Warning,
+}
+
+/// One finding from a conflict check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// What was found
+    pub message: String,
+    /// What the user can do about it
+    pub suggestion: String,
+}
+
+/// Result of checking an integration for conflicts.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictReport {
+    /// True if there are no error-level diagnostics
+    pub ok: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ConflictReport {
+    fn new(diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            ok: !diagnostics.iter().any(|d| d.severity == Severity::Error),
+            diagnostics,
+        }
+    }
+
+    /// Turn error-level diagnostics into a single error message.
+    pub fn into_result(self) -> Result<(), String> {
+        if self.ok {
+            return Ok(());
+        }
+        Err(self
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .map(|d| format!("{} {}", d.message, d.suggestion))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// Check whether a TCP port on localhost can be bound.
+fn check_tcp_port(port: u16) -> Option<Diagnostic> {
+    match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(_) => None,
+        Err(e) if e.kind() == ErrorKind::AddrInUse => {
+            let owner = port_owner(port).map(|o| format!(" by {}", o)).unwrap_or_default();
+            Some(Diagnostic {
+                severity: Severity::Error,
+                message: format!("Port {} is already in use{}.", port, owner),
+                suggestion: "Quit the other program or choose a different port in settings.".to_string(),
+            })
+        }
+        Err(e) => Some(Diagnostic {
+            severity: Severity::Error,
+            message: format!("Port {} cannot be bound: {}.", port, e),
+            suggestion: "Choose a port above 1024 that isn't blocked by a firewall.".to_string(),
+        }),
+    }
+}
+
+/// Name and PID of the process listening on a TCP port, via `lsof`.
+fn port_owner(port: u16) -> Option<String> {
+    let output = Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fpc"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let pid = stdout.lines().find_map(|l| l.strip_prefix('p'))?;
+    let name = stdout.lines().find_map(|l| l.strip_prefix('c')).unwrap_or("unknown");
+    Some(format!("{} (pid {})", name, pid))
+}
+
+/// Check whether a unix socket path is free to bind.
+fn check_socket_path(path: &Path) -> Option<Diagnostic> {
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            return Some(Diagnostic {
+                severity: Severity::Error,
+                message: format!("Socket directory {} does not exist.", parent.display()),
+                suggestion: "Create the directory or choose a different socket path.".to_string(),
+            });
+        }
+    }
+
+    if !path.exists() {
+        return None;
+    }
+
+    match UnixStream::connect(path) {
+        Ok(_) => Some(Diagnostic {
+            severity: Severity::Error,
+            message: format!("Socket {} is in use by a running process.", path.display()),
+            suggestion: "Quit the other DPUI instance or choose a different socket path.".to_string(),
+        }),
+        Err(_) => Some(Diagnostic {
+            severity: Severity::Warning,
+            message: format!("Stale socket file found at {}.", path.display()),
+            suggestion: "It will be removed when the socket server starts.".to_string(),
+        }),
+    }
+}
+
+/// PIDs of other running DPUI processes.
+fn other_instances() -> Vec<u32> {
+    let Some(name) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().to_string()))
+    else {
+        return Vec::new();
+    };
+
+    let Ok(output) = Command::new("pgrep").args(["-x", &name]).output() else {
+        return Vec::new();
+    };
+
+    let own_pid = std::process::id();
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.trim().parse().ok())
+        .filter(|pid| *pid != own_pid)
+        .collect()
+}
+
+/// Check an integration for conflicts before enabling it.
+///
+/// # Arguments
+/// * `kind` - Integration about to be enabled
+/// * `port` - TCP port for HTTP or the remote control server
+/// * `socket_path` - Socket path for the socket server
+pub fn check(kind: IntegrationKind, port: Option<u16>, socket_path: Option<&Path>) -> ConflictReport {
+    let mut diagnostics = Vec::new();
+
+    let others = other_instances();
+    if !others.is_empty() {
+        diagnostics.push(Diagnostic {
+            // A second instance would share the MQTT client ID and fight over topics
+            severity: if kind == IntegrationKind::Mqtt { Severity::Error } else { Severity::Warning },
+            message: format!(
+                "Another DPUI instance is running (pid {}).",
+                others.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            suggestion: "Quit other instances, including ones in other user sessions.".to_string(),
+        });
+    }
+
+    match kind {
+        IntegrationKind::Http | IntegrationKind::Remote => diagnostics.extend(port.and_then(check_tcp_port)),
+        IntegrationKind::Socket => diagnostics.extend(socket_path.and_then(check_socket_path)),
+        IntegrationKind::Mqtt => {}
+    }
+
+    ConflictReport::new(diagnostics)
+}
+
+/// Check an integration for conflicts without enabling it.
+///
+/// # Arguments
+/// * `kind` - "http", "socket", "mqtt" or "remote"
+/// * `port` - TCP port to check (HTTP)
+/// * `socket_path` - Socket path to check (socket server)
+///
+/// # Returns
+/// * `ConflictReport` - Diagnostics with suggested fixes
+#[tauri::command]
+pub async fn check_integration_conflicts(
+    kind: IntegrationKind,
+    port: Option<u16>,
+    socket_path: Option<String>,
+) -> ConflictReport {
+    check(kind, port, socket_path.as_deref().map(Path::new))
+}
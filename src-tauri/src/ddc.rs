@@ -0,0 +1,638 @@
+//! Control of external monitors over DDC/CI.
+//!
+//! Monitors expose settings such as brightness and volume as VCP codes that can be read
+//! and written over the I2C lines of the video cable. On Apple Silicon Macs
+//! the I2C bus of each external display is reached through its
+//! `DCPAVServiceProxy` IOKit service; that service is matched to a display by
+//! the EDID serial number of the `AppleCLCD2` service on the same display
+//! pipe. Intel Macs are not supported; the built-in panel's brightness is set
+//! through `backlight` instead.
+//!
+//! Presets can carry a brightness, speaker volume and input source per
+//! display, set once the layout is applied.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::backlight;
+use crate::displayplacer::{find_display, get_displays, run_blocking, Display};
+use crate::presets::Preset;
+
+/// I2C address of the DDC/CI interface of a monitor
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const DDC_ADDRESS: u32 = 0x37;
+
+/// Sub-address DDC/CI messages from the host are written to
+const HOST_ADDRESS: u8 = 0x51;
+
+/// Destination address the checksum of messages to the monitor starts from
+const MONITOR_WRITE_ADDRESS: u8 = 0x6E;
+
+/// Destination address the checksum of replies from the monitor starts from
+const HOST_READ_ADDRESS: u8 = 0x50;
+
+/// VCP code of the luminance control
+pub const VCP_BRIGHTNESS: u8 = 0x10;
+
+/// VCP code of the input source select
+pub const VCP_INPUT_SOURCE: u8 = 0x60;
+
+/// VCP code of the speaker volume
+pub const VCP_VOLUME: u8 = 0x62;
+
+/// VCP code of the audio mute, 1 muted and 2 unmuted
+pub const VCP_MUTE: u8 = 0x8D;
+
+/// How long a monitor may take to prepare a reply, per the DDC/CI spec
+const REPLY_DELAY: Duration = Duration::from_millis(40);
+
+/// Attempts at reading a VCP value; monitors drop requests now and then
+const READ_ATTEMPTS: usize = 3;
+
+/// Held during each DDC transaction so requests and replies don't interleave
+static DDC_LOCK: Mutex<()> = Mutex::new(());
+
+/// A monitor input that can be selected over DDC/CI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputSource {
+    Hdmi1,
+    Hdmi2,
+    Dp1,
+    Dp2,
+    Usbc,
+}
+
+impl InputSource {
+    /// Value of the input source select VCP code.
+    ///
+    /// USB-C has no MCCS value; most monitors with a USB-C input use 0x1B.
+    fn vcp_value(self) -> u16 {
+        match self {
+            InputSource::Dp1 => 0x0F,
+            InputSource::Dp2 => 0x10,
+            InputSource::Hdmi1 => 0x11,
+            InputSource::Hdmi2 => 0x12,
+            InputSource::Usbc => 0x1B,
+        }
+    }
+}
+
+fn checksum(start: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(start, |acc, b| acc ^ b)
+}
+
+/// "Set VCP Feature" message, without the sub-address.
+fn set_vcp_message(code: u8, value: u16) -> [u8; 6] {
+    let [high, low] = value.to_be_bytes();
+    let mut message = [0x84, 0x03, code, high, low, 0];
+    message[5] = checksum(MONITOR_WRITE_ADDRESS ^ HOST_ADDRESS, &message[..5]);
+    message
+}
+
+/// "Get VCP Feature" request, without the sub-address.
+fn get_vcp_message(code: u8) -> [u8; 4] {
+    let mut message = [0x82, 0x01, code, 0];
+    message[3] = checksum(MONITOR_WRITE_ADDRESS ^ HOST_ADDRESS, &message[..3]);
+    message
+}
+
+/// Current and maximum value from a "Get VCP Feature" reply.
+fn parse_vcp_reply(code: u8, reply: &[u8]) -> Result<(u16, u16), String> {
+    if reply.len() < 11 || reply[2] != 0x02 || reply[4] != code {
+        return Err(format!("Unexpected reply to VCP request 0x{:02X}", code));
+    }
+    if checksum(HOST_READ_ADDRESS, &reply[..10]) != reply[10] {
+        return Err(format!("Corrupt reply to VCP request 0x{:02X}", code));
+    }
+    if reply[3] != 0 {
+        return Err(format!("Monitor does not support VCP code 0x{:02X}", code));
+    }
+
+    let max = u16::from_be_bytes([reply[6], reply[7]]);
+    let current = u16::from_be_bytes([reply[8], reply[9]]);
+    Ok((current, max))
+}
+
+/// The display pipe part of an IOKit service path, e.g. `.../dispext0@A0000000`.
+///
+/// A display's `AppleCLCD2` and `DCPAVServiceProxy` services share it.
+fn display_pipe(path: &str) -> Option<&str> {
+    let mut end = 0;
+    for component in path.split('/') {
+        end += component.len() + 1;
+        if component.starts_with("disp") {
+            return Some(&path[..end - 1]);
+        }
+    }
+    None
+}
+
+/// The AV service carrying the DDC bus of a display.
+fn service_for(display: &Display) -> Result<av::AvService, String> {
+    if display.builtin {
+        return Err("The built-in display cannot be controlled over DDC".to_string());
+    }
+
+    let mut services = av::external_services();
+    let serial = display
+        .serial_id
+        .as_deref()
+        .and_then(|s| s.trim_start_matches('s').parse::<u32>().ok());
+    let monitor_path = serial.and_then(|serial| {
+        av::monitor_serials()
+            .into_iter()
+            .find(|(_, s)| *s == serial)
+            .map(|(path, _)| path)
+    });
+
+    let position = match monitor_path.as_deref().and_then(display_pipe) {
+        Some(pipe) => services.iter().position(|(path, _)| display_pipe(path) == Some(pipe)),
+        // Without a serial to go by, the only external display must be this one
+        None if services.len() == 1 => Some(0),
+        None => None,
+    };
+    position
+        .map(|i| services.swap_remove(i).1)
+        .ok_or_else(|| format!("No DDC connection found for display {}", display.id))
+}
+
+/// Read a VCP value from a display; returns the current and maximum value.
+pub(crate) fn read_vcp(display: &Display, code: u8) -> Result<(u16, u16), String> {
+    let service = service_for(display)?;
+    let _guard = DDC_LOCK.lock().map_err(|_| "DDC lock poisoned".to_string())?;
+
+    let mut last_error = String::new();
+    for _ in 0..READ_ATTEMPTS {
+        service.write(HOST_ADDRESS, &get_vcp_message(code))?;
+        thread::sleep(REPLY_DELAY);
+
+        let mut reply = [0u8; 12];
+        match service.read(HOST_ADDRESS, &mut reply).and_then(|_| parse_vcp_reply(code, &reply)) {
+            Ok(value) => return Ok(value),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Write a VCP value to a display.
+pub(crate) fn write_vcp(display: &Display, code: u8, value: u16) -> Result<(), String> {
+    let service = service_for(display)?;
+    let _guard = DDC_LOCK.lock().map_err(|_| "DDC lock poisoned".to_string())?;
+
+    service.write(HOST_ADDRESS, &set_vcp_message(code, value))?;
+    // Give the monitor time to act before the next message
+    thread::sleep(REPLY_DELAY);
+    Ok(())
+}
+
+/// Read a VCP value as a percentage of its maximum.
+fn read_percent(display: &Display, code: u8) -> Result<u16, String> {
+    let (current, max) = read_vcp(display, code)?;
+    Ok((u32::from(current) * 100 / u32::from(max.max(1))).min(100) as u16)
+}
+
+/// Write a VCP value as a percentage of its maximum.
+fn write_percent(display: &Display, code: u8, percent: u16) -> Result<(), String> {
+    if percent > 100 {
+        return Err(format!("Invalid value {}; expected 0-100", percent));
+    }
+    // Nearly every monitor uses 100 as the maximum
+    let max = read_vcp(display, code).map_or(100, |(_, max)| max.max(1));
+    let value = (u32::from(percent) * u32::from(max) / 100) as u16;
+
+    write_vcp(display, code, value)
+}
+
+/// Set a display's brightness as a percentage of its maximum.
+///
+/// The built-in panel is set natively, external monitors over DDC/CI.
+fn set_brightness_percent(display: &Display, percent: u16) -> Result<(), String> {
+    if display.builtin {
+        return backlight::set(percent);
+    }
+    write_percent(display, VCP_BRIGHTNESS, percent)?;
+    log::info!("[DDC] Set brightness of {} to {}%", display.id, percent);
+    Ok(())
+}
+
+/// Set the volume of a display's speakers as a percentage of their maximum.
+fn set_volume_percent(display: &Display, percent: u16) -> Result<(), String> {
+    write_percent(display, VCP_VOLUME, percent)?;
+    log::info!("[DDC] Set volume of {} to {}%", display.id, percent);
+    Ok(())
+}
+
+/// Switch a display to another input.
+///
+/// The monitor usually goes dark for this Mac, so the switch can't be confirmed.
+fn switch_input(display: &Display, source: InputSource) -> Result<(), String> {
+    write_vcp(display, VCP_INPUT_SOURCE, source.vcp_value())?;
+    log::info!("[DDC] Switched {} to input {:?}", display.id, source);
+    Ok(())
+}
+
+/// Set the brightness, volume and input sources a preset specifies, once its layout is applied.
+///
+/// Inputs are switched last, since a monitor switched to another computer
+/// stops answering. Displays that are not connected are skipped. Failures are
+/// logged and do not stop the remaining displays.
+pub(crate) async fn apply_preset(preset: &Preset) {
+    if preset.brightness.is_empty() && preset.volume.is_empty() && preset.input_sources.is_empty() {
+        return;
+    }
+    let displays = match get_displays().await {
+        Ok(config) => config.displays,
+        Err(e) => {
+            log::error!("[DDC] Failed to read displays: {}", e);
+            return;
+        }
+    };
+
+    let connected = |id: &String| find_display(&displays, id).cloned();
+    let brightness: Vec<(Display, u16)> = preset
+        .brightness
+        .iter()
+        .filter_map(|(id, percent)| Some((connected(id)?, *percent)))
+        .collect();
+    let volume: Vec<(Display, u16)> = preset
+        .volume
+        .iter()
+        .filter_map(|(id, percent)| Some((connected(id)?, *percent)))
+        .collect();
+    let inputs: Vec<(Display, InputSource)> = preset
+        .input_sources
+        .iter()
+        .filter_map(|(id, source)| Some((connected(id)?, *source)))
+        .collect();
+
+    let result = run_blocking(move || {
+        for (display, percent) in brightness {
+            if let Err(e) = set_brightness_percent(&display, percent) {
+                log::error!("[DDC] Failed to set brightness of {}: {}", display.id, e);
+            }
+        }
+        for (display, percent) in volume {
+            if let Err(e) = set_volume_percent(&display, percent) {
+                log::error!("[DDC] Failed to set volume of {}: {}", display.id, e);
+            }
+        }
+        for (display, source) in inputs {
+            if let Err(e) = switch_input(&display, source) {
+                log::error!("[DDC] Failed to switch input of {}: {}", display.id, e);
+            }
+        }
+        Ok(())
+    })
+    .await;
+    if let Err(e) = result {
+        log::error!("[DDC] {}", e);
+    }
+}
+
+async fn connected_display(display_id: &str) -> Result<Display, String> {
+    let config = get_displays().await?;
+    find_display(&config.displays, display_id)
+        .cloned()
+        .ok_or_else(|| format!("Display {} is not connected", display_id))
+}
+
+/// Read a display's brightness
+///
+/// External monitors are read over DDC/CI and the built-in panel natively.
+///
+/// # Arguments
+/// * `display_id` - Display identifier (persistent, contextual or serial screen ID)
+///
+/// # Returns
+/// * `Ok(u16)` - Brightness as a percentage of the display's maximum
+/// * `Err(String)` - Error message if the display isn't connected or doesn't answer over DDC
+#[tauri::command]
+pub async fn get_brightness(display_id: String) -> Result<u16, String> {
+    let display = connected_display(&display_id).await?;
+    if display.builtin {
+        return backlight::get();
+    }
+    run_blocking(move || read_percent(&display, VCP_BRIGHTNESS)).await
+}
+
+/// Set a display's brightness
+///
+/// External monitors are set over DDC/CI and the built-in panel natively.
+///
+/// # Arguments
+/// * `display_id` - Display identifier (persistent, contextual or serial screen ID)
+/// * `value` - Brightness as a percentage, 0-100
+///
+/// # Returns
+/// * `Ok(())` - Brightness sent to the monitor
+/// * `Err(String)` - Error message if the value is out of range, the display isn't
+///   connected or it can't be reached over DDC
+#[tauri::command]
+pub async fn set_brightness(display_id: String, value: u16) -> Result<(), String> {
+    let display = connected_display(&display_id).await?;
+    run_blocking(move || set_brightness_percent(&display, value)).await
+}
+
+/// Read the volume of an external monitor's speakers over DDC/CI
+///
+/// # Arguments
+/// * `display_id` - Display identifier (persistent, contextual or serial screen ID)
+///
+/// # Returns
+/// * `Ok(u16)` - Volume as a percentage of the monitor's maximum
+/// * `Err(String)` - Error message if the display isn't connected or has no speakers it controls over DDC
+#[tauri::command]
+pub async fn get_volume(display_id: String) -> Result<u16, String> {
+    let display = connected_display(&display_id).await?;
+    run_blocking(move || read_percent(&display, VCP_VOLUME)).await
+}
+
+/// Set the volume of an external monitor's speakers over DDC/CI
+///
+/// # Arguments
+/// * `display_id` - Display identifier (persistent, contextual or serial screen ID)
+/// * `value` - Volume as a percentage, 0-100
+///
+/// # Returns
+/// * `Ok(())` - Volume sent to the monitor
+/// * `Err(String)` - Error message if the value is out of range, the display isn't
+///   connected or it can't be reached over DDC
+#[tauri::command]
+pub async fn set_volume(display_id: String, value: u16) -> Result<(), String> {
+    let display = connected_display(&display_id).await?;
+    run_blocking(move || set_volume_percent(&display, value)).await
+}
+
+/// Mute or unmute an external monitor's speakers over DDC/CI
+///
+/// # Arguments
+/// * `display_id` - Display identifier (persistent, contextual or serial screen ID)
+/// * `muted` - `true` to mute, `false` to unmute
+///
+/// # Returns
+/// * `Ok(())` - Mute state sent to the monitor
+/// * `Err(String)` - Error message if the display isn't connected or can't be reached over DDC
+#[tauri::command]
+pub async fn set_muted(display_id: String, muted: bool) -> Result<(), String> {
+    let display = connected_display(&display_id).await?;
+    run_blocking(move || {
+        write_vcp(&display, VCP_MUTE, if muted { 1 } else { 2 })?;
+        log::info!("[DDC] {} {}", if muted { "Muted" } else { "Unmuted" }, display.id);
+        Ok(())
+    })
+    .await
+}
+
+/// Switch an external monitor to another input over DDC/CI
+///
+/// Together with a preset this works as a software KVM: the Mac gives up the
+/// monitor to a computer on another input.
+///
+/// # Arguments
+/// * `display_id` - Display identifier (persistent, contextual or serial screen ID)
+/// * `source` - Input to switch to: `hdmi1`, `hdmi2`, `dp1`, `dp2` or `usbc`
+///
+/// # Returns
+/// * `Ok(())` - Input switch sent to the monitor
+/// * `Err(String)` - Error message if the display isn't connected or can't be reached over DDC
+#[tauri::command]
+pub async fn set_input_source(display_id: String, source: InputSource) -> Result<(), String> {
+    let display = connected_display(&display_id).await?;
+    run_blocking(move || switch_input(&display, source)).await
+}
+
+#[cfg(target_os = "macos")]
+mod av {
+    use std::ffi::{c_char, c_void, CStr};
+
+    type CFTypeRef = *const c_void;
+    type IoObject = u32;
+
+    /// `kIOMainPortDefault`
+    const MAIN_PORT_DEFAULT: u32 = 0;
+
+    /// `kCFStringEncodingUTF8`
+    const UTF8_ENCODING: u32 = 0x0800_0100;
+
+    /// `kCFNumberSInt64Type`
+    const SINT64_TYPE: isize = 4;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> CFTypeRef;
+        fn IOServiceGetMatchingServices(main_port: u32, matching: CFTypeRef, iterator: *mut IoObject) -> i32;
+        fn IOIteratorNext(iterator: IoObject) -> IoObject;
+        fn IOObjectRelease(object: IoObject) -> i32;
+        fn IORegistryEntryGetPath(entry: IoObject, plane: *const c_char, path: *mut c_char) -> i32;
+        fn IORegistryEntryCreateCFProperty(entry: IoObject, key: CFTypeRef, allocator: CFTypeRef, options: u32)
+            -> CFTypeRef;
+        fn IOAVServiceCreateWithService(allocator: CFTypeRef, service: IoObject) -> CFTypeRef;
+        fn IOAVServiceReadI2C(service: CFTypeRef, chip: u32, offset: u32, buffer: *mut u8, size: u32) -> i32;
+        fn IOAVServiceWriteI2C(service: CFTypeRef, chip: u32, offset: u32, buffer: *const u8, size: u32) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(allocator: CFTypeRef, string: *const c_char, encoding: u32) -> CFTypeRef;
+        fn CFStringGetCString(string: CFTypeRef, buffer: *mut c_char, size: isize, encoding: u32) -> bool;
+        fn CFDictionaryGetValue(dictionary: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+        fn CFNumberGetValue(number: CFTypeRef, number_type: isize, value: *mut c_void) -> bool;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    /// An `IOAVService`, released when dropped.
+    pub struct AvService(CFTypeRef);
+
+    impl AvService {
+        pub fn write(&self, offset: u8, data: &[u8]) -> Result<(), String> {
+            let status = unsafe {
+                IOAVServiceWriteI2C(self.0, super::DDC_ADDRESS, offset.into(), data.as_ptr(), data.len() as u32)
+            };
+            if status != 0 {
+                return Err(format!("I2C write failed ({:#x})", status));
+            }
+            Ok(())
+        }
+
+        pub fn read(&self, offset: u8, buffer: &mut [u8]) -> Result<(), String> {
+            let status = unsafe {
+                IOAVServiceReadI2C(self.0, super::DDC_ADDRESS, offset.into(), buffer.as_mut_ptr(), buffer.len() as u32)
+            };
+            if status != 0 {
+                return Err(format!("I2C read failed ({:#x})", status));
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for AvService {
+        fn drop(&mut self) {
+            unsafe { CFRelease(self.0) };
+        }
+    }
+
+    fn cf_string(s: &CStr) -> CFTypeRef {
+        unsafe { CFStringCreateWithCString(std::ptr::null(), s.as_ptr(), UTF8_ENCODING) }
+    }
+
+    /// Call `f` with each service of an IOKit class and its registry path.
+    fn for_each_service(class: &CStr, mut f: impl FnMut(IoObject, String)) {
+        unsafe {
+            let mut iterator: IoObject = 0;
+            if IOServiceGetMatchingServices(MAIN_PORT_DEFAULT, IOServiceMatching(class.as_ptr()), &mut iterator) != 0 {
+                return;
+            }
+            loop {
+                let service = IOIteratorNext(iterator);
+                if service == 0 {
+                    break;
+                }
+                let mut path = [0 as c_char; 512];
+                if IORegistryEntryGetPath(service, c"IOService".as_ptr(), path.as_mut_ptr()) == 0 {
+                    f(service, CStr::from_ptr(path.as_ptr()).to_string_lossy().into_owned());
+                }
+                IOObjectRelease(service);
+            }
+            IOObjectRelease(iterator);
+        }
+    }
+
+    /// A string property of a service.
+    fn string_property(service: IoObject, key: &CStr) -> Option<String> {
+        unsafe {
+            let key = cf_string(key);
+            let value = IORegistryEntryCreateCFProperty(service, key, std::ptr::null(), 0);
+            CFRelease(key);
+            if value.is_null() {
+                return None;
+            }
+            let mut buffer = [0 as c_char; 64];
+            let ok = CFStringGetCString(value, buffer.as_mut_ptr(), buffer.len() as isize, UTF8_ENCODING);
+            CFRelease(value);
+            ok.then(|| CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned())
+        }
+    }
+
+    /// `DisplayAttributes.ProductAttributes.SerialNumber` of an `AppleCLCD2` service.
+    fn edid_serial(service: IoObject) -> Option<u32> {
+        unsafe {
+            let attributes_key = cf_string(c"DisplayAttributes");
+            let attributes = IORegistryEntryCreateCFProperty(service, attributes_key, std::ptr::null(), 0);
+            CFRelease(attributes_key);
+            if attributes.is_null() {
+                return None;
+            }
+
+            let product_key = cf_string(c"ProductAttributes");
+            let serial_key = cf_string(c"SerialNumber");
+            let product = CFDictionaryGetValue(attributes, product_key);
+            let number = if product.is_null() {
+                std::ptr::null()
+            } else {
+                CFDictionaryGetValue(product, serial_key)
+            };
+            let mut serial: i64 = 0;
+            let ok = !number.is_null() && CFNumberGetValue(number, SINT64_TYPE, &mut serial as *mut i64 as *mut c_void);
+            CFRelease(serial_key);
+            CFRelease(product_key);
+            CFRelease(attributes);
+
+            ok.then(|| u32::try_from(serial).ok()).flatten()
+        }
+    }
+
+    /// AV services of external displays, with their registry paths.
+    pub fn external_services() -> Vec<(String, AvService)> {
+        let mut services = Vec::new();
+        for_each_service(c"DCPAVServiceProxy", |service, path| {
+            if string_property(service, c"Location").as_deref() != Some("External") {
+                return;
+            }
+            let av = unsafe { IOAVServiceCreateWithService(std::ptr::null(), service) };
+            if !av.is_null() {
+                services.push((path, AvService(av)));
+            }
+        });
+        services
+    }
+
+    /// Registry paths of connected monitors with their EDID serial numbers.
+    pub fn monitor_serials() -> Vec<(String, u32)> {
+        let mut monitors = Vec::new();
+        for_each_service(c"AppleCLCD2", |service, path| {
+            if let Some(serial) = edid_serial(service) {
+                monitors.push((path, serial));
+            }
+        });
+        monitors
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod av {
+    pub struct AvService;
+
+    impl AvService {
+        pub fn write(&self, _offset: u8, _data: &[u8]) -> Result<(), String> {
+            Err("DDC is only available on macOS".to_string())
+        }
+
+        pub fn read(&self, _offset: u8, _buffer: &mut [u8]) -> Result<(), String> {
+            Err("DDC is only available on macOS".to_string())
+        }
+    }
+
+    pub fn external_services() -> Vec<(String, AvService)> {
+        Vec::new()
+    }
+
+    pub fn monitor_serials() -> Vec<(String, u32)> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vcp_messages() {
+        assert_eq!(set_vcp_message(VCP_BRIGHTNESS, 70), [0x84, 0x03, 0x10, 0x00, 0x46, 0xEE]);
+        assert_eq!(get_vcp_message(VCP_BRIGHTNESS), [0x82, 0x01, 0x10, 0xAC]);
+
+        let mut reply = [0x6E, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x46, 0x00, 0x00];
+        reply[10] = checksum(HOST_READ_ADDRESS, &reply[..10]);
+        assert_eq!(parse_vcp_reply(VCP_BRIGHTNESS, &reply), Ok((70, 100)));
+
+        // Wrong code, unsupported code and a corrupt checksum
+        assert!(parse_vcp_reply(0x60, &reply).is_err());
+        let mut unsupported = reply;
+        unsupported[3] = 0x01;
+        unsupported[10] = checksum(HOST_READ_ADDRESS, &unsupported[..10]);
+        assert!(parse_vcp_reply(VCP_BRIGHTNESS, &unsupported).is_err());
+        reply[10] ^= 0xFF;
+        assert!(parse_vcp_reply(VCP_BRIGHTNESS, &reply).is_err());
+    }
+
+    #[test]
+    fn test_input_source() {
+        let source: InputSource = serde_json::from_str("\"hdmi2\"").unwrap();
+        assert_eq!(source, InputSource::Hdmi2);
+        assert_eq!(source.vcp_value(), 0x12);
+        assert_eq!(serde_json::to_string(&InputSource::Usbc).unwrap(), "\"usbc\"");
+        assert_eq!(set_vcp_message(VCP_INPUT_SOURCE, InputSource::Dp1.vcp_value())[..5], [0x84, 0x03, 0x60, 0x00, 0x0F]);
+    }
+
+    #[test]
+    fn test_display_pipe() {
+        let proxy = "IOService:/AppleARMPE/arm-io@10F00000/AppleT600xIO/dispext0@A0000000/AppleDCPLinkService/DCPAVServiceProxy";
+        let clcd = "IOService:/AppleARMPE/arm-io@10F00000/AppleT600xIO/dispext0@A0000000/IOMobileFramebufferShim/AppleCLCD2";
+        assert_eq!(display_pipe(proxy), Some("IOService:/AppleARMPE/arm-io@10F00000/AppleT600xIO/dispext0@A0000000"));
+        assert_eq!(display_pipe(proxy), display_pipe(clcd));
+        assert_eq!(display_pipe("IOService:/AppleARMPE/arm-io@10F00000"), None);
+    }
+}
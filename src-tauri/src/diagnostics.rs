@@ -0,0 +1,193 @@
+//! Diagnostics report for bug reports.
+//!
+//! `run_diagnostics` collects everything needed to tell why DPUI can't see or
+//! change displays — whether displayplacer runs and its output parses, how
+//! many displays macOS and displayplacer each report, whether the shortcut
+//! plugin is up and whether the config directory is writable — into one
+//! structured report that can be pasted into an issue.
+
+use serde::Serialize;
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_global_shortcut::GlobalShortcut;
+
+use crate::displayplacer::{
+    displayplacer_binary, displayplacer_version, read_displays_uncached, run_blocking, run_with_timeout,
+};
+use crate::{hotkeys, logging, paths, settings};
+
+/// Structured result of `run_diagnostics`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    /// macOS version from `sw_vers`
+    pub os_version: Option<String>,
+    pub displayplacer_found: bool,
+    /// Binary that is run, from settings, PATH or Homebrew
+    pub displayplacer_path: Option<String>,
+    /// Version reported by `displayplacer --version`
+    pub displayplacer_version: Option<String>,
+    /// Whether `displayplacer list` output could be parsed
+    pub output_parseable: bool,
+    /// Why `displayplacer list` failed or couldn't be parsed
+    pub list_error: Option<String>,
+    /// Displays reported by `displayplacer list`, including disabled ones
+    pub cli_display_count: Option<usize>,
+    /// Active displays reported by macOS
+    pub native_display_count: Option<usize>,
+    pub global_shortcut_active: bool,
+    pub hotkeys_enabled: bool,
+    /// Bindings DPUI knows about
+    pub registered_hotkeys: usize,
+    /// Bindings actually registered with the system
+    pub active_hotkeys: usize,
+    pub config_dir: Option<String>,
+    pub config_dir_writable: bool,
+    pub logs_dir: Option<String>,
+    /// Human-readable list of everything that looks wrong
+    pub problems: Vec<String>,
+}
+
+/// First line of a command's stdout, if it ran successfully within the command timeout.
+fn command_line(program: &str, args: &[&str]) -> Option<String> {
+    let timeout = Duration::from_secs(settings::current().command_timeout_secs);
+    let output = run_with_timeout(program, Command::new(program).args(args), timeout).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.trim().to_string())
+}
+
+/// Check the config directory accepts new files by writing and removing one.
+fn check_config_dir_writable() -> (Option<String>, bool) {
+    let Ok(dir) = paths::config_dir() else {
+        return (None, false);
+    };
+    let probe = dir.join(".diagnostics-write-test");
+    let writable = fs::write(&probe, b"ok").is_ok();
+    let _ = fs::remove_file(&probe);
+
+    (Some(dir.display().to_string()), writable)
+}
+
+/// Describe everything in the report that looks wrong.
+fn find_problems(report: &DiagnosticsReport) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !report.displayplacer_found {
+        problems.push("displayplacer is not installed".to_string());
+    } else if !report.output_parseable {
+        problems.push(format!(
+            "displayplacer list failed: {}",
+            report.list_error.as_deref().unwrap_or("unknown error")
+        ));
+    }
+    if let (Some(cli), Some(native)) = (report.cli_display_count, report.native_display_count) {
+        // displayplacer also lists disabled displays, so it may report more
+        if cli < native {
+            problems.push(format!("macOS reports {} displays but displayplacer only {}", native, cli));
+        }
+    }
+    if !report.global_shortcut_active {
+        problems.push("Global shortcut plugin is not running".to_string());
+    } else if report.hotkeys_enabled && report.active_hotkeys < report.registered_hotkeys {
+        problems.push(format!(
+            "{} of {} hotkeys are not registered with the system",
+            report.registered_hotkeys - report.active_hotkeys,
+            report.registered_hotkeys
+        ));
+    }
+    if !report.config_dir_writable {
+        problems.push("Config directory is not writable".to_string());
+    }
+
+    problems
+}
+
+/// Collect a diagnostics report for bug reports
+///
+/// # Arguments
+/// * `app` - Application handle
+///
+/// # Returns
+/// * `Ok(DiagnosticsReport)` - What was checked and any problems found
+/// * `Err(String)` - Error message if the checks couldn't run
+#[tauri::command]
+pub async fn run_diagnostics(app: AppHandle) -> Result<DiagnosticsReport, String> {
+    let mut report = run_blocking(|| {
+        let binary = displayplacer_binary().ok();
+        let mut report = DiagnosticsReport {
+            os_version: command_line("sw_vers", &["-productVersion"]),
+            displayplacer_found: binary.is_some(),
+            displayplacer_path: binary.as_ref().map(|path| path.display().to_string()),
+            displayplacer_version: binary
+                .and(displayplacer_version())
+                .map(|version| format!("{}.{}.{}", version.0, version.1, version.2)),
+            ..Default::default()
+        };
+
+        match read_displays_uncached() {
+            Ok(config) => {
+                report.output_parseable = true;
+                report.cli_display_count = Some(config.displays.len());
+            }
+            Err(e) => report.list_error = Some(e),
+        }
+
+        (report.config_dir, report.config_dir_writable) = check_config_dir_writable();
+        report.logs_dir = logging::log_dir().ok().map(|dir| dir.display().to_string());
+        Ok(report)
+    })
+    .await?;
+
+    report.app_version = app.package_info().version.to_string();
+    report.native_display_count = app.available_monitors().ok().map(|monitors| monitors.len());
+
+    report.global_shortcut_active = app.try_state::<GlobalShortcut<Wry>>().is_some();
+    report.hotkeys_enabled = hotkeys::is_enabled();
+    let bindings = hotkeys::get_registered_hotkeys().await;
+    report.registered_hotkeys = bindings.len();
+    if report.global_shortcut_active {
+        report.active_hotkeys = bindings.iter().filter(|b| hotkeys::is_active(&app, &b.shortcut)).count();
+    }
+
+    report.problems = find_problems(&report);
+    log::info!("[Diagnostics] {} problem(s) found", report.problems.len());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_problems() {
+        let healthy = DiagnosticsReport {
+            displayplacer_found: true,
+            output_parseable: true,
+            cli_display_count: Some(3),
+            native_display_count: Some(2),
+            global_shortcut_active: true,
+            hotkeys_enabled: true,
+            registered_hotkeys: 2,
+            active_hotkeys: 2,
+            config_dir_writable: true,
+            ..Default::default()
+        };
+        assert!(find_problems(&healthy).is_empty());
+
+        let broken = DiagnosticsReport {
+            cli_display_count: Some(1),
+            active_hotkeys: 1,
+            config_dir_writable: false,
+            ..healthy.clone()
+        };
+        assert_eq!(find_problems(&broken).len(), 3);
+
+        let missing = DiagnosticsReport { displayplacer_found: false, ..healthy };
+        assert_eq!(find_problems(&missing), vec!["displayplacer is not installed"]);
+    }
+}
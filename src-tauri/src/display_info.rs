@@ -0,0 +1,239 @@
+//! Human-readable monitor names from IOKit/EDID.
+//!
+//! displayplacer identifies screens by UUID, which means nothing to users.
+//! This module reads each monitor's EDID product attributes from the IOKit
+//! registry (via `ioreg`) and matches them to displayplacer screens by serial
+//! number, filling in `Display::name` and `Display::vendor`.
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::displayplacer::{find_section, run_with_timeout, Display, ScreenSection};
+use crate::settings;
+use crate::trace::{self, CommandTrace};
+
+/// EDID-derived information about one connected monitor.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonitorInfo {
+    /// Product name (e.g. "DELL U2720Q")
+    pub name: Option<String>,
+    /// Three-letter PNP manufacturer ID (e.g. "DEL")
+    pub manufacturer_id: Option<String>,
+    /// EDID serial number, matching displayplacer's serial screen ID
+    pub serial: Option<u32>,
+}
+
+/// IOKit classes that carry EDID attributes (Apple Silicon and Intel respectively)
+const IOREG_CLASSES: [&str; 2] = ["AppleCLCD2", "IODisplayConnect"];
+
+/// Query IOKit for all connected monitors.
+///
+/// Failures are logged and result in an empty list; names are a nicety and
+/// must never prevent displays from being listed. `ioreg` is killed after
+/// `command_timeout_secs` like displayplacer.
+pub fn query_monitors() -> Vec<MonitorInfo> {
+    let mut monitors = Vec::new();
+    let timeout = Duration::from_secs(settings::current().command_timeout_secs);
+
+    for class in IOREG_CLASSES {
+        let args: Vec<String> = ["-l", "-w0", "-r", "-c", class].iter().map(|a| a.to_string()).collect();
+        let mut trace = CommandTrace::start("ioreg", &args);
+
+        match run_with_timeout("ioreg", Command::new("ioreg").args(&args), timeout) {
+            Ok(output) => {
+                trace.finish(&output);
+                monitors.extend(parse_ioreg_output(&String::from_utf8_lossy(&output.stdout)));
+            }
+            Err(e) => {
+                log::error!("[DisplayInfo] {}", e);
+                trace.fail(&e);
+            }
+        }
+        trace::record(trace);
+
+        if !monitors.is_empty() {
+            break;
+        }
+    }
+
+    monitors
+}
+
+/// Fill in `name` and `vendor` for each display.
+///
+/// Displays are matched to monitors by serial number. The built-in panel is
+/// named from its screen type, and if exactly one external display and one
+/// monitor remain unmatched they are paired.
+///
+/// # Arguments
+/// * `displays` - Displays parsed from displayplacer output
+/// * `sections` - Per-screen sections keyed by lowercase persistent ID
+/// * `notes` - Receives a description of each matching decision
+pub fn resolve_names(displays: &mut [Display], sections: &HashMap<String, ScreenSection>, notes: &mut Vec<String>) {
+    let mut monitors = query_monitors();
+
+    let mut unmatched = Vec::new();
+    for (idx, display) in displays.iter_mut().enumerate() {
+        let screen_type = find_section(sections, &display.id)
+            .and_then(|s| s.screen_type.as_deref())
+            .unwrap_or("");
+
+        if screen_type.contains("built in") {
+            display.name = Some("Built-in Display".to_string());
+            display.vendor = Some("Apple".to_string());
+            display.builtin = true;
+            notes.push(format!("Display {} is the built-in panel", display.id));
+            continue;
+        }
+
+        let serial = display
+            .serial_id
+            .as_deref()
+            .and_then(|s| s.trim_start_matches('s').parse::<u32>().ok());
+
+        match monitors.iter().position(|m| serial.is_some() && m.serial == serial) {
+            Some(pos) => {
+                apply_monitor_info(display, &monitors.remove(pos));
+                notes.push(format!("Matched display {} to EDID by serial", display.id));
+            }
+            None => unmatched.push(idx),
+        }
+    }
+
+    if unmatched.len() == 1 && monitors.len() == 1 {
+        let display = &mut displays[unmatched[0]];
+        apply_monitor_info(display, &monitors[0]);
+        notes.push(format!("Matched display {} to the only remaining EDID", display.id));
+    }
+}
+
+/// Copy name and vendor from a monitor onto a display.
+fn apply_monitor_info(display: &mut Display, monitor: &MonitorInfo) {
+    display.name = monitor.name.clone();
+    display.vendor = monitor
+        .manufacturer_id
+        .as_deref()
+        .map(|id| vendor_name(id).unwrap_or(id).to_string());
+}
+
+/// Parse `ioreg -l` output into monitor records.
+///
+/// Handles both the Apple Silicon `DisplayAttributes` dictionary
+/// (`"ProductName"="LG HDR 4K"`, `"ManufacturerID"="GSM"`, `"SerialNumber"=123`)
+/// and the Intel `IODisplayConnect` keys (`"DisplayProductName" = {"en_US"="..."}`,
+/// `"DisplayVendorID" = 4268`, `"DisplaySerialNumber" = 123`).
+fn parse_ioreg_output(output: &str) -> Vec<MonitorInfo> {
+    output
+        .split("+-o ")
+        .skip(1)
+        .filter_map(|entry| {
+            let name = quoted_value(entry, "\"ProductName\"=")
+                .or_else(|| quoted_value(entry, "\"DisplayProductName\" = {\"en_US\"="));
+            let manufacturer_id = quoted_value(entry, "\"ManufacturerID\"=")
+                .or_else(|| numeric_value(entry, "\"DisplayVendorID\" = ").and_then(decode_pnp_id));
+            let serial = numeric_value(entry, "\"SerialNumber\"=")
+                .or_else(|| numeric_value(entry, "\"DisplaySerialNumber\" = "))
+                .and_then(|s| u32::try_from(s).ok());
+
+            if name.is_none() && manufacturer_id.is_none() {
+                return None;
+            }
+
+            Some(MonitorInfo {
+                name,
+                manufacturer_id,
+                serial,
+            })
+        })
+        .collect()
+}
+
+/// Extract the quoted string following `key`.
+fn quoted_value(entry: &str, key: &str) -> Option<String> {
+    let start = entry.find(key)? + key.len();
+    let rest = entry[start..].strip_prefix('"')?;
+    let end = rest.find('"')?;
+    Some(rest[..end].trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Extract the unsigned integer following `key`.
+fn numeric_value(entry: &str, key: &str) -> Option<u64> {
+    let start = entry.find(key)? + key.len();
+    let digits: String = entry[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Decode a numeric EDID vendor ID into its three-letter PNP ID.
+///
+/// # Examples
+/// ```ignore
+/// assert_eq!(decode_pnp_id(4268), Some("DEL".to_string()));
+/// ```
+fn decode_pnp_id(vendor_id: u64) -> Option<String> {
+    let letters: Option<String> = [10, 5, 0]
+        .iter()
+        .map(|shift| {
+            let code = ((vendor_id >> shift) & 0x1f) as u8;
+            (1..=26).contains(&code).then(|| (b'A' + code - 1) as char)
+        })
+        .collect();
+    letters
+}
+
+/// Map common PNP manufacturer IDs to brand names.
+fn vendor_name(pnp_id: &str) -> Option<&'static str> {
+    let name = match pnp_id {
+        "ACI" | "AUS" => "ASUS",
+        "ACR" => "Acer",
+        "AOC" => "AOC",
+        "APP" => "Apple",
+        "BNQ" => "BenQ",
+        "DEL" => "Dell",
+        "ENC" => "EIZO",
+        "GBT" => "Gigabyte",
+        "GSM" => "LG",
+        "HWP" => "HP",
+        "LEN" => "Lenovo",
+        "MSI" => "MSI",
+        "NEC" => "NEC",
+        "PHL" => "Philips",
+        "SAM" | "SEC" => "Samsung",
+        "SNY" => "Sony",
+        "VSC" => "ViewSonic",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ioreg_output() {
+        let output = r#"+-o AppleCLCD2  <class AppleCLCD2>
+    | "DisplayAttributes" = {"ProductAttributes"={"ManufacturerID"="GSM","ProductName"="LG HDR 4K","SerialNumber"=4251086178}}
++-o IODisplayConnect  <class IODisplayConnect>
+    | "DisplayProductName" = {"en_US"="DELL U2720Q"}
+    | "DisplayVendorID" = 4268
+    | "DisplaySerialNumber" = 1234
+"#;
+        let monitors = parse_ioreg_output(output);
+
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].name.as_deref(), Some("LG HDR 4K"));
+        assert_eq!(monitors[0].manufacturer_id.as_deref(), Some("GSM"));
+        assert_eq!(monitors[0].serial, Some(4251086178));
+        assert_eq!(monitors[1].name.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(monitors[1].manufacturer_id.as_deref(), Some("DEL"));
+        assert_eq!(monitors[1].serial, Some(1234));
+    }
+
+    #[test]
+    fn test_decode_pnp_id() {
+        assert_eq!(decode_pnp_id(4268), Some("DEL".to_string()));
+        assert_eq!(vendor_name("GSM"), Some("LG"));
+        assert_eq!(vendor_name("XYZ"), None);
+    }
+}
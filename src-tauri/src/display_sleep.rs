@@ -0,0 +1,59 @@
+//! Blanking every screen without touching the layout.
+//!
+//! Sleeping the displays is what `pmset displaysleepnow` does: the layout,
+//! windows and running apps stay as they are, and any input or
+//! `wake_displays` turns the screens back on.
+
+use std::process::Command;
+
+use crate::displayplacer::run_blocking;
+
+/// Run a system tool, turning a failure into an error message.
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Put all displays to sleep right away.
+pub(crate) fn sleep_now() -> Result<(), String> {
+    run("pmset", &["displaysleepnow"])?;
+    log::info!("[DisplaySleep] Displays put to sleep");
+    Ok(())
+}
+
+/// Wake sleeping displays by declaring the user active.
+pub(crate) fn wake_now() -> Result<(), String> {
+    run("caffeinate", &["-u", "-t", "1"])?;
+    log::info!("[DisplaySleep] Displays woken");
+    Ok(())
+}
+
+/// Put all displays to sleep without changing the layout
+///
+/// # Returns
+/// * `Ok(())` - Displays are asleep
+/// * `Err(String)` - Error message if pmset failed
+#[tauri::command]
+pub async fn sleep_displays() -> Result<(), String> {
+    run_blocking(sleep_now).await
+}
+
+/// Wake displays put to sleep with `sleep_displays`
+///
+/// # Returns
+/// * `Ok(())` - Displays are awake
+/// * `Err(String)` - Error message if caffeinate failed
+#[tauri::command]
+pub async fn wake_displays() -> Result<(), String> {
+    run_blocking(wake_now).await
+}
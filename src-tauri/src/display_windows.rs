@@ -0,0 +1,150 @@
+//! Secondary windows, one on each display besides the primary.
+//!
+//! Created at startup and kept in step with hotplug by the display watcher:
+//! a window opens on each newly connected display and the extra ones close
+//! when displays go away. Windows are labelled `display_<n>` by monitor index.
+//!
+//! Each window loads `index.html?view=display&display=<ID>` and asks
+//! `get_window_display` which display it currently sits on, so it can show
+//! controls for that display only.
+
+use tauri::{AppHandle, Manager, Monitor, WebviewUrl, WebviewWindowBuilder};
+
+use crate::displayplacer::{get_displays, Display};
+use crate::window_state;
+
+const LABEL_PREFIX: &str = "display_";
+
+const WINDOW_WIDTH: f64 = 600.0;
+const WINDOW_HEIGHT: f64 = 700.0;
+
+fn window_label(index: usize) -> String {
+    format!("{}{}", LABEL_PREFIX, index)
+}
+
+/// Labels of secondary windows whose monitor index no longer exists.
+fn stale_labels<'a>(labels: impl Iterator<Item = &'a str>, monitor_count: usize) -> Vec<String> {
+    labels
+        .filter(|label| {
+            label
+                .strip_prefix(LABEL_PREFIX)
+                .and_then(|index| index.parse::<usize>().ok())
+                .is_some_and(|index| index == 0 || index >= monitor_count)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// The display whose origin, in points, is `origin`.
+fn display_at(displays: &[Display], origin: (i32, i32)) -> Option<&Display> {
+    // Rounding the scaled position can be a point off
+    displays
+        .iter()
+        .filter(|d| d.enabled)
+        .find(|d| (d.origin.0 - origin.0).abs() <= 1 && (d.origin.1 - origin.1).abs() <= 1)
+}
+
+/// The display a monitor reported by the window system corresponds to.
+pub(crate) fn display_for_monitor<'a>(displays: &'a [Display], monitor: &Monitor) -> Option<&'a Display> {
+    let position = monitor.position().to_logical::<f64>(monitor.scale_factor());
+    display_at(displays, (position.x.round() as i32, position.y.round() as i32))
+}
+
+/// Open a window on each secondary display that lacks one and close windows
+/// of displays that are gone.
+///
+/// `displays` is the current layout, used to tell each new window its display.
+/// Windows that stay are left where the user put them.
+pub fn sync(app: &AppHandle, displays: &[Display]) -> Result<(), String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+
+    let windows = app.webview_windows();
+    for label in stale_labels(windows.keys().map(String::as_str), monitors.len()) {
+        if let Some(window) = windows.get(&label) {
+            let _ = window.close();
+            log::info!("[Windows] Closed {}, its display is gone", label);
+        }
+    }
+
+    // The first monitor is the primary and has the main window
+    for (index, monitor) in monitors.iter().enumerate().skip(1) {
+        let label = window_label(index);
+        if windows.contains_key(&label) {
+            continue;
+        }
+
+        // A smaller window centered on the display
+        let position = monitor.position();
+        let size = monitor.size();
+        let x = position.x as f64 + (size.width as f64 - WINDOW_WIDTH) / 2.0;
+        let y = position.y as f64 + (size.height as f64 - WINDOW_HEIGHT) / 2.0;
+
+        let display_id = display_for_monitor(displays, monitor)
+            .map(|d| d.persistent_id.clone().unwrap_or_else(|| d.id.clone()))
+            .unwrap_or_default();
+        let url = format!("index.html?view=display&display={}", display_id);
+
+        let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+            .title(format!("DPUI - Display {}", index + 1))
+            .inner_size(WINDOW_WIDTH, WINDOW_HEIGHT)
+            .position(x, y)
+            .resizable(true)
+            .build()
+            .map_err(|e| format!("Failed to create {}: {}", label, e))?;
+        window_state::track(&window);
+
+        log::info!("[Windows] Created window on display {} at ({}, {})", index + 1, x, y);
+    }
+
+    Ok(())
+}
+
+/// Get the display a window currently sits on
+///
+/// # Arguments
+/// * `window_label` - Label of the window, e.g. `display_1`
+///
+/// # Returns
+/// * `Ok(Some(Display))` - The display under the window
+/// * `Ok(None)` - The window is not on any display known to displayplacer
+/// * `Err(String)` - Error message if the window doesn't exist or displays can't be read
+#[tauri::command]
+pub async fn get_window_display(app: AppHandle, window_label: String) -> Result<Option<Display>, String> {
+    let window = app
+        .get_webview_window(&window_label)
+        .ok_or_else(|| format!("Window {} not found", window_label))?;
+    let Some(monitor) = window
+        .current_monitor()
+        .map_err(|e| format!("Failed to get monitor of {}: {}", window_label, e))?
+    else {
+        return Ok(None);
+    };
+
+    let config = get_displays().await?;
+    Ok(display_for_monitor(&config.displays, &monitor).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_labels() {
+        let labels = ["main", "quick-panel", "display_1", "display_2", "display_3"];
+        assert_eq!(stale_labels(labels.into_iter(), 2), vec!["display_2", "display_3"]);
+        assert!(stale_labels(labels.into_iter(), 4).is_empty());
+        assert_eq!(stale_labels(labels.into_iter(), 1).len(), 3);
+    }
+
+    #[test]
+    fn test_display_at() {
+        let displays = crate::displayplacer::parse_config_displays(
+            r#""id:A res:1728x1117 enabled:true origin:(0,0) degree:0" "id:B res:2560x1440 enabled:true origin:(1728,-323) degree:0""#,
+        );
+        assert_eq!(display_at(&displays, (1727, -323)).map(|d| d.id.as_str()), Some("B"));
+        assert_eq!(display_at(&displays, (0, 0)).map(|d| d.id.as_str()), Some("A"));
+        assert!(display_at(&displays, (3000, 0)).is_none());
+    }
+}
@@ -16,7 +16,25 @@
 //! ```
 
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::apply_queue::{self, ApplyOutcome};
+use crate::display_info;
+use crate::history::{self, HistorySource};
+use crate::power;
+use crate::rollback;
+use crate::settings;
+use crate::trace::{self, CommandTrace};
+use crate::virtual_displays;
+use crate::undo::{self, UndoStack};
 
 /// Represents a single display/monitor in the system.
 ///
@@ -24,16 +42,158 @@ use std::process::Command;
 /// its unique identifier, resolution, position, rotation, and enabled state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Display {
-    /// Unique identifier for the display (UUID format)
+    /// Identifier displayplacer currently uses for the display (normally the persistent UUID)
     pub id: String,
+    /// Persistent screen ID (UUID, stable across reboots)
+    #[serde(default)]
+    pub persistent_id: Option<String>,
+    /// Contextual screen ID (small integer, may change when displays are reconnected)
+    #[serde(default)]
+    pub contextual_id: Option<String>,
+    /// Serial screen ID (e.g. "s4251086178")
+    #[serde(default)]
+    pub serial_id: Option<String>,
     /// Display resolution in format "WIDTHxHEIGHT" (e.g., "2560x1440")
     pub resolution: String,
+    /// Refresh rate in Hz, if reported
+    #[serde(default)]
+    pub hz: Option<u32>,
+    /// Color depth in bits, if reported
+    #[serde(default)]
+    pub color_depth: Option<u32>,
+    /// Whether a scaled (HiDPI) mode is in use, if reported
+    #[serde(default)]
+    pub scaling: Option<bool>,
     /// Display position as (x, y) coordinates where (0,0) is the primary display
     pub origin: (i32, i32),
     /// Display rotation in degrees (0, 90, 180, or 270)
     pub rotation: i32,
     /// Whether the display is currently enabled
     pub enabled: bool,
+    /// Modes the display currently advertises for its active rotation
+    #[serde(default)]
+    pub modes: Vec<DisplayMode>,
+    /// Human-readable monitor name from EDID (e.g. "LG HDR 4K")
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Monitor manufacturer from EDID (e.g. "LG")
+    #[serde(default)]
+    pub vendor: Option<String>,
+    /// Whether this is the built-in panel of a laptop or iMac
+    #[serde(default)]
+    pub builtin: bool,
+    /// Whether this is a virtual display DPUI created
+    #[serde(default)]
+    pub virtual_display: bool,
+}
+
+/// A set of displays showing the same content.
+///
+/// displayplacer expresses this as `id:PRIMARY+MIRROR1+MIRROR2` in a single group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirrorGroup {
+    /// Display whose resolution and origin the group uses
+    pub primary: String,
+    /// Displays mirroring the primary
+    pub mirrors: Vec<String>,
+}
+
+/// A single mode from a display's "Resolutions for rotation" table.
+///
+/// displayplacer lists these as e.g. `mode 12: res:1920x1080 hz:60 color_depth:8 scaling:on`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplayMode {
+    /// Mode index as reported by displayplacer
+    pub index: u32,
+    /// Mode resolution in format "WIDTHxHEIGHT"
+    pub resolution: String,
+    /// Refresh rate in Hz, if reported
+    pub hz: Option<u32>,
+    /// Color depth in bits, if reported
+    pub color_depth: Option<u32>,
+    /// Whether this is a scaled (HiDPI) mode
+    pub scaling: bool,
+    /// Whether this is the mode the display is currently using
+    pub current: bool,
+}
+
+/// Notice emitted when a requested mode is replaced with a supported one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeNotice {
+    /// Display whose mode was adjusted
+    pub display_id: String,
+    /// Requested mode (e.g. "3840x2160@144")
+    pub requested: String,
+    /// Mode that will be applied instead
+    pub applied: String,
+    /// Human-readable description of the substitution
+    pub message: String,
+}
+
+/// Result of checking a configuration against the current mode tables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeCheck {
+    /// Configuration with unsupported modes replaced by their nearest supported mode
+    pub config: String,
+    /// One notice per substituted mode
+    pub notices: Vec<ModeNotice>,
+}
+
+/// Kind of problem found while validating a configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigIssue {
+    /// A display group has no `id:`
+    Unparsable,
+    /// The referenced display is not connected
+    UnknownDisplay,
+    /// The display does not advertise the requested resolution/refresh rate
+    UnsupportedMode,
+    /// Two enabled displays would overlap
+    Overlap,
+}
+
+/// Warning produced by `validate_config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigWarning {
+    pub issue: ConfigIssue,
+    /// Display the warning is about, if any
+    pub display_id: Option<String>,
+    pub message: String,
+}
+
+/// A single way a display would change if a configuration were applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DisplayChange {
+    /// The configuration references a display that is not connected
+    Missing,
+    /// The display is connected but not mentioned by the configuration
+    NotInConfig,
+    ResolutionChanged { from: String, to: String },
+    RefreshRateChanged { from: Option<u32>, to: u32 },
+    OriginChanged { from: (i32, i32), to: (i32, i32) },
+    RotationChanged { from: i32, to: i32 },
+    EnabledChanged { from: bool, to: bool },
+    /// The set of displays mirroring this one changes
+    MirroringChanged { from: Vec<String>, to: Vec<String> },
+}
+
+/// Difference between a configuration and the current layout for one display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplayDifference {
+    pub display_id: String,
+    /// Monitor name, if known
+    pub name: Option<String>,
+    pub change: DisplayChange,
+}
+
+/// Result of checking the layout after an apply against the requested one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApplyVerification {
+    /// Displays whose resolution or origin differs; in each change `from` is
+    /// the actual value and `to` the requested one
+    pub mismatches: Vec<DisplayDifference>,
 }
 
 /// Complete display configuration including all connected displays.
@@ -44,6 +204,15 @@ pub struct Display {
 pub struct DisplayConfig {
     /// List of all displays found in the system
     pub displays: Vec<Display>,
+    /// Displays currently mirroring each other
+    #[serde(default)]
+    pub mirror_groups: Vec<MirrorGroup>,
+    /// Whether the lid is closed; `None` for Macs without a lid
+    #[serde(default)]
+    pub lid_closed: Option<bool>,
+    /// Whether the built-in display is connected and enabled
+    #[serde(default)]
+    pub builtin_active: bool,
     /// Raw output from displayplacer command for debugging
     pub raw_command: String,
 }
@@ -65,10 +234,269 @@ pub struct DisplayConfig {
 /// * Unable to parse displayplacer output
 #[tauri::command]
 pub async fn get_displays() -> Result<DisplayConfig, String> {
-    let output = Command::new("displayplacer")
-        .arg("list")
-        .output()
-        .map_err(|e| format!("Failed to execute displayplacer: {}", e))?;
+    run_blocking(read_displays).await
+}
+
+/// Most recent display list, shared by every caller of `read_displays`
+struct DisplayCache {
+    /// Bumped on every invalidation so reads that started earlier aren't cached
+    generation: u64,
+    entry: Option<(Instant, DisplayConfig)>,
+}
+
+static DISPLAY_CACHE: Mutex<DisplayCache> = Mutex::new(DisplayCache {
+    generation: 0,
+    entry: None,
+});
+
+/// Forget the cached display list, e.g. after an apply or a hotplug.
+pub(crate) fn invalidate_display_cache() {
+    if let Ok(mut cache) = DISPLAY_CACHE.lock() {
+        cache.generation += 1;
+        cache.entry = None;
+    }
+}
+
+/// The last display list read, however old, without running anything.
+///
+/// For menu construction, which runs on the main thread and must never wait
+/// for displayplacer. `None` if nothing was read since the cache was invalidated.
+pub(crate) fn cached_displays() -> Option<DisplayConfig> {
+    let cache = DISPLAY_CACHE.lock().ok()?;
+    cache.entry.as_ref().map(|(_, config)| config.clone())
+}
+
+/// Synchronous core of `get_displays`, for callers outside the async runtime
+/// such as menu construction.
+///
+/// Results are reused for `display_cache_ttl_ms` (see settings), since the
+/// tray, frontend and validation paths can ask several times per second.
+pub(crate) fn read_displays() -> Result<DisplayConfig, String> {
+    let ttl = Duration::from_millis(settings::current().display_cache_ttl_ms);
+    let generation = {
+        let cache = DISPLAY_CACHE.lock().map_err(|_| "Display cache lock poisoned".to_string())?;
+        if let Some((fetched_at, config)) = &cache.entry {
+            if fetched_at.elapsed() < ttl {
+                return Ok(config.clone());
+            }
+        }
+        cache.generation
+    };
+
+    let config = read_displays_uncached()?;
+
+    if let Ok(mut cache) = DISPLAY_CACHE.lock() {
+        if cache.generation == generation {
+            cache.entry = Some((Instant::now(), config.clone()));
+        }
+    }
+
+    Ok(config)
+}
+
+/// Run `displayplacer list` and parse its output, bypassing the cache.
+pub(crate) fn read_displays_uncached() -> Result<DisplayConfig, String> {
+    let args = vec!["list".to_string()];
+    let mut trace = CommandTrace::start("displayplacer", &args);
+
+    let result = run_displayplacer(&args, &mut trace).and_then(|output| {
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+
+        // Parse the displayplacer output the way this release prints it
+        let version = displayplacer_version();
+        let mut displays = parse_displayplacer_output(&stdout, version, &mut trace.parser_notes)?;
+
+        // Resolve human-readable names from EDID
+        display_info::resolve_names(&mut displays, &parse_screen_sections(&stdout), &mut trace.parser_notes);
+        virtual_displays::mark(&mut displays, &settings::current().virtual_displays);
+
+        Ok(DisplayConfig {
+            builtin_active: displays.iter().any(|d| d.builtin && d.enabled),
+            lid_closed: power::lid_closed(),
+            displays,
+            mirror_groups: parse_mirror_groups(&stdout, version),
+            raw_command: stdout,
+        })
+    });
+
+    if let Err(e) = &result {
+        trace.fail(e);
+    }
+    trace::record(trace);
+
+    result
+}
+
+/// Prefix of the error returned when displayplacer can't be found, so the UI
+/// can tell "not installed" apart from displayplacer failing
+pub const NOT_INSTALLED_ERROR: &str = "displayplacer_not_found";
+
+/// Where Homebrew installs binaries (Apple Silicon and Intel). Apps launched
+/// from Finder don't inherit the shell's PATH, so these are probed explicitly.
+pub(crate) const HOMEBREW_BIN_DIRS: [&str; 2] = ["/opt/homebrew/bin", "/usr/local/bin"];
+
+/// Look for the displayplacer binary.
+///
+/// A custom path wins if set, even if it doesn't exist, so a typo isn't
+/// silently replaced by another install. Otherwise `PATH` is searched, then
+/// the Homebrew directories.
+fn find_binary(custom: Option<&str>, path_var: Option<&OsStr>, fallback_dirs: &[&str]) -> Option<PathBuf> {
+    if let Some(custom) = custom.filter(|c| !c.trim().is_empty()) {
+        let path = PathBuf::from(custom.trim());
+        return path.is_file().then_some(path);
+    }
+
+    let path_dirs = path_var.map(|var| std::env::split_paths(var).collect::<Vec<_>>()).unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(fallback_dirs.iter().map(PathBuf::from))
+        .map(|dir| dir.join("displayplacer"))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Resolve the displayplacer binary to run.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path of the binary
+/// * `Err(String)` - Error starting with `NOT_INSTALLED_ERROR` if it can't be found
+pub(crate) fn displayplacer_binary() -> Result<PathBuf, String> {
+    let custom = settings::current().displayplacer_path;
+    if let Some(path) = find_binary(custom.as_deref(), std::env::var_os("PATH").as_deref(), &HOMEBREW_BIN_DIRS) {
+        return Ok(path);
+    }
+
+    Err(match custom.filter(|c| !c.trim().is_empty()) {
+        Some(custom) => format!("{}: no displayplacer binary at {}", NOT_INSTALLED_ERROR, custom),
+        None => format!(
+            "{}: displayplacer not found in PATH or {}. Install it with `brew install displayplacer` \
+             or set its location in settings",
+            NOT_INSTALLED_ERROR,
+            HOMEBREW_BIN_DIRS.join(", ")
+        ),
+    })
+}
+
+/// Check a custom displayplacer path before it is stored in settings.
+pub(crate) fn validate_binary_path(path: &str) -> Result<(), String> {
+    if Path::new(path.trim()).is_file() {
+        Ok(())
+    } else {
+        Err(format!("No displayplacer binary at {}", path))
+    }
+}
+
+/// A displayplacer release, as reported by `displayplacer --version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct DisplayplacerVersion(pub u32, pub u32, pub u32);
+
+impl DisplayplacerVersion {
+    /// Find a version such as "v1.4.0" or "1.3" in `--version` output.
+    fn parse(output: &str) -> Option<Self> {
+        output.split_whitespace().find_map(|word| {
+            let mut parts = word.trim_start_matches('v').split('.');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            let patch = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+            Some(Self(major, minor, patch))
+        })
+    }
+}
+
+/// Version of the binary last probed, keyed by its path
+static VERSION_CACHE: Mutex<Option<(PathBuf, Option<DisplayplacerVersion>)>> = Mutex::new(None);
+
+/// Version of the installed displayplacer, probed once per binary.
+///
+/// `None` if it can't be run or doesn't report a version; the output format is
+/// then detected from the output itself.
+pub(crate) fn displayplacer_version() -> Option<DisplayplacerVersion> {
+    let binary = displayplacer_binary().ok()?;
+    if let Ok(cache) = VERSION_CACHE.lock() {
+        if let Some((_, version)) = cache.as_ref().filter(|(path, _)| *path == binary) {
+            return *version;
+        }
+    }
+
+    let timeout = Duration::from_secs(settings::current().command_timeout_secs);
+    let version = run_with_timeout("displayplacer", Command::new(&binary).arg("--version"), timeout)
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| DisplayplacerVersion::parse(&String::from_utf8_lossy(&output.stdout)));
+    log::info!("[Display] {} reports version {:?}", binary.display(), version);
+
+    if let Ok(mut cache) = VERSION_CACHE.lock() {
+        *cache = Some((binary, version));
+    }
+    version
+}
+
+/// How a release prints the command in `displayplacer list` output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Before 1.2: screen groups may be printed without quotes and carry no
+    /// `enabled:` field; disabled screens are only marked by the word "disabled"
+    Legacy,
+    /// 1.2 and later: every screen group is quoted
+    Quoted,
+}
+
+impl OutputFormat {
+    /// Strategy for a release; if the version is unknown, it is detected from the output.
+    fn for_version(version: Option<DisplayplacerVersion>, output: &str) -> Self {
+        match version {
+            Some(version) if version < DisplayplacerVersion(1, 2, 0) => Self::Legacy,
+            Some(_) => Self::Quoted,
+            None => {
+                let quoted = output
+                    .lines()
+                    .any(|line| line.trim().starts_with("displayplacer") && line.contains("\"id:"));
+                if quoted {
+                    Self::Quoted
+                } else {
+                    Self::Legacy
+                }
+            }
+        }
+    }
+
+    /// Split a displayplacer command line into one string per screen group.
+    fn screen_groups(self, line: &str) -> Vec<String> {
+        match self {
+            Self::Quoted => line.split('"').map(str::to_string).collect(),
+            Self::Legacy => {
+                // A group starts at each id: parameter, quoted or not
+                let mut groups: Vec<Vec<&str>> = Vec::new();
+                for word in line.split_whitespace().map(|word| word.trim_matches('"')) {
+                    if word.starts_with("id:") {
+                        groups.push(Vec::new());
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.push(word);
+                    }
+                }
+                groups.into_iter().map(|group| group.join(" ")).collect()
+            }
+        }
+    }
+}
+
+/// Run displayplacer with the given arguments.
+///
+/// Every argument is passed as a separate argv element. The outcome is written
+/// into `trace`; callers are responsible for recording it once any parsing is done.
+///
+/// If displayplacer runs longer than the `command_timeout_secs` setting, it is
+/// killed and a timeout error is returned.
+///
+/// # Returns
+/// * `Ok(Output)` - The process exited successfully
+/// * `Err(String)` - displayplacer could not be spawned, timed out or exited with an error
+fn run_displayplacer(args: &[String], trace: &mut CommandTrace) -> Result<Output, String> {
+    let timeout = Duration::from_secs(settings::current().command_timeout_secs);
+    let binary = displayplacer_binary()?;
+    let output = run_with_timeout("displayplacer", Command::new(binary).args(args), timeout)?;
+
+    trace.finish(&output);
 
     if !output.status.success() {
         return Err(format!(
@@ -77,17 +505,106 @@ pub async fn get_displays() -> Result<DisplayConfig, String> {
         ));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(output)
+}
 
-    // Parse the displayplacer output
-    let displays = parse_displayplacer_output(&stdout)?;
+/// Run a command to completion, killing it if it takes longer than `timeout`.
+///
+/// stdout and stderr are drained on separate threads so a chatty process
+/// can't block on a full pipe while we wait for it. `name` is used in errors.
+pub(crate) fn run_with_timeout(name: &str, command: &mut Command, timeout: Duration) -> Result<Output, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute {}: {}", name, e))?;
+
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
 
-    Ok(DisplayConfig {
-        displays,
-        raw_command: stdout.to_string(),
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out after {} seconds", name, timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Failed to wait for {}: {}", name, e)),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
     })
 }
 
+/// Run displayplacer with the given arguments and record the trace immediately.
+///
+/// The display cache is invalidated, as the layout may have changed even if
+/// displayplacer reported an error.
+///
+/// Runs under the apply queue's lock, so changes made outside the queue (toggles,
+/// rotations, undo, KVM and session restores) never run alongside a queued apply.
+pub(crate) fn execute_displayplacer(args: Vec<String>) -> Result<Output, String> {
+    let mut trace = CommandTrace::start("displayplacer", &args);
+    let result = apply_queue::exclusive(|| run_displayplacer(&args, &mut trace));
+    invalidate_display_cache();
+
+    if let Err(e) = &result {
+        trace.fail(e);
+    }
+    trace::record(trace);
+
+    result
+}
+
+/// Run blocking work such as spawning displayplacer on the blocking thread pool.
+///
+/// Display changes can take seconds; doing them inline would stall the async
+/// runtime that every other command shares.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))?
+}
+
+/// `execute_displayplacer` for async callers.
+pub(crate) async fn spawn_displayplacer(args: Vec<String>) -> Result<Output, String> {
+    run_blocking(move || execute_displayplacer(args)).await
+}
+
+/// Run a change made outside the apply queue (toggles, rotations, mirroring).
+///
+/// Like `apply_layout_now`, the outcome is recorded in the history under
+/// `source` and, once displayplacer succeeds, `replaced` is pushed for undo
+/// and a layout awaiting confirmation is kept.
+async fn apply_change(args: Vec<String>, replaced: Option<DisplayConfig>, source: HistorySource) -> Result<(), String> {
+    let config = args.iter().map(|arg| format!("\"{}\"", arg)).collect::<Vec<_>>().join(" ");
+    let result = spawn_displayplacer(args).await.map(|_| ());
+    history::record(source, &config, &result);
+    result?;
+
+    undo::changed(replaced);
+    Ok(())
+}
+
 /// Apply a display configuration using displayplacer.
 ///
 /// Executes a displayplacer command with the provided configuration string.
@@ -95,30 +612,664 @@ pub async fn get_displays() -> Result<DisplayConfig, String> {
 ///
 /// # Arguments
 /// * `config` - displayplacer command arguments (e.g., "id:xxx res:2560x1440 origin:(0,0)")
+/// * `source` - What triggered the apply, for the history log (defaults to manual)
 ///
 /// # Returns
-/// * `Ok(())` - Configuration applied successfully
-/// * `Err(String)` - Error message if the configuration fails
+/// * `Ok(Some(ApplyVerification))` - Configuration applied; lists any displays
+///   whose resolution or origin differs from what was requested
+/// * `Ok(None)` - Skipped because a newer configuration was queued
+/// * `Err(String)` - Error message if the configuration is malformed or fails
+///
+/// The config is split into one argument per display with `tokenize_config`;
+/// malformed input is rejected before displayplacer is run.
+///
+/// The layout being replaced is pushed onto the undo stack so it can be restored
+/// with `revert_last_apply`. Display IDs of any type (persistent, contextual or
+/// serial) are then resolved to the ID displayplacer currently uses.
+///
+/// Before running, every display's requested `res:`/`hz:` is checked against the
+/// current mode table. Modes the display no longer advertises are downgraded to
+/// the nearest supported mode and a `mode-downgraded` event is emitted with the
+/// list of substitutions, rather than letting displayplacer fail mid-apply.
+///
+/// Applies run one at a time through the apply queue.
+///
+/// Once applied, the new layout has to be confirmed with `confirm_applied_config`
+/// or it is reverted when the countdown runs out.
 ///
 /// # Example
 /// ```no_run
-/// apply_config("id:37D88 res:2560x1440 origin:(0,0) degree:0".to_string()).await?;
+/// apply_config(app, "id:37D88 res:2560x1440 origin:(0,0) degree:0".to_string(), None).await?;
 /// ```
 #[tauri::command]
-pub async fn apply_config(config: String) -> Result<(), String> {
-    let output = Command::new("displayplacer")
-        .arg(&config)
-        .output()
-        .map_err(|e| format!("Failed to execute displayplacer: {}", e))?;
+pub async fn apply_config(
+    app: AppHandle,
+    config: String,
+    source: Option<HistorySource>,
+) -> Result<Option<ApplyVerification>, String> {
+    match apply_layout(&app, config, source.unwrap_or(HistorySource::Manual)).await? {
+        ApplyOutcome::Applied {
+            verification,
+            undo_snapshot,
+        } => {
+            rollback::arm(&app, undo_snapshot);
+            Ok(Some(verification))
+        }
+        ApplyOutcome::Skipped => Ok(None),
+    }
+}
 
-    if !output.status.success() {
-        return Err(format!(
-            "displayplacer failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+/// Apply a configuration without asking for confirmation.
+///
+/// Used by unattended callers such as the scheduler. The apply waits its turn
+/// in the apply queue and may be skipped if a newer one is queued behind it.
+pub(crate) async fn apply_layout(
+    app: &AppHandle,
+    config: String,
+    source: HistorySource,
+) -> Result<ApplyOutcome, String> {
+    apply_queue::enqueue(app, config, source).await
+}
+
+/// Apply a configuration immediately; only called by the apply queue's worker.
+///
+/// The outcome is recorded in the configuration history under `source`.
+///
+/// Once displayplacer succeeds, the resulting layout is re-read and compared
+/// with the request, since displayplacer sometimes silently falls back to
+/// another mode or position. A layout still awaiting confirmation is kept, as
+/// this apply replaces it.
+///
+/// # Returns
+/// * `Ok((ApplyVerification, Option<u64>))` - Verification and the undo snapshot
+///   of the replaced layout, for `rollback::arm`
+/// * `Err(String)` - Error message if the config is malformed or displayplacer fails
+pub(crate) async fn apply_layout_now(
+    app: &AppHandle,
+    config: String,
+    source: HistorySource,
+) -> Result<(ApplyVerification, Option<u64>), String> {
+    // The layout being replaced, kept for undo once the apply succeeds
+    let mut replaced = None;
+
+    // If the current state can't be read, fall back to applying the config as-is
+    let config = match get_displays().await {
+        Ok(current) => {
+            replaced = Some(current.clone());
+
+            let config = resolve_config_ids(&config, &current.displays);
+            // Without a display at (0,0), keep the current primary where it is
+            let primary = current
+                .displays
+                .iter()
+                .find(|d| d.enabled && d.origin == (0, 0))
+                .map(|d| d.id.clone());
+            let config = crate::layout::normalize_config(&config, None, primary.as_deref())?;
+            let check = downgrade_unsupported_modes(&config, &current.displays);
+            if !check.notices.is_empty() {
+                for notice in &check.notices {
+                    log::info!("[Display] {}", notice.message);
+                }
+                let _ = app.emit("mode-downgraded", &check.notices);
+            }
+            check.config
+        }
+        Err(_) => config,
+    };
+
+    let result = match tokenize_config(&config) {
+        Ok(args) => spawn_displayplacer(args).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    history::record(source, &config, &result);
+    result?;
+
+    rollback::cancel(app);
+    let undo_snapshot = replaced.and_then(|layout| app.state::<UndoStack>().push(layout));
+
+    let verification = verify_layout(&config).await;
+    if !verification.mismatches.is_empty() {
+        log::info!("[Display] {} display(s) differ from the requested layout", verification.mismatches.len());
+        let _ = app.emit("layout-mismatch", &verification);
     }
 
-    Ok(())
+    Ok((verification, undo_snapshot))
+}
+
+/// Compare the layout displayplacer actually produced with the one requested.
+///
+/// If the displays can't be re-read, nothing is reported.
+async fn verify_layout(config: &str) -> ApplyVerification {
+    let mismatches = match get_displays().await {
+        Ok(after) => diff_layout(config, &after)
+            .into_iter()
+            .filter(|d| {
+                matches!(
+                    d.change,
+                    DisplayChange::ResolutionChanged { .. } | DisplayChange::OriginChanged { .. }
+                )
+            })
+            .collect(),
+        Err(e) => {
+            log::error!("[Display] Could not verify applied layout: {}", e);
+            Vec::new()
+        }
+    };
+
+    ApplyVerification { mismatches }
+}
+
+/// Parse the displays described by a configuration string or full command.
+///
+/// Mirror groups (`id:A+B`) are kept as a single entry.
+pub(crate) fn parse_config_displays(config: &str) -> Vec<Display> {
+    let mut displays = Vec::new();
+    map_config_groups(config, |group| {
+        displays.extend(parse_display_string(group));
+        group.to_string()
+    });
+    displays
+}
+
+/// Build displayplacer arguments that reproduce the given layout exactly.
+///
+/// Returns one argument per display, e.g.
+/// `id:xxx res:2560x1440 hz:60 color_depth:8 enabled:true scaling:on origin:(0,0) degree:0`.
+pub(crate) fn display_args(displays: &[Display]) -> Vec<String> {
+    displays
+        .iter()
+        .map(|d| {
+            let mut parts = vec![format!("id:{}", d.id), format!("res:{}", d.resolution)];
+            if let Some(hz) = d.hz {
+                parts.push(format!("hz:{}", hz));
+            }
+            if let Some(depth) = d.color_depth {
+                parts.push(format!("color_depth:{}", depth));
+            }
+            parts.push(format!("enabled:{}", d.enabled));
+            if let Some(scaling) = d.scaling {
+                parts.push(format!("scaling:{}", if scaling { "on" } else { "off" }));
+            }
+            parts.push(format!("origin:({},{})", d.origin.0, d.origin.1));
+            parts.push(format!("degree:{}", d.rotation));
+            parts.join(" ")
+        })
+        .collect()
+}
+
+/// Build a full displayplacer command that reproduces the current layout.
+///
+/// Mirrored displays are folded into their group's `id:PRIMARY+MIRROR` argument,
+/// e.g. `displayplacer "id:A+B res:1920x1080 ..." "id:C res:2560x1440 ..."`.
+pub(crate) fn layout_command(config: &DisplayConfig) -> String {
+    let args: Vec<String> = config
+        .displays
+        .iter()
+        .filter(|d| !config.mirror_groups.iter().any(|g| g.mirrors.contains(&d.id)))
+        .map(|d| {
+            let args = display_args(std::slice::from_ref(d)).remove(0);
+            match config.mirror_groups.iter().find(|g| g.primary == d.id) {
+                Some(group) => {
+                    let group_id = std::iter::once(&group.primary)
+                        .chain(&group.mirrors)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join("+");
+                    args.replacen(&format!("id:{}", d.id), &format!("id:{}", group_id), 1)
+                }
+                None => args,
+            }
+        })
+        .map(|arg| format!("\"{}\"", arg))
+        .collect();
+
+    format!("displayplacer {}", args.join(" "))
+}
+
+/// Parameters displayplacer accepts inside a display group.
+const GROUP_PARAMS: &[&str] = &[
+    "id:",
+    "res:",
+    "hz:",
+    "color_depth:",
+    "scaling:",
+    "origin:",
+    "degree:",
+    "enabled:",
+    "mode:",
+];
+
+/// Characters allowed in a display group besides ASCII letters and digits
+const GROUP_PUNCTUATION: &[char] = &[':', '(', ')', ',', '-', '+', '.', '_'];
+
+/// Split a configuration into one displayplacer argument per display.
+///
+/// Accepts a bare display group (`id:A res:1920x1080`), several quoted groups
+/// (`"id:A ..." "id:B ..."`) or a full command starting with `displayplacer`.
+/// Unquoted input with several `id:` parameters is split at each of them.
+/// Every parameter must be one displayplacer understands and may only contain
+/// letters, digits and the punctuation its values use, so a config can't pass
+/// extra flags or arguments.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - One argument per display, parameters separated by single spaces
+/// * `Err(String)` - The config is empty, has an unterminated quote or text
+///   between quoted groups, or a group is malformed
+pub(crate) fn tokenize_config(config: &str) -> Result<Vec<String>, String> {
+    let trimmed = config.trim();
+    let body = match trimmed.strip_prefix("displayplacer") {
+        Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => rest.trim(),
+        _ => trimmed,
+    };
+
+    let mut groups: Vec<Vec<&str>> = Vec::new();
+    if body.contains('"') {
+        let parts: Vec<&str> = body.split('"').collect();
+        if parts.len().is_multiple_of(2) {
+            return Err("Unterminated quote in display configuration".to_string());
+        }
+        for (index, part) in parts.iter().enumerate() {
+            if index % 2 == 0 {
+                if !part.trim().is_empty() {
+                    return Err(format!("Unexpected \"{}\" outside a quoted display group", part.trim()));
+                }
+            } else if !part.trim().is_empty() {
+                groups.push(part.split_whitespace().collect());
+            }
+        }
+    } else {
+        for param in body.split_whitespace() {
+            match groups.last_mut() {
+                Some(group) if !param.starts_with("id:") => group.push(param),
+                _ => groups.push(vec![param]),
+            }
+        }
+    }
+
+    if groups.is_empty() {
+        return Err("No display configurations found".to_string());
+    }
+
+    groups
+        .iter()
+        .map(|params| {
+            let group = params.join(" ");
+            if !params.first().is_some_and(|p| p.starts_with("id:") && p.len() > 3) {
+                return Err(format!("\"{}\" has no display id", group));
+            }
+            for param in params {
+                if !GROUP_PARAMS.iter().any(|prefix| param.starts_with(prefix)) {
+                    return Err(format!("Unrecognized parameter \"{}\" in \"{}\"", param, group));
+                }
+                if let Some(c) = param
+                    .chars()
+                    .find(|c| !c.is_ascii_alphanumeric() && !GROUP_PUNCTUATION.contains(c))
+                {
+                    return Err(format!("Invalid character '{}' in \"{}\"", c, param));
+                }
+            }
+            Ok(group)
+        })
+        .collect()
+}
+
+/// Parse a pasted displayplacer command into its canonical form.
+///
+/// Accepts the command as copied from `displayplacer list`, a terminal or a
+/// notes app: the leading `displayplacer` is optional, typographic quotes are
+/// treated as plain quotes and backslash line continuations are joined.
+///
+/// # Returns
+/// * `Ok(String)` - Command in the same form as `layout_command`,
+///   e.g. `displayplacer "id:A res:1920x1080 origin:(0,0) degree:0"`
+/// * `Err(String)` - The command is rejected by `tokenize_config`
+pub(crate) fn parse_command(raw: &str) -> Result<String, String> {
+    let normalized = raw
+        .replace(['\u{201C}', '\u{201D}'], "\"")
+        .replace("\\\r\n", " ")
+        .replace("\\\n", " ");
+
+    let args: Vec<String> = tokenize_config(&normalized)?
+        .iter()
+        .map(|group| format!("\"{}\"", group))
+        .collect();
+
+    Ok(format!("displayplacer {}", args.join(" ")))
+}
+
+/// Width and height of a display in points, accounting for rotation.
+pub(crate) fn display_size(display: &Display) -> (i32, i32) {
+    let (width, height) = parse_resolution(&display.resolution).unwrap_or((0, 0));
+    let (width, height) = (width as i32, height as i32);
+
+    if display.rotation == 90 || display.rotation == 270 {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+/// Build arguments that mirror `mirrored_ids` onto `primary_id`.
+///
+/// The primary's group becomes `id:PRIMARY+MIRROR...` and the mirrored
+/// displays' own groups are dropped; all other displays keep their layout.
+fn mirror_args(displays: &[Display], primary_id: &str, mirrored_ids: &[String]) -> Result<Vec<String>, String> {
+    let primary = find_display(displays, primary_id).ok_or(format!("Display {} not found", primary_id))?;
+
+    let mut mirrored = Vec::new();
+    for id in mirrored_ids {
+        let display = find_display(displays, id).ok_or(format!("Display {} not found", id))?;
+        if display.id == primary.id {
+            return Err("A display cannot mirror itself".to_string());
+        }
+        mirrored.push(display.id.clone());
+    }
+
+    if mirrored.is_empty() {
+        return Err("No displays to mirror".to_string());
+    }
+
+    let mut group_id = primary.id.clone();
+    for id in &mirrored {
+        group_id.push('+');
+        group_id.push_str(id);
+    }
+
+    Ok(displays
+        .iter()
+        .filter(|d| !mirrored.contains(&d.id))
+        .map(|d| {
+            let args = display_args(std::slice::from_ref(d)).remove(0);
+            if d.id == primary.id {
+                args.replacen(&format!("id:{}", d.id), &format!("id:{}", group_id), 1)
+            } else {
+                args
+            }
+        })
+        .collect())
+}
+
+/// Build arguments that break every mirror group into separate displays.
+///
+/// Former mirrors are switched to their own current mode (if known) and placed
+/// side by side to the right of the existing layout so nothing overlaps.
+fn unmirror_args(displays: &[Display], groups: &[MirrorGroup]) -> Vec<String> {
+    let is_mirror = |d: &Display| groups.iter().any(|g| g.mirrors.contains(&d.id));
+
+    let mut layout: Vec<Display> = displays.iter().filter(|d| !is_mirror(d)).cloned().collect();
+    let mut next_x = layout
+        .iter()
+        .map(|d| d.origin.0 + display_size(d).0)
+        .max()
+        .unwrap_or(0);
+
+    for display in displays.iter().filter(|d| is_mirror(d)) {
+        let mut display = display.clone();
+        if let Some(mode) = display.modes.iter().find(|m| m.current) {
+            display.resolution = mode.resolution.clone();
+            display.hz = mode.hz.or(display.hz);
+        }
+        display.origin = (next_x, 0);
+        next_x += display_size(&display).0;
+        layout.push(display);
+    }
+
+    display_args(&layout)
+}
+
+/// Mirror one or more displays onto a primary display.
+///
+/// # Arguments
+/// * `primary_id` - Display whose content and mode the mirrors show (any ID type)
+/// * `mirrored_ids` - Displays that should mirror the primary (any ID type)
+///
+/// # Returns
+/// * `Ok(())` - Mirroring enabled
+/// * `Err(String)` - Error message if a display is unknown or displayplacer fails
+#[tauri::command]
+pub async fn set_mirroring(primary_id: String, mirrored_ids: Vec<String>) -> Result<(), String> {
+    let current = get_displays().await?;
+    let args = mirror_args(&current.displays, &primary_id, &mirrored_ids)?;
+
+    apply_change(args, Some(current), HistorySource::Manual).await
+}
+
+/// Stop all mirroring, giving every display its own place in the layout.
+///
+/// # Returns
+/// * `Ok(())` - Mirroring disabled (or nothing was mirrored)
+/// * `Err(String)` - Error message if displayplacer fails
+#[tauri::command]
+pub async fn disable_mirroring() -> Result<(), String> {
+    unmirror_displays(HistorySource::Manual).await
+}
+
+/// `disable_mirroring`, recorded in the history under `source`.
+async fn unmirror_displays(source: HistorySource) -> Result<(), String> {
+    let current = get_displays().await?;
+
+    if current.mirror_groups.is_empty() {
+        return Ok(());
+    }
+
+    let args = unmirror_args(&current.displays, &current.mirror_groups);
+    apply_change(args, Some(current), source).await
+}
+
+/// Layout saved by `mirror_all_displays`, restored by `restore_from_mirroring`
+static PRE_MIRROR_LAYOUT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Mirror every other enabled display onto the main one, saving the layout first.
+///
+/// The main display is the one at the origin.
+pub(crate) async fn mirror_all_displays() -> Result<(), String> {
+    let current = get_displays().await?;
+    let enabled: Vec<&Display> = current.displays.iter().filter(|d| d.enabled).collect();
+    let main = enabled
+        .iter()
+        .find(|d| d.origin == (0, 0))
+        .or(enabled.first())
+        .ok_or("No display is turned on")?;
+    let others: Vec<String> = enabled.iter().filter(|d| d.id != main.id).map(|d| d.id.clone()).collect();
+    if others.is_empty() {
+        return Err("There is no other display to mirror".to_string());
+    }
+    let args = mirror_args(&current.displays, &main.id, &others)?;
+
+    if let Ok(mut saved) = PRE_MIRROR_LAYOUT.lock() {
+        *saved = Some(layout_command(&current));
+    }
+    apply_change(args, Some(current), HistorySource::Tray).await
+}
+
+/// Undo `mirror_all_displays` by applying the layout saved before it.
+///
+/// Without a saved layout (e.g. after a restart) mirroring is simply turned off.
+pub(crate) async fn restore_from_mirroring(app: &AppHandle) -> Result<(), String> {
+    let saved = PRE_MIRROR_LAYOUT.lock().ok().and_then(|mut s| s.take());
+
+    match saved {
+        Some(config) => apply_layout(app, config, HistorySource::Tray).await.map(|_| ()),
+        None => unmirror_displays(HistorySource::Tray).await,
+    }
+}
+
+/// Build arguments that change one display's rotation, keeping its mode and origin.
+fn rotation_args(displays: &[Display], display_id: &str, degrees: i32) -> Result<Vec<String>, String> {
+    if ![0, 90, 180, 270].contains(&degrees) {
+        return Err(format!("Invalid rotation {}; expected 0, 90, 180 or 270", degrees));
+    }
+
+    let mut display = find_display(displays, display_id)
+        .ok_or(format!("Display {} not found", display_id))?
+        .clone();
+    display.rotation = degrees;
+
+    Ok(display_args(&[display]))
+}
+
+/// Rotate a single display.
+///
+/// Reads the current configuration first so that only the rotation changes;
+/// the display's resolution, refresh rate and origin are preserved.
+///
+/// # Arguments
+/// * `display_id` - Display to rotate (any ID type)
+/// * `degrees` - New rotation: 0, 90, 180 or 270
+///
+/// # Returns
+/// * `Ok(())` - Rotation applied
+/// * `Err(String)` - Error message if the rotation is invalid, the display is unknown, or displayplacer fails
+#[tauri::command]
+pub async fn set_rotation(display_id: String, degrees: i32) -> Result<(), String> {
+    rotate_display(&display_id, degrees, HistorySource::Manual).await
+}
+
+/// `set_rotation`, recorded in the history under `source`.
+pub(crate) async fn rotate_display(display_id: &str, degrees: i32, source: HistorySource) -> Result<(), String> {
+    let current = get_displays().await?;
+    let args = rotation_args(&current.displays, display_id, degrees)?;
+
+    apply_change(args, Some(current), source).await
+}
+
+/// Build arguments that change one display's mode, keeping the rest of its configuration.
+///
+/// If the display reports a mode table, the requested mode must be in it; the
+/// mode's scaling and color depth are then applied as well.
+fn resolution_args(
+    displays: &[Display],
+    display_id: &str,
+    width: u32,
+    height: u32,
+    hz: Option<u32>,
+) -> Result<Vec<String>, String> {
+    let mut display = find_display(displays, display_id)
+        .ok_or(format!("Display {} not found", display_id))?
+        .clone();
+    let resolution = format!("{}x{}", width, height);
+
+    if !display.modes.is_empty() {
+        let mode = display
+            .modes
+            .iter()
+            .filter(|m| m.resolution == resolution && (hz.is_none() || m.hz == hz))
+            .max_by_key(|m| (m.scaling == display.scaling.unwrap_or(false), m.hz))
+            .ok_or(match hz {
+                Some(hz) => format!("Display {} does not support {}@{}Hz", display.id, resolution, hz),
+                None => format!("Display {} does not support {}", display.id, resolution),
+            })?;
+        display.hz = mode.hz;
+        display.color_depth = mode.color_depth.or(display.color_depth);
+        display.scaling = Some(mode.scaling);
+    } else if hz.is_some() {
+        display.hz = hz;
+    }
+    display.resolution = resolution;
+
+    Ok(display_args(&[display]))
+}
+
+/// Change a single display's resolution and refresh rate.
+///
+/// Reads the current configuration first and merges the new mode into it, so
+/// callers don't need to reconstruct the full layout string.
+///
+/// # Arguments
+/// * `display_id` - Display to change (any ID type)
+/// * `width` - Horizontal resolution in points
+/// * `height` - Vertical resolution in points
+/// * `hz` - Refresh rate; if omitted, the highest rate available for the resolution is used
+///
+/// # Returns
+/// * `Ok(())` - Mode applied
+/// * `Err(String)` - Error message if the display is unknown, the mode unsupported, or displayplacer fails
+#[tauri::command]
+pub async fn set_resolution(display_id: String, width: u32, height: u32, hz: Option<u32>) -> Result<(), String> {
+    change_resolution(&display_id, width, height, hz, HistorySource::Manual).await
+}
+
+/// `set_resolution`, recorded in the history under `source`.
+pub(crate) async fn change_resolution(
+    display_id: &str,
+    width: u32,
+    height: u32,
+    hz: Option<u32>,
+    source: HistorySource,
+) -> Result<(), String> {
+    let current = get_displays().await?;
+    let args = resolution_args(&current.displays, display_id, width, height, hz)?;
+
+    apply_change(args, Some(current), source).await
+}
+
+/// Check a configuration against the modes each display currently supports.
+///
+/// Does not execute anything; returns the configuration that would actually be
+/// applied along with a notice for every mode that had to be downgraded.
+///
+/// # Arguments
+/// * `config` - displayplacer configuration string or full command
+///
+/// # Returns
+/// * `Ok(ModeCheck)` - Adjusted configuration and substitution notices
+/// * `Err(String)` - Error message if the current displays cannot be read
+#[tauri::command]
+pub async fn check_config_modes(config: String) -> Result<ModeCheck, String> {
+    let current = get_displays().await?;
+    Ok(downgrade_unsupported_modes(&config, &current.displays))
+}
+
+/// Validate a configuration without executing displayplacer.
+///
+/// # Arguments
+/// * `config` - displayplacer configuration string or full command
+///
+/// # Returns
+/// * `Ok(Vec<ConfigWarning>)` - Problems found; empty if the configuration looks safe to apply
+/// * `Err(String)` - Error message if the current displays cannot be read
+#[tauri::command]
+pub async fn validate_config(config: String) -> Result<Vec<ConfigWarning>, String> {
+    let current = get_displays().await?;
+    Ok(validate_layout(&config, &current.displays))
+}
+
+/// Layouts from before a display was turned off with `close_gaps_on_toggle`, by display ID
+static PRE_TOGGLE_LAYOUTS: Mutex<Vec<(String, Vec<Display>)>> = Mutex::new(Vec::new());
+
+/// Build arguments that turn a display on or off, closing or reopening its gap.
+///
+/// Turning a display off moves the others together. Turning it back on
+/// restores `saved`, the layout from before it was turned off, as long as the
+/// same other displays are still on; otherwise it is just turned on.
+fn toggle_args(displays: &[Display], id: &str, enabled: bool, saved: Option<&[Display]>) -> Vec<String> {
+    let toggle = format!("id:{} enabled:{}", id, enabled);
+    let enabled_ids = |layout: &[Display]| {
+        let mut ids: Vec<String> = layout
+            .iter()
+            .filter(|d| d.enabled && d.id != id)
+            .map(|d| d.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    if !enabled {
+        let closed = crate::layout::close_gap(displays, id);
+        let moved: Vec<Display> = closed
+            .into_iter()
+            .filter(|d| d.enabled && displays.iter().any(|c| c.id == d.id && c.origin != d.origin))
+            .collect();
+        return std::iter::once(toggle).chain(display_args(&moved)).collect();
+    }
+
+    match saved.filter(|saved| enabled_ids(saved) == enabled_ids(displays)) {
+        Some(saved) => {
+            let restored: Vec<Display> = saved.iter().filter(|d| d.enabled).cloned().collect();
+            display_args(&restored)
+        }
+        None => vec![toggle],
+    }
 }
 
 /// Toggle a display's enabled/disabled state.
@@ -126,8 +1277,12 @@ pub async fn apply_config(config: String) -> Result<(), String> {
 /// Enables or disables a specific display using its unique identifier.
 /// This is the core function for turning displays on and off programmatically.
 ///
+/// With the `close_gaps_on_toggle` setting, the other displays are moved to
+/// close the gap a turned-off display leaves, and moved back when it is
+/// turned on again.
+///
 /// # Arguments
-/// * `id` - Unique display identifier (UUID format)
+/// * `id` - Display identifier (persistent, contextual or serial screen ID)
 /// * `enabled` - `true` to enable the display, `false` to disable
 ///
 /// # Returns
@@ -138,32 +1293,120 @@ pub async fn apply_config(config: String) -> Result<(), String> {
 /// The frontend should implement safety checks to prevent disabling all displays.
 /// This function will execute the command regardless of safety implications.
 ///
-/// # Example
-/// ```no_run
-/// // Turn off a display
-/// toggle_display_enabled("37D8832A-2D66-02CA-B9F7-8F30A301B230".to_string(), false).await?;
+/// # Example
+/// ```no_run
+/// // Turn off a display
+/// toggle_display_enabled("37D8832A-2D66-02CA-B9F7-8F30A301B230".to_string(), false).await?;
+///
+/// // Turn on a display
+/// toggle_display_enabled("37D8832A-2D66-02CA-B9F7-8F30A301B230".to_string(), true).await?;
+/// ```
+#[tauri::command]
+pub async fn toggle_display_enabled(id: String, enabled: bool) -> Result<(), String> {
+    set_display_enabled(id, enabled, HistorySource::Manual).await
+}
+
+/// `toggle_display_enabled`, recorded in the history under `source`.
+async fn set_display_enabled(id: String, enabled: bool, source: HistorySource) -> Result<(), String> {
+    // Accept any ID type; fall back to the given ID if displays can't be read
+    let current = get_displays().await.ok();
+    let id = match &current {
+        Some(current) => find_display(&current.displays, &id).map_or(id, |d| d.id.clone()),
+        None => id,
+    };
+
+    let close_gaps = current.as_ref().filter(|_| settings::current().close_gaps_on_toggle);
+    let args = match close_gaps {
+        Some(current) => {
+            let layouts = PRE_TOGGLE_LAYOUTS.lock().map_err(|_| "Layout lock poisoned".to_string())?;
+            let saved = layouts.iter().find(|(saved_id, _)| *saved_id == id).map(|(_, layout)| layout.as_slice());
+            toggle_args(&current.displays, &id, enabled, saved)
+        }
+        None => vec![format!("id:{} enabled:{}", id, enabled)],
+    };
+    // Only touch the saved layouts once the toggle has happened
+    let before = close_gaps.map(|current| current.displays.clone());
+
+    apply_change(args, current, source).await?;
+
+    if let Some(before) = before {
+        let mut layouts = PRE_TOGGLE_LAYOUTS.lock().map_err(|_| "Layout lock poisoned".to_string())?;
+        layouts.retain(|(saved_id, _)| *saved_id != id);
+        if !enabled {
+            layouts.push((id, before));
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn a display off if it is on and on if it is off.
+///
+/// Refuses to turn off the last enabled display, which would leave no way to
+/// see the screen.
+///
+/// # Returns
+/// * `Ok(bool)` - Whether the display is on now
+/// * `Err(String)` - Error message if the display isn't connected, is the only
+///   one on, or displayplacer fails
+pub(crate) async fn toggle_display(display_id: &str, source: HistorySource) -> Result<bool, String> {
+    let current = get_displays().await?;
+    let display = find_display(&current.displays, display_id)
+        .ok_or_else(|| format!("Display {} is not connected", display_id))?;
+
+    let enable = !display.enabled;
+    if !enable && current.displays.iter().filter(|d| d.enabled).count() <= 1 {
+        return Err("Cannot turn off the only enabled display".to_string());
+    }
+    set_display_enabled(display.id.clone(), enable, source).await?;
+    Ok(enable)
+}
+
+/// Build the argument that turns off every enabled display except the built-in panel.
+fn external_disable_args(displays: &[Display]) -> Result<Vec<String>, String> {
+    let builtin = displays
+        .iter()
+        .find(|d| d.builtin)
+        .ok_or("No built-in display found")?;
+    if !builtin.enabled {
+        return Err("The built-in display is turned off".to_string());
+    }
+
+    let args: Vec<String> = displays
+        .iter()
+        .filter(|d| !d.builtin && d.enabled)
+        .map(|d| format!("id:{} enabled:false", d.id))
+        .collect();
+    if args.is_empty() {
+        return Err("No external displays are turned on".to_string());
+    }
+    Ok(args)
+}
+
+/// Turn off every external display, keeping only the built-in panel on.
+///
+/// All displays are turned off in a single displayplacer call, e.g. before
+/// unplugging a dock or for a focus session.
 ///
-/// // Turn on a display
-/// toggle_display_enabled("37D8832A-2D66-02CA-B9F7-8F30A301B230".to_string(), true).await?;
-/// ```
+/// # Returns
+/// * `Ok(usize)` - Number of displays turned off
+/// * `Err(String)` - Error message if there is no enabled built-in display, no
+///   external display is on, or displayplacer fails
 #[tauri::command]
-pub async fn toggle_display_enabled(id: String, enabled: bool) -> Result<(), String> {
-    let enabled_str = if enabled { "true" } else { "false" };
-    let config = format!("id:{} enabled:{}", id, enabled_str);
+pub async fn disable_external_displays() -> Result<usize, String> {
+    turn_off_external_displays(HistorySource::Manual).await
+}
 
-    let output = Command::new("displayplacer")
-        .arg(&config)
-        .output()
-        .map_err(|e| format!("Failed to execute displayplacer: {}", e))?;
+/// `disable_external_displays`, recorded in the history under `source`.
+pub(crate) async fn turn_off_external_displays(source: HistorySource) -> Result<usize, String> {
+    let current = get_displays().await?;
+    let args = external_disable_args(&current.displays)?;
+    let count = args.len();
 
-    if !output.status.success() {
-        return Err(format!(
-            "displayplacer failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+    apply_change(args, Some(current), source).await?;
 
-    Ok(())
+    log::info!("[Display] Turned off {} external displays", count);
+    Ok(count)
 }
 
 /// Parse displayplacer output to extract display information.
@@ -174,6 +1417,8 @@ pub async fn toggle_display_enabled(id: String, enabled: bool) -> Result<(), Str
 ///
 /// # Arguments
 /// * `output` - Raw stdout from displayplacer list command
+/// * `version` - Release that produced the output, if known; selects the parsing strategy
+/// * `notes` - Receives a description of each parser decision (for developer-mode traces)
 ///
 /// # Returns
 /// * `Ok(Vec<Display>)` - List of parsed displays
@@ -183,15 +1428,23 @@ pub async fn toggle_display_enabled(id: String, enabled: bool) -> Result<(), Str
 /// The parser:
 /// 1. Searches for "Execute the command below" marker
 /// 2. Filters lines that start with "displayplacer" and contain both "id:" and "origin:"
-/// 3. Extracts display parameters from quoted configuration strings
-fn parse_displayplacer_output(output: &str) -> Result<Vec<Display>, String> {
+/// 3. Extracts display parameters from each screen group (quoted, or split at
+///    `id:` for releases that don't quote them)
+fn parse_displayplacer_output(
+    output: &str,
+    version: Option<DisplayplacerVersion>,
+    notes: &mut Vec<String>,
+) -> Result<Vec<Display>, String> {
     let mut displays = Vec::new();
     let mut found_execute_line = false;
+    let format = OutputFormat::for_version(version, output);
+    notes.push(format!("Parsing output of version {:?} as {:?}", version, format));
 
     // Look for the "Execute the command below" section
-    for line in output.lines() {
+    for (line_no, line) in output.lines().enumerate() {
         // Check if we found the "Execute the command below" marker
         if line.contains("Execute the command below") {
+            notes.push(format!("Found execute marker on line {}", line_no + 1));
             found_execute_line = true;
             continue;
         }
@@ -205,31 +1458,548 @@ fn parse_displayplacer_output(output: &str) -> Result<Vec<Display>, String> {
             // Extract display configurations from the command
             // Example: displayplacer "id:1 res:2560x1440 origin:(0,0) degree:0"
 
-            let parts: Vec<&str> = line.split('"').collect();
-            for part in parts.iter() {
+            for part in format.screen_groups(line) {
                 if part.contains("id:") && part.contains("origin:") {
-                    if let Some(display) = parse_display_string(part) {
-                        displays.push(display);
+                    match parse_display_string(&part) {
+                        Some(display) => {
+                            notes.push(format!("Parsed display {} from \"{}\"", display.id, part));
+                            // Mirrored displays share one group ("id:A+B"); list each separately
+                            for id in display.id.split('+') {
+                                displays.push(Display {
+                                    id: id.to_string(),
+                                    ..display.clone()
+                                });
+                            }
+                        }
+                        None => notes.push(format!("Skipped group without id: \"{}\"", part)),
                     }
                 }
             }
         }
     }
 
+    if !found_execute_line {
+        notes.push("No execute marker found".to_string());
+    }
+
     if displays.is_empty() {
         return Err("No displays found in displayplacer output".to_string());
     }
 
+    // Attach each display's IDs and mode table from the per-screen section
+    let sections = parse_screen_sections(output);
+    for display in displays.iter_mut() {
+        match find_section(&sections, &display.id) {
+            Some(section) => {
+                notes.push(format!("Attached {} modes to display {}", section.modes.len(), display.id));
+                display.persistent_id = Some(section.persistent_id.clone());
+                display.contextual_id = section.contextual_id.clone();
+                display.serial_id = section.serial_id.clone();
+                display.modes = section.modes.clone();
+            }
+            None => notes.push(format!("No screen section found for display {}", display.id)),
+        }
+    }
+
     Ok(displays)
 }
 
+/// Parse mirror groups (`id:A+B`) from the displayplacer command in list output.
+fn parse_mirror_groups(output: &str, version: Option<DisplayplacerVersion>) -> Vec<MirrorGroup> {
+    let Some((_, command)) = output.split_once("Execute the command below") else {
+        return Vec::new();
+    };
+    let format = OutputFormat::for_version(version, output);
+
+    command
+        .lines()
+        .filter(|line| line.trim().starts_with("displayplacer"))
+        .flat_map(|line| format.screen_groups(line))
+        .filter_map(|part| {
+            let id = part.split_whitespace().find_map(|p| p.strip_prefix("id:"))?;
+            let mut ids = id.split('+').map(str::to_string);
+            let primary = ids.next()?;
+            let mirrors: Vec<String> = ids.collect();
+            (!mirrors.is_empty()).then_some(MirrorGroup { primary, mirrors })
+        })
+        .collect()
+}
+
+/// Details from a screen's section of `displayplacer list` output.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ScreenSection {
+    /// Persistent screen ID (UUID)
+    pub persistent_id: String,
+    /// Contextual screen ID (e.g. "1")
+    pub contextual_id: Option<String>,
+    /// Serial screen ID (e.g. "s4251086178")
+    pub serial_id: Option<String>,
+    /// Screen type description (e.g. "27 inch external screen")
+    pub screen_type: Option<String>,
+    /// Modes listed under "Resolutions for rotation N:"
+    pub modes: Vec<DisplayMode>,
+}
+
+/// Parse the per-screen sections from displayplacer output.
+///
+/// Each screen section starts with `Persistent screen id:` and is followed by
+/// its other IDs, its type and its mode table. The returned map is keyed by the
+/// lowercase persistent screen ID.
+fn parse_screen_sections(output: &str) -> HashMap<String, ScreenSection> {
+    let mut sections: HashMap<String, ScreenSection> = HashMap::new();
+    let mut current_id: Option<String> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(id) = trimmed.strip_prefix("Persistent screen id:") {
+            let key = id.trim().to_lowercase();
+            sections.entry(key.clone()).or_insert_with(|| ScreenSection {
+                persistent_id: id.trim().to_string(),
+                ..Default::default()
+            });
+            current_id = Some(key);
+        } else if trimmed.contains("Execute the command below") {
+            current_id = None;
+        } else if let Some(section) = current_id.as_ref().and_then(|id| sections.get_mut(id)) {
+            if let Some(contextual) = trimmed.strip_prefix("Contextual screen id:") {
+                section.contextual_id = Some(contextual.trim().to_string());
+            } else if let Some(serial) = trimmed.strip_prefix("Serial screen id:") {
+                section.serial_id = Some(serial.trim().to_string());
+            } else if let Some(screen_type) = trimmed.strip_prefix("Type:") {
+                section.screen_type = Some(screen_type.trim().to_string());
+            } else if let Some(mode) = parse_mode_line(trimmed) {
+                section.modes.push(mode);
+            }
+        }
+    }
+
+    sections
+}
+
+/// Find the screen section for a display ID of any type.
+pub(crate) fn find_section<'a>(sections: &'a HashMap<String, ScreenSection>, id: &str) -> Option<&'a ScreenSection> {
+    sections.get(&id.to_lowercase()).or_else(|| {
+        sections
+            .values()
+            .find(|s| s.contextual_id.as_deref() == Some(id) || s.serial_id.as_deref() == Some(id))
+    })
+}
+
+/// Parse a single mode line.
+///
+/// # Arguments
+/// * `line` - Line like "mode 12: res:1920x1080 hz:60 color_depth:8 scaling:on <-- current mode"
+///
+/// # Returns
+/// * `Some(DisplayMode)` - Successfully parsed mode
+/// * `None` - If the line is not a mode line
+fn parse_mode_line(line: &str) -> Option<DisplayMode> {
+    let rest = line.strip_prefix("mode ")?;
+    let (index, params) = rest.split_once(':')?;
+    let index = index.trim().parse().ok()?;
+
+    let mut mode = DisplayMode {
+        index,
+        resolution: String::new(),
+        hz: None,
+        color_depth: None,
+        scaling: false,
+        current: params.contains("current mode"),
+    };
+
+    for part in params.split_whitespace() {
+        if let Some(res) = part.strip_prefix("res:") {
+            mode.resolution = res.to_string();
+        } else if let Some(hz) = part.strip_prefix("hz:") {
+            mode.hz = hz.parse().ok();
+        } else if let Some(depth) = part.strip_prefix("color_depth:") {
+            mode.color_depth = depth.parse().ok();
+        } else if let Some(scaling) = part.strip_prefix("scaling:") {
+            mode.scaling = scaling == "on";
+        }
+    }
+
+    if mode.resolution.is_empty() {
+        None
+    } else {
+        Some(mode)
+    }
+}
+
+/// Parse a resolution string in format "WIDTHxHEIGHT".
+pub(crate) fn parse_resolution(s: &str) -> Option<(u32, u32)> {
+    let (w, h) = s.split_once('x')?;
+    Some((w.parse().ok()?, h.parse().ok()?))
+}
+
+/// Find the supported mode closest to the requested resolution and refresh rate.
+///
+/// Prefers an exact resolution match; otherwise picks the largest mode that fits
+/// within the requested resolution, falling back to the smallest mode available.
+/// Among modes of the chosen resolution, the exact refresh rate wins, then the
+/// highest rate not above the requested one, then the closest.
+///
+/// # Returns
+/// * `Some(&DisplayMode)` - Nearest supported mode
+/// * `None` - If the display has no modes or the resolution cannot be parsed
+fn nearest_mode<'a>(modes: &'a [DisplayMode], resolution: &str, hz: Option<u32>) -> Option<&'a DisplayMode> {
+    let (width, height) = parse_resolution(resolution)?;
+    let sized: Vec<(&DisplayMode, (u32, u32))> = modes
+        .iter()
+        .filter_map(|m| parse_resolution(&m.resolution).map(|r| (m, r)))
+        .collect();
+
+    let target = if sized.iter().any(|(_, r)| *r == (width, height)) {
+        (width, height)
+    } else {
+        sized
+            .iter()
+            .filter(|(_, (w, h))| *w <= width && *h <= height)
+            .max_by_key(|(_, (w, h))| w * h)
+            .or_else(|| sized.iter().min_by_key(|(_, (w, h))| w * h))
+            .map(|(_, r)| *r)?
+    };
+
+    let candidates: Vec<&DisplayMode> = sized
+        .iter()
+        .filter(|(_, r)| *r == target)
+        .map(|(m, _)| *m)
+        .collect();
+
+    let Some(hz) = hz else {
+        return candidates.into_iter().max_by_key(|m| m.hz.unwrap_or(0));
+    };
+
+    candidates
+        .iter()
+        .find(|m| m.hz == Some(hz))
+        .or_else(|| {
+            candidates
+                .iter()
+                .filter(|m| m.hz.is_some_and(|h| h <= hz))
+                .max_by_key(|m| m.hz)
+        })
+        .or_else(|| {
+            candidates
+                .iter()
+                .min_by_key(|m| m.hz.map_or(u32::MAX, |h| h.abs_diff(hz)))
+        })
+        .copied()
+}
+
+/// Find a connected display by any of its persistent, contextual or serial IDs.
+///
+/// IDs are compared case-insensitively. The ID displayplacer currently uses
+/// takes precedence over the alternative IDs.
+pub(crate) fn find_display<'a>(displays: &'a [Display], id: &str) -> Option<&'a Display> {
+    displays.iter().find(|d| d.id.eq_ignore_ascii_case(id)).or_else(|| {
+        displays.iter().find(|d| {
+            [&d.persistent_id, &d.contextual_id, &d.serial_id]
+                .iter()
+                .any(|alt| alt.as_deref().is_some_and(|alt| alt.eq_ignore_ascii_case(id)))
+        })
+    })
+}
+
+/// Apply `rewrite` to every per-display group in a configuration.
+///
+/// Accepts either a full command with quoted groups or a single unquoted
+/// group; everything outside the groups is preserved as-is.
+pub(crate) fn map_config_groups(config: &str, mut rewrite: impl FnMut(&str) -> String) -> String {
+    if config.contains('"') {
+        config
+            .split('"')
+            .map(|part| if part.contains("id:") { rewrite(part) } else { part.to_string() })
+            .collect::<Vec<_>>()
+            .join("\"")
+    } else {
+        rewrite(config.trim())
+    }
+}
+
+/// Rewrite every `id:` in a configuration to the ID displayplacer currently uses.
+///
+/// Presets may have been saved with a persistent, contextual or serial ID;
+/// contextual IDs in particular change when displays are reconnected. IDs that
+/// don't match a connected display are left untouched.
+fn resolve_config_ids(config: &str, displays: &[Display]) -> String {
+    map_config_groups(config, |group| {
+        group
+            .split_whitespace()
+            .map(|p| match p.strip_prefix("id:").and_then(|id| find_display(displays, id)) {
+                Some(display) => format!("id:{}", display.id),
+                None => p.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    })
+}
+
+/// Replace unsupported `res:`/`hz:` values with the nearest supported mode.
+///
+/// Displays that are not connected, have no mode table, or whose requested
+/// mode is supported are left untouched.
+///
+/// # Arguments
+/// * `config` - Configuration string or full displayplacer command
+/// * `displays` - Currently connected displays with their mode tables
+fn downgrade_unsupported_modes(config: &str, displays: &[Display]) -> ModeCheck {
+    let mut notices = Vec::new();
+
+    let rewrite_group = |group: &str, notices: &mut Vec<ModeNotice>| -> String {
+        let params: Vec<&str> = group.split_whitespace().collect();
+        let find = |prefix: &str| params.iter().find_map(|p| p.strip_prefix(prefix));
+
+        let (Some(id), Some(res)) = (find("id:"), find("res:")) else {
+            return group.to_string();
+        };
+        let hz = find("hz:").and_then(|h| h.parse::<u32>().ok());
+
+        let Some(display) = find_display(displays, id) else {
+            return group.to_string();
+        };
+        if display.modes.is_empty() {
+            return group.to_string();
+        }
+
+        let supported = display
+            .modes
+            .iter()
+            .any(|m| m.resolution == res && (hz.is_none() || m.hz == hz));
+        if supported {
+            return group.to_string();
+        }
+
+        let Some(mode) = nearest_mode(&display.modes, res, hz) else {
+            return group.to_string();
+        };
+
+        let describe = |res: &str, hz: Option<u32>| match hz {
+            Some(hz) => format!("{}@{}", res, hz),
+            None => res.to_string(),
+        };
+        let requested = describe(res, hz);
+        let applied = describe(&mode.resolution, hz.and(mode.hz));
+        notices.push(ModeNotice {
+            display_id: display.id.clone(),
+            message: format!(
+                "Display {} no longer supports {}; using {} instead",
+                display.id, requested, applied
+            ),
+            requested,
+            applied,
+        });
+
+        params
+            .iter()
+            .map(|p| {
+                if p.starts_with("res:") {
+                    format!("res:{}", mode.resolution)
+                } else if p.starts_with("hz:") {
+                    mode.hz.map_or_else(|| p.to_string(), |h| format!("hz:{}", h))
+                } else {
+                    p.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let config = map_config_groups(config, |group| rewrite_group(group, &mut notices));
+
+    ModeCheck { config, notices }
+}
+
+/// Check a configuration against the connected displays without applying it.
+///
+/// Every display group must reference a connected display, request a mode the
+/// display advertises and not overlap any other enabled display.
+pub(crate) fn validate_layout(config: &str, displays: &[Display]) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    // Enabled displays with the origin and size they would get
+    let mut placed: Vec<Display> = Vec::new();
+
+    map_config_groups(config, |group| {
+        let Some(target) = parse_display_string(group) else {
+            warnings.push(ConfigWarning {
+                issue: ConfigIssue::Unparsable,
+                display_id: None,
+                message: format!("\"{}\" has no display id", group.trim()),
+            });
+            return group.to_string();
+        };
+
+        let mut members = Vec::new();
+        for id in target.id.split('+') {
+            match find_display(displays, id) {
+                Some(display) => members.push(display),
+                None => warnings.push(ConfigWarning {
+                    issue: ConfigIssue::UnknownDisplay,
+                    display_id: Some(id.to_string()),
+                    message: format!("Display {} is not connected", id),
+                }),
+            }
+        }
+
+        let requested = parse_resolution(&target.resolution);
+        for display in &members {
+            let supported = display.modes.is_empty()
+                || requested.is_none()
+                || display.modes.iter().any(|m| {
+                    parse_resolution(&m.resolution) == requested && target.hz.is_none_or(|hz| m.hz == Some(hz))
+                });
+            if !supported {
+                let mode = match target.hz {
+                    Some(hz) => format!("{}@{}", target.resolution, hz),
+                    None => target.resolution.clone(),
+                };
+                warnings.push(ConfigWarning {
+                    issue: ConfigIssue::UnsupportedMode,
+                    display_id: Some(display.id.clone()),
+                    message: format!("{} does not support {}", display.name.as_deref().unwrap_or(&display.id), mode),
+                });
+            }
+        }
+
+        let disabled = group.split_whitespace().any(|p| p == "enabled:false");
+        if let (Some(display), false, Some(_)) = (members.first(), disabled, requested) {
+            placed.push(Display {
+                resolution: target.resolution.clone(),
+                origin: target.origin,
+                rotation: target.rotation,
+                ..(*display).clone()
+            });
+        }
+
+        group.to_string()
+    });
+
+    for (i, a) in placed.iter().enumerate() {
+        let a_size = display_size(a);
+        for b in &placed[i + 1..] {
+            let b_size = display_size(b);
+            let overlaps = a.origin.0 < b.origin.0 + b_size.0
+                && b.origin.0 < a.origin.0 + a_size.0
+                && a.origin.1 < b.origin.1 + b_size.1
+                && b.origin.1 < a.origin.1 + a_size.1;
+            if overlaps {
+                warnings.push(ConfigWarning {
+                    issue: ConfigIssue::Overlap,
+                    display_id: Some(b.id.clone()),
+                    message: format!("Displays {} and {} overlap", a.id, b.id),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Compare a configuration with the current layout.
+///
+/// Only parameters the configuration actually sets are compared, so a preset
+/// without `hz:` doesn't report a refresh rate change. Display IDs of any type
+/// are matched against the connected displays.
+pub(crate) fn diff_layout(config: &str, current: &DisplayConfig) -> Vec<DisplayDifference> {
+    let mut differences = Vec::new();
+    let mut referenced = Vec::new();
+
+    let mut push = |display_id: &str, name: Option<String>, change| {
+        differences.push(DisplayDifference {
+            display_id: display_id.to_string(),
+            name,
+            change,
+        })
+    };
+
+    map_config_groups(config, |group| {
+        let Some(target) = parse_display_string(group) else {
+            return group.to_string();
+        };
+
+        // Resolve every member of a mirror group (id:A+B) to a connected display
+        let mut members = Vec::new();
+        for id in target.id.split('+') {
+            match find_display(&current.displays, id) {
+                Some(display) => members.push(display),
+                None => push(id, None, DisplayChange::Missing),
+            }
+        }
+        referenced.extend(members.iter().map(|d| d.id.clone()));
+
+        let Some(display) = members.first().copied() else {
+            return group.to_string();
+        };
+        let has = |key: &str| group.split_whitespace().any(|p| p.starts_with(key));
+        let change = |c| (display.id.as_str(), display.name.clone(), c);
+        let mut changes = Vec::new();
+
+        if has("res:") && parse_resolution(&target.resolution) != parse_resolution(&display.resolution) {
+            changes.push(change(DisplayChange::ResolutionChanged {
+                from: display.resolution.clone(),
+                to: target.resolution.clone(),
+            }));
+        }
+        if let Some(hz) = target.hz.filter(|hz| display.hz != Some(*hz)) {
+            changes.push(change(DisplayChange::RefreshRateChanged { from: display.hz, to: hz }));
+        }
+        if has("origin:") && target.origin != display.origin {
+            changes.push(change(DisplayChange::OriginChanged {
+                from: display.origin,
+                to: target.origin,
+            }));
+        }
+        if has("degree:") && target.rotation != display.rotation {
+            changes.push(change(DisplayChange::RotationChanged {
+                from: display.rotation,
+                to: target.rotation,
+            }));
+        }
+        let enabled = group.split_whitespace().find_map(|p| p.strip_prefix("enabled:")).map(|v| v == "true");
+        if let Some(enabled) = enabled.filter(|e| *e != display.enabled) {
+            changes.push(change(DisplayChange::EnabledChanged {
+                from: display.enabled,
+                to: enabled,
+            }));
+        }
+
+        let mut to: Vec<String> = members[1..].iter().map(|d| d.id.clone()).collect();
+        let mut from = current
+            .mirror_groups
+            .iter()
+            .find(|g| g.primary == display.id)
+            .map(|g| g.mirrors.clone())
+            .unwrap_or_default();
+        to.sort();
+        from.sort();
+        if from != to {
+            changes.push(change(DisplayChange::MirroringChanged { from, to }));
+        }
+
+        for (id, name, c) in changes {
+            push(id, name, c);
+        }
+        group.to_string()
+    });
+
+    for display in &current.displays {
+        if !referenced.contains(&display.id) {
+            push(&display.id, display.name.clone(), DisplayChange::NotInConfig);
+        }
+    }
+
+    differences
+}
+
 /// Parse a single display configuration string.
 ///
 /// Extracts display parameters from a displayplacer configuration string
-/// containing id, resolution, origin, rotation, and enabled state.
+/// containing id, resolution, refresh rate, color depth, scaling, origin,
+/// rotation, and enabled state.
 ///
 /// # Arguments
-/// * `config` - Configuration string like "id:xxx res:2560x1440 origin:(0,0) degree:0"
+/// * `config` - Configuration string like "id:xxx res:2560x1440 hz:60 color_depth:8 scaling:on origin:(0,0) degree:0"
 ///
 /// # Returns
 /// * `Some(Display)` - Successfully parsed display
@@ -243,9 +2013,13 @@ fn parse_displayplacer_output(output: &str) -> Result<Vec<Display>, String> {
 fn parse_display_string(config: &str) -> Option<Display> {
     let mut id = String::new();
     let mut resolution = String::new();
+    let mut hz = None;
+    let mut color_depth = None;
+    let mut scaling = None;
     let mut origin = (0, 0);
     let mut rotation = 0;
-    let enabled = !config.contains("disabled");
+    // Older releases only mark disabled screens; newer ones print enabled:true|false
+    let mut enabled = !config.contains("disabled");
 
     // Split by spaces and parse each parameter
     for part in config.split_whitespace() {
@@ -253,6 +2027,14 @@ fn parse_display_string(config: &str) -> Option<Display> {
             id = part.strip_prefix("id:").unwrap_or("").to_string();
         } else if part.starts_with("res:") {
             resolution = part.strip_prefix("res:").unwrap_or("").to_string();
+        } else if let Some(value) = part.strip_prefix("hz:") {
+            hz = value.parse().ok();
+        } else if let Some(value) = part.strip_prefix("color_depth:") {
+            color_depth = value.parse().ok();
+        } else if let Some(value) = part.strip_prefix("scaling:") {
+            scaling = Some(value == "on");
+        } else if let Some(value) = part.strip_prefix("enabled:") {
+            enabled = value != "false";
         } else if part.starts_with("origin:") {
             // Parse origin:(x,y)
             let origin_str = part.strip_prefix("origin:").unwrap_or("");
@@ -271,10 +2053,21 @@ fn parse_display_string(config: &str) -> Option<Display> {
     if !id.is_empty() {
         Some(Display {
             id,
+            persistent_id: None,
+            contextual_id: None,
+            serial_id: None,
             resolution,
+            hz,
+            color_depth,
+            scaling,
             origin,
             rotation,
             enabled,
+            modes: Vec::new(),
+            name: None,
+            vendor: None,
+            builtin: false,
+            virtual_display: false,
         })
     } else {
         None
@@ -316,6 +2109,26 @@ fn parse_coordinates(s: &str) -> Option<(i32, i32)> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_find_binary() {
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("dpui-binary-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("displayplacer");
+        fs::write(&binary, b"").unwrap();
+        let fallback = dir.to_str().unwrap();
+
+        assert_eq!(find_binary(None, None, &[fallback]), Some(binary.clone()));
+        assert_eq!(find_binary(None, Some(dir.as_os_str()), &[]), Some(binary.clone()));
+        assert_eq!(find_binary(Some(binary.to_str().unwrap()), None, &[]), Some(binary.clone()));
+        // A custom path that doesn't exist isn't replaced by another install
+        assert_eq!(find_binary(Some("/nonexistent/displayplacer"), None, &[fallback]), None);
+        assert_eq!(find_binary(None, None, &["/nonexistent"]), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_parse_coordinates() {
         assert_eq!(parse_coordinates("(0,0)"), Some((0, 0)));
@@ -334,4 +2147,392 @@ mod tests {
         assert_eq!(display.rotation, 0);
         assert!(display.enabled);
     }
+
+    #[test]
+    fn test_parse_display_string_mode_params() {
+        let config = "id:1 res:1920x1080 hz:144 color_depth:8 enabled:true scaling:off origin:(-1920,0) degree:90";
+        let display = parse_display_string(config).unwrap();
+
+        assert_eq!(display.hz, Some(144));
+        assert_eq!(display.color_depth, Some(8));
+        assert_eq!(display.scaling, Some(false));
+        assert_eq!(display.origin, (-1920, 0));
+        assert_eq!(display.rotation, 90);
+
+        let display = parse_display_string("id:1 res:1920x1080 origin:(0,0) degree:0").unwrap();
+        assert_eq!(display.hz, None);
+        assert_eq!(display.color_depth, None);
+        assert_eq!(display.scaling, None);
+    }
+
+    const LIST_OUTPUT: &str = "Persistent screen id: AAAA-1
+Contextual screen id: 1
+Serial screen id: s4251086178
+Type: 27 inch external screen
+Resolutions for rotation 0:
+  mode 0: res:3840x2160 hz:60 color_depth:8
+  mode 1: res:2560x1440 hz:60 color_depth:8 scaling:on <-- current mode
+  mode 2: res:2560x1440 hz:30 color_depth:8 scaling:on
+  mode 3: res:1920x1080 hz:60 color_depth:8
+
+Execute the command below to set your screens to the current arrangement:
+
+displayplacer \"id:AAAA-1 res:2560x1440 hz:60 color_depth:8 scaling:on origin:(0,0) degree:0\"
+";
+
+    #[test]
+    fn test_parse_screen_sections() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+        let modes = &displays[0].modes;
+
+        assert_eq!(modes.len(), 4);
+        assert_eq!(modes[1].resolution, "2560x1440");
+        assert_eq!(modes[1].hz, Some(60));
+        assert!(modes[1].scaling);
+        assert!(modes[1].current);
+        assert!(!modes[0].current);
+    }
+
+    #[test]
+    fn test_display_args_round_trip() {
+        let config = "id:AAAA-1 res:2560x1440 hz:60 color_depth:8 enabled:true scaling:on origin:(-2560,0) degree:90";
+        let display = parse_display_string(config).unwrap();
+
+        assert_eq!(display_args(&[display]), vec![config.to_string()]);
+    }
+
+    #[test]
+    fn test_resolve_config_ids() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+
+        assert_eq!(displays[0].persistent_id.as_deref(), Some("AAAA-1"));
+        assert_eq!(displays[0].contextual_id.as_deref(), Some("1"));
+        assert_eq!(displays[0].serial_id.as_deref(), Some("s4251086178"));
+
+        assert_eq!(
+            resolve_config_ids("displayplacer \"id:1 res:1920x1080\" \"id:s4251086178 res:1920x1080\"", &displays),
+            "displayplacer \"id:AAAA-1 res:1920x1080\" \"id:AAAA-1 res:1920x1080\""
+        );
+        assert_eq!(resolve_config_ids("id:unknown res:1920x1080", &displays), "id:unknown res:1920x1080");
+    }
+
+    /// Output of a release before 1.2, which doesn't quote screen groups
+    const LIST_OUTPUT_V1_1: &str = "Persistent screen id: AAAA-1
+Type: MacBook built in screen
+Resolution: 1440x900
+
+Persistent screen id: BBBB-2
+Type: 24 inch external screen
+Resolution: 1920x1080
+
+Execute the command below to set your screens to the current arrangement:
+
+displayplacer id:AAAA-1 res:1440x900 scaling:on origin:(0,0) degree:0 id:BBBB-2 res:1920x1080 scaling:off origin:(1440,0) degree:0
+";
+
+    /// Output of 1.4, which prints enabled:true|false for every screen
+    const LIST_OUTPUT_V1_4: &str = "Persistent screen id: AAAA-1
+Contextual screen id: 1
+Type: MacBook built in screen
+
+Persistent screen id: BBBB-2
+Contextual screen id: 2
+Type: 24 inch external screen
+
+Execute the command below to set your screens to the current arrangement:
+
+displayplacer \"id:AAAA-1 res:1440x900 hz:60 color_depth:8 enabled:true scaling:on origin:(0,0) degree:0\" \"id:BBBB-2 res:1920x1080 hz:60 color_depth:8 enabled:false scaling:off origin:(1440,0) degree:0\"
+";
+
+    #[test]
+    fn test_parse_output_versions() {
+        assert_eq!(DisplayplacerVersion::parse("displayplacer v1.4.0"), Some(DisplayplacerVersion(1, 4, 0)));
+        assert_eq!(DisplayplacerVersion::parse("1.2"), Some(DisplayplacerVersion(1, 2, 0)));
+        assert_eq!(DisplayplacerVersion::parse("displayplacer"), None);
+
+        let v1_1 = Some(DisplayplacerVersion(1, 1, 0));
+        let v1_4 = Some(DisplayplacerVersion(1, 4, 0));
+        assert_eq!(OutputFormat::for_version(v1_1, LIST_OUTPUT_V1_4), OutputFormat::Legacy);
+        assert_eq!(OutputFormat::for_version(None, LIST_OUTPUT_V1_1), OutputFormat::Legacy);
+        assert_eq!(OutputFormat::for_version(None, LIST_OUTPUT_V1_4), OutputFormat::Quoted);
+
+        for (version, output) in [(v1_1, LIST_OUTPUT_V1_1), (None, LIST_OUTPUT_V1_1), (v1_4, LIST_OUTPUT_V1_4)] {
+            let displays = parse_displayplacer_output(output, version, &mut Vec::new()).unwrap();
+            assert_eq!(displays.len(), 2, "{:?}", version);
+            assert_eq!(displays[0].id, "AAAA-1");
+            assert_eq!(displays[1].resolution, "1920x1080");
+            assert_eq!(displays[1].origin, (1440, 0));
+        }
+
+        let v1_1_displays = parse_displayplacer_output(LIST_OUTPUT_V1_1, v1_1, &mut Vec::new()).unwrap();
+        assert_eq!(v1_1_displays[0].hz, None);
+        assert!(v1_1_displays[1].enabled);
+
+        let v1_4_displays = parse_displayplacer_output(LIST_OUTPUT_V1_4, v1_4, &mut Vec::new()).unwrap();
+        assert_eq!(v1_4_displays[0].hz, Some(60));
+        assert!(v1_4_displays[0].enabled);
+        assert!(!v1_4_displays[1].enabled);
+        assert_eq!(v1_4_displays[1].contextual_id.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_mirroring() {
+        let output = "Execute the command below to set your screens to the current arrangement:
+
+displayplacer \"id:A+B res:1920x1080 hz:60 color_depth:8 enabled:true scaling:off origin:(0,0) degree:0\" \"id:C res:2560x1440 hz:60 color_depth:8 enabled:true scaling:off origin:(1920,0) degree:0\"
+";
+        let displays = parse_displayplacer_output(output, None, &mut Vec::new()).unwrap();
+        let groups = parse_mirror_groups(output, None);
+
+        assert_eq!(displays.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(), vec!["A", "B", "C"]);
+        assert_eq!(
+            groups,
+            vec![MirrorGroup {
+                primary: "A".to_string(),
+                mirrors: vec!["B".to_string()],
+            }]
+        );
+
+        let args = unmirror_args(&displays, &groups);
+        assert_eq!(args.len(), 3);
+        assert!(args[2].starts_with("id:B "));
+        assert!(args[2].contains("origin:(4480,0)"));
+
+        let config = DisplayConfig {
+            displays: displays.clone(),
+            mirror_groups: groups.clone(),
+            lid_closed: None,
+            builtin_active: false,
+            raw_command: String::new(),
+        };
+        assert_eq!(
+            layout_command(&config),
+            "displayplacer \"id:A+B res:1920x1080 hz:60 color_depth:8 enabled:true scaling:off origin:(0,0) degree:0\" \"id:C res:2560x1440 hz:60 color_depth:8 enabled:true scaling:off origin:(1920,0) degree:0\""
+        );
+
+        let args = mirror_args(&displays, "C", &["B".to_string()]).unwrap();
+        assert_eq!(args.len(), 2);
+        assert!(args[1].starts_with("id:C+B "));
+        assert!(mirror_args(&displays, "C", &["C".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_rotation_args() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+
+        let args = rotation_args(&displays, "1", 90).unwrap();
+        assert_eq!(
+            args,
+            vec!["id:AAAA-1 res:2560x1440 hz:60 color_depth:8 enabled:true scaling:on origin:(0,0) degree:90"]
+        );
+        assert!(rotation_args(&displays, "AAAA-1", 45).is_err());
+        assert!(rotation_args(&displays, "missing", 90).is_err());
+    }
+
+    #[test]
+    fn test_resolution_args() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+
+        let args = resolution_args(&displays, "AAAA-1", 2560, 1440, Some(30)).unwrap();
+        assert_eq!(
+            args,
+            vec!["id:AAAA-1 res:2560x1440 hz:30 color_depth:8 enabled:true scaling:on origin:(0,0) degree:0"]
+        );
+
+        let args = resolution_args(&displays, "AAAA-1", 1920, 1080, None).unwrap();
+        assert!(args[0].contains("res:1920x1080 hz:60"));
+        assert!(args[0].contains("scaling:off"));
+
+        assert!(resolution_args(&displays, "AAAA-1", 1920, 1080, Some(144)).is_err());
+    }
+
+    #[test]
+    fn test_nearest_mode() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+        let modes = &displays[0].modes;
+
+        // Unsupported refresh rate picks the best rate at the same resolution
+        assert_eq!(nearest_mode(modes, "2560x1440", Some(144)).unwrap().index, 1);
+        // Unsupported resolution picks the largest mode that fits
+        assert_eq!(nearest_mode(modes, "3440x1440", Some(60)).unwrap().index, 1);
+        // Nothing fits, so fall back to the smallest mode
+        assert_eq!(nearest_mode(modes, "1280x720", None).unwrap().index, 3);
+    }
+
+    #[test]
+    fn test_validate_layout() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+
+        let valid = "displayplacer \"id:1 res:1920x1080 hz:60 origin:(0,0) degree:0\"";
+        assert!(validate_layout(valid, &displays).is_empty());
+
+        let warnings = validate_layout(
+            "displayplacer \"id:AAAA-1 res:2560x1440 hz:144 origin:(0,0) degree:0\" \"id:BBBB-2 res:1920x1080 origin:(0,0) degree:0\"",
+            &displays,
+        );
+        let issues: Vec<ConfigIssue> = warnings.iter().map(|w| w.issue).collect();
+        assert_eq!(issues, vec![ConfigIssue::UnsupportedMode, ConfigIssue::UnknownDisplay]);
+    }
+
+    #[test]
+    fn test_validate_layout_overlap() {
+        let mut displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+        let mut second = displays[0].clone();
+        second.id = "BBBB-2".to_string();
+        second.persistent_id = None;
+        second.contextual_id = None;
+        second.serial_id = None;
+        displays.push(second);
+
+        let overlapping = "displayplacer \"id:AAAA-1 res:2560x1440 origin:(0,0) degree:0\" \"id:BBBB-2 res:2560x1440 origin:(2000,0) degree:0\"";
+        let warnings = validate_layout(overlapping, &displays);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].issue, ConfigIssue::Overlap);
+
+        let side_by_side = overlapping.replace("origin:(2000,0)", "origin:(2560,0)");
+        assert!(validate_layout(&side_by_side, &displays).is_empty());
+    }
+
+    #[test]
+    fn test_diff_layout() {
+        let current = DisplayConfig {
+            displays: parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap(),
+            mirror_groups: Vec::new(),
+            lid_closed: None,
+            builtin_active: false,
+            raw_command: String::new(),
+        };
+
+        let same = "displayplacer \"id:1 res:2560x1440 hz:60 origin:(0,0) degree:0\"";
+        assert!(diff_layout(same, &current).is_empty());
+
+        let changes: Vec<DisplayChange> = diff_layout(
+            "displayplacer \"id:AAAA-1+BBBB-2 res:1920x1080 origin:(0,0) degree:90\"",
+            &current,
+        )
+        .into_iter()
+        .map(|d| d.change)
+        .collect();
+        assert_eq!(
+            changes,
+            vec![
+                DisplayChange::Missing,
+                DisplayChange::ResolutionChanged {
+                    from: "2560x1440".to_string(),
+                    to: "1920x1080".to_string(),
+                },
+                DisplayChange::RotationChanged { from: 0, to: 90 },
+            ]
+        );
+
+        let unrelated = diff_layout("id:CCCC-3 res:1920x1080", &current);
+        assert_eq!(unrelated.len(), 2);
+        assert_eq!(unrelated[1].display_id, "AAAA-1");
+        assert_eq!(unrelated[1].change, DisplayChange::NotInConfig);
+    }
+
+    #[test]
+    fn test_downgrade_unsupported_modes() {
+        let displays = parse_displayplacer_output(LIST_OUTPUT, None, &mut Vec::new()).unwrap();
+
+        let check = downgrade_unsupported_modes(
+            "displayplacer \"id:AAAA-1 res:5120x2880 hz:60 origin:(0,0) degree:0\"",
+            &displays,
+        );
+        assert_eq!(
+            check.config,
+            "displayplacer \"id:AAAA-1 res:3840x2160 hz:60 origin:(0,0) degree:0\""
+        );
+        assert_eq!(check.notices.len(), 1);
+        assert_eq!(check.notices[0].applied, "3840x2160@60");
+
+        let supported = "id:AAAA-1 res:1920x1080 hz:60 origin:(0,0) degree:0";
+        let check = downgrade_unsupported_modes(supported, &displays);
+        assert_eq!(check.config, supported);
+        assert!(check.notices.is_empty());
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let pasted = "displayplacer \u{201C}id:AAAA-1 res:2560x1440 hz:60  origin:(0,0) degree:0\u{201D} \\\n  \"id:BBBB-2 enabled:false\"";
+        assert_eq!(
+            parse_command(pasted).unwrap(),
+            "displayplacer \"id:AAAA-1 res:2560x1440 hz:60 origin:(0,0) degree:0\" \"id:BBBB-2 enabled:false\""
+        );
+        assert_eq!(
+            parse_command("id:AAAA-1 res:1920x1080").unwrap(),
+            "displayplacer \"id:AAAA-1 res:1920x1080\""
+        );
+
+        assert!(parse_command("displayplacer").is_err());
+        assert!(parse_command("displayplacer \"res:1920x1080 origin:(0,0)\"").is_err());
+        assert!(parse_command("displayplacer \"id:AAAA-1 resolution:1920x1080\"").is_err());
+    }
+
+    #[test]
+    fn test_toggle_args() {
+        let before = parse_config_displays(
+            r#""id:A res:1728x1117 enabled:true origin:(0,0) degree:0" "id:B res:2560x1440 enabled:true origin:(1728,-323) degree:0" "id:C res:1920x1080 enabled:true origin:(4288,0) degree:0""#,
+        );
+
+        // Turning B off moves C next to A
+        assert_eq!(
+            toggle_args(&before, "B", false, None),
+            vec![
+                "id:B enabled:false".to_string(),
+                "id:C res:1920x1080 enabled:true origin:(1728,0) degree:0".to_string(),
+            ]
+        );
+
+        // Turning it back on restores the saved layout
+        let after = crate::layout::close_gap(&before, "B");
+        assert_eq!(toggle_args(&after, "B", true, Some(&before)), display_args(&before));
+
+        // Unless the other displays changed in the meantime
+        let mut changed = after.clone();
+        changed[2].enabled = false;
+        assert_eq!(toggle_args(&changed, "B", true, Some(&before)), vec!["id:B enabled:true".to_string()]);
+    }
+
+    #[test]
+    fn test_tokenize_config() {
+        assert_eq!(
+            tokenize_config("id:A+B res:1920x1080 origin:(0,0) degree:0").unwrap(),
+            vec!["id:A+B res:1920x1080 origin:(0,0) degree:0"]
+        );
+        assert_eq!(
+            tokenize_config("displayplacer \"id:A  res:2560x1440\" \"id:B enabled:false\"").unwrap(),
+            vec!["id:A res:2560x1440", "id:B enabled:false"]
+        );
+        assert_eq!(
+            tokenize_config("id:A res:2560x1440 id:B color_depth:8").unwrap(),
+            vec!["id:A res:2560x1440", "id:B color_depth:8"]
+        );
+
+        assert!(tokenize_config("").is_err());
+        assert!(tokenize_config("\"id:A res:1920x1080").is_err());
+        assert!(tokenize_config("\"id:A\" --help").is_err());
+        assert!(tokenize_config("id:A --version").is_err());
+        assert!(tokenize_config("id:A res:1920x1080;reboot").is_err());
+        assert!(tokenize_config("res:1920x1080 id:A").is_err());
+    }
+
+    #[test]
+    fn test_external_disable_args() {
+        let mut builtin = parse_display_string("id:A res:1512x982 origin:(0,0) degree:0").unwrap();
+        builtin.builtin = true;
+        let external = parse_display_string("id:B res:2560x1440 origin:(1512,0) degree:0").unwrap();
+        let mut off = parse_display_string("id:C res:1920x1080 origin:(0,0) degree:0").unwrap();
+        off.enabled = false;
+
+        assert_eq!(
+            external_disable_args(&[builtin.clone(), external.clone(), off.clone()]).unwrap(),
+            vec!["id:B enabled:false"]
+        );
+        assert!(external_disable_args(&[builtin.clone(), off]).is_err());
+        assert!(external_disable_args(std::slice::from_ref(&external)).is_err());
+
+        builtin.enabled = false;
+        assert!(external_disable_args(&[builtin, external]).is_err());
+    }
 }
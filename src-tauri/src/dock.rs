@@ -0,0 +1,282 @@
+//! Presets triggered by docks.
+//!
+//! Polls the USB and Thunderbolt devices in the IOKit registry (via `ioreg`)
+//! and, when a device matching one of the `docks` bindings in settings shows
+//! up, applies that binding's preset — so the CalDigit at home and the Dell
+//! dock at work each set up their own desk. A binding can also name a preset
+//! for when its dock goes away. Like other triggers, docks present at launch
+//! are only recorded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::history::HistorySource;
+use crate::presets::apply_preset_config;
+use crate::session;
+use crate::settings;
+use crate::state::AppState;
+use crate::system_tray::show_tray_notification;
+
+/// How often connected devices are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// `ioreg` arguments listing USB devices and Thunderbolt switches (docks show up as either)
+const IOREG_QUERIES: [&[&str]; 2] = [
+    &["-p", "IOUSB", "-l", "-w0"],
+    &["-r", "-c", "IOThunderboltSwitch", "-l", "-w0"],
+];
+
+/// Names of the bindings whose dock was connected at the last check; `None` until the first check
+static LAST_DOCKED: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Kind of bus a device is attached through.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceBus {
+    Usb,
+    Thunderbolt,
+}
+
+/// A USB or Thunderbolt device, as offered when binding a dock.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockDevice {
+    pub bus: DeviceBus,
+    /// Product name (e.g. "CalDigit TS3 Plus")
+    pub name: Option<String>,
+    pub vendor_id: Option<u32>,
+    pub product_id: Option<u32>,
+}
+
+/// A dock mapped to presets, stored in settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockBinding {
+    /// Label shown to the user, e.g. "Home desk"
+    pub name: String,
+    /// USB vendor ID (or Thunderbolt vendor ID) to match
+    #[serde(default)]
+    pub vendor_id: Option<u32>,
+    /// USB product ID (or Thunderbolt device ID) to match
+    #[serde(default)]
+    pub product_id: Option<u32>,
+    /// Device name to match, case-insensitively
+    #[serde(default)]
+    pub device_name: Option<String>,
+    /// Preset applied when the dock is connected
+    pub preset_id: String,
+    /// Preset applied when the dock is disconnected
+    #[serde(default)]
+    pub undock_preset_id: Option<String>,
+}
+
+impl DockBinding {
+    /// Whether a device is this dock. Every given field has to match.
+    fn matches(&self, device: &DockDevice) -> bool {
+        let same = |expected: Option<u32>, actual: Option<u32>| expected.is_none() || expected == actual;
+        let name = self.device_name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+        same(self.vendor_id, device.vendor_id)
+            && same(self.product_id, device.product_id)
+            && name.is_none_or(|name| device.name.as_deref().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+    }
+
+    /// Check a binding before it is stored in settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Dock name is required".to_string());
+        }
+        let has_name = self.device_name.as_deref().is_some_and(|n| !n.trim().is_empty());
+        if self.vendor_id.is_none() && self.product_id.is_none() && !has_name {
+            return Err(format!("Dock {} needs a vendor ID, product ID or device name", self.name));
+        }
+        Ok(())
+    }
+}
+
+/// Parse one `"key" = value` property line from `ioreg -l` output.
+fn parse_property(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim().trim_start_matches('|').trim();
+    let (key, value) = line.strip_prefix('"')?.split_once("\" = ")?;
+    Some((key, value.trim()))
+}
+
+/// Parse `ioreg -l` output into devices.
+///
+/// Each `+-o` line starts a device; USB devices carry `idVendor`, `idProduct`
+/// and `USB Product Name`, Thunderbolt switches `Vendor ID`, `Device ID` and
+/// `Device Model Name`. Entries without any of them (hubs' ports, the root
+/// controller) are skipped.
+fn parse_ioreg_devices(output: &str, bus: DeviceBus) -> Vec<DockDevice> {
+    let mut devices = Vec::new();
+    let mut current: Option<DockDevice> = None;
+
+    let mut finish = |device: Option<DockDevice>| {
+        if let Some(device) = device.filter(|d| d.name.is_some() || d.vendor_id.is_some()) {
+            devices.push(device);
+        }
+    };
+
+    for line in output.lines() {
+        if line.contains("+-o ") {
+            finish(current.take());
+            current = Some(DockDevice {
+                bus,
+                name: None,
+                vendor_id: None,
+                product_id: None,
+            });
+            continue;
+        }
+        let (Some(device), Some((key, value))) = (current.as_mut(), parse_property(line)) else {
+            continue;
+        };
+        match key {
+            "idVendor" | "Vendor ID" => device.vendor_id = value.parse().ok(),
+            "idProduct" | "Device ID" => device.product_id = value.parse().ok(),
+            "USB Product Name" | "Device Model Name" => {
+                device.name = Some(value.trim_matches('"').to_string()).filter(|n| !n.is_empty())
+            }
+            _ => {}
+        }
+    }
+    finish(current);
+
+    devices
+}
+
+/// Every USB device and Thunderbolt switch connected now.
+pub fn connected_devices() -> Vec<DockDevice> {
+    let mut devices = Vec::new();
+
+    for (args, bus) in IOREG_QUERIES.into_iter().zip([DeviceBus::Usb, DeviceBus::Thunderbolt]) {
+        match Command::new("ioreg").args(args).output() {
+            Ok(output) => devices.extend(parse_ioreg_devices(&String::from_utf8_lossy(&output.stdout), bus)),
+            Err(e) => log::error!("[Dock] Failed to execute ioreg: {}", e),
+        }
+    }
+
+    devices
+}
+
+/// Start polling for docks.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        if session::is_active() {
+            check_docks(&app);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+/// Apply the presets of docks connected or disconnected since the last check.
+fn check_docks(app: &AppHandle) {
+    let bindings = settings::current().docks;
+    if bindings.is_empty() {
+        // Start over from whatever is connected once a dock is bound
+        if let Ok(mut last) = LAST_DOCKED.lock() {
+            *last = None;
+        }
+        return;
+    }
+
+    let devices = connected_devices();
+    let docked: HashSet<String> = bindings
+        .iter()
+        .filter(|b| devices.iter().any(|d| b.matches(d)))
+        .map(|b| b.name.clone())
+        .collect();
+    let previous = match LAST_DOCKED.lock() {
+        Ok(mut last) => last.replace(docked.clone()),
+        Err(_) => return,
+    };
+    let Some(previous) = previous else {
+        return;
+    };
+
+    for binding in &bindings {
+        let preset_id = match (previous.contains(&binding.name), docked.contains(&binding.name)) {
+            (false, true) => {
+                log::info!("[Dock] {} connected", binding.name);
+                Some(&binding.preset_id)
+            }
+            (true, false) => {
+                log::info!("[Dock] {} disconnected", binding.name);
+                binding.undock_preset_id.as_ref()
+            }
+            _ => None,
+        };
+        if let Some(preset_id) = preset_id {
+            apply(app, preset_id);
+        }
+    }
+}
+
+fn apply(app: &AppHandle, preset_id: &str) {
+    let Some(preset) = app.state::<AppState>().find_preset(preset_id) else {
+        log::error!("[Dock] Preset {} not found", preset_id);
+        return;
+    };
+
+    match tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::Automation)) {
+        Ok(_) => show_tray_notification("DPUI", &format!("Applied: {}", preset.name)),
+        Err(e) => {
+            log::error!("[Dock] Failed to apply {}: {}", preset.name, e);
+            show_tray_notification("DPUI", &format!("Failed to apply {}: {}", preset.name, e));
+        }
+    }
+}
+
+/// Get the USB and Thunderbolt devices connected now
+///
+/// # Returns
+/// * `Ok(Vec<DockDevice>)` - Devices, for picking the one to bind a dock to
+#[tauri::command]
+pub async fn get_connected_devices() -> Result<Vec<DockDevice>, String> {
+    Ok(connected_devices())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ioreg_devices() {
+        let usb = r#"+-o Root  <class IORegistryEntry, id 0x100000100, retain 20>
+  +-o CalDigit TS3 Plus@01100000  <class IOUSBHostDevice, id 0x100000a31, registered, matched, active>
+  | {
+  |   "idProduct" = 43
+  |   "USB Product Name" = "CalDigit TS3 Plus"
+  |   "idVendor" = 8584
+  | }
+  | +-o AppleUSB20InternalHub@01140000  <class IOUSBHostDevice, id 0x100000a40>
+  |   {
+  |     "kUSBCurrentConfiguration" = 1
+  |   }
+"#;
+        let devices = parse_ioreg_devices(usb, DeviceBus::Usb);
+        assert_eq!(
+            devices,
+            vec![DockDevice {
+                bus: DeviceBus::Usb,
+                name: Some("CalDigit TS3 Plus".to_string()),
+                vendor_id: Some(8584),
+                product_id: Some(43),
+            }]
+        );
+
+        let binding = DockBinding {
+            name: "Home".to_string(),
+            vendor_id: Some(8584),
+            product_id: None,
+            device_name: Some("caldigit ts3 plus".to_string()),
+            preset_id: "p".to_string(),
+            undock_preset_id: None,
+        };
+        assert!(binding.matches(&devices[0]));
+        assert!(!DockBinding { product_id: Some(1), ..binding.clone() }.matches(&devices[0]));
+        assert!(DockBinding { vendor_id: None, device_name: None, ..binding }.validate().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Frontmost application, for automation rules that depend on it.
+//!
+//! An observer for NSWorkspace's app activation notification remembers which
+//! application is frontmost and re-evaluates automation rules whenever it
+//! changes, so a rule can mirror displays when Keynote comes to the front.
+
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use tauri::AppHandle;
+
+use crate::automation;
+use crate::displayplacer::get_displays;
+use crate::session;
+
+/// The application in front of the others.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrontmostApp {
+    /// Bundle identifier, e.g. "com.apple.iWork.Keynote"
+    pub bundle_id: Option<String>,
+    /// Name shown in the menu bar, e.g. "Keynote"
+    pub name: Option<String>,
+}
+
+impl FrontmostApp {
+    /// Whether `app` names this application, by bundle ID or name.
+    pub fn is(&self, app: &str) -> bool {
+        [&self.bundle_id, &self.name]
+            .into_iter()
+            .flatten()
+            .any(|key| key.eq_ignore_ascii_case(app.trim()))
+    }
+}
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Application activated last; `None` until the observer has seen one
+static FRONTMOST: Mutex<Option<FrontmostApp>> = Mutex::new(None);
+
+/// The application in front, as of the last activation.
+pub fn current() -> Option<FrontmostApp> {
+    FRONTMOST.lock().ok().and_then(|app| app.clone())
+}
+
+/// Start observing application activation.
+///
+/// # Returns
+/// * `Ok(())` - Observer registered
+/// * `Err(String)` - Error message if the observer could not be registered
+pub fn start(app: AppHandle) -> Result<(), String> {
+    APP.set(app).map_err(|_| "Frontmost app observer already started".to_string())?;
+    if let Ok(mut frontmost) = FRONTMOST.lock() {
+        *frontmost = ns::frontmost_application();
+    }
+    ns::register_observer()?;
+
+    log::info!("[Frontmost] Observing application activation");
+    Ok(())
+}
+
+/// Called on the main thread whenever an application is activated.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn on_activation() {
+    let Some(app) = APP.get().cloned() else {
+        return;
+    };
+    let frontmost = ns::frontmost_application();
+    match FRONTMOST.lock() {
+        Ok(mut last) if *last != frontmost => *last = frontmost.clone(),
+        _ => return,
+    }
+    log::debug!("[Frontmost] {:?} is now frontmost", frontmost);
+
+    tauri::async_runtime::spawn(async move {
+        if !session::is_active() {
+            return;
+        }
+        match get_displays().await {
+            Ok(config) => automation::evaluate(&app, &config).await,
+            Err(e) => log::error!("[Frontmost] Failed to read displays: {}", e),
+        }
+    });
+}
+
+/// Get the application currently in front
+///
+/// # Returns
+/// * `Ok(Some(FrontmostApp))` - Frontmost application, for naming it in a rule
+/// * `Ok(None)` - Not known yet
+#[tauri::command]
+pub async fn get_frontmost_app() -> Result<Option<FrontmostApp>, String> {
+    Ok(current())
+}
+
+#[cfg(target_os = "macos")]
+mod ns {
+    use crate::objc::{self, Id, Sel};
+
+    use super::FrontmostApp;
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSWorkspaceDidActivateApplicationNotification: Id;
+    }
+
+    /// `-[DPUIActivationObserver handleActivation:]`
+    extern "C" fn handle_activation(_this: Id, _cmd: Sel, _notification: Id) {
+        super::on_activation();
+    }
+
+    pub fn frontmost_application() -> Option<FrontmostApp> {
+        unsafe {
+            let workspace = objc::send(objc::class(c"NSWorkspace")?, c"sharedWorkspace");
+            let app = objc::send(workspace, c"frontmostApplication");
+            if app.is_null() {
+                return None;
+            }
+            Some(FrontmostApp {
+                bundle_id: objc::to_string(objc::send(app, c"bundleIdentifier")),
+                name: objc::to_string(objc::send(app, c"localizedName")),
+            })
+        }
+    }
+
+    pub fn register_observer() -> Result<(), String> {
+        let activated = unsafe { NSWorkspaceDidActivateApplicationNotification };
+        objc::observe_workspace(
+            c"DPUIActivationObserver",
+            &[(c"handleActivation:", activated, handle_activation)],
+        )
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod ns {
+    use super::FrontmostApp;
+
+    pub fn frontmost_application() -> Option<FrontmostApp> {
+        None
+    }
+
+    pub fn register_observer() -> Result<(), String> {
+        Err("Application activation notifications are only available on macOS".to_string())
+    }
+}
@@ -0,0 +1,235 @@
+//! Headless mode and its launchd agent.
+//!
+//! Started with `--headless`, DPUI creates no webview at all: only the tray,
+//! hotkeys, triggers and servers run. The main window is still created on
+//! demand from the tray, and closing it again keeps DPUI running.
+//!
+//! For users who never open the GUI, a launchd agent can start DPUI headless
+//! at login and restart it if it crashes. The agent is a plist in
+//! `~/Library/LaunchAgents`, installed from settings or with
+//! `dpui-cli agent install`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, WebviewWindow, WebviewWindowBuilder};
+
+use crate::window_state;
+
+/// Command-line flag selecting headless mode
+pub const HEADLESS_FLAG: &str = "--headless";
+
+/// Label of the launchd agent, also the plist's file name
+const AGENT_LABEL: &str = "com.jelly.dpui.headless";
+
+/// Label of the main window in tauri.conf.json
+const MAIN_WINDOW: &str = "main";
+
+static HEADLESS: AtomicBool = AtomicBool::new(false);
+
+/// Decide the run mode from the command line; called once at startup.
+pub fn init_from_args() {
+    HEADLESS.store(std::env::args().any(|arg| arg == HEADLESS_FLAG), Ordering::Relaxed);
+}
+
+/// Whether DPUI was started with `--headless`.
+pub fn is_headless() -> bool {
+    HEADLESS.load(Ordering::Relaxed)
+}
+
+/// The main window, created from tauri.conf.json if it doesn't exist yet.
+///
+/// The window is declared with `"create": false` so headless mode can skip it.
+pub fn main_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        return Ok(window);
+    }
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == MAIN_WINDOW)
+        .ok_or("Main window is missing from the configuration")?;
+
+    let window = WebviewWindowBuilder::from_config(app, config)
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Failed to create main window: {}", e))?;
+    window_state::track(&window);
+    Ok(window)
+}
+
+/// Escape text for an XML property list.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The launchd plist running `executable` headless at login.
+///
+/// `KeepAlive` restarts DPUI after a crash but not after quitting from the tray.
+fn agent_plist(executable: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>ProcessType</key>
+    <string>Interactive</string>
+</dict>
+</plist>
+"#,
+        AGENT_LABEL,
+        xml_escape(&executable.to_string_lossy()),
+        HEADLESS_FLAG
+    )
+}
+
+fn agent_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join("Library/LaunchAgents").join(format!("{}.plist", AGENT_LABEL)))
+        .ok_or_else(|| "Cannot resolve home directory".to_string())
+}
+
+/// launchd domain of the logged-in user, e.g. `gui/501`.
+fn gui_domain() -> Result<String, String> {
+    let output = Command::new("id")
+        .arg("-u")
+        .output()
+        .map_err(|e| format!("Failed to execute id: {}", e))?;
+    Ok(format!("gui/{}", String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+fn launchctl(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("launchctl")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute launchctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "launchctl {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the launchd agent is installed.
+pub fn agent_installed() -> bool {
+    agent_path().is_ok_and(|path| path.exists())
+}
+
+/// Install the launchd agent for `executable`.
+///
+/// With `load_now` the agent also starts a headless instance right away;
+/// otherwise it takes effect at the next login. A running DPUI installing
+/// its own agent must not load it, or there would be two instances.
+pub fn install_agent(executable: &Path, load_now: bool) -> Result<PathBuf, String> {
+    let path = agent_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create LaunchAgents directory: {}", e))?;
+    }
+    if path.exists() {
+        // Reinstalling picks up a moved executable
+        let _ = launchctl(&["bootout", &format!("{}/{}", gui_domain()?, AGENT_LABEL)]);
+    }
+
+    fs::write(&path, agent_plist(executable)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    if load_now {
+        launchctl(&["bootstrap", &gui_domain()?, &path.to_string_lossy()])?;
+    }
+
+    log::info!("[Headless] Installed launch agent for {}", executable.display());
+    Ok(path)
+}
+
+/// Remove the launchd agent; nothing to do if it isn't installed.
+///
+/// With `unload_now` a headless instance the agent started is quit as well.
+pub fn uninstall_agent(unload_now: bool) -> Result<(), String> {
+    let path = agent_path()?;
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if unload_now {
+        // Not loaded is fine, e.g. after a failed bootstrap
+        let _ = launchctl(&["bootout", &format!("{}/{}", gui_domain()?, AGENT_LABEL)]);
+    }
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+
+    log::info!("[Headless] Removed launch agent");
+    Ok(())
+}
+
+/// The DPUI app executable, for callers other than the app itself.
+///
+/// `dpui-cli` normally sits next to it; otherwise the copy in /Applications is used.
+pub fn app_executable() -> Result<PathBuf, String> {
+    let sibling = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("dpui")))
+        .filter(|path| path.is_file());
+    let installed = PathBuf::from("/Applications/DPUI.app/Contents/MacOS/dpui");
+
+    sibling
+        .or_else(|| installed.is_file().then_some(installed))
+        .ok_or_else(|| "Cannot find the DPUI app next to dpui-cli or in /Applications".to_string())
+}
+
+/// Install or remove the launchd agent that runs DPUI headless at login
+///
+/// Changes take effect at the next login, so this instance keeps running.
+///
+/// # Arguments
+/// * `installed` - `true` to install the agent, `false` to remove it
+///
+/// # Returns
+/// * `Ok(())` - Agent updated
+/// * `Err(String)` - Error message if the plist or launchctl failed
+#[tauri::command]
+pub async fn set_launch_agent(installed: bool) -> Result<(), String> {
+    if installed {
+        let executable = std::env::current_exe().map_err(|e| format!("Cannot resolve executable: {}", e))?;
+        install_agent(&executable, false).map(|_| ())
+    } else {
+        uninstall_agent(false)
+    }
+}
+
+/// Whether the headless launchd agent is installed
+///
+/// # Returns
+/// * `Ok(bool)` - `true` if the agent plist exists
+#[tauri::command]
+pub async fn get_launch_agent() -> Result<bool, String> {
+    Ok(agent_installed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_plist() {
+        let plist = agent_plist(Path::new("/Applications/R&D/DPUI.app/Contents/MacOS/dpui"));
+        assert!(plist.contains("<string>com.jelly.dpui.headless</string>"));
+        assert!(plist.contains("<string>/Applications/R&amp;D/DPUI.app/Contents/MacOS/dpui</string>"));
+        assert!(plist.contains("<string>--headless</string>"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Log of every applied configuration.
+//!
+//! Each apply is recorded through the storage backend (by default as one JSON
+//! line in `history.jsonl` in the config directory), recording when it happened, what triggered it, the exact config
+//! string and whether displayplacer accepted it. Entries can be listed and
+//! re-applied from the UI.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::displayplacer::{apply_config, ApplyVerification};
+use crate::presets::get_config_dir;
+use crate::storage;
+
+/// Rotate the history file once it grows beyond this size
+const MAX_HISTORY_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Default number of entries returned by `get_history`
+const DEFAULT_LIMIT: usize = 50;
+
+/// What triggered an apply.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySource {
+    Preset,
+    Hotkey,
+    Tray,
+    Manual,
+    Automation,
+    /// A `dpui://` URL, the CLI or another app
+    External,
+    /// A paired device over the network
+    Remote,
+}
+
+/// One applied configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    /// RFC 3339 timestamp of the apply
+    pub timestamp: String,
+    pub source: HistorySource,
+    /// Config string as passed to displayplacer
+    pub config: String,
+    pub success: bool,
+    /// Error message when the apply failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+pub(crate) fn get_history_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("history.jsonl"))
+}
+
+/// Append the outcome of an apply to the history file.
+///
+/// Failures to write are logged rather than returned so they never mask the
+/// result of the apply itself.
+pub fn record(source: HistorySource, config: &str, result: &Result<(), String>) {
+    let entry = HistoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        source,
+        config: config.to_string(),
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+
+    if let Err(e) = storage::backend().append_history(&entry) {
+        log::error!("[History] Failed to write history entry: {}", e);
+    }
+}
+
+/// Append an entry as one JSON line, rotating the file when it grows too large.
+pub(crate) fn append_to_file(entry: &HistoryEntry) -> Result<(), String> {
+    append_to(&get_history_path()?, entry, MAX_HISTORY_FILE_BYTES)
+}
+
+/// Append `entry` to the file at `path`, first moving it to `.jsonl.old` if it is over `max_bytes`.
+fn append_to(path: &Path, entry: &HistoryEntry, max_bytes: u64) -> Result<(), String> {
+    if fs::metadata(path).map(|m| m.len() > max_bytes).unwrap_or(false) {
+        fs::rename(path, path.with_extension("jsonl.old"))
+            .map_err(|e| format!("Failed to rotate history file: {}", e))?;
+    }
+
+    let line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open history file: {}", e))?;
+
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to append history entry: {}", e))
+}
+
+/// Read all entries from the history file, oldest first. Lines that fail to parse are skipped.
+pub(crate) fn read_file_entries() -> Result<Vec<HistoryEntry>, String> {
+    read_entries(&get_history_path()?)
+}
+
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read history file: {}", e))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Get the most recent applied configurations.
+///
+/// # Arguments
+/// * `limit` - Maximum number of entries to return (defaults to 50)
+///
+/// # Returns
+/// * `Ok(Vec<HistoryEntry>)` - Entries, newest first
+/// * `Err(String)` - Error message if the history cannot be read
+#[tauri::command]
+pub async fn get_history(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    let mut entries = storage::backend().load_history()?;
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(entries)
+}
+
+/// Apply the configuration recorded in a history entry again.
+///
+/// # Arguments
+/// * `id` - ID of the history entry
+///
+/// # Returns
+/// * `Ok(Option<ApplyVerification>)` - Same as `apply_config`
+/// * `Err(String)` - Error message if the entry does not exist or the apply fails
+#[tauri::command]
+pub async fn apply_history_entry(app: AppHandle, id: String) -> Result<Option<ApplyVerification>, String> {
+    let entry = storage::backend()
+        .load_history()?
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| format!("History entry {} not found", id))?;
+
+    apply_config(app, entry.config, Some(HistorySource::Manual)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(config: &str) -> HistoryEntry {
+        HistoryEntry {
+            id: config.to_string(),
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+            source: HistorySource::Manual,
+            config: config.to_string(),
+            success: true,
+            error: None,
+        }
+    }
+
+    fn configs(path: &Path) -> Vec<String> {
+        read_entries(path).unwrap().into_iter().map(|e| e.config).collect()
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("dpui-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.jsonl");
+        let old = dir.join("history.jsonl.old");
+
+        append_to(&path, &entry("a"), 1).unwrap();
+        assert_eq!(configs(&path), ["a"]);
+        assert!(!old.exists());
+
+        // Over the limit: the file is moved aside and a new one started
+        append_to(&path, &entry("b"), 1).unwrap();
+        assert_eq!(configs(&path), ["b"]);
+        assert_eq!(configs(&old), ["a"]);
+
+        // Only one old generation is kept
+        append_to(&path, &entry("c"), 1).unwrap();
+        assert_eq!(configs(&path), ["c"]);
+        assert_eq!(configs(&old), ["b"]);
+
+        // Under the limit entries accumulate
+        append_to(&path, &entry("d"), u64::MAX).unwrap();
+        assert_eq!(configs(&path), ["c", "d"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+//! Pre/post apply hooks for presets.
+//!
+//! A preset may define shell commands to run before and after it is applied.
+//! Hooks run through `/bin/sh -c` with a structured environment so scripts
+//! don't need to re-query display state:
+//!
+//! | Variable                 | Value                                                      |
+//! |--------------------------|------------------------------------------------------------|
+//! | `DPUI_HOOK`              | `pre` or `post`                                            |
+//! | `DPUI_PRESET_ID`         | Preset ID                                                  |
+//! | `DPUI_PRESET_NAME`       | Preset name                                                |
+//! | `DPUI_PRESET_CONFIG`     | The preset's displayplacer configuration                   |
+//! | `DPUI_DISPLAY_COUNT`     | Number of displays connected when the hook runs            |
+//! | `DPUI_CHANGED_DISPLAYS`  | JSON array of IDs of displays the preset changes           |
+//!
+//! A hook that runs longer than a minute is killed and counts as failed.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::time::Duration;
+use tauri::State;
+
+use crate::displayplacer::{find_display, get_displays, parse_config_displays, run_blocking, run_with_timeout, Display};
+use crate::presets::Preset;
+use crate::state::AppState;
+use crate::trace::{self, CommandTrace};
+
+/// How long a hook may run before it is killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// When a hook runs relative to applying the preset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    Pre,
+    Post,
+}
+
+impl HookKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HookKind::Pre => "pre",
+            HookKind::Post => "post",
+        }
+    }
+}
+
+/// IDs of displays whose configuration the preset would change.
+///
+/// A display counts as changed if it isn't connected, or if its resolution,
+/// refresh rate, origin, rotation or enabled state differs from the preset.
+fn changed_displays(preset: &Preset, current: &[Display]) -> Vec<String> {
+    parse_config_displays(&preset.config)
+        .into_iter()
+        .filter(|wanted| match find_display(current, &wanted.id) {
+            Some(actual) => {
+                actual.resolution != wanted.resolution
+                    || (wanted.hz.is_some() && actual.hz != wanted.hz)
+                    || actual.origin != wanted.origin
+                    || actual.rotation != wanted.rotation
+                    || actual.enabled != wanted.enabled
+            }
+            None => true,
+        })
+        .map(|d| d.id)
+        .collect()
+}
+
+/// Build the environment passed to a preset's hooks.
+pub fn hook_env(kind: HookKind, preset: &Preset, current: &[Display]) -> BTreeMap<String, String> {
+    let changed = changed_displays(preset, current);
+
+    BTreeMap::from([
+        ("DPUI_HOOK".to_string(), kind.as_str().to_string()),
+        ("DPUI_PRESET_ID".to_string(), preset.id.clone()),
+        ("DPUI_PRESET_NAME".to_string(), preset.name.clone()),
+        ("DPUI_PRESET_CONFIG".to_string(), preset.config.clone()),
+        ("DPUI_DISPLAY_COUNT".to_string(), current.len().to_string()),
+        (
+            "DPUI_CHANGED_DISPLAYS".to_string(),
+            serde_json::to_string(&changed).unwrap_or_else(|_| "[]".to_string()),
+        ),
+    ])
+}
+
+/// Run a preset's hook, if it defines one.
+///
+/// # Arguments
+/// * `kind` - Which hook to run
+/// * `preset` - Preset being applied
+/// * `current` - Displays connected when the hook runs
+///
+/// # Returns
+/// * `Ok(())` - Hook succeeded or the preset has no hook of this kind
+/// * `Err(String)` - Error message if the hook could not run, timed out or exited with an error
+pub async fn run_hook(kind: HookKind, preset: &Preset, current: &[Display]) -> Result<(), String> {
+    let command = match kind {
+        HookKind::Pre => &preset.pre_hook,
+        HookKind::Post => &preset.post_hook,
+    };
+    let Some(command) = command.as_deref().filter(|c| !c.trim().is_empty()) else {
+        return Ok(());
+    };
+
+    let args = vec!["-c".to_string(), command.to_string()];
+    let env = hook_env(kind, preset, current);
+
+    // Off the async runtime, since hooks may take a while
+    run_blocking(move || run_shell(kind, args, env)).await
+}
+
+/// Run a hook through `/bin/sh`, killing it after `HOOK_TIMEOUT`.
+fn run_shell(kind: HookKind, args: Vec<String>, env: BTreeMap<String, String>) -> Result<(), String> {
+    let mut trace = CommandTrace::start("/bin/sh", &args);
+
+    let name = format!("{} hook", kind.as_str());
+    let result = run_with_timeout(&name, Command::new("/bin/sh").args(&args).envs(env), HOOK_TIMEOUT)
+        .and_then(|output| {
+            trace.finish(&output);
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} hook failed: {}",
+                    kind.as_str(),
+                    String::from_utf8_lossy(&output.stderr)
+                ))
+            }
+        });
+
+    if let Err(e) = &result {
+        trace.fail(e);
+    }
+    trace::record(trace);
+
+    result
+}
+
+/// Preview the environment a preset's hooks would receive right now.
+///
+/// Documents the hook contract for script authors without running anything.
+///
+/// # Arguments
+/// * `preset_id` - Preset to preview
+///
+/// # Returns
+/// * `Ok(BTreeMap)` - Variable names and values for the `pre` hook
+/// * `Err(String)` - Error message if the preset or displays cannot be loaded
+#[tauri::command]
+pub async fn get_hook_env_preview(
+    state: State<'_, AppState>,
+    preset_id: String,
+) -> Result<BTreeMap<String, String>, String> {
+    let preset = state.find_preset(&preset_id).ok_or("Preset not found")?;
+
+    let current = get_displays().await?;
+
+    Ok(hook_env(HookKind::Pre, &preset, &current.displays))
+}
@@ -0,0 +1,323 @@
+//! Recording a hotkey by pressing it.
+//!
+//! `start_hotkey_capture` listens system-wide for the next key combination
+//! with a CoreGraphics event tap and returns it as a shortcut string that
+//! `register_target` accepts, so the preset editor can offer "press keys to
+//! record" instead of free-text entry. Existing shortcuts are suspended while
+//! capturing so pressing one records it instead of firing it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::displayplacer::run_blocking;
+use crate::hotkeys::{self, HotkeyResult};
+
+/// How long to wait for a key combination
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// macOS virtual key code of Escape, which cancels the capture
+const ESCAPE_KEYCODE: u16 = 53;
+
+/// `CGEventFlags` masks for the modifiers a shortcut can use
+const FLAG_SHIFT: u64 = 0x0002_0000;
+const FLAG_CONTROL: u64 = 0x0004_0000;
+const FLAG_ALTERNATE: u64 = 0x0008_0000;
+const FLAG_COMMAND: u64 = 0x0010_0000;
+
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// Shortcut name of a macOS virtual key code (ANSI layout), if it can be bound.
+fn key_name(keycode: u16) -> Option<&'static str> {
+    let name = match keycode {
+        0 => "A",
+        1 => "S",
+        2 => "D",
+        3 => "F",
+        4 => "H",
+        5 => "G",
+        6 => "Z",
+        7 => "X",
+        8 => "C",
+        9 => "V",
+        11 => "B",
+        12 => "Q",
+        13 => "W",
+        14 => "E",
+        15 => "R",
+        16 => "Y",
+        17 => "T",
+        18 => "1",
+        19 => "2",
+        20 => "3",
+        21 => "4",
+        22 => "6",
+        23 => "5",
+        24 => "Equal",
+        25 => "9",
+        26 => "7",
+        27 => "Minus",
+        28 => "8",
+        29 => "0",
+        30 => "BracketRight",
+        31 => "O",
+        32 => "U",
+        33 => "BracketLeft",
+        34 => "I",
+        35 => "P",
+        36 => "Enter",
+        37 => "L",
+        38 => "J",
+        39 => "Quote",
+        40 => "K",
+        41 => "Semicolon",
+        42 => "Backslash",
+        43 => "Comma",
+        44 => "Slash",
+        45 => "N",
+        46 => "M",
+        47 => "Period",
+        48 => "Tab",
+        49 => "Space",
+        50 => "Backquote",
+        51 => "Backspace",
+        96 => "F5",
+        97 => "F6",
+        98 => "F7",
+        99 => "F3",
+        100 => "F8",
+        101 => "F9",
+        103 => "F11",
+        105 => "F13",
+        107 => "F14",
+        109 => "F10",
+        111 => "F12",
+        113 => "F15",
+        115 => "Home",
+        116 => "PageUp",
+        117 => "Delete",
+        118 => "F4",
+        119 => "End",
+        120 => "F2",
+        121 => "PageDown",
+        122 => "F1",
+        123 => "Left",
+        124 => "Right",
+        125 => "Down",
+        126 => "Up",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Build a shortcut string such as "Cmd+Shift+1" from a key press.
+///
+/// Returns `None` for keys that can't be bound and for combinations without a
+/// modifier, except function keys, which are commonly bound on their own.
+fn shortcut_from_key(keycode: u16, flags: u64) -> Option<String> {
+    let key = key_name(keycode)?;
+
+    let mut parts: Vec<&str> = [
+        (FLAG_COMMAND, "Cmd"),
+        (FLAG_CONTROL, "Ctrl"),
+        (FLAG_ALTERNATE, "Alt"),
+        (FLAG_SHIFT, "Shift"),
+    ]
+    .iter()
+    .filter(|(mask, _)| flags & mask != 0)
+    .map(|(_, name)| *name)
+    .collect();
+
+    let is_function_key = key.len() > 1 && key.starts_with('F') && key[1..].chars().all(|c| c.is_ascii_digit());
+    if parts.is_empty() && !is_function_key {
+        return None;
+    }
+
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+/// Wait for a bindable key combination, Escape or the timeout.
+fn capture() -> HotkeyResult<String> {
+    let deadline = Instant::now() + CAPTURE_TIMEOUT;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Some((keycode, flags)) = tap::next_key_press(remaining)? else {
+            return Err("No key combination was pressed".to_string());
+        };
+
+        if keycode == ESCAPE_KEYCODE && flags & (FLAG_COMMAND | FLAG_CONTROL | FLAG_ALTERNATE | FLAG_SHIFT) == 0 {
+            return Err("Hotkey capture cancelled".to_string());
+        }
+        if let Some(shortcut) = shortcut_from_key(keycode, flags) {
+            return Ok(shortcut);
+        }
+    }
+}
+
+/// Record the next key combination pressed anywhere
+///
+/// Waits up to 10 seconds. Plain keys without a modifier are ignored (except
+/// function keys) and Escape cancels. Requires Input Monitoring permission.
+///
+/// # Returns
+/// * `Ok(String)` - Shortcut in canonical form, e.g. "Cmd+Shift+1"
+/// * `Err(String)` - Error message if capture timed out, was cancelled, is
+///   already running or keys can't be monitored
+#[tauri::command]
+pub async fn start_hotkey_capture(app: AppHandle) -> HotkeyResult<String> {
+    if CAPTURING.swap(true, Ordering::SeqCst) {
+        return Err("A hotkey capture is already in progress".to_string());
+    }
+
+    // Pressing an existing shortcut should record it rather than fire it
+    if let Err(e) = hotkeys::suspend_all(&app) {
+        log::error!("[HotkeyCapture] {}", e);
+    }
+    let result = run_blocking(capture).await;
+    hotkeys::resume_all(&app);
+    CAPTURING.store(false, Ordering::SeqCst);
+
+    if let Ok(shortcut) = &result {
+        log::info!("[HotkeyCapture] Captured {}", shortcut);
+    }
+    result
+}
+
+#[cfg(target_os = "macos")]
+mod tap {
+    use std::ffi::c_void;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    type CFTypeRef = *const c_void;
+    type CGEventRef = *mut c_void;
+    type TapCallback =
+        extern "C" fn(proxy: *mut c_void, event_type: u32, event: CGEventRef, user_info: *mut c_void) -> CGEventRef;
+
+    /// `kCGSessionEventTap`
+    const SESSION_EVENT_TAP: u32 = 1;
+    /// `kCGHeadInsertEventTap`
+    const HEAD_INSERT_EVENT_TAP: u32 = 0;
+    /// `kCGEventTapOptionListenOnly`
+    const LISTEN_ONLY: u32 = 1;
+    /// `kCGEventKeyDown`
+    const KEY_DOWN: u32 = 10;
+    /// `kCGKeyboardEventKeycode`
+    const KEYBOARD_EVENT_KEYCODE: u32 = 9;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventTapCreate(
+            tap: u32,
+            place: u32,
+            options: u32,
+            events_of_interest: u64,
+            callback: TapCallback,
+            user_info: *mut c_void,
+        ) -> CFTypeRef;
+        fn CGEventTapEnable(tap: CFTypeRef, enable: bool);
+        fn CGEventGetIntegerValueField(event: CGEventRef, field: u32) -> i64;
+        fn CGEventGetFlags(event: CGEventRef) -> u64;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopDefaultMode: CFTypeRef;
+        fn CFMachPortCreateRunLoopSource(allocator: CFTypeRef, port: CFTypeRef, order: isize) -> CFTypeRef;
+        fn CFMachPortInvalidate(port: CFTypeRef);
+        fn CFRunLoopGetCurrent() -> CFTypeRef;
+        fn CFRunLoopAddSource(run_loop: CFTypeRef, source: CFTypeRef, mode: CFTypeRef);
+        fn CFRunLoopRunInMode(mode: CFTypeRef, seconds: f64, return_after_source_handled: bool) -> i32;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    /// Key code and flags of the last key press seen by the tap
+    static PRESSED: Mutex<Option<(u16, u64)>> = Mutex::new(None);
+
+    extern "C" fn on_event(
+        _proxy: *mut c_void,
+        event_type: u32,
+        event: CGEventRef,
+        _user_info: *mut c_void,
+    ) -> CGEventRef {
+        if event_type == KEY_DOWN {
+            let (keycode, flags) =
+                unsafe { (CGEventGetIntegerValueField(event, KEYBOARD_EVENT_KEYCODE), CGEventGetFlags(event)) };
+            if let Ok(mut pressed) = PRESSED.lock() {
+                *pressed = Some((keycode as u16, flags));
+            }
+        }
+        event
+    }
+
+    /// Wait up to `timeout` for the next key press in the login session.
+    pub fn next_key_press(timeout: Duration) -> Result<Option<(u16, u64)>, String> {
+        if let Ok(mut pressed) = PRESSED.lock() {
+            *pressed = None;
+        }
+
+        unsafe {
+            let port = CGEventTapCreate(
+                SESSION_EVENT_TAP,
+                HEAD_INSERT_EVENT_TAP,
+                LISTEN_ONLY,
+                1 << KEY_DOWN,
+                on_event,
+                std::ptr::null_mut(),
+            );
+            if port.is_null() {
+                return Err("Cannot listen for key presses; allow DPUI under Input Monitoring in System Settings"
+                    .to_string());
+            }
+            let source = CFMachPortCreateRunLoopSource(std::ptr::null_mut(), port, 0);
+            CFRunLoopAddSource(CFRunLoopGetCurrent(), source, kCFRunLoopDefaultMode);
+            CGEventTapEnable(port, true);
+
+            // Returns after each handled event, so check for a press in between
+            let deadline = std::time::Instant::now() + timeout;
+            let pressed = loop {
+                let pressed = PRESSED.lock().ok().and_then(|mut p| p.take());
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                if pressed.is_some() || remaining.is_zero() {
+                    break pressed;
+                }
+                CFRunLoopRunInMode(kCFRunLoopDefaultMode, remaining.as_secs_f64(), true);
+            };
+
+            CGEventTapEnable(port, false);
+            CFMachPortInvalidate(port);
+            CFRelease(source);
+            CFRelease(port);
+
+            Ok(pressed)
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod tap {
+    use std::time::Duration;
+
+    pub fn next_key_press(_timeout: Duration) -> Result<Option<(u16, u64)>, String> {
+        Err("Hotkey capture is only available on macOS".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcut_from_key() {
+        assert_eq!(shortcut_from_key(18, FLAG_COMMAND | FLAG_SHIFT).as_deref(), Some("Cmd+Shift+1"));
+        assert_eq!(shortcut_from_key(2, FLAG_CONTROL | FLAG_ALTERNATE).as_deref(), Some("Ctrl+Alt+D"));
+        assert_eq!(shortcut_from_key(122, 0).as_deref(), Some("F1"));
+        assert_eq!(shortcut_from_key(0, 0), None);
+        assert_eq!(shortcut_from_key(200, FLAG_COMMAND), None);
+
+        let shortcut = shortcut_from_key(126, FLAG_COMMAND | FLAG_ALTERNATE).unwrap();
+        assert!(shortcut.parse::<tauri_plugin_global_shortcut::Shortcut>().is_ok());
+    }
+}
@@ -1,18 +1,54 @@
-//! Global hotkey management for preset activation.
+//! Global hotkey management for presets, workspaces and display actions.
 //!
 //! This module handles system-wide keyboard shortcuts that allow users
 //! to quickly apply display presets without switching to the application.
+//!
+//! A shortcut may also be a sequence of strokes separated by commas, such as
+//! "Cmd+Shift+D, 1". Only the first stroke (the leader) is registered
+//! permanently; pressing it registers the possible next strokes until one is
+//! pressed or `SEQUENCE_TIMEOUT` passes, so a single prefix can fan out to
+//! many presets without taking the plain keys away from other apps.
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
-/// Represents a hotkey binding for a preset.
+use crate::display_sleep;
+use crate::displayplacer::{find_display, get_displays, rotate_display, toggle_display};
+use crate::history::HistorySource;
+use crate::presets::{spawn_apply_preset, Preset};
+use crate::state::AppState;
+use crate::storage;
+use crate::system_tray::{show_tray_notification, update_tray_menu};
+use crate::workspaces::spawn_apply_workspace;
+
+/// What a hotkey applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyTarget {
+    #[default]
+    Preset,
+    Workspace,
+    /// Turn a display on or off
+    DisplayToggle,
+    /// Rotate a display by 90° clockwise, cycling 0 → 90 → 180 → 270
+    DisplayRotate,
+    /// Put all displays to sleep; the binding has no ID
+    SleepDisplays,
+}
+
+/// Represents a hotkey binding for a preset, workspace or display action.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HotkeyBinding {
-    /// Unique preset ID
+    /// Unique preset ID, or the workspace or display ID for other targets
     pub preset_id: String,
-    /// Shortcut string (e.g., "Cmd+Shift+1")
+    /// What the binding applies
+    #[serde(default)]
+    pub target: HotkeyTarget,
+    /// Shortcut string (e.g., "Cmd+Shift+1", or "Cmd+Shift+D, 1" for a sequence)
     pub shortcut: String,
     /// Human-readable description
     pub description: String,
@@ -21,6 +57,91 @@ pub struct HotkeyBinding {
 /// Result type for hotkey operations
 pub type HotkeyResult<T> = Result<T, String>;
 
+/// Bindings registered through this module, kept so they can be listed and
+/// re-registered after being suspended
+static REGISTERED: Mutex<Vec<HotkeyBinding>> = Mutex::new(Vec::new());
+
+/// Whether shortcuts are active; false while paused with `set_hotkeys_enabled`
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// How long a sequence waits for its next stroke
+const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// A sequence whose leader has been pressed and that waits for the next stroke
+struct PendingSequence {
+    /// Identifies this step, so a stale timeout doesn't end a newer sequence
+    step: u64,
+    /// Next strokes registered with the plugin while waiting
+    followers: Vec<Shortcut>,
+}
+
+static PENDING: Mutex<Option<PendingSequence>> = Mutex::new(None);
+
+static SEQUENCE_STEP: AtomicU64 = AtomicU64::new(0);
+
+/// Split a shortcut string into its strokes.
+///
+/// Strokes are separated by commas; a comma right after "+" is the Comma key.
+fn split_strokes(shortcut_str: &str) -> Vec<&str> {
+    let mut strokes = Vec::new();
+    let mut start = 0;
+    for (i, c) in shortcut_str.char_indices() {
+        let stroke = shortcut_str[start..i].trim();
+        if c == ',' && !stroke.is_empty() && !stroke.ends_with('+') {
+            strokes.push(stroke);
+            start = i + 1;
+        }
+    }
+    strokes.push(shortcut_str[start..].trim());
+    strokes
+}
+
+/// Parse a single shortcut or a comma-separated sequence of strokes.
+pub(crate) fn parse_strokes(shortcut_str: &str) -> HotkeyResult<Vec<Shortcut>> {
+    split_strokes(shortcut_str)
+        .into_iter()
+        .map(|stroke| {
+            stroke
+                .parse::<Shortcut>()
+                .map_err(|e| format!("Invalid shortcut format: {}", e))
+        })
+        .collect()
+}
+
+/// Check that a shortcut is not in use and doesn't overlap a sequence.
+///
+/// A sequence may not start with another binding's shortcut or be the start
+/// of another sequence, since it would be unclear which one a press belongs to.
+fn check_available(app: &AppHandle, strokes: &[Shortcut], shortcut_str: &str) -> HotkeyResult<()> {
+    if strokes.len() == 1 && app.global_shortcut().is_registered(strokes[0]) {
+        return Err(format!("Shortcut {} is already in use", shortcut_str));
+    }
+
+    let registered = REGISTERED.lock().map(|r| r.clone()).unwrap_or_default();
+    for binding in &registered {
+        let Ok(other) = parse_strokes(&binding.shortcut) else {
+            continue;
+        };
+        if other.len() == 1 && strokes.len() == 1 {
+            continue;
+        }
+        if other == strokes {
+            return Err(format!("Shortcut {} is already in use", shortcut_str));
+        }
+        if other.starts_with(strokes) || strokes.starts_with(&other) {
+            return Err(format!("Shortcut {} overlaps {}", shortcut_str, binding.shortcut));
+        }
+    }
+    Ok(())
+}
+
+/// Save the current bindings through the storage backend, logging failures.
+fn persist_bindings(registered: &[HotkeyBinding]) {
+    if let Err(e) = storage::backend().save_hotkeys(registered) {
+        log::error!("[Hotkey] Failed to save hotkey bindings: {}", e);
+    }
+}
+
 /// Register a global hotkey for a preset.
 ///
 /// # Arguments
@@ -37,43 +158,455 @@ pub async fn register_preset_hotkey(
     preset_id: String,
     shortcut_str: String,
 ) -> HotkeyResult<()> {
-    let shortcut = match shortcut_str.parse::<Shortcut>() {
-        Ok(s) => s,
-        Err(e) => return Err(format!("Invalid shortcut format: {}", e)),
+    register_target(&app, HotkeyTarget::Preset, &preset_id, &shortcut_str)
+}
+
+/// Register a global hotkey that turns a display on or off.
+///
+/// # Arguments
+/// * `app` - Tauri application handle
+/// * `display_id` - Display ID of any type (persistent, contextual or serial)
+/// * `shortcut_str` - Keyboard shortcut string (e.g., "Cmd+Shift+1")
+///
+/// # Returns
+/// * `Ok(())` - Hotkey registered successfully
+/// * `Err(String)` - Error message if registration fails
+#[tauri::command]
+pub async fn register_display_toggle_hotkey(
+    app: AppHandle,
+    display_id: String,
+    shortcut_str: String,
+) -> HotkeyResult<()> {
+    register_target(&app, HotkeyTarget::DisplayToggle, &display_id, &shortcut_str)
+}
+
+/// Register a global hotkey that rotates a display by 90° per press.
+///
+/// # Arguments
+/// * `app` - Tauri application handle
+/// * `display_id` - Display ID of any type (persistent, contextual or serial)
+/// * `shortcut_str` - Keyboard shortcut string (e.g., "Cmd+Shift+R")
+///
+/// # Returns
+/// * `Ok(())` - Hotkey registered successfully
+/// * `Err(String)` - Error message if registration fails
+#[tauri::command]
+pub async fn register_display_rotate_hotkey(
+    app: AppHandle,
+    display_id: String,
+    shortcut_str: String,
+) -> HotkeyResult<()> {
+    register_target(&app, HotkeyTarget::DisplayRotate, &display_id, &shortcut_str)
+}
+
+/// Register a global hotkey that puts all displays to sleep.
+///
+/// # Arguments
+/// * `app` - Tauri application handle
+/// * `shortcut_str` - Keyboard shortcut string (e.g., "Cmd+Shift+L")
+///
+/// # Returns
+/// * `Ok(())` - Hotkey registered successfully
+/// * `Err(String)` - Error message if registration fails
+#[tauri::command]
+pub async fn register_sleep_displays_hotkey(app: AppHandle, shortcut_str: String) -> HotkeyResult<()> {
+    register_target(&app, HotkeyTarget::SleepDisplays, "", &shortcut_str)
+}
+
+/// Register a global hotkey that applies a preset or workspace or acts on a display.
+///
+/// # Arguments
+/// * `app` - Tauri application handle
+/// * `target` - What `id` refers to and what the hotkey does
+/// * `id` - Preset, workspace or display ID
+/// * `shortcut_str` - Keyboard shortcut string (e.g., "Cmd+Shift+1")
+pub fn register_target(app: &AppHandle, target: HotkeyTarget, id: &str, shortcut_str: &str) -> HotkeyResult<()> {
+    let strokes = parse_strokes(shortcut_str)?;
+    check_available(app, &strokes, shortcut_str)?;
+
+    let description = match target {
+        HotkeyTarget::Preset => format!("Apply preset with {}", shortcut_str),
+        HotkeyTarget::Workspace => format!("Apply workspace with {}", shortcut_str),
+        HotkeyTarget::DisplayToggle => format!("Toggle display with {}", shortcut_str),
+        HotkeyTarget::DisplayRotate => format!("Rotate display with {}", shortcut_str),
+        HotkeyTarget::SleepDisplays => format!("Sleep displays with {}", shortcut_str),
+    };
+    let binding = HotkeyBinding {
+        preset_id: id.to_string(),
+        target,
+        shortcut: shortcut_str.to_string(),
+        description,
     };
 
-    // Check if shortcut is already registered
-    let shortcuts = app.global_shortcut();
-    if shortcuts.is_registered(shortcut) {
-        return Err(format!("Shortcut {} is already in use", shortcut_str));
+    // While paused the binding is only remembered, and registered on resume
+    if is_enabled() {
+        register_binding(app, &binding)?;
     }
 
-    // Register the shortcut
-    let preset_id_clone = preset_id.clone();
-    let app_clone = app.clone();
+    // Store the binding for later reference
+    if let Ok(mut registered) = REGISTERED.lock() {
+        registered.retain(|b| b.shortcut != binding.shortcut);
+        registered.push(binding.clone());
+        persist_bindings(&registered);
+    }
+    let _ = app.emit("hotkey-registered", binding);
 
-    shortcuts
-        .on_shortcut(shortcut, move |_app, _shortcut, _event| {
-            if matches!(_event.state, ShortcutState::Pressed) {
-                // Emit event to frontend to apply the preset
-                let _ = app_clone.emit("apply-preset-hotkey", &preset_id_clone);
+    Ok(())
+}
+
+/// Register the global shortcut for a binding with the plugin.
+///
+/// For a sequence only the leader is registered, and only if no other
+/// sequence has registered it already.
+fn register_binding(app: &AppHandle, binding: &HotkeyBinding) -> HotkeyResult<()> {
+    let strokes = parse_strokes(&binding.shortcut)?;
 
-                // Log the hotkey activation
-                println!("[Hotkey] Activated preset: {}", preset_id_clone);
+    if strokes.len() > 1 {
+        let leader = strokes[0];
+        if app.global_shortcut().is_registered(leader) {
+            return Ok(());
+        }
+        return app
+            .global_shortcut()
+            .on_shortcut(leader, move |app, _shortcut, event| {
+                if matches!(event.state, ShortcutState::Pressed) {
+                    advance_sequence(app, vec![leader]);
+                }
+            })
+            .map_err(|e| format!("Failed to register shortcut: {}", e));
+    }
+
+    let binding = binding.clone();
+    app.global_shortcut()
+        .on_shortcut(strokes[0], move |app, _shortcut, event| {
+            if matches!(event.state, ShortcutState::Pressed) {
+                activate(app, &binding);
             }
         })
-        .map_err(|e| format!("Failed to register shortcut: {}", e))?;
+        .map_err(|e| format!("Failed to register shortcut: {}", e))
+}
 
-    // Store the binding for later reference
-    let _ = app.emit("hotkey-registered", HotkeyBinding {
-        preset_id,
-        shortcut: shortcut_str.clone(),
-        description: format!("Apply preset with {}", shortcut_str),
+/// Run the action of a binding whose shortcut was pressed.
+fn activate(app: &AppHandle, binding: &HotkeyBinding) {
+    // Log the hotkey activation
+    log::info!("[Hotkey] Activated {:?}: {}", binding.target, binding.preset_id);
+
+    // Apply in the backend so it works while the window is hidden
+    let id = binding.preset_id.clone();
+    match binding.target {
+        HotkeyTarget::Preset => spawn_apply_preset(app, id, HistorySource::Hotkey),
+        HotkeyTarget::Workspace => spawn_apply_workspace(app, id),
+        HotkeyTarget::DisplayToggle => spawn_toggle_display(id),
+        HotkeyTarget::DisplayRotate => spawn_rotate_display(id),
+        HotkeyTarget::SleepDisplays => spawn_sleep_displays(),
+    }
+}
+
+/// Handle a stroke of a sequence, given all strokes pressed so far.
+///
+/// Runs the sequence they complete, or registers the strokes that can follow
+/// and waits for one of them until `SEQUENCE_TIMEOUT` passes.
+fn advance_sequence(app: &AppHandle, pressed: Vec<Shortcut>) {
+    end_sequence(app);
+
+    let bindings = REGISTERED.lock().map(|r| r.clone()).unwrap_or_default();
+    let mut followers: Vec<Shortcut> = Vec::new();
+    for binding in &bindings {
+        let Ok(strokes) = parse_strokes(&binding.shortcut) else {
+            continue;
+        };
+        if strokes.len() < 2 || !strokes.starts_with(&pressed) {
+            continue;
+        }
+        if strokes.len() == pressed.len() {
+            activate(app, binding);
+            return;
+        }
+        let next = strokes[pressed.len()];
+        if !followers.contains(&next) {
+            followers.push(next);
+        }
+    }
+
+    // A follower that is also a standalone shortcut is left to that shortcut
+    followers.retain(|&next| {
+        let pressed = pressed.clone();
+        let result = app.global_shortcut().on_shortcut(next, move |app, _shortcut, event| {
+            if matches!(event.state, ShortcutState::Pressed) {
+                let mut pressed = pressed.clone();
+                pressed.push(next);
+                advance_sequence(app, pressed);
+            }
+        });
+        if let Err(e) = &result {
+            log::error!("[Hotkey] Failed to register sequence stroke {}: {}", next, e);
+        }
+        result.is_ok()
     });
+    if followers.is_empty() {
+        return;
+    }
+
+    let step = SEQUENCE_STEP.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some(PendingSequence { step, followers });
+    }
 
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(SEQUENCE_TIMEOUT);
+        let timed_out = PENDING
+            .lock()
+            .map(|p| p.as_ref().is_some_and(|p| p.step == step))
+            .unwrap_or(false);
+        if timed_out {
+            end_sequence(&app);
+        }
+    });
+}
+
+/// Stop waiting for the next stroke of a sequence and unregister its followers.
+fn end_sequence(app: &AppHandle) {
+    let Some(pending) = PENDING.lock().ok().and_then(|mut p| p.take()) else {
+        return;
+    };
+    for follower in pending.followers {
+        let _ = app.global_shortcut().unregister(follower);
+    }
+}
+
+/// Unregister a shortcut with the plugin after its binding was removed.
+///
+/// A sequence's leader stays registered while another sequence still uses it.
+fn unregister_binding(app: &AppHandle, shortcut_str: &str) -> HotkeyResult<()> {
+    let strokes = parse_strokes(shortcut_str)?;
+
+    if strokes.len() > 1 {
+        let leader = strokes[0];
+        let shared = REGISTERED
+            .lock()
+            .map(|r| {
+                r.iter()
+                    .filter_map(|b| parse_strokes(&b.shortcut).ok())
+                    .any(|other| other.len() > 1 && other[0] == leader)
+            })
+            .unwrap_or(false);
+        if shared {
+            return Ok(());
+        }
+    }
+
+    app.global_shortcut()
+        .unregister(strokes[0])
+        .map_err(|e| format!("Failed to unregister shortcut: {}", e))
+}
+
+/// Turn a display off if it is on and on if it is off, reporting failures to the user.
+fn spawn_toggle_display(display_id: String) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = toggle_display(&display_id, HistorySource::Hotkey).await {
+            log::error!("[Hotkey] Failed to toggle display {}: {}", display_id, e);
+            show_tray_notification("DPUI", &format!("Failed to toggle display: {}", e));
+        }
+    });
+}
+
+/// Put all displays to sleep, reporting failures to the user.
+fn spawn_sleep_displays() {
+    tauri::async_runtime::spawn(async {
+        if let Err(e) = display_sleep::sleep_displays().await {
+            log::error!("[Hotkey] Failed to sleep displays: {}", e);
+            show_tray_notification("DPUI", &format!("Failed to sleep displays: {}", e));
+        }
+    });
+}
+
+/// Rotate a display a further 90° clockwise, reporting failures to the user.
+fn spawn_rotate_display(display_id: String) {
+    tauri::async_runtime::spawn(async move {
+        let result = async {
+            let current = get_displays().await?;
+            let display = find_display(&current.displays, &display_id)
+                .ok_or_else(|| format!("Display {} is not connected", display_id))?;
+
+            rotate_display(&display.id, next_rotation(display.rotation), HistorySource::Hotkey).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            log::error!("[Hotkey] Failed to rotate display {}: {}", display_id, e);
+            show_tray_notification("DPUI", &format!("Failed to rotate display: {}", e));
+        }
+    });
+}
+
+/// The rotation after `degrees` in the 0 → 90 → 180 → 270 → 0 cycle.
+fn next_rotation(degrees: i32) -> i32 {
+    (degrees.rem_euclid(360) / 90 * 90 + 90) % 360
+}
+
+/// Bring preset hotkey registrations in line with the presets' `hotkey` fields.
+///
+/// Bindings for presets that were removed or whose shortcut changed are
+/// unregistered, and shortcuts that aren't registered yet are registered.
+/// Failures are logged and skipped.
+pub fn sync_preset_hotkeys(app: &AppHandle, presets: &[Preset]) {
+    let registered: Vec<HotkeyBinding> = REGISTERED
+        .lock()
+        .map(|r| r.iter().filter(|b| b.target == HotkeyTarget::Preset).cloned().collect())
+        .unwrap_or_default();
+
+    let wanted = |binding: &HotkeyBinding| {
+        presets
+            .iter()
+            .any(|p| p.id == binding.preset_id && p.hotkey.as_deref() == Some(binding.shortcut.as_str()))
+    };
+    for binding in registered.iter().filter(|b| !wanted(b)) {
+        unregister_target(app, &binding.shortcut);
+    }
+
+    for preset in presets {
+        let Some(shortcut) = preset.hotkey.as_deref() else {
+            continue;
+        };
+        if registered.iter().any(|b| b.preset_id == preset.id && b.shortcut == shortcut) {
+            continue;
+        }
+        if let Err(e) = register_target(app, HotkeyTarget::Preset, &preset.id, shortcut) {
+            log::error!("[Hotkey] Failed to register {} for {}: {}", shortcut, preset.name, e);
+        }
+    }
+}
+
+/// Temporarily unregister every shortcut, keeping the bindings for `resume_all`.
+pub fn suspend_all(app: &AppHandle) -> HotkeyResult<()> {
+    end_sequence(app);
+    app.global_shortcut()
+        .unregister_all()
+        .map_err(|e| format!("Failed to suspend shortcuts: {}", e))?;
+
+    log::info!("[Hotkey] Suspended all shortcuts");
+    Ok(())
+}
+
+/// Re-register every binding previously suspended with `suspend_all`.
+///
+/// Bindings that fail to register are logged and skipped. Does nothing while
+/// hotkeys are paused.
+pub fn resume_all(app: &AppHandle) {
+    if !is_enabled() {
+        return;
+    }
+
+    let bindings = REGISTERED.lock().map(|r| r.clone()).unwrap_or_default();
+
+    for binding in &bindings {
+        if app.global_shortcut().is_registered(binding.shortcut.as_str()) {
+            continue;
+        }
+        if let Err(e) = register_binding(app, binding) {
+            log::error!("[Hotkey] Failed to re-register {}: {}", binding.shortcut, e);
+        }
+    }
+
+    log::info!("[Hotkey] Resumed {} shortcuts", bindings.len());
+}
+
+/// Unregister and re-register every binding.
+///
+/// For when the system may have dropped registrations the plugin still
+/// considers active, e.g. after sleep. Does nothing while hotkeys are paused.
+pub fn reregister_all(app: &AppHandle) {
+    if !is_enabled() {
+        return;
+    }
+    if let Err(e) = suspend_all(app) {
+        log::error!("[Hotkey] {}", e);
+    }
+    resume_all(app);
+}
+
+/// Whether hotkeys are active, i.e. not paused.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Pause or resume every hotkey.
+///
+/// Pausing unregisters all shortcuts so games or remote-desktop sessions
+/// receive the key combinations; the bindings are kept and registered again
+/// on resume. The state is not persisted, so hotkeys are active after a restart.
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> HotkeyResult<()> {
+    if ENABLED.swap(enabled, Ordering::SeqCst) == enabled {
+        return Ok(());
+    }
+
+    if enabled {
+        resume_all(app);
+    } else if let Err(e) = suspend_all(app) {
+        ENABLED.store(true, Ordering::SeqCst);
+        return Err(e);
+    }
+
+    log::info!("[Hotkey] Hotkeys {}", if enabled { "resumed" } else { "paused" });
+    let _ = app.emit("hotkeys-enabled-changed", enabled);
+    if let Err(e) = update_tray_menu(app) {
+        log::error!("[Hotkey] Failed to update tray menu: {}", e);
+    }
     Ok(())
 }
 
+/// Pause or resume all global hotkeys.
+///
+/// # Arguments
+/// * `app` - Tauri application handle
+/// * `enabled` - `false` to unregister every shortcut, `true` to register them again
+///
+/// # Returns
+/// * `Ok(())` - Hotkeys paused or resumed
+/// * `Err(String)` - Error message if the shortcuts could not be unregistered
+#[tauri::command]
+pub async fn set_hotkeys_enabled(app: AppHandle, enabled: bool) -> HotkeyResult<()> {
+    set_enabled(&app, enabled)
+}
+
+/// Whether global hotkeys are currently active (not paused).
+#[tauri::command]
+pub async fn get_hotkeys_enabled() -> bool {
+    is_enabled()
+}
+
+/// Shortcut of the binding registered for a preset, workspace or display.
+pub fn shortcut_for(target: HotkeyTarget, id: &str) -> Option<String> {
+    let registered = REGISTERED.lock().ok()?;
+    registered
+        .iter()
+        .find(|b| b.target == target && b.preset_id == id)
+        .map(|b| b.shortcut.clone())
+}
+
+/// Whether a shortcut is currently registered with the system.
+///
+/// For a sequence this is whether its leader is registered. Invalid shortcut
+/// strings are never active.
+pub fn is_active(app: &AppHandle, shortcut_str: &str) -> bool {
+    parse_strokes(shortcut_str)
+        .map(|strokes| app.global_shortcut().is_registered(strokes[0]))
+        .unwrap_or(false)
+}
+
+/// Unregister a shortcut registered with `register_target`, logging failures.
+pub fn unregister_target(app: &AppHandle, shortcut_str: &str) {
+    if let Ok(mut registered) = REGISTERED.lock() {
+        registered.retain(|b| b.shortcut != shortcut_str);
+        persist_bindings(&registered);
+    }
+
+    if let Err(e) = unregister_binding(app, shortcut_str) {
+        log::error!("[Hotkey] Failed to unregister {}: {}", shortcut_str, e);
+    }
+}
+
 /// Unregister a global hotkey.
 ///
 /// # Arguments
@@ -85,18 +618,16 @@ pub async fn register_preset_hotkey(
 /// * `Err(String)` - Error message if unregistration fails
 #[tauri::command]
 pub async fn unregister_hotkey(app: AppHandle, shortcut_str: String) -> HotkeyResult<()> {
-    let shortcut = match shortcut_str.parse::<Shortcut>() {
-        Ok(s) => s,
-        Err(e) => return Err(format!("Invalid shortcut format: {}", e)),
-    };
+    parse_strokes(&shortcut_str)?;
 
-    let shortcuts = app.global_shortcut();
+    if let Ok(mut registered) = REGISTERED.lock() {
+        registered.retain(|b| b.shortcut != shortcut_str);
+        persist_bindings(&registered);
+    }
 
-    shortcuts
-        .unregister(shortcut)
-        .map_err(|e| format!("Failed to unregister shortcut: {}", e))?;
+    unregister_binding(&app, &shortcut_str)?;
 
-    println!("[Hotkey] Unregistered: {}", shortcut_str);
+    log::info!("[Hotkey] Unregistered: {}", shortcut_str);
     Ok(())
 }
 
@@ -116,7 +647,12 @@ pub async fn unregister_all_hotkeys(app: AppHandle) -> HotkeyResult<()> {
         .unregister_all()
         .map_err(|e| format!("Failed to unregister all shortcuts: {}", e))?;
 
-    println!("[Hotkey] Unregistered all shortcuts");
+    if let Ok(mut registered) = REGISTERED.lock() {
+        registered.clear();
+        persist_bindings(&registered);
+    }
+
+    log::info!("[Hotkey] Unregistered all shortcuts");
     Ok(())
 }
 
@@ -131,24 +667,17 @@ pub async fn unregister_all_hotkeys(app: AppHandle) -> HotkeyResult<()> {
 /// * `Err(String)` - Error message if check fails
 #[tauri::command]
 pub async fn is_hotkey_available(app: AppHandle, shortcut_str: String) -> HotkeyResult<bool> {
-    let shortcut = match shortcut_str.parse::<Shortcut>() {
-        Ok(s) => s,
-        Err(e) => return Err(format!("Invalid shortcut format: {}", e)),
-    };
-
-    let shortcuts = app.global_shortcut();
-    Ok(!shortcuts.is_registered(shortcut))
+    let strokes = parse_strokes(&shortcut_str)?;
+    Ok(check_available(&app, &strokes, &shortcut_str).is_ok())
 }
 
 /// Get list of all registered hotkeys.
 ///
-/// Note: This is a placeholder as Tauri doesn't provide a way to list all shortcuts.
-/// In a real implementation, you would maintain your own registry.
+/// Tauri doesn't provide a way to list shortcuts, so this returns the
+/// bindings registered through this module.
 #[tauri::command]
 pub async fn get_registered_hotkeys() -> Vec<HotkeyBinding> {
-    // TODO: Maintain a persistent registry of hotkey bindings
-    // For now, return empty list
-    vec![]
+    REGISTERED.lock().map(|r| r.clone()).unwrap_or_default()
 }
 
 /// Validate a hotkey string format.
@@ -161,20 +690,70 @@ pub async fn get_registered_hotkeys() -> Vec<HotkeyBinding> {
 /// * `Err(String)` - Error message describing the validation issue
 #[tauri::command]
 pub async fn validate_hotkey_format(shortcut_str: String) -> HotkeyResult<()> {
-    match shortcut_str.parse::<Shortcut>() {
+    match parse_strokes(&shortcut_str) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Invalid shortcut format: {}. Examples: Cmd+Shift+1, Ctrl+Alt+D", e)),
+        Err(e) => Err(format!("{}. Examples: Cmd+Shift+1, Ctrl+Alt+D, Cmd+Shift+D, 1", e)),
     }
 }
 
 /// Initialize default hotkeys on application startup.
 ///
-/// This function can be called from the main.rs setup to register
-/// default hotkeys for existing presets with configured shortcuts.
-pub fn initialize_default_hotkeys(_app: &AppHandle) -> HotkeyResult<()> {
-    // Load presets and register their hotkeys
-    // This would typically load from your preset storage
+/// Registers the shortcut of every preset that has a `hotkey` set, so they
+/// work right after launch without opening the UI, and restores saved display
+/// bindings. Shortcuts that fail to register are logged and skipped. Requires
+/// `AppState` to be managed.
+pub fn initialize_default_hotkeys(app: &AppHandle) -> HotkeyResult<()> {
+    let store = app
+        .try_state::<AppState>()
+        .ok_or("Presets are not loaded yet")?
+        .presets();
+    sync_preset_hotkeys(app, &store.presets);
 
-    println!("[Hotkey] Initialized default hotkeys");
+    // Preset and workspace bindings are derived from their own stores
+    let saved = storage::backend().load_hotkeys().unwrap_or_else(|e| {
+        log::error!("[Hotkey] Failed to load saved bindings: {}", e);
+        Vec::new()
+    });
+    let is_display_binding = |b: &&HotkeyBinding| {
+        matches!(
+            b.target,
+            HotkeyTarget::DisplayToggle | HotkeyTarget::DisplayRotate | HotkeyTarget::SleepDisplays
+        )
+    };
+    for binding in saved.iter().filter(is_display_binding) {
+        if let Err(e) = register_target(app, binding.target, &binding.preset_id, &binding.shortcut) {
+            log::error!("[Hotkey] Failed to restore {}: {}", binding.shortcut, e);
+        }
+    }
+
+    let count = store.presets.iter().filter(|p| p.hotkey.is_some()).count();
+    log::info!("[Hotkey] Initialized {} preset hotkeys", count);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_rotation() {
+        assert_eq!(next_rotation(0), 90);
+        assert_eq!(next_rotation(90), 180);
+        assert_eq!(next_rotation(180), 270);
+        assert_eq!(next_rotation(270), 0);
+        assert_eq!(next_rotation(-90), 0);
+    }
+
+    #[test]
+    fn test_split_strokes() {
+        assert_eq!(split_strokes("Cmd+Shift+1"), vec!["Cmd+Shift+1"]);
+        assert_eq!(split_strokes("Cmd+Shift+D, 1"), vec!["Cmd+Shift+D", "1"]);
+        assert_eq!(split_strokes("Cmd+,, Cmd+,"), vec!["Cmd+,", "Cmd+,"]);
+        assert_eq!(split_strokes("Ctrl+K,Ctrl+2"), vec!["Ctrl+K", "Ctrl+2"]);
+
+        let strokes = parse_strokes("Cmd+Shift+D, 1").unwrap();
+        assert_eq!(strokes.len(), 2);
+        assert!(strokes.starts_with(&parse_strokes("Shift+Cmd+D").unwrap()));
+        assert!(parse_strokes("Cmd+Shift+D, ").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! "Identify displays" overlay.
+//!
+//! Shows a small borderless window in the top-left corner of every monitor
+//! with a big number and the display's name, for a few seconds. The numbers
+//! follow the display list in the UI, so users can tell which UUID is which
+//! physical screen.
+
+use std::time::Duration;
+use tauri::{AppHandle, LogicalPosition, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::display_windows::display_for_monitor;
+use crate::displayplacer::get_displays;
+use crate::url_scheme::percent_encode;
+
+const LABEL_PREFIX: &str = "identify_";
+
+/// How long the overlays stay up
+const SHOW_FOR: Duration = Duration::from_secs(3);
+
+const OVERLAY_WIDTH: f64 = 260.0;
+const OVERLAY_HEIGHT: f64 = 180.0;
+/// Distance from the monitor's top-left corner, clear of the menu bar
+const OVERLAY_INSET: f64 = 48.0;
+
+fn close_overlays(app: &AppHandle) {
+    for (label, window) in app.webview_windows() {
+        if label.starts_with(LABEL_PREFIX) {
+            let _ = window.close();
+        }
+    }
+}
+
+/// Show each display's number and name on the display itself for a few seconds
+///
+/// # Arguments
+/// * `app` - Application handle
+///
+/// # Returns
+/// * `Ok(())` - Overlays shown; they close on their own
+/// * `Err(String)` - Error message if monitors or displays can't be read
+#[tauri::command]
+pub async fn identify_displays(app: AppHandle) -> Result<(), String> {
+    // Identifying again restarts the timer
+    close_overlays(&app);
+
+    let config = get_displays().await?;
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+
+    for (index, monitor) in monitors.iter().enumerate() {
+        let display = display_for_monitor(&config.displays, monitor);
+        // Number displays as the UI lists them
+        let number = display
+            .and_then(|d| config.displays.iter().position(|c| c.id == d.id))
+            .unwrap_or(index)
+            + 1;
+        let name = display.and_then(|d| d.name.clone()).unwrap_or_default();
+
+        let position = monitor.position().to_logical::<f64>(monitor.scale_factor());
+        let url = format!("index.html?view=identify&number={}&name={}", number, percent_encode(&name));
+        WebviewWindowBuilder::new(&app, format!("{}{}", LABEL_PREFIX, index), WebviewUrl::App(url.into()))
+            .title(format!("Display {}", number))
+            .inner_size(OVERLAY_WIDTH, OVERLAY_HEIGHT)
+            .decorations(false)
+            .resizable(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .focused(false)
+            .build()
+            .and_then(|window| {
+                window.set_position(LogicalPosition::new(position.x + OVERLAY_INSET, position.y + OVERLAY_INSET))
+            })
+            .map_err(|e| format!("Failed to create overlay: {}", e))?;
+    }
+
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(SHOW_FOR);
+        close_overlays(&handle);
+    });
+
+    log::info!("[Identify] Showing overlays on {} monitors", monitors.len());
+    Ok(())
+}
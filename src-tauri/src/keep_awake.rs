@@ -0,0 +1,92 @@
+//! Keeping the displays awake while a preset is active.
+//!
+//! A preset with `keep_awake` holds an IOKit power assertion that stops the
+//! displays from sleeping, e.g. for a presentation. Applying any other preset
+//! releases it.
+
+use std::sync::Mutex;
+
+/// The assertion currently held, if any
+static ASSERTION: Mutex<Option<u32>> = Mutex::new(None);
+
+/// Hold or release the assertion keeping the displays awake.
+///
+/// `reason` is shown by `pmset -g assertions`. Holding it again keeps the
+/// existing assertion.
+pub(crate) fn set(enabled: bool, reason: &str) -> Result<(), String> {
+    let mut held = ASSERTION.lock().map_err(|_| "Keep-awake lock poisoned".to_string())?;
+    match (enabled, *held) {
+        (true, None) => {
+            *held = Some(pm::prevent_display_sleep(reason)?);
+            log::info!("[KeepAwake] Keeping displays awake for {}", reason);
+        }
+        (false, Some(id)) => {
+            pm::release(id)?;
+            *held = None;
+            log::info!("[KeepAwake] Displays may sleep again");
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod pm {
+    use std::ffi::{c_char, c_void, CString};
+
+    type CFTypeRef = *const c_void;
+
+    /// `kIOPMAssertionLevelOn`
+    const LEVEL_ON: u32 = 255;
+
+    /// `kCFStringEncodingUTF8`
+    const UTF8_ENCODING: u32 = 0x0800_0100;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(kind: CFTypeRef, level: u32, name: CFTypeRef, id: *mut u32) -> i32;
+        fn IOPMAssertionRelease(id: u32) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(allocator: CFTypeRef, string: *const c_char, encoding: u32) -> CFTypeRef;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    pub fn prevent_display_sleep(reason: &str) -> Result<u32, String> {
+        let reason = CString::new(reason).map_err(|e| e.to_string())?;
+        unsafe {
+            let kind = CFStringCreateWithCString(std::ptr::null(), c"PreventUserIdleDisplaySleep".as_ptr(), UTF8_ENCODING);
+            let name = CFStringCreateWithCString(std::ptr::null(), reason.as_ptr(), UTF8_ENCODING);
+            let mut id = 0;
+            let status = IOPMAssertionCreateWithName(kind, LEVEL_ON, name, &mut id);
+            CFRelease(name);
+            CFRelease(kind);
+
+            if status != 0 {
+                return Err(format!("Failed to keep displays awake ({:#x})", status));
+            }
+            Ok(id)
+        }
+    }
+
+    pub fn release(id: u32) -> Result<(), String> {
+        let status = unsafe { IOPMAssertionRelease(id) };
+        if status != 0 {
+            return Err(format!("Failed to release keep-awake assertion ({:#x})", status));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod pm {
+    pub fn prevent_display_sleep(_reason: &str) -> Result<u32, String> {
+        Err("Keeping displays awake is only available on macOS".to_string())
+    }
+
+    pub fn release(_id: u32) -> Result<(), String> {
+        Ok(())
+    }
+}
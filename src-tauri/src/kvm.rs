@@ -0,0 +1,242 @@
+//! KVM switch support.
+//!
+//! When a KVM switches away, its displays vanish; when it switches back they
+//! reappear with identical fingerprints. In KVM mode the last stable layout is
+//! remembered while displays are away, automation is suppressed, and once every
+//! vanished display has returned within the window the exact prior layout is
+//! restored in a single displayplacer call.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::displayplacer::{display_args, execute_displayplacer, Display};
+use crate::settings;
+use crate::undo;
+
+/// A display that disappeared while KVM mode was active.
+struct VanishedDisplay {
+    display: Display,
+    since: Instant,
+}
+
+#[derive(Default)]
+struct KvmState {
+    /// Last layout observed with no displays missing
+    stable_layout: Vec<Display>,
+    /// Displays currently away, keyed by fingerprint
+    vanished: HashMap<String, VanishedDisplay>,
+}
+
+/// KVM status reported to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct KvmStatus {
+    /// Whether KVM mode is enabled in settings
+    pub enabled: bool,
+    /// IDs of displays that vanished and are expected back
+    pub waiting_for: Vec<String>,
+    /// Whether automation is currently suppressed
+    pub suppressing: bool,
+}
+
+static STATE: OnceLock<Mutex<KvmState>> = OnceLock::new();
+
+fn state() -> &'static Mutex<KvmState> {
+    STATE.get_or_init(|| Mutex::new(KvmState::default()))
+}
+
+/// Identity of a physical display that survives a KVM round trip.
+fn fingerprint(display: &Display) -> String {
+    format!(
+        "{}|{}|{}|{}",
+        display.id.to_lowercase(),
+        display.vendor.as_deref().unwrap_or(""),
+        display.name.as_deref().unwrap_or(""),
+        display.modes.len()
+    )
+}
+
+/// Whether rules and other automation should hold off.
+///
+/// True while any display is away on the KVM and still within the window.
+pub fn is_suppressing() -> bool {
+    let window = Duration::from_secs(settings::current().kvm_window_secs);
+    state()
+        .lock()
+        .map(|s| s.vanished.values().any(|v| v.since.elapsed() < window))
+        .unwrap_or(false)
+}
+
+/// Track vanished and returned displays after a topology change.
+///
+/// Displays away for longer than `window` are forgotten, along with their place
+/// in the stable layout, so they stop suppressing automation and their old
+/// layout is never restored.
+///
+/// # Returns
+/// * `Some(layout)` - Every vanished display is back; `layout` should be restored
+/// * `None` - Nothing to restore
+fn track(state: &mut KvmState, current: &[Display], window: Duration, now: Instant) -> Option<Vec<Display>> {
+    let expired: Vec<String> = state
+        .vanished
+        .iter()
+        .filter(|(_, v)| now.duration_since(v.since) >= window)
+        .map(|(fp, _)| fp.clone())
+        .collect();
+    for fp in &expired {
+        if let Some(v) = state.vanished.remove(fp) {
+            log::info!("[KVM] Display {} did not return in time", v.display.id);
+        }
+    }
+    state.stable_layout.retain(|d| !expired.contains(&fingerprint(d)));
+
+    let present: HashSet<String> = current.iter().map(fingerprint).collect();
+
+    // Remember displays from the stable layout that just went away
+    let newly_vanished: Vec<Display> = state
+        .stable_layout
+        .iter()
+        .filter(|d| !present.contains(&fingerprint(d)))
+        .cloned()
+        .collect();
+    for display in newly_vanished {
+        state.vanished.entry(fingerprint(&display)).or_insert_with(|| {
+            log::info!("[KVM] Display {} went away", display.id);
+            VanishedDisplay { display, since: now }
+        });
+    }
+
+    if state.vanished.is_empty() {
+        state.stable_layout = current.to_vec();
+        return None;
+    }
+
+    let returned: Vec<String> = state.vanished.keys().filter(|fp| present.contains(*fp)).cloned().collect();
+    for fp in &returned {
+        if let Some(v) = state.vanished.remove(fp) {
+            log::info!("[KVM] Display {} returned", v.display.id);
+        }
+    }
+
+    if !state.vanished.is_empty() || returned.is_empty() {
+        // Still waiting for displays to come back; keep the stable layout
+        return None;
+    }
+    Some(state.stable_layout.clone())
+}
+
+/// Update KVM tracking after the display topology changed.
+///
+/// Restores the remembered layout once every vanished display has returned.
+///
+/// # Arguments
+/// * `app` - Application handle used to emit `kvm-restored`
+/// * `current` - Displays connected after the change
+pub fn on_topology_change(app: &AppHandle, current: &[Display]) {
+    let settings = settings::current();
+    let Ok(mut state) = state().lock() else {
+        return;
+    };
+
+    if !settings.kvm_mode {
+        state.stable_layout = current.to_vec();
+        state.vanished.clear();
+        return;
+    }
+
+    let window = Duration::from_secs(settings.kvm_window_secs);
+    let Some(layout) = track(&mut state, current, window, Instant::now()) else {
+        return;
+    };
+
+    // Everything is back: restore the exact prior layout
+    match execute_displayplacer(display_args(&layout)) {
+        Ok(_) => {
+            undo::changed(None);
+            log::info!("[KVM] Restored layout after switch");
+            let _ = app.emit("kvm-restored", &layout);
+        }
+        Err(e) => {
+            log::error!("[KVM] Failed to restore layout: {}", e);
+            state.stable_layout = current.to_vec();
+        }
+    }
+}
+
+/// Get the current KVM mode status.
+#[tauri::command]
+pub async fn get_kvm_status() -> KvmStatus {
+    let enabled = settings::current().kvm_mode;
+    let waiting_for = state()
+        .lock()
+        .map(|s| s.vanished.values().map(|v| v.display.id.clone()).collect())
+        .unwrap_or_default();
+
+    KvmStatus {
+        enabled,
+        waiting_for,
+        suppressing: is_suppressing(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayplacer::parse_config_displays;
+
+    const WINDOW: Duration = Duration::from_secs(30);
+
+    fn ids(displays: &[Display]) -> Vec<&str> {
+        displays.iter().map(|d| d.id.as_str()).collect()
+    }
+
+    fn layouts() -> (Vec<Display>, Vec<Display>) {
+        let both = parse_config_displays(
+            r#""id:A res:1728x1117 origin:(0,0) degree:0" "id:B res:2560x1440 origin:(1728,0) degree:0""#,
+        );
+        let only_a = both[..1].to_vec();
+        (both, only_a)
+    }
+
+    #[test]
+    fn test_return_within_window() {
+        let (both, only_a) = layouts();
+        let now = Instant::now();
+        let mut state = KvmState::default();
+
+        assert!(track(&mut state, &both, WINDOW, now).is_none());
+        assert!(track(&mut state, &only_a, WINDOW, now).is_none());
+        assert_eq!(state.vanished.len(), 1);
+
+        // B comes back moved; the layout from before the switch is restored
+        let mut moved = both.clone();
+        moved[1].origin = (-2560, 0);
+        let restored = track(&mut state, &moved, WINDOW, now + Duration::from_secs(10)).unwrap();
+        assert_eq!(restored[1].origin, (1728, 0));
+        assert!(state.vanished.is_empty());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let (both, only_a) = layouts();
+        let now = Instant::now();
+        let mut state = KvmState::default();
+
+        track(&mut state, &both, WINDOW, now);
+        track(&mut state, &only_a, WINDOW, now);
+
+        // Once the window has passed, B is forgotten and the current layout adopted
+        let later = now + WINDOW;
+        assert!(track(&mut state, &only_a, WINDOW, later).is_none());
+        assert!(state.vanished.is_empty());
+        assert_eq!(ids(&state.stable_layout), ["A"]);
+
+        // Further changes don't bring it back, and its return restores nothing
+        assert!(track(&mut state, &only_a, WINDOW, later + WINDOW).is_none());
+        assert!(state.vanished.is_empty());
+        assert!(track(&mut state, &both, WINDOW, later + WINDOW).is_none());
+        assert_eq!(ids(&state.stable_layout), ["A", "B"]);
+    }
+}
@@ -0,0 +1,385 @@
+//! Arrangement of display rectangles.
+//!
+//! macOS only accepts layouts in which every display shares an edge with
+//! another and none overlap. The drag-and-drop canvas hands its rough
+//! positions to `arrange_layout`, which fixes them up:
+//!
+//! 1. Edges within `SNAP_DISTANCE` points of another display's edge snap to it.
+//! 2. A display that still overlaps another, or touches none, is moved to the
+//!    nearest spot flush against one of the displays placed before it.
+//!
+//! Displays are placed one at a time; the first keeps its position and the
+//! one that was just moved goes last, so the rest of the layout stays put.
+//!
+//! When a display is turned off, `close_gap` pulls the remaining displays
+//! together so no dead space is left where it was.
+//!
+//! Before any apply, origins are also re-based so the primary display sits at
+//! (0,0), since that is how macOS picks the main display.
+
+use serde::{Deserialize, Serialize};
+
+use crate::displayplacer::{display_size, map_config_groups, parse_config_displays, Display};
+
+/// How close, in points, an edge has to be to another to snap to it
+const SNAP_DISTANCE: i32 = 100;
+
+/// Largest origin coordinate, in points, sent to displayplacer
+const MAX_COORDINATE: i32 = 32_767;
+
+/// A display's bounds in points.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutRect {
+    pub id: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl LayoutRect {
+    fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    fn at(&self, x: i32, y: i32) -> Self {
+        Self {
+            x,
+            y,
+            ..self.clone()
+        }
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.x < other.right() && other.x < self.right() && self.y < other.bottom() && other.y < self.bottom()
+    }
+
+    /// Whether the two share a stretch of edge, not just a corner.
+    fn touches(&self, other: &Self) -> bool {
+        let spans_y = self.y < other.bottom() && other.y < self.bottom();
+        let spans_x = self.x < other.right() && other.x < self.right();
+        ((self.right() == other.x || other.right() == self.x) && spans_y)
+            || ((self.bottom() == other.y || other.bottom() == self.y) && spans_x)
+    }
+}
+
+/// The candidate closest to `value`, if within `SNAP_DISTANCE`.
+fn nearest(value: i32, candidates: impl Iterator<Item = i32>) -> Option<i32> {
+    candidates
+        .filter(|c| (c - value).abs() <= SNAP_DISTANCE)
+        .min_by_key(|c| (c - value).abs())
+}
+
+/// Snap each axis of `rect` to the nearest edge of the placed displays.
+fn snap(rect: &LayoutRect, placed: &[LayoutRect]) -> LayoutRect {
+    let xs = placed
+        .iter()
+        .flat_map(|p| [p.x - rect.width, p.right(), p.x, p.right() - rect.width]);
+    let ys = placed
+        .iter()
+        .flat_map(|p| [p.y - rect.height, p.bottom(), p.y, p.bottom() - rect.height]);
+
+    rect.at(nearest(rect.x, xs).unwrap_or(rect.x), nearest(rect.y, ys).unwrap_or(rect.y))
+}
+
+/// Move `rect` to the closest position flush against a placed display that
+/// overlaps none of them.
+fn attach(rect: &LayoutRect, placed: &[LayoutRect]) -> LayoutRect {
+    let valid = |r: &LayoutRect| placed.iter().all(|p| !r.overlaps(p)) && placed.iter().any(|p| r.touches(p));
+    if valid(rect) {
+        return rect.clone();
+    }
+
+    // Sides of each placed display, sliding along it only as far as still shares an edge
+    let candidates = placed.iter().flat_map(|p| {
+        let y = rect.y.clamp(p.y - rect.height + 1, p.bottom() - 1);
+        let x = rect.x.clamp(p.x - rect.width + 1, p.right() - 1);
+        [
+            rect.at(p.right(), y),
+            rect.at(p.x - rect.width, y),
+            rect.at(x, p.bottom()),
+            rect.at(x, p.y - rect.height),
+        ]
+    });
+
+    candidates
+        .filter(valid)
+        .min_by_key(|c| {
+            let (dx, dy) = (i64::from(c.x - rect.x), i64::from(c.y - rect.y));
+            dx * dx + dy * dy
+        })
+        .unwrap_or_else(|| rect.clone())
+}
+
+/// Snap and separate `rects`, keeping the first in place and placing `moved` last.
+///
+/// Returns the rectangles in their original order.
+pub(crate) fn arrange(rects: &[LayoutRect], moved: Option<&str>) -> Vec<LayoutRect> {
+    let mut order: Vec<&LayoutRect> = rects.iter().filter(|r| Some(r.id.as_str()) != moved).collect();
+    order.extend(rects.iter().filter(|r| Some(r.id.as_str()) == moved));
+
+    let mut placed: Vec<LayoutRect> = Vec::with_capacity(rects.len());
+    for rect in order {
+        let arranged = if placed.is_empty() {
+            rect.clone()
+        } else {
+            attach(&snap(rect, &placed), &placed)
+        };
+        placed.push(arranged);
+    }
+
+    rects
+        .iter()
+        .filter_map(|r| placed.iter().find(|p| p.id == r.id).cloned())
+        .collect()
+}
+
+/// The layout after turning off `removed_id`, with the others moved to close its gap.
+///
+/// Displays are placed outward from the primary, each kept where it is if it
+/// still touches one already placed and otherwise moved flush against the
+/// nearest. Returns all displays, `removed_id` disabled.
+pub(crate) fn close_gap(displays: &[Display], removed_id: &str) -> Vec<Display> {
+    let mut rects: Vec<LayoutRect> = displays
+        .iter()
+        .filter(|d| d.enabled && d.id != removed_id)
+        .map(|d| {
+            let (width, height) = display_size(d);
+            LayoutRect {
+                id: d.id.clone(),
+                x: d.origin.0,
+                y: d.origin.1,
+                width,
+                height,
+            }
+        })
+        .collect();
+    rects.sort_by_key(|r| r.x.abs() + r.y.abs());
+
+    let mut placed: Vec<LayoutRect> = Vec::with_capacity(rects.len());
+    for rect in &rects {
+        let moved = if placed.is_empty() {
+            rect.clone()
+        } else {
+            attach(rect, &placed)
+        };
+        placed.push(moved);
+    }
+
+    displays
+        .iter()
+        .map(|d| {
+            let mut display = d.clone();
+            if d.id == removed_id {
+                display.enabled = false;
+            } else if let Some(rect) = placed.iter().find(|r| r.id == d.id) {
+                display.origin = (rect.x, rect.y);
+            }
+            display
+        })
+        .collect()
+}
+
+/// Re-base the origins in a configuration so the primary display is at (0,0).
+///
+/// The primary is `primary_id` if given. Otherwise a display already at (0,0)
+/// stays primary, so presets keep their chosen main display; failing that,
+/// `fallback_id` (normally the current primary) is used. A partial config that
+/// leaves the fallback out is not shifted at all, since its origins are relative
+/// to a display it doesn't list. Without a fallback the first enabled display
+/// is used. Disabled displays are shifted along with the rest.
+///
+/// # Returns
+/// * `Ok(String)` - The configuration with shifted origins
+/// * `Err(String)` - Error message if an origin would end up out of range
+pub(crate) fn normalize_config(
+    config: &str,
+    primary_id: Option<&str>,
+    fallback_id: Option<&str>,
+) -> Result<String, String> {
+    let displays = parse_config_displays(config);
+    let enabled = || displays.iter().filter(|d| d.enabled);
+    // Mirror groups (`id:A+B`) count as each of their members
+    let find = |id: &str| enabled().find(|d| d.id.split('+').any(|member| member == id));
+
+    let primary = match primary_id {
+        Some(id) => Some(find(id).ok_or_else(|| format!("Display {} is not in the layout", id))?),
+        None => enabled().find(|d| d.origin == (0, 0)).or_else(|| match fallback_id {
+            Some(id) => find(id),
+            None => enabled().next(),
+        }),
+    };
+    let (dx, dy) = primary.map(|p| p.origin).unwrap_or((0, 0));
+
+    let mut out_of_range = None;
+    let normalized = map_config_groups(config, |group| {
+        group
+            .split_whitespace()
+            .map(|part| {
+                let Some((x, y)) = parse_origin(part) else {
+                    return part.to_string();
+                };
+                let (x, y) = (x - dx, y - dy);
+                if x.abs() > MAX_COORDINATE || y.abs() > MAX_COORDINATE {
+                    out_of_range = Some(format!("Origin ({},{}) is out of range", x, y));
+                }
+                format!("origin:({},{})", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+
+    match out_of_range {
+        Some(e) => Err(e),
+        None => Ok(normalized),
+    }
+}
+
+/// `(x, y)` of an `origin:(x,y)` argument.
+fn parse_origin(part: &str) -> Option<(i32, i32)> {
+    let (x, y) = part.strip_prefix("origin:(")?.strip_suffix(')')?.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Shift a layout's origins so the primary display is at (0,0)
+///
+/// # Arguments
+/// * `config` - Configuration string or full displayplacer command
+/// * `primary_id` - Display to make primary; if omitted, the one at (0,0) or else the first enabled display
+///
+/// # Returns
+/// * `Ok(String)` - The configuration with re-based origins
+/// * `Err(String)` - Error message if the display is not in the layout or an origin is out of range
+#[tauri::command]
+pub async fn normalize_layout(config: String, primary_id: Option<String>) -> Result<String, String> {
+    normalize_config(&config, primary_id.as_deref(), None)
+}
+
+/// Snap edges and remove overlaps and gaps in a dragged layout
+///
+/// # Arguments
+/// * `displays` - Displays with their rough origins from the canvas
+/// * `moved_id` - ID of the display that was dragged; the others move as little as possible
+///
+/// # Returns
+/// * `Ok(Vec<Display>)` - The displays with corrected origins; disabled ones are unchanged
+#[tauri::command]
+pub async fn arrange_layout(displays: Vec<Display>, moved_id: Option<String>) -> Result<Vec<Display>, String> {
+    let rects: Vec<LayoutRect> = displays
+        .iter()
+        .filter(|d| d.enabled)
+        .map(|d| {
+            let (width, height) = display_size(d);
+            LayoutRect {
+                id: d.id.clone(),
+                x: d.origin.0,
+                y: d.origin.1,
+                width,
+                height,
+            }
+        })
+        .collect();
+    let arranged = arrange(&rects, moved_id.as_deref());
+
+    Ok(displays
+        .into_iter()
+        .map(|mut display| {
+            if let Some(rect) = arranged.iter().find(|r| r.id == display.id) {
+                display.origin = (rect.x, rect.y);
+            }
+            display
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrange() {
+        let rect = |id: &str, x, y, width, height| LayoutRect {
+            id: id.to_string(),
+            x,
+            y,
+            width,
+            height,
+        };
+        let origins = |rects: Vec<LayoutRect>| rects.into_iter().map(|r| (r.x, r.y)).collect::<Vec<_>>();
+        let laptop = rect("A", 0, 0, 1728, 1117);
+
+        // A small gap snaps shut, and the top edges line up
+        let gap = [laptop.clone(), rect("B", 1780, 40, 2560, 1440)];
+        assert_eq!(origins(arrange(&gap, Some("B"))), vec![(0, 0), (1728, 0)]);
+
+        // An overlap is pushed out the short way
+        let overlap = [laptop.clone(), rect("B", 300, -1300, 2560, 1440)];
+        assert_eq!(origins(arrange(&overlap, Some("B"))), vec![(0, 0), (300, -1440)]);
+
+        // A display far away is brought back next to the layout
+        let far = [laptop.clone(), rect("B", 5000, 300, 1920, 1080)];
+        assert_eq!(origins(arrange(&far, Some("B"))), vec![(0, 0), (1728, 300)]);
+
+        // The moved display goes last, even when listed first
+        let moved_first = [rect("B", 1000, 200, 1920, 1080), laptop];
+        assert_eq!(origins(arrange(&moved_first, Some("B"))), vec![(1728, 200), (0, 0)]);
+    }
+
+    #[test]
+    fn test_close_gap() {
+        let displays = parse_config_displays(
+            r#""id:A res:1728x1117 enabled:true origin:(0,0) degree:0" "id:B res:2560x1440 enabled:true origin:(1728,-323) degree:0" "id:C res:1920x1080 enabled:true origin:(4288,0) degree:0""#,
+        );
+        let origins = |displays: Vec<Display>| {
+            displays
+                .into_iter()
+                .map(|d| (d.id, d.enabled, d.origin))
+                .collect::<Vec<_>>()
+        };
+
+        // The display right of the middle one moves over
+        assert_eq!(
+            origins(close_gap(&displays, "B")),
+            vec![
+                ("A".to_string(), true, (0, 0)),
+                ("B".to_string(), false, (1728, -323)),
+                ("C".to_string(), true, (1728, 0)),
+            ]
+        );
+
+        // Turning off the last one leaves no gap to close
+        assert_eq!(
+            origins(close_gap(&displays, "C")),
+            vec![
+                ("A".to_string(), true, (0, 0)),
+                ("B".to_string(), true, (1728, -323)),
+                ("C".to_string(), false, (4288, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_config() {
+        let dragged = r#"displayplacer "id:A res:1728x1117 origin:(100,50) degree:0" "id:B res:2560x1440 origin:(1828,-273) degree:0""#;
+        let normalized = r#"displayplacer "id:A res:1728x1117 origin:(0,0) degree:0" "id:B res:2560x1440 origin:(1728,-323) degree:0""#;
+        assert_eq!(normalize_config(dragged, None, None).unwrap(), normalized);
+        assert_eq!(
+            normalize_config(dragged, None, Some("B")).unwrap(),
+            normalized.replace("(0,0)", "(-1728,323)").replace("(1728,-323)", "(0,0)")
+        );
+
+        // A display at (0,0) is kept as primary unless another is asked for
+        assert_eq!(normalize_config(normalized, None, Some("B")).unwrap(), normalized);
+        assert!(normalize_config(normalized, Some("C"), None).is_err());
+        let far = r#""id:A res:1920x1080 origin:(0,0) degree:0" "id:B res:1920x1080 origin:(40000,0) degree:0""#;
+        assert!(normalize_config(far, None, None).is_err());
+
+        // A partial config without the primary keeps its origins
+        let partial = r#""id:B res:2560x1440 origin:(1728,0) degree:0""#;
+        assert_eq!(normalize_config(partial, None, Some("A")).unwrap(), partial);
+    }
+}
@@ -1,16 +1,115 @@
+mod apply_queue;
+mod atomic_file;
+mod automation;
+mod backlight;
+mod backups;
+pub mod cli;
+mod conflicts;
+mod ddc;
+mod diagnostics;
+mod display_info;
+mod display_sleep;
+mod display_windows;
 mod displayplacer;
-mod presets;
+mod dock;
+mod frontmost;
+mod headless;
+mod history;
+mod hooks;
+mod hotkey_capture;
 mod hotkeys;
+mod identify;
+mod keep_awake;
+mod kvm;
+mod layout;
+mod logging;
+mod login_item;
+mod migrations;
+mod night_shift;
+#[cfg(target_os = "macos")]
+mod objc;
+mod paths;
+mod power;
+mod preset_watcher;
+mod presets;
+mod quick_panel;
+mod remote;
+mod rollback;
+mod scheduler;
+mod scripting;
+mod session;
+mod settings;
+mod setup;
+mod socket;
+mod state;
+mod storage;
+mod streamdeck;
 mod system_tray;
+mod trace;
+mod undo;
+mod url_scheme;
+mod virtual_displays;
+mod wake;
+mod watcher;
+mod wifi;
+mod window_state;
+mod workspaces;
 
-use displayplacer::{apply_config, get_displays, toggle_display_enabled};
-use presets::{add_preset, delete_preset, load_presets, save_presets, update_preset};
+use automation::{add_rule, delete_rule, load_rules, update_rule};
+use backups::{list_presets_backups, restore_presets_backup};
+use conflicts::check_integration_conflicts;
+use ddc::{get_brightness, get_volume, set_brightness, set_input_source, set_muted, set_volume};
+use diagnostics::run_diagnostics;
+use displayplacer::{
+    apply_config, check_config_modes, disable_external_displays, disable_mirroring, get_displays, set_mirroring,
+    read_displays, set_resolution, set_rotation, toggle_display_enabled, validate_config,
+};
+use dock::get_connected_devices;
+use display_sleep::{sleep_displays, wake_displays};
+use display_windows::get_window_display;
+use identify::identify_displays;
+use layout::{arrange_layout, normalize_layout};
+use window_state::set_always_on_top;
+use frontmost::get_frontmost_app;
+use headless::{get_launch_agent, set_launch_agent};
+use history::{apply_history_entry, get_history};
+use hooks::get_hook_env_preview;
+use night_shift::{get_night_shift, set_night_shift};
+use virtual_displays::{create_virtual_display, list_virtual_displays, remove_virtual_display};
+use presets::{
+    add_preset, apply_preset, create_preset_from_current, delete_preset, diff_preset, get_preset_stats,
+    get_presets_by_tag, get_storage_location, import_from_command, load_presets, reorder_presets, save_presets,
+    set_storage_path, update_preset, validate_preset,
+};
+use remote::{
+    apply_remote_preset, discover_remote_hosts, get_paired_devices, list_remote_presets, pair_remote_host,
+    start_remote_pairing, unpair_device,
+};
+use rollback::{confirm_applied_config, reject_applied_config};
+use hotkey_capture::start_hotkey_capture;
 use hotkeys::{
-    register_preset_hotkey, unregister_hotkey, unregister_all_hotkeys,
+    register_preset_hotkey, register_display_toggle_hotkey, register_display_rotate_hotkey,
+    register_sleep_displays_hotkey,
+    unregister_hotkey, unregister_all_hotkeys, set_hotkeys_enabled, get_hotkeys_enabled,
     is_hotkey_available, get_registered_hotkeys, validate_hotkey_format,
     initialize_default_hotkeys
 };
-use system_tray::{init_system_tray, handle_tray_menu_event, update_tray_menu};
+use kvm::get_kvm_status;
+use logging::{get_recent_logs, set_log_level};
+use login_item::{get_launch_at_login, set_launch_at_login};
+use paths::get_app_paths;
+use scheduler::{add_schedule, delete_schedule, end_sunset, get_sunset_status, list_schedules, snooze_sunset};
+use settings::{get_settings, update_settings};
+use setup::{install_displayplacer, run_setup_checks};
+use system_tray::{init_system_tray, handle_tray_menu_event, show_tray_notification, update_tray_menu};
+use trace::get_last_command_trace;
+use state::AppState;
+use storage::set_storage_backend;
+use undo::{get_undo_depth, revert_last_apply, UndoStack};
+use wifi::get_wifi_network;
+use workspaces::{add_workspace, apply_workspace, delete_workspace, load_workspaces, update_workspace};
+
+use tauri::{Emitter, Manager};
 
 /// Update the system tray menu (command for frontend)
 #[tauri::command]
@@ -19,85 +118,247 @@ async fn update_tray_presets(app: tauri::AppHandle) -> Result<(), String> {
         .map_err(|e| format!("Failed to update tray menu: {}", e))
 }
 
-/// Create windows on all available displays
-fn create_multi_display_windows(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    // Get all available monitors
-    let _monitors = app.primary_monitor()?.ok_or("No primary monitor found")?;
-    let available_monitors = app.available_monitors()?;
-
-    println!("Found {} monitors", available_monitors.len());
-
-    // If only one monitor, just use the main window
-    if available_monitors.len() <= 1 {
-        return Ok(());
-    }
-
-    // Create a secondary window on each additional monitor
-    for (idx, monitor) in available_monitors.iter().enumerate().skip(1) {
-        let position = monitor.position();
-        let size = monitor.size();
-
-        // Create a smaller window centered on each display
-        let window_width = 600.0;
-        let window_height = 700.0;
-        let x = position.x as f64 + (size.width as f64 - window_width) / 2.0;
-        let y = position.y as f64 + (size.height as f64 - window_height) / 2.0;
-
-        tauri::WebviewWindowBuilder::new(
-            app,
-            format!("display_{}", idx),
-            tauri::WebviewUrl::App("index.html".into()),
-        )
-        .title(format!("DPUI - Display {}", idx + 1))
-        .inner_size(window_width, window_height)
-        .position(x, y)
-        .resizable(true)
-        .build()?;
-
-        println!("Created window on display {} at ({}, {})", idx + 1, x, y);
-    }
-
-    Ok(())
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+    headless::init_from_args();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .manage(UndoStack::default())
         .invoke_handler(tauri::generate_handler![
             get_displays,
             apply_config,
+            check_config_modes,
+            validate_config,
             toggle_display_enabled,
+            disable_external_displays,
+            set_mirroring,
+            disable_mirroring,
+            set_rotation,
+            set_resolution,
+            revert_last_apply,
+            get_undo_depth,
+            confirm_applied_config,
+            reject_applied_config,
+            get_history,
+            apply_history_entry,
             load_presets,
             save_presets,
             add_preset,
+            apply_preset,
+            create_preset_from_current,
+            import_from_command,
+            diff_preset,
+            validate_preset,
+            get_presets_by_tag,
+            get_preset_stats,
             delete_preset,
+            reorder_presets,
+            list_presets_backups,
+            restore_presets_backup,
             update_preset,
+            get_storage_location,
+            set_storage_path,
+            set_storage_backend,
+            get_app_paths,
+            set_launch_at_login,
+            get_launch_at_login,
+            set_launch_agent,
+            get_launch_agent,
+            get_hook_env_preview,
+            // Workspace commands
+            load_workspaces,
+            add_workspace,
+            update_workspace,
+            delete_workspace,
+            apply_workspace,
+            // Automation commands
+            load_rules,
+            add_rule,
+            update_rule,
+            delete_rule,
+            get_wifi_network,
+            get_connected_devices,
+            get_frontmost_app,
+            get_window_display,
+            identify_displays,
+            arrange_layout,
+            normalize_layout,
+            get_brightness,
+            set_brightness,
+            set_input_source,
+            get_volume,
+            set_volume,
+            set_muted,
+            get_night_shift,
+            set_night_shift,
+            sleep_displays,
+            wake_displays,
+            create_virtual_display,
+            remove_virtual_display,
+            list_virtual_displays,
+            set_always_on_top,
             // Hotkey commands
             register_preset_hotkey,
+            register_display_toggle_hotkey,
+            register_display_rotate_hotkey,
+            register_sleep_displays_hotkey,
+            set_hotkeys_enabled,
+            get_hotkeys_enabled,
             unregister_hotkey,
             unregister_all_hotkeys,
             is_hotkey_available,
             get_registered_hotkeys,
             validate_hotkey_format,
+            start_hotkey_capture,
             // System tray commands
             update_tray_presets,
+            // Settings commands
+            get_settings,
+            update_settings,
+            run_setup_checks,
+            install_displayplacer,
+            // Developer commands
+            get_last_command_trace,
+            run_diagnostics,
+            get_recent_logs,
+            set_log_level,
+            // Integration commands
+            check_integration_conflicts,
+            // Remote control commands
+            start_remote_pairing,
+            get_paired_devices,
+            unpair_device,
+            discover_remote_hosts,
+            pair_remote_host,
+            list_remote_presets,
+            apply_remote_preset,
+            // KVM commands
+            get_kvm_status,
+            // Scheduler commands
+            get_sunset_status,
+            snooze_sunset,
+            end_sunset,
+            list_schedules,
+            add_schedule,
+            delete_schedule,
         ])
         .setup(|app| {
-            // Create windows on all displays
-            if let Err(e) = create_multi_display_windows(&app.handle()) {
-                eprintln!("Failed to create multi-display windows: {}", e);
+            // Resolve where files live before anything touches them
+            paths::init(app.handle())?;
+
+            // Load settings, reporting any recovery from a corrupt file
+            if let Some(notice) = settings::init() {
+                let _ = app.emit("settings-reset", &notice);
+                show_tray_notification("DPUI Settings", &notice);
+            }
+            if let Err(e) = logging::apply_level(&settings::current().log_level) {
+                log::warn!("[Logging] {}", e);
+            }
+
+            system_tray::init_notifications(app.handle().clone());
+            undo::init(app.handle().clone());
+            app.manage(AppState::load());
+            let handle = app.handle().clone();
+            app.state::<AppState>().on_change(move |_| {
+                if let Err(e) = update_tray_menu(&handle) {
+                    log::error!("Failed to update tray menu: {}", e);
+                }
+            });
+
+            // Run configuration changes one at a time
+            apply_queue::start(app.handle().clone());
+
+            // Recreate virtual displays before anything reads the layout
+            if let Err(e) = virtual_displays::start() {
+                log::error!("Failed to create virtual displays: {}", e);
+            }
+
+            if headless::is_headless() {
+                // Tray only: no Dock icon, and no webview until the window is opened
+                #[cfg(target_os = "macos")]
+                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+                log::info!("Running headless");
+            } else {
+                if let Err(e) = headless::main_window(app.handle()) {
+                    log::error!("{}", e);
+                }
+                // Create windows on all displays; the watcher keeps them in step
+                let displays = read_displays().map(|c| c.displays).unwrap_or_default();
+                if let Err(e) = display_windows::sync(app.handle(), &displays) {
+                    log::error!("Failed to create multi-display windows: {}", e);
+                }
             }
 
             // Initialize default hotkeys
             if let Err(e) = initialize_default_hotkeys(&app.handle()) {
-                eprintln!("Failed to initialize default hotkeys: {}", e);
+                log::error!("Failed to initialize default hotkeys: {}", e);
             }
 
+            // Register workspace hotkeys
+            workspaces::register_hotkeys(app.handle());
+
             // Initialize system tray
             if let Err(e) = init_system_tray(app) {
-                eprintln!("Failed to initialize system tray: {}", e);
+                log::error!("Failed to initialize system tray: {}", e);
+            }
+
+            // Watch for display hotplug and reconfiguration
+            if let Err(e) = watcher::start(app.handle().clone()) {
+                log::error!("Failed to start display watcher: {}", e);
+            }
+
+            // Apply presets mapped to Wi-Fi networks
+            wifi::start(app.handle().clone());
+
+            // Apply presets bound to docks when they are plugged in or removed
+            dock::start(app.handle().clone());
+
+            // Pick up presets edited by hand or synced from another Mac
+            preset_watcher::start(app.handle().clone());
+
+            // Run time-based features
+            scheduler::start(app.handle().clone());
+
+            // Accept presets from paired devices
+            if let Err(e) = remote::start(app.handle().clone()) {
+                log::error!("Failed to start remote control: {}", e);
+            }
+
+            // Accept JSON-RPC requests from scripts and the CLI
+            if let Err(e) = socket::start(app.handle().clone()) {
+                log::error!("Failed to start control socket: {}", e);
+            }
+
+            // Serve the Stream Deck plugin on localhost
+            if let Err(e) = streamdeck::start(app.handle().clone()) {
+                log::error!("Failed to start Stream Deck endpoint: {}", e);
+            }
+
+            // Pause while another user is active
+            session::start(app.handle().clone());
+
+            // Answer `tell application "DPUI"` commands
+            if let Err(e) = scripting::start(app.handle().clone()) {
+                log::error!("Failed to register AppleScript commands: {}", e);
+            }
+
+            // Re-evaluate automation rules when switching between AC and battery
+            if let Err(e) = power::start(app.handle().clone()) {
+                log::error!("Failed to observe power source changes: {}", e);
+            }
+
+            // Re-evaluate automation rules when another application comes to the front
+            if let Err(e) = frontmost::start(app.handle().clone()) {
+                log::error!("Failed to observe application activation: {}", e);
+            }
+
+            // Shortcuts can silently stop working after sleep or a session switch
+            if let Err(e) = wake::start(app.handle().clone()) {
+                log::error!("Failed to observe wake notifications: {}", e);
             }
 
             Ok(())
@@ -106,6 +367,24 @@ pub fn run() {
             // Handle tray menu events
             handle_tray_menu_event(app, &event.id.as_ref());
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            if let tauri::RunEvent::ExitRequested { code, api, .. } = &_event {
+                // Closing the window opened from the tray doesn't end a headless session
+                if code.is_none() && headless::is_headless() {
+                    api.prevent_exit();
+                } else {
+                    window_state::save_all(_app);
+                }
+            }
+
+            // dpui:// URLs opened with `open` or from another app
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = &_event {
+                for url in urls {
+                    url_scheme::handle_url(_app, url.as_str());
+                }
+            }
+        });
 }
@@ -0,0 +1,244 @@
+//! Application log.
+//!
+//! Everything DPUI logs through the `log` macros is written to
+//! `~/Library/Logs/dpui/dpui.log` (also visible in Console.app) and echoed to
+//! the terminal. The file is rotated by size, keeping a few generations, so
+//! users can look back at why a preset didn't apply without running from a
+//! terminal. Other crates only contribute warnings and errors.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Rotate the log file once it grows beyond this size
+const MAX_LOG_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Number of rotated files kept next to the current one (`dpui.log.1` is the newest)
+const MAX_ROTATED_FILES: usize = 3;
+
+/// Lines returned by `get_recent_logs` when no count is given, and the most it returns
+const DEFAULT_RECENT_LINES: usize = 200;
+const MAX_RECENT_LINES: usize = 5000;
+
+/// Target prefix of records logged by DPUI itself
+const OWN_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+static LOG_FILE: Mutex<Option<LogFile>> = Mutex::new(None);
+
+static LOGGER: FileLogger = FileLogger;
+
+struct FileLogger;
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.target().starts_with(OWN_TARGET) {
+            metadata.level() <= log::max_level()
+        } else {
+            metadata.level() <= Level::Warn
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.args()
+        );
+        if record.level() <= Level::Warn {
+            eprint!("{}", line);
+        } else {
+            print!("{}", line);
+        }
+
+        if let Ok(mut guard) = LOG_FILE.lock() {
+            if let Some(log_file) = guard.as_mut() {
+                if log_file.size + line.len() as u64 > MAX_LOG_FILE_BYTES {
+                    *guard = rotate();
+                }
+            }
+            if let Some(log_file) = guard.as_mut() {
+                if log_file.file.write_all(line.as_bytes()).is_ok() {
+                    log_file.size += line.len() as u64;
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut guard) = LOG_FILE.lock() {
+            if let Some(log_file) = guard.as_mut() {
+                let _ = log_file.file.flush();
+            }
+        }
+    }
+}
+
+/// Directory holding the log files.
+pub fn log_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "macos")]
+    let base = dirs::home_dir().map(|home| home.join("Library").join("Logs"));
+    #[cfg(not(target_os = "macos"))]
+    let base = dirs::data_local_dir();
+
+    base.map(|dir| dir.join("dpui")).ok_or_else(|| "Cannot find log directory".to_string())
+}
+
+fn log_path(generation: usize) -> Result<PathBuf, String> {
+    let name = match generation {
+        0 => "dpui.log".to_string(),
+        n => format!("dpui.log.{}", n),
+    };
+    Ok(log_dir()?.join(name))
+}
+
+fn open_log_file() -> Result<LogFile, String> {
+    let path = log_path(0)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    Ok(LogFile { file, size })
+}
+
+/// Shift `dpui.log` to `dpui.log.1` (and so on) and open a fresh file.
+fn rotate() -> Option<LogFile> {
+    for generation in (0..MAX_ROTATED_FILES).rev() {
+        if let (Ok(from), Ok(to)) = (log_path(generation), log_path(generation + 1)) {
+            if from.exists() {
+                let _ = fs::rename(from, to);
+            }
+        }
+    }
+    open_log_file().ok()
+}
+
+/// Parse a level name such as "info" or "debug".
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse::<LevelFilter>()
+        .map_err(|_| format!("Invalid log level \"{}\", expected off, error, warn, info, debug or trace", level))
+}
+
+/// Check a level name before it is stored in settings.
+pub fn validate_level(level: &str) -> Result<(), String> {
+    parse_level(level).map(|_| ())
+}
+
+/// Change which records from DPUI are logged.
+pub fn apply_level(level: &str) -> Result<(), String> {
+    log::set_max_level(parse_level(level)?);
+    Ok(())
+}
+
+/// Install the logger and open the log file.
+///
+/// Called at the very start of `run` so startup is logged too. Logs at info
+/// level until settings are loaded and `apply_level` is called. If the file
+/// can't be opened, records still reach the terminal.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_err() {
+        return;
+    }
+    log::set_max_level(LevelFilter::Info);
+
+    match open_log_file() {
+        Ok(log_file) => {
+            if let Ok(mut guard) = LOG_FILE.lock() {
+                *guard = Some(log_file);
+            }
+        }
+        Err(e) => log::error!("[Logging] {}", e),
+    }
+}
+
+/// The last `count` lines of `content`.
+fn last_lines(content: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(count)..].iter().map(|line| line.to_string()).collect()
+}
+
+/// Get the most recent log lines
+///
+/// # Arguments
+/// * `lines` - Number of lines to return (default 200, at most 5000)
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Log lines, oldest first; continues into the previous
+///   file if the current one is shorter
+/// * `Err(String)` - Error message if the log file can't be read
+#[tauri::command]
+pub async fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, String> {
+    let count = lines.unwrap_or(DEFAULT_RECENT_LINES).min(MAX_RECENT_LINES);
+    LOGGER.flush();
+
+    let mut recent = Vec::new();
+    for generation in 0..=MAX_ROTATED_FILES {
+        let remaining = count - recent.len();
+        if remaining == 0 {
+            break;
+        }
+        let path = log_path(generation)?;
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        let mut older = last_lines(&content, remaining);
+        older.append(&mut recent);
+        recent = older;
+    }
+
+    Ok(recent)
+}
+
+/// Set how much DPUI logs and remember it for the next launch
+///
+/// # Arguments
+/// * `level` - One of "off", "error", "warn", "info", "debug" or "trace"
+///
+/// # Returns
+/// * `Ok(())` - Level applied and saved
+/// * `Err(String)` - Error message if the level is invalid or settings can't be written
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<(), String> {
+    let level = level.to_lowercase();
+    apply_level(&level)?;
+    crate::settings::store_log_level(level.clone())?;
+
+    log::info!("[Logging] Log level set to {}", level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_lines() {
+        assert_eq!(last_lines("a\nb\nc\n", 2), vec!["b", "c"]);
+        assert_eq!(last_lines("a\nb", 5), vec!["a", "b"]);
+        assert!(last_lines("", 3).is_empty());
+        assert!(last_lines("a\n", 0).is_empty());
+
+        assert_eq!(parse_level("DEBUG"), Ok(LevelFilter::Debug));
+        assert!(parse_level("verbose").is_err());
+    }
+}
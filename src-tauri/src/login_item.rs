@@ -0,0 +1,136 @@
+//! Launching DPUI at login.
+//!
+//! Registers the app as a login item with `SMAppService.mainAppService`
+//! (macOS 13+), so presets, hotkeys and hotplug handling are available right
+//! after logging in. The registration is owned by the system and shows up in
+//! System Settings › General › Login Items, so nothing is stored in settings.
+
+use tauri::AppHandle;
+
+use crate::system_tray::update_tray_menu;
+
+/// Whether DPUI is registered to launch at login.
+pub fn is_enabled() -> Result<bool, String> {
+    sm::status().map(|status| status == sm::STATUS_ENABLED)
+}
+
+/// Register or unregister DPUI as a login item.
+///
+/// # Arguments
+/// * `app` - Application handle
+/// * `enabled` - `true` to launch at login, `false` to stop
+///
+/// # Returns
+/// * `Ok(())` - Login item updated
+/// * `Err(String)` - Error message if the system refused the change
+#[tauri::command]
+pub async fn set_launch_at_login(app: AppHandle, enabled: bool) -> Result<(), String> {
+    if is_enabled()? == enabled {
+        return Ok(());
+    }
+
+    if enabled {
+        sm::register()?;
+    } else {
+        sm::unregister()?;
+    }
+    log::info!("[LoginItem] Launch at login {}", if enabled { "enabled" } else { "disabled" });
+
+    if sm::status()? == sm::STATUS_REQUIRES_APPROVAL {
+        log::info!("[LoginItem] Login item needs approval in System Settings");
+    }
+    if let Err(e) = update_tray_menu(&app) {
+        log::error!("[LoginItem] Failed to update tray menu: {}", e);
+    }
+    Ok(())
+}
+
+/// Whether DPUI launches at login.
+///
+/// # Returns
+/// * `Ok(bool)` - `true` if registered as a login item
+/// * `Err(String)` - Error message if the status can't be read (e.g. before macOS 13)
+#[tauri::command]
+pub async fn get_launch_at_login() -> Result<bool, String> {
+    is_enabled()
+}
+
+#[cfg(target_os = "macos")]
+mod sm {
+    use std::ffi::CStr;
+
+    use crate::objc::{self, Id, Sel};
+
+    /// `SMAppServiceStatusEnabled`
+    pub const STATUS_ENABLED: isize = 1;
+    /// `SMAppServiceStatusRequiresApproval`
+    pub const STATUS_REQUIRES_APPROVAL: isize = 2;
+
+    #[link(name = "ServiceManagement", kind = "framework")]
+    extern "C" {}
+
+    /// `[SMAppService mainAppService]`
+    fn main_app_service() -> Result<Id, String> {
+        let class = objc::class(c"SMAppService").ok_or("Launch at login requires macOS 13 or later")?;
+        Ok(unsafe { objc::send(class, c"mainAppService") })
+    }
+
+    /// `[error localizedDescription]` as a Rust string
+    fn describe_error(error: Id) -> String {
+        if error.is_null() {
+            return "Unknown error".to_string();
+        }
+        unsafe { objc::to_string(objc::send(error, c"localizedDescription")) }.unwrap_or_else(|| "Unknown error".to_string())
+    }
+
+    /// Send `registerAndReturnError:` or `unregisterAndReturnError:`.
+    fn call_with_error(selector: &CStr) -> Result<(), String> {
+        let service = main_app_service()?;
+        let mut error: Id = std::ptr::null_mut();
+        let ok = unsafe {
+            let call: unsafe extern "C" fn(Id, Sel, *mut Id) -> bool = objc::msg_send();
+            call(service, objc::sel(selector), &mut error)
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(describe_error(error))
+        }
+    }
+
+    pub fn register() -> Result<(), String> {
+        call_with_error(c"registerAndReturnError:").map_err(|e| format!("Failed to enable launch at login: {}", e))
+    }
+
+    pub fn unregister() -> Result<(), String> {
+        call_with_error(c"unregisterAndReturnError:").map_err(|e| format!("Failed to disable launch at login: {}", e))
+    }
+
+    pub fn status() -> Result<isize, String> {
+        let service = main_app_service()?;
+        Ok(unsafe {
+            let status: unsafe extern "C" fn(Id, Sel) -> isize = objc::msg_send();
+            status(service, objc::sel(c"status"))
+        })
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod sm {
+    pub const STATUS_ENABLED: isize = 1;
+    pub const STATUS_REQUIRES_APPROVAL: isize = 2;
+
+    const UNSUPPORTED: &str = "Launch at login is only available on macOS";
+
+    pub fn register() -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn unregister() -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn status() -> Result<isize, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
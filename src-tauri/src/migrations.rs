@@ -0,0 +1,117 @@
+//! Schema migrations for the presets file.
+//!
+//! Each migration upgrades the raw JSON of a store from one version to the
+//! next, so renamed fields and new defaults are handled before serde sees the
+//! file. Migrations run in order until the store reaches `PRESETS_VERSION`.
+
+use serde_json::{Map, Value};
+
+/// Schema version written by this build.
+pub const PRESETS_VERSION: &str = "1.1";
+
+/// A single upgrade step.
+struct Migration {
+    from: &'static str,
+    to: &'static str,
+    apply: fn(&mut Map<String, Value>) -> Result<(), String>,
+}
+
+/// Every upgrade step, oldest first.
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: "1.0",
+    to: "1.1",
+    apply: v1_0_to_v1_1,
+}];
+
+/// 1.0 → 1.1: presets gain tags, folders and a manual order. Version 1.0
+/// files were ordered by position, so `sort_order` is seeded from it.
+fn v1_0_to_v1_1(store: &mut Map<String, Value>) -> Result<(), String> {
+    let presets = store
+        .get_mut("presets")
+        .and_then(Value::as_array_mut)
+        .ok_or("Presets file has no preset list")?;
+
+    for (index, preset) in presets.iter_mut().enumerate() {
+        let preset = preset
+            .as_object_mut()
+            .ok_or_else(|| format!("Preset {} is not an object", index))?;
+
+        if !preset.get("tags").is_some_and(Value::is_array) {
+            preset.insert("tags".to_string(), Value::Array(Vec::new()));
+        }
+        preset.entry("sort_order").or_insert(Value::from(index));
+    }
+
+    Ok(())
+}
+
+/// Upgrade a presets store to `PRESETS_VERSION`.
+///
+/// Files without a `version` are treated as 1.0.
+///
+/// # Returns
+/// * `Ok(Some(from))` - The store was migrated from version `from`
+/// * `Ok(None)` - The store is already current
+/// * `Err(String)` - The version is unknown or newer than this build, or a migration failed
+pub fn migrate_presets(value: &mut Value) -> Result<Option<String>, String> {
+    let store = value.as_object_mut().ok_or("Presets file is not a JSON object")?;
+    let original = store
+        .get("version")
+        .and_then(Value::as_str)
+        .unwrap_or("1.0")
+        .to_string();
+
+    if original == PRESETS_VERSION {
+        return Ok(None);
+    }
+
+    let mut version = original.clone();
+    while version != PRESETS_VERSION {
+        let migration = MIGRATIONS.iter().find(|m| m.from == version).ok_or_else(|| {
+            format!(
+                "Presets file version {} is not supported by this version of DPUI (expected {})",
+                original, PRESETS_VERSION
+            )
+        })?;
+
+        (migration.apply)(store).map_err(|e| format!("Migration to {} failed: {}", migration.to, e))?;
+        version = migration.to.to_string();
+        store.insert("version".to_string(), Value::from(version.clone()));
+    }
+
+    Ok(Some(original))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_from_1_0() {
+        let mut store = json!({
+            "version": "1.0",
+            "presets": [
+                { "id": "a", "name": "Desk", "config": "id:A", "hotkey": "Cmd+1", "created_at": "" },
+                { "id": "b", "name": "Sofa", "config": "id:B", "tags": null, "created_at": "" }
+            ]
+        });
+
+        assert_eq!(migrate_presets(&mut store).unwrap(), Some("1.0".to_string()));
+        assert_eq!(store["version"], PRESETS_VERSION);
+        assert_eq!(store["presets"][0]["sort_order"], 0);
+        assert_eq!(store["presets"][1]["sort_order"], 1);
+        assert_eq!(store["presets"][1]["tags"], json!([]));
+
+        assert_eq!(migrate_presets(&mut store).unwrap(), None);
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_version() {
+        let mut store = json!({ "version": "9.0", "presets": [] });
+        assert!(migrate_presets(&mut store).is_err());
+
+        let mut broken = json!({ "presets": "nope" });
+        assert!(migrate_presets(&mut broken).is_err());
+    }
+}
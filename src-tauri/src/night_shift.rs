@@ -0,0 +1,129 @@
+//! Night Shift on and off.
+//!
+//! Night Shift has no public API; it is driven through `CBBlueLightClient`
+//! from the private CoreBrightness framework, as Control Center does. The
+//! framework is loaded at runtime, and presets can turn Night Shift on or off
+//! along with their layout.
+
+/// Whether Night Shift is currently on.
+pub(crate) fn is_enabled() -> Result<bool, String> {
+    native::is_enabled()
+}
+
+/// Turn Night Shift on or off.
+pub(crate) fn set_enabled(enabled: bool) -> Result<(), String> {
+    native::set_enabled(enabled)?;
+
+    log::info!("[NightShift] Turned {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/// Whether Night Shift is on
+///
+/// # Returns
+/// * `Ok(bool)` - `true` if Night Shift is on
+/// * `Err(String)` - Error message if Night Shift is unavailable
+#[tauri::command]
+pub async fn get_night_shift() -> Result<bool, String> {
+    is_enabled()
+}
+
+/// Turn Night Shift on or off
+///
+/// # Arguments
+/// * `enabled` - `true` to turn Night Shift on
+///
+/// # Returns
+/// * `Ok(())` - Night Shift changed
+/// * `Err(String)` - Error message if Night Shift is unavailable
+#[tauri::command]
+pub async fn set_night_shift(enabled: bool) -> Result<(), String> {
+    set_enabled(enabled)
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::ffi::{c_char, c_void};
+
+    use crate::objc::{self, Id, Sel};
+
+    const FRAMEWORK: &std::ffi::CStr = c"/System/Library/PrivateFrameworks/CoreBrightness.framework/CoreBrightness";
+
+    /// `RTLD_LAZY`
+    const LAZY: i32 = 0x1;
+
+    /// `CBBlueLightClient`'s status, filled in by `getBlueLightStatus:`
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)]
+    struct BlueLightStatus {
+        active: bool,
+        enabled: bool,
+        sun_schedule_permitted: bool,
+        mode: i32,
+        /// From and to hour and minute
+        schedule: [i32; 4],
+        disable_flags: u64,
+        available: bool,
+    }
+
+    extern "C" {
+        fn dlopen(path: *const c_char, mode: i32) -> *mut c_void;
+    }
+
+    /// Call `f` with a new `CBBlueLightClient`, released afterwards.
+    fn with_client<T>(f: impl FnOnce(Id) -> Result<T, String>) -> Result<T, String> {
+        unsafe {
+            if dlopen(FRAMEWORK.as_ptr(), LAZY).is_null() {
+                return Err("CoreBrightness is not available".to_string());
+            }
+            let class = objc::class(c"CBBlueLightClient").ok_or("Night Shift is not supported on this Mac")?;
+            let client = objc::new(class).ok_or("Failed to connect to Night Shift")?;
+
+            let result = f(client);
+            objc::release(client);
+            result
+        }
+    }
+
+    pub fn is_enabled() -> Result<bool, String> {
+        with_client(|client| {
+            let mut status = BlueLightStatus::default();
+            let ok = unsafe {
+                let get_status: unsafe extern "C" fn(Id, Sel, *mut BlueLightStatus) -> bool = objc::msg_send();
+                get_status(client, objc::sel(c"getBlueLightStatus:"), &mut status)
+            };
+            if !ok {
+                return Err("Failed to read Night Shift status".to_string());
+            }
+            Ok(status.enabled)
+        })
+    }
+
+    pub fn set_enabled(enabled: bool) -> Result<(), String> {
+        with_client(|client| {
+            let ok = unsafe {
+                let set: unsafe extern "C" fn(Id, Sel, bool) -> bool = objc::msg_send();
+                set(client, objc::sel(c"setEnabled:"), enabled)
+            };
+            if ok {
+                Ok(())
+            } else {
+                Err("Failed to change Night Shift".to_string())
+            }
+        })
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod native {
+    const UNSUPPORTED: &str = "Night Shift is only available on macOS";
+
+    pub fn is_enabled() -> Result<bool, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn set_enabled(_enabled: bool) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
@@ -0,0 +1,175 @@
+//! Objective-C runtime helpers for the macOS integrations.
+//!
+//! DPUI talks to a handful of AppKit, Foundation and private framework
+//! classes directly through the runtime rather than pulling in a binding
+//! crate. `objc_msgSend` has no fixed signature, so every call goes through a
+//! pointer cast to the exact signature of the method being sent.
+
+use std::ffi::{c_char, c_void, CStr, CString};
+
+pub type Id = *mut c_void;
+pub type Sel = *mut c_void;
+
+/// Method implementation for a notification observer: `- (void)handle:(NSNotification *)n`
+pub type NotificationHandler = extern "C" fn(this: Id, cmd: Sel, notification: Id);
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_allocateClassPair(superclass: Id, name: *const c_char, extra_bytes: usize) -> Id;
+    fn objc_registerClassPair(class: Id);
+    fn class_addMethod(class: Id, name: Sel, imp: *const c_void, types: *const c_char) -> bool;
+    fn objc_msgSend();
+}
+
+#[link(name = "Foundation", kind = "framework")]
+extern "C" {}
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+/// `objc_msgSend` cast to the signature of the method being called.
+///
+/// # Safety
+/// `F` must be an `unsafe extern "C" fn` with the exact signature of the
+/// method, receiver and selector included.
+pub unsafe fn msg_send<F: Copy>() -> F {
+    let send = objc_msgSend as unsafe extern "C" fn();
+    debug_assert_eq!(std::mem::size_of::<F>(), std::mem::size_of_val(&send));
+    std::mem::transmute_copy(&send)
+}
+
+/// A class by name, if it is loaded.
+pub fn class(name: &CStr) -> Option<Id> {
+    let class = unsafe { objc_getClass(name.as_ptr()) };
+    (!class.is_null()).then_some(class)
+}
+
+pub fn sel(name: &CStr) -> Sel {
+    unsafe { sel_registerName(name.as_ptr()) }
+}
+
+/// `[receiver selector]` for a method without arguments that returns an object.
+///
+/// # Safety
+/// `receiver` must be an object or class that responds to `selector`.
+pub unsafe fn send(receiver: Id, selector: &CStr) -> Id {
+    let send: unsafe extern "C" fn(Id, Sel) -> Id = msg_send();
+    send(receiver, sel(selector))
+}
+
+/// `[class alloc]`
+///
+/// # Safety
+/// `class` must be a class.
+pub unsafe fn alloc(class: Id) -> Id {
+    send(class, c"alloc")
+}
+
+/// `[[class alloc] init]`, `None` if init fails.
+///
+/// # Safety
+/// `class` must be a class whose plain `init` is usable.
+pub unsafe fn new(class: Id) -> Option<Id> {
+    let object = send(alloc(class), c"init");
+    (!object.is_null()).then_some(object)
+}
+
+/// `[object release]`
+///
+/// # Safety
+/// `object` must be an object the caller owns.
+pub unsafe fn release(object: Id) {
+    let release: unsafe extern "C" fn(Id, Sel) = msg_send();
+    release(object, sel(c"release"));
+}
+
+/// An autoreleased `NSString`; NUL bytes are dropped.
+///
+/// # Safety
+/// Must be called with an autorelease pool in place, e.g. on the main thread.
+pub unsafe fn ns_string(s: &str) -> Id {
+    let Some(class) = class(c"NSString") else {
+        return std::ptr::null_mut();
+    };
+    let s = CString::new(s.replace('\0', "")).unwrap_or_default();
+    let create: unsafe extern "C" fn(Id, Sel, *const c_char) -> Id = msg_send();
+    create(class, sel(c"stringWithUTF8String:"), s.as_ptr())
+}
+
+/// The contents of an `NSString`, `None` if it is nil.
+///
+/// # Safety
+/// `string` must be nil or an `NSString`.
+pub unsafe fn to_string(string: Id) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    let utf8: unsafe extern "C" fn(Id, Sel) -> *const c_char = msg_send();
+    let chars = utf8(string, sel(c"UTF8String"));
+    (!chars.is_null()).then(|| CStr::from_ptr(chars).to_string_lossy().into_owned())
+}
+
+/// Create and register a subclass of `superclass` with the given methods.
+///
+/// # Arguments
+/// * `methods` - Selector, implementation and type encoding of each method
+///
+/// # Returns
+/// * `Ok(Id)` - The new class
+/// * `Err(String)` - Error message if the superclass is missing or the name is taken
+pub fn define_class(superclass: &CStr, name: &CStr, methods: &[(&CStr, *const c_void, &CStr)]) -> Result<Id, String> {
+    let superclass = class(superclass).ok_or_else(|| format!("{} is unavailable", superclass.to_string_lossy()))?;
+    unsafe {
+        let class = objc_allocateClassPair(superclass, name.as_ptr(), 0);
+        if class.is_null() {
+            return Err(format!("Failed to create {} class", name.to_string_lossy()));
+        }
+        for (selector, implementation, types) in methods {
+            class_addMethod(class, sel(selector), *implementation, types.as_ptr());
+        }
+        objc_registerClassPair(class);
+        Ok(class)
+    }
+}
+
+/// Call handlers for `NSWorkspace` notifications.
+///
+/// Defines an observer class named `class_name` with one method per handler
+/// and registers an instance with the shared workspace's notification
+/// center. Handlers run on the main thread.
+///
+/// # Arguments
+/// * `handlers` - Selector to define, notification name and handler for each notification
+pub fn observe_workspace(class_name: &CStr, handlers: &[(&CStr, Id, NotificationHandler)]) -> Result<(), String> {
+    let methods: Vec<(&CStr, *const c_void, &CStr)> = handlers
+        .iter()
+        .map(|(selector, _, handler)| (*selector, *handler as *const c_void, c"v@:@"))
+        .collect();
+    let class = define_class(c"NSObject", class_name, &methods)?;
+
+    unsafe {
+        let observer = new(class).ok_or("Failed to create notification observer")?;
+        let workspace_class = self::class(c"NSWorkspace").ok_or("NSWorkspace is unavailable")?;
+        let workspace = send(workspace_class, c"sharedWorkspace");
+        let center = send(workspace, c"notificationCenter");
+        if center.is_null() {
+            return Err("NSWorkspace notification center is unavailable".to_string());
+        }
+
+        let add_observer: unsafe extern "C" fn(Id, Sel, Id, Sel, Id, Id) = msg_send();
+        for (selector, name, _) in handlers {
+            add_observer(
+                center,
+                sel(c"addObserver:selector:name:object:"),
+                observer,
+                sel(selector),
+                *name,
+                std::ptr::null_mut(),
+            );
+        }
+    }
+
+    Ok(())
+}
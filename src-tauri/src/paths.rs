@@ -0,0 +1,132 @@
+//! Location of DPUI's files on disk.
+//!
+//! Everything lives in the platform config directory resolved by Tauri
+//! (`~/Library/Application Support/com.jelly.dpui` on macOS), which also
+//! works for sandboxed builds. Earlier versions used `~/.config/dpui`; its
+//! contents are moved over once on first launch.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+use crate::{backups, history, logging, presets, settings};
+
+/// `identifier` from tauri.conf.json
+pub const BUNDLE_IDENTIFIER: &str = "com.jelly.dpui";
+
+/// Config directory resolved at startup
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Where files were kept before the move to the platform config directory.
+fn legacy_config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("dpui"))
+}
+
+/// Resolve the config directory and migrate files from the legacy location.
+///
+/// Must run at the start of the setup hook, before anything reads or writes
+/// DPUI's files.
+pub fn init(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Cannot resolve config directory: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    if let Some(legacy) = legacy_config_dir().filter(|legacy| legacy.is_dir() && *legacy != dir) {
+        migrate_legacy_dir(&legacy, &dir);
+    }
+
+    log::info!("[Paths] Config directory: {}", dir.display());
+    Ok(CONFIG_DIR.get_or_init(|| dir).clone())
+}
+
+/// Resolve the config directory without a running app, as the CLI does.
+///
+/// Matches what Tauri resolves for the app: the platform config directory
+/// joined with the bundle identifier. Nothing is migrated.
+pub fn init_standalone() -> Result<PathBuf, String> {
+    let dir = dirs::config_dir()
+        .map(|dir| dir.join(BUNDLE_IDENTIFIER))
+        .ok_or_else(|| "Cannot resolve config directory".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    Ok(CONFIG_DIR.get_or_init(|| dir).clone())
+}
+
+/// Move every entry of `legacy` into `dir`, then remove `legacy` if it ended up empty.
+///
+/// Entries that already exist in `dir` are left where they are so nothing is
+/// overwritten; failures are logged and retried on the next launch.
+fn migrate_legacy_dir(legacy: &Path, dir: &Path) {
+    let entries = match fs::read_dir(legacy) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::error!("[Paths] Failed to read {}: {}", legacy.display(), e);
+            return;
+        }
+    };
+
+    let mut moved = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let target = dir.join(entry.file_name());
+        if target.exists() {
+            log::warn!("[Paths] Not migrating {}: already exists in {}", entry.path().display(), dir.display());
+            continue;
+        }
+        match fs::rename(entry.path(), &target) {
+            Ok(()) => moved += 1,
+            Err(e) => log::error!("[Paths] Failed to migrate {}: {}", entry.path().display(), e),
+        }
+    }
+
+    if fs::remove_dir(legacy).is_ok() {
+        log::info!("[Paths] Moved {} files from {} and removed it", moved, legacy.display());
+    } else if moved > 0 {
+        log::info!("[Paths] Moved {} files from {}", moved, legacy.display());
+    }
+}
+
+/// The config directory resolved by `init`.
+pub fn config_dir() -> Result<PathBuf, String> {
+    CONFIG_DIR
+        .get()
+        .cloned()
+        .ok_or_else(|| "Config directory has not been initialized".to_string())
+}
+
+/// Resolved locations of DPUI's files.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppPaths {
+    pub config_dir: String,
+    /// May be outside the config directory when a storage path is set
+    pub presets_file: String,
+    pub settings_file: String,
+    pub history_file: String,
+    pub backups_dir: String,
+    pub logs_dir: String,
+    /// Pre-migration directory, if it still exists (e.g. because of conflicts)
+    pub legacy_config_dir: Option<String>,
+}
+
+/// Get the locations of DPUI's files
+///
+/// # Returns
+/// * `Ok(AppPaths)` - The resolved paths
+/// * `Err(String)` - Error message if the config directory is unavailable
+#[tauri::command]
+pub async fn get_app_paths() -> Result<AppPaths, String> {
+    let display = |path: PathBuf| path.display().to_string();
+
+    Ok(AppPaths {
+        config_dir: display(config_dir()?),
+        presets_file: display(presets::get_presets_path()?),
+        settings_file: display(settings::get_settings_path()?),
+        history_file: display(history::get_history_path()?),
+        backups_dir: display(backups::get_backups_dir()?),
+        logs_dir: display(logging::log_dir()?),
+        legacy_config_dir: legacy_config_dir().filter(|dir| dir.is_dir()).map(display),
+    })
+}
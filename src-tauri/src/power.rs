@@ -0,0 +1,212 @@
+//! Power source of the Mac.
+//!
+//! Reads whether the Mac is running on AC power or battery from IOKit's power
+//! sources API, and observes its power source notifications so automation
+//! rules are re-evaluated when the Mac is plugged in or unplugged. Also reads
+//! whether the lid is closed from the power management root domain.
+
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::automation;
+use crate::displayplacer::{get_displays, run_with_timeout};
+use crate::session;
+use crate::settings;
+use crate::trace::{self, CommandTrace};
+
+/// Whether the Mac is running on AC power.
+///
+/// `None` if the power source can't be read (e.g. outside macOS). Desktops
+/// without a battery always report AC power.
+pub fn on_ac_power() -> Option<bool> {
+    ps::providing_power_source().map(|source| source != ps::BATTERY_POWER)
+}
+
+/// Whether the lid is closed, from `AppleClamshellState` in `ioreg` output.
+fn parse_clamshell_state(output: &str) -> Option<bool> {
+    output.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once('=')?;
+        if key.trim() != "\"AppleClamshellState\"" {
+            return None;
+        }
+        match value.trim() {
+            "Yes" => Some(true),
+            "No" => Some(false),
+            _ => None,
+        }
+    })
+}
+
+/// Whether the lid of the Mac is closed.
+///
+/// `None` for Macs without a lid or if the state can't be read. Runs on every
+/// display read, so `ioreg` is timed out and traced like displayplacer.
+pub fn lid_closed() -> Option<bool> {
+    let args: Vec<String> = ["-r", "-k", "AppleClamshellState", "-d", "1"].iter().map(|a| a.to_string()).collect();
+    let mut trace = CommandTrace::start("ioreg", &args);
+    let timeout = Duration::from_secs(settings::current().command_timeout_secs);
+
+    let output = run_with_timeout("ioreg", Command::new("ioreg").args(&args), timeout);
+    match &output {
+        Ok(output) => trace.finish(output),
+        Err(e) => {
+            log::error!("[Power] {}", e);
+            trace.fail(e);
+        }
+    }
+    trace::record(trace);
+
+    let output = output.ok().filter(|output| output.status.success())?;
+    parse_clamshell_state(&String::from_utf8_lossy(&output.stdout))
+}
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Power source seen at the last notification, to tell a switch between AC
+/// and battery from the frequent battery level updates
+static LAST_ON_AC: Mutex<Option<bool>> = Mutex::new(None);
+
+/// Start observing power source changes.
+///
+/// Each switch between AC power and battery emits `power-source-changed` and
+/// re-evaluates automation rules.
+///
+/// # Returns
+/// * `Ok(())` - Observer registered
+/// * `Err(String)` - Error message if the observer could not be registered
+pub fn start(app: AppHandle) -> Result<(), String> {
+    APP.set(app).map_err(|_| "Power observer already started".to_string())?;
+    if let Ok(mut last) = LAST_ON_AC.lock() {
+        *last = on_ac_power();
+    }
+    ps::register_observer()?;
+
+    log::info!("[Power] Observing power source changes");
+    Ok(())
+}
+
+/// Called on the main thread whenever the power sources change.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn on_power_sources_changed() {
+    let Some(app) = APP.get().cloned() else {
+        return;
+    };
+    let on_ac = on_ac_power();
+    let previous = match LAST_ON_AC.lock() {
+        Ok(mut last) => std::mem::replace(&mut *last, on_ac),
+        Err(_) => return,
+    };
+    if previous == on_ac {
+        return;
+    }
+
+    log::info!("[Power] Now on {}", if on_ac == Some(true) { "AC power" } else { "battery" });
+    let _ = app.emit("power-source-changed", on_ac);
+
+    tauri::async_runtime::spawn(async move {
+        // Another user owns the displays; leave them alone
+        if !session::is_active() {
+            return;
+        }
+        match get_displays().await {
+            Ok(config) => automation::evaluate(&app, &config).await,
+            Err(e) => log::error!("[Power] Failed to read displays: {}", e),
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+mod ps {
+    use std::ffi::{c_char, c_void, CStr};
+
+    type CFTypeRef = *const c_void;
+
+    /// `kIOPMBatteryPowerKey`
+    pub const BATTERY_POWER: &str = "Battery Power";
+
+    /// `kCFStringEncodingUTF8`
+    const UTF8_ENCODING: u32 = 0x0800_0100;
+
+    type PowerSourceCallback = extern "C" fn(context: *mut c_void);
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+        fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFTypeRef;
+        fn IOPSNotificationCreateRunLoopSource(callback: PowerSourceCallback, context: *mut c_void) -> CFTypeRef;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopDefaultMode: CFTypeRef;
+        fn CFStringGetCString(string: CFTypeRef, buffer: *mut c_char, size: isize, encoding: u32) -> bool;
+        fn CFRunLoopGetMain() -> CFTypeRef;
+        fn CFRunLoopAddSource(run_loop: CFTypeRef, source: CFTypeRef, mode: CFTypeRef);
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    extern "C" fn on_notification(_context: *mut c_void) {
+        super::on_power_sources_changed();
+    }
+
+    /// Deliver power source notifications on the main run loop.
+    pub fn register_observer() -> Result<(), String> {
+        unsafe {
+            let source = IOPSNotificationCreateRunLoopSource(on_notification, std::ptr::null_mut());
+            if source.is_null() {
+                return Err("Failed to create power source notification".to_string());
+            }
+            // The run loop keeps its own reference for as long as DPUI runs
+            CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopDefaultMode);
+            CFRelease(source);
+        }
+        Ok(())
+    }
+
+    /// Type of the source currently powering the Mac, e.g. "AC Power" or "Battery Power".
+    pub fn providing_power_source() -> Option<String> {
+        unsafe {
+            let snapshot = IOPSCopyPowerSourcesInfo();
+            if snapshot.is_null() {
+                return None;
+            }
+
+            // Owned by the snapshot, so read it before releasing that
+            let source = IOPSGetProvidingPowerSourceType(snapshot);
+            let mut buffer = [0 as c_char; 64];
+            let ok = !source.is_null()
+                && CFStringGetCString(source, buffer.as_mut_ptr(), buffer.len() as isize, UTF8_ENCODING);
+            CFRelease(snapshot);
+
+            ok.then(|| CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned())
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod ps {
+    pub const BATTERY_POWER: &str = "Battery Power";
+
+    pub fn providing_power_source() -> Option<String> {
+        None
+    }
+
+    pub fn register_observer() -> Result<(), String> {
+        Err("Power source notifications are only available on macOS".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clamshell_state() {
+        let output = "+-o IOPMrootDomain  <class IOPMrootDomain, id 0x100000261>\n    {\n      \"AppleClamshellCausesSleep\" = No\n      \"AppleClamshellState\" = Yes\n    }\n";
+        assert_eq!(parse_clamshell_state(output), Some(true));
+        assert_eq!(parse_clamshell_state(&output.replace("State\" = Yes", "State\" = No")), Some(false));
+        assert_eq!(parse_clamshell_state("+-o IOPMrootDomain\n    {\n    }\n"), None);
+    }
+}
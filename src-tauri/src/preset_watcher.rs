@@ -0,0 +1,98 @@
+//! Watcher for edits to presets.json made outside the app.
+//!
+//! The file may be hand-edited or replaced by a sync service such as iCloud
+//! Drive or Dropbox. A background thread hashes it every `POLL_INTERVAL`;
+//! writes made by DPUI itself are recorded with `record_write` so only
+//! external changes are reported. On a change the tray menu and preset
+//! hotkeys are refreshed and a `presets-changed` event is emitted.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::hotkeys;
+use crate::presets::{get_presets_path, read_presets};
+use crate::state::AppState;
+use crate::storage::{self, StorageBackend};
+use crate::system_tray::update_tray_menu;
+
+/// How often the presets file is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Hash of the presets file as last written or seen by DPUI
+static KNOWN_HASH: Mutex<Option<u64>> = Mutex::new(None);
+
+fn hash_contents(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash of the presets file on disk; `None` if it doesn't exist or can't be read.
+fn current_hash() -> Option<u64> {
+    let path = get_presets_path().ok()?;
+    fs::read(path).ok().map(|contents| hash_contents(&contents))
+}
+
+/// Record contents DPUI just wrote so the watcher doesn't report them.
+pub fn record_write(contents: &[u8]) {
+    if let Ok(mut known) = KNOWN_HASH.lock() {
+        *known = Some(hash_contents(contents));
+    }
+}
+
+/// Start watching presets.json for external edits.
+///
+/// Does nothing when presets are not stored in presets.json.
+pub fn start(app: AppHandle) {
+    if storage::backend().kind() != StorageBackend::Json {
+        return;
+    }
+
+    if let Ok(mut known) = KNOWN_HASH.lock() {
+        *known = current_hash();
+    }
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let hash = current_hash();
+        let changed = match KNOWN_HASH.lock() {
+            Ok(mut known) if *known != hash => {
+                *known = hash;
+                true
+            }
+            _ => false,
+        };
+
+        if changed {
+            handle_external_change(&app);
+        }
+    });
+
+    log::info!("[PresetWatcher] Watching presets file");
+}
+
+/// Reload presets after an external edit and refresh everything derived from them.
+fn handle_external_change(app: &AppHandle) {
+    // A half-written or hand-broken file is reported and picked up on the next change
+    let store = match read_presets() {
+        Ok(store) => store,
+        Err(e) => {
+            log::error!("[PresetWatcher] Ignoring unreadable presets file: {}", e);
+            return;
+        }
+    };
+
+    log::info!("[PresetWatcher] Presets changed on disk ({} presets)", store.presets.len());
+    app.state::<AppState>().replace_presets(store.clone());
+
+    hotkeys::sync_preset_hotkeys(app, &store.presets);
+    if let Err(e) = update_tray_menu(app) {
+        log::error!("[PresetWatcher] Failed to update tray menu: {}", e);
+    }
+    let _ = app.emit("presets-changed", &store);
+}
@@ -1,6 +1,30 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::apply_queue::ApplyOutcome;
+use crate::atomic_file::write_atomic;
+use crate::backups::backup_presets;
+use crate::ddc::{self, InputSource};
+use crate::displayplacer::{
+    apply_layout, diff_layout, get_displays, layout_command, parse_command, validate_layout, ConfigIssue, Display,
+    DisplayChange, DisplayConfig, DisplayDifference,
+};
+use crate::history::HistorySource;
+use crate::hooks::{run_hook, HookKind};
+use crate::keep_awake;
+use crate::migrations::{migrate_presets, PRESETS_VERSION};
+use crate::night_shift;
+use crate::paths;
+use crate::preset_watcher;
+use crate::rollback;
+use crate::settings;
+use crate::state::AppState;
+use crate::streamdeck;
+use crate::system_tray::{show_tray_notification, update_tray_menu};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Preset {
@@ -9,6 +33,101 @@ pub struct Preset {
     pub config: String,
     pub hotkey: Option<String>,
     pub created_at: String,
+    /// Free-form notes, e.g. "Use when docked at standing desk, TV off"
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Shell command run before the preset is applied
+    #[serde(default)]
+    pub pre_hook: Option<String>,
+    /// Shell command run after the preset is applied
+    #[serde(default)]
+    pub post_hook: Option<String>,
+    /// Free-form labels such as "Home" or "Office"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Folder the preset is grouped under in the tray menu
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Position in the user-defined order; ties keep insertion order
+    #[serde(default)]
+    pub sort_order: u32,
+    /// When the preset was last applied successfully (RFC 3339)
+    #[serde(default)]
+    pub last_applied_at: Option<String>,
+    /// How many times the preset has been applied successfully
+    #[serde(default)]
+    pub apply_count: u32,
+    /// Brightness (0-100) set over DDC/CI per display ID once the layout is applied
+    #[serde(default)]
+    pub brightness: BTreeMap<String, u16>,
+    /// Speaker volume (0-100) set over DDC/CI per display ID once the layout is applied
+    #[serde(default)]
+    pub volume: BTreeMap<String, u16>,
+    /// Input switched to over DDC/CI per display ID, after the layout and brightness
+    #[serde(default)]
+    pub input_sources: BTreeMap<String, InputSource>,
+    /// Turn Night Shift on or off when applied; left as is if unset
+    #[serde(default)]
+    pub night_shift: Option<bool>,
+    /// Keep the displays from sleeping until another preset is applied
+    #[serde(default)]
+    pub keep_awake: bool,
+}
+
+impl Preset {
+    /// A new preset with a fresh ID and no optional fields set.
+    pub fn new(name: String, config: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            config,
+            hotkey: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            description: None,
+            pre_hook: None,
+            post_hook: None,
+            tags: Vec::new(),
+            folder: None,
+            sort_order: 0,
+            last_applied_at: None,
+            apply_count: 0,
+            brightness: BTreeMap::new(),
+            volume: BTreeMap::new(),
+            input_sources: BTreeMap::new(),
+            night_shift: None,
+            keep_awake: false,
+        }
+    }
+}
+
+/// Usage statistics for a single preset.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetStats {
+    pub preset_id: String,
+    pub name: String,
+    pub apply_count: u32,
+    pub last_applied_at: Option<String>,
+}
+
+/// Where presets are stored and the locations the user can pick from.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageLocation {
+    /// Directory presets.json is currently stored in
+    pub path: String,
+    /// Default directory used when no storage path is set
+    pub default_path: String,
+    /// Suggested directory in iCloud Drive, if iCloud Drive is enabled
+    pub icloud_drive_path: Option<String>,
+}
+
+/// Whether a preset can be applied with the displays currently connected.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetValidation {
+    pub preset_id: String,
+    /// Display IDs in the preset's config that are not connected
+    pub missing_displays: Vec<String>,
+    /// True if every display the preset references is connected
+    pub applicable: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,26 +139,131 @@ pub struct PresetStore {
 impl Default for PresetStore {
     fn default() -> Self {
         Self {
-            version: "1.0".to_string(),
+            version: PRESETS_VERSION.to_string(),
             presets: Vec::new(),
         }
     }
 }
 
-/// Get the path to the presets file
-fn get_presets_path() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Cannot find home directory")?;
-    let config_dir = home.join(".config").join("dpui");
+/// Get the DPUI configuration directory, creating it if needed
+pub(crate) fn get_config_dir() -> Result<PathBuf, String> {
+    let config_dir = paths::config_dir()?;
 
     // Create directory if it doesn't exist
     fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
 
-    Ok(config_dir.join("presets.json"))
+    Ok(config_dir)
+}
+
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    match path.strip_prefix('~') {
+        Some(rest) => {
+            let home = dirs::home_dir().ok_or("Cannot find home directory")?;
+            Ok(home.join(rest.trim_start_matches('/')))
+        }
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Directory for presets inside iCloud Drive, if iCloud Drive is enabled.
+fn icloud_drive_dir() -> Option<PathBuf> {
+    let drive = dirs::home_dir()?.join("Library/Mobile Documents/com~apple~CloudDocs");
+    drive.is_dir().then(|| drive.join("DPUI"))
+}
+
+/// Get the directory presets are stored in, creating it if needed.
+///
+/// This is the configured storage path (e.g. a synced iCloud Drive or Dropbox
+/// folder) or the config directory if none is set.
+fn get_storage_dir() -> Result<PathBuf, String> {
+    let Some(path) = settings::current().storage_path else {
+        return get_config_dir();
+    };
+
+    let dir = expand_home(&path)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create storage directory: {}", e))?;
+
+    Ok(dir)
+}
+
+/// Get the path to the presets file
+pub(crate) fn get_presets_path() -> Result<PathBuf, String> {
+    Ok(get_storage_dir()?.join("presets.json"))
+}
+
+/// Describe the current preset storage location.
+fn storage_location() -> Result<StorageLocation, String> {
+    Ok(StorageLocation {
+        path: get_storage_dir()?.display().to_string(),
+        default_path: get_config_dir()?.display().to_string(),
+        icloud_drive_path: icloud_drive_dir().map(|p| p.display().to_string()),
+    })
+}
+
+/// Get where presets are stored
+#[tauri::command]
+pub async fn get_storage_location() -> Result<StorageLocation, String> {
+    storage_location()
 }
 
-/// Load presets from file
+/// Move preset storage to another directory
+///
+/// The existing presets file is moved to the new directory. If the new
+/// directory already has a presets file (for example one synced from another
+/// Mac), that file is used and the old one is left in place. Presets are
+/// reloaded from the new location either way.
+///
+/// # Arguments
+/// * `path` - New directory, `~` is expanded; `None` or empty restores the default
+///
+/// # Returns
+/// * `Ok(StorageLocation)` - The new storage location
+/// * `Err(String)` - Error message if the directory cannot be created or the file cannot be moved
 #[tauri::command]
-pub async fn load_presets() -> Result<PresetStore, String> {
+pub async fn set_storage_path(state: State<'_, AppState>, path: Option<String>) -> Result<StorageLocation, String> {
+    // Make sure pending edits land in the old file before it is moved
+    state.flush();
+
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let new_dir = match &path {
+        Some(p) => expand_home(p)?,
+        None => get_config_dir()?,
+    };
+    fs::create_dir_all(&new_dir).map_err(|e| format!("Failed to create storage directory: {}", e))?;
+
+    let old_path = get_presets_path()?;
+    let new_path = new_dir.join("presets.json");
+
+    if old_path != new_path && old_path.exists() {
+        if new_path.exists() {
+            log::info!("[Presets] Using existing presets at {}", new_path.display());
+        } else {
+            let content = fs::read(&old_path).map_err(|e| format!("Failed to read presets: {}", e))?;
+            write_atomic(&new_path, &content).map_err(|e| format!("Failed to move presets: {}", e))?;
+            fs::remove_file(&old_path).map_err(|e| format!("Failed to remove old presets: {}", e))?;
+            log::info!("[Presets] Moved presets to {}", new_path.display());
+        }
+    }
+
+    settings::store_storage_path(path)?;
+    state.replace_presets(read_presets()?);
+
+    storage_location()
+}
+
+/// Load presets
+#[tauri::command]
+pub async fn load_presets(state: State<'_, AppState>) -> Result<PresetStore, String> {
+    Ok(state.presets())
+}
+
+/// Read presets from disk.
+///
+/// Commands use the copy in `AppState`; this is for loading it at startup
+/// and after the file changes on disk. Older store files are migrated to the current schema, keeping a copy of
+/// the original next to it. Presets are returned in their user-defined order.
+pub(crate) fn read_presets() -> Result<PresetStore, String> {
     let path = get_presets_path()?;
 
     if !path.exists() {
@@ -47,81 +271,595 @@ pub async fn load_presets() -> Result<PresetStore, String> {
     }
 
     let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read presets: {}", e))?;
+    let (mut store, migrated) = parse_store(&content)?;
 
-    serde_json::from_str(&content).map_err(|e| format!("Failed to parse presets: {}", e))
+    if let Some(from) = migrated {
+        // Keep the original so a bad migration can be recovered by hand
+        let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+        backup_name.push(format!(".v{}.bak", from));
+        fs::copy(&path, path.with_file_name(backup_name))
+            .map_err(|e| format!("Failed to back up presets before migration: {}", e))?;
+
+        write_presets(&store)?;
+        log::info!("[Presets] Migrated presets from version {} to {}", from, PRESETS_VERSION);
+    }
+
+    store.presets.sort_by_key(|p| p.sort_order);
+
+    Ok(store)
+}
+
+/// Parse the contents of a presets file, migrating it to the current schema.
+///
+/// # Returns
+/// * `Ok((PresetStore, Option<String>))` - The store, and the version it was migrated from if it was
+/// * `Err(String)` - Error message if the contents cannot be parsed or migrated
+pub(crate) fn parse_store(content: &str) -> Result<(PresetStore, Option<String>), String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse presets: {}", e))?;
+    let migrated = migrate_presets(&mut value)?;
+
+    let store = serde_json::from_value(value).map_err(|e| format!("Failed to parse presets: {}", e))?;
+
+    Ok((store, migrated))
 }
 
-/// Save presets to file
+/// Replace all presets
 #[tauri::command]
-pub async fn save_presets(store: PresetStore) -> Result<(), String> {
+pub async fn save_presets(state: State<'_, AppState>, store: PresetStore) -> Result<(), String> {
+    state.update_presets(|presets| {
+        *presets = store;
+        Ok(())
+    })
+}
+
+/// Write presets to disk.
+///
+/// The file is replaced atomically after the previous version is copied to
+/// the backups directory; a failed backup is logged but doesn't block the save.
+pub(crate) fn write_presets(store: &PresetStore) -> Result<(), String> {
+    if let Err(e) = backup_presets(&get_presets_path()?) {
+        log::error!("[Presets] {}", e);
+    }
+
+    write_store(store)
+}
+
+/// Atomically replace the presets file without taking a backup.
+pub(crate) fn write_store(store: &PresetStore) -> Result<(), String> {
     let path = get_presets_path()?;
 
     let content =
-        serde_json::to_string_pretty(&store).map_err(|e| format!("Failed to serialize presets: {}", e))?;
+        serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize presets: {}", e))?;
 
-    fs::write(&path, content).map_err(|e| format!("Failed to write presets: {}", e))?;
+    write_atomic(&path, content.as_bytes()).map_err(|e| format!("Failed to write presets: {}", e))?;
+    preset_watcher::record_write(content.as_bytes());
 
     Ok(())
 }
 
-/// Add a new preset
+/// Apply a preset's configuration, running its pre/post hooks around it.
+///
+/// A failing pre hook aborts the apply; a failing post hook is only logged,
+/// since the layout has already changed by then. Does not ask for confirmation,
+/// so it is also suitable for unattended callers.
+///
+/// If the apply is skipped in favour of a newer one, the post hook doesn't run.
+pub(crate) async fn apply_preset_config(
+    app: &AppHandle,
+    preset: &Preset,
+    source: HistorySource,
+) -> Result<ApplyOutcome, String> {
+    let before = get_displays().await.map(|c| c.displays).unwrap_or_default();
+    run_hook(HookKind::Pre, preset, &before).await?;
+
+    let outcome = apply_layout(app, preset.config.clone(), source).await?;
+    if outcome == ApplyOutcome::Skipped {
+        return Ok(outcome);
+    }
+    ddc::apply_preset(preset).await;
+    if let Err(e) = keep_awake::set(preset.keep_awake, &format!("DPUI preset {}", preset.name)) {
+        log::error!("[Presets] {}", e);
+    }
+    if let Some(enabled) = preset.night_shift {
+        if let Err(e) = night_shift::set_enabled(enabled) {
+            log::error!("[Presets] Failed to change Night Shift: {}", e);
+        }
+    }
+
+    if let Err(e) = record_usage(&app.state::<AppState>(), &preset.id) {
+        log::error!("[Presets] Failed to record usage: {}", e);
+    }
+    // The active preset changed even if the display set did not
+    if let Ok(config) = get_displays().await {
+        streamdeck::publish_state(app, &config);
+    }
+    // Move the tray's checkmark to this preset
+    if let Err(e) = update_tray_menu(app) {
+        log::error!("[Presets] Failed to update tray menu: {}", e);
+    }
+
+    let after = get_displays().await.map(|c| c.displays).unwrap_or_default();
+    if let Err(e) = run_hook(HookKind::Post, preset, &after).await {
+        log::error!("[Presets] {}", e);
+    }
+
+    Ok(outcome)
+}
+
+/// Bump a preset's apply count and last-applied time.
+fn record_usage(state: &AppState, preset_id: &str) -> Result<(), String> {
+    // Usage counters alone aren't worth rotating out a backup
+    state.update_presets_quietly(|store| {
+        if let Some(preset) = store.presets.iter_mut().find(|p| p.id == preset_id) {
+            preset.apply_count += 1;
+            preset.last_applied_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        Ok(())
+    })
+}
+
+/// The most recently applied preset, if the current layout still matches it.
+///
+/// Displays the preset doesn't mention are ignored, so connecting an extra
+/// monitor doesn't clear it.
+pub(crate) fn active_preset<'a>(store: &'a PresetStore, current: &DisplayConfig) -> Option<&'a Preset> {
+    let preset = *recent_presets(store, 1).first()?;
+
+    diff_layout(&preset.config, current)
+        .iter()
+        .all(|d| d.change == DisplayChange::NotInConfig)
+        .then_some(preset)
+}
+
+/// The most recently applied preset, if the current layout drifted from it.
+///
+/// Only drift on displays the preset mentions counts, and only while all of
+/// them are connected; a missing display means the setup changed rather than
+/// that macOS scrambled it.
+pub(crate) fn drifted_preset<'a>(store: &'a PresetStore, current: &DisplayConfig) -> Option<&'a Preset> {
+    let preset = *recent_presets(store, 1).first()?;
+    let differences = diff_layout(&preset.config, current);
+
+    let missing = differences.iter().any(|d| d.change == DisplayChange::Missing);
+    let drifted = differences.iter().any(|d| d.change != DisplayChange::NotInConfig);
+    (drifted && !missing).then_some(preset)
+}
+
+/// Find a preset by ID, or else by name ignoring case.
+pub(crate) fn find_preset_by_key<'a>(store: &'a PresetStore, key: &str) -> Option<&'a Preset> {
+    let key = key.trim();
+    store
+        .presets
+        .iter()
+        .find(|p| p.id == key)
+        .or_else(|| store.presets.iter().find(|p| p.name.eq_ignore_ascii_case(key)))
+}
+
+/// When a preset was last applied, if ever.
+fn last_applied(last_applied_at: Option<&str>) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    last_applied_at.and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+}
+
+/// Up to `limit` presets that have been applied, most recently applied first.
+pub(crate) fn recent_presets(store: &PresetStore, limit: usize) -> Vec<&Preset> {
+    let mut recent: Vec<&Preset> = store
+        .presets
+        .iter()
+        .filter(|p| last_applied(p.last_applied_at.as_deref()).is_some())
+        .collect();
+    recent.sort_by_key(|p| Reverse(last_applied(p.last_applied_at.as_deref())));
+    recent.truncate(limit);
+    recent
+}
+
+/// A preset made for exactly the connected displays that differs from the current layout.
+///
+/// Presets applied more recently are preferred. Returns `None` if no preset
+/// fits, or if one already matches the layout.
+pub(crate) fn preset_for_displays<'a>(store: &'a PresetStore, current: &DisplayConfig) -> Option<&'a Preset> {
+    let differences = |p: &Preset| diff_layout(&p.config, current);
+    let fits = |p: &&Preset| {
+        !differences(p)
+            .iter()
+            .any(|d| matches!(d.change, DisplayChange::Missing | DisplayChange::NotInConfig))
+    };
+
+    let preset = recent_presets(store, usize::MAX)
+        .into_iter()
+        .find(fits)
+        .or_else(|| store.presets.iter().find(fits))?;
+    (!differences(preset).is_empty()).then_some(preset)
+}
+
+/// Get usage statistics for every preset
+///
+/// # Returns
+/// * `Ok(Vec<PresetStats>)` - One entry per preset, most recently used first;
+///   presets that were never applied come last in their stored order
+/// * `Err(String)` - Error message if the presets cannot be loaded
+#[tauri::command]
+pub async fn get_preset_stats(state: State<'_, AppState>) -> Result<Vec<PresetStats>, String> {
+    let store = state.presets();
+
+    let mut stats: Vec<PresetStats> = store
+        .presets
+        .into_iter()
+        .map(|p| PresetStats {
+            preset_id: p.id,
+            name: p.name,
+            apply_count: p.apply_count,
+            last_applied_at: p.last_applied_at,
+        })
+        .collect();
+    stats.sort_by_key(|s| Reverse(last_applied(s.last_applied_at.as_deref())));
+
+    Ok(stats)
+}
+
+/// Apply a saved preset.
+///
+/// Runs entirely in the backend so hotkeys and the tray work while the window
+/// is hidden. The new layout has to be confirmed like any other interactive apply.
+///
+/// # Arguments
+/// * `preset_id` - ID of the preset to apply
+/// * `source` - What triggered the apply, for the history log (defaults to preset)
+///
+/// # Returns
+/// * `Ok(())` - Preset applied successfully
+/// * `Err(String)` - Error message if the preset doesn't exist or the apply fails
+#[tauri::command]
+pub async fn apply_preset(app: AppHandle, preset_id: String, source: Option<HistorySource>) -> Result<(), String> {
+    apply_saved_preset(&app, &preset_id, source.unwrap_or(HistorySource::Preset), true).await
+}
+
+/// Apply a saved preset by ID.
+///
+/// `confirm` arms the rollback countdown. Only the window's own apply sets it:
+/// hotkeys, the tray and other unattended callers have nobody to answer the
+/// `confirm-layout` prompt, so their layouts would always be reverted.
+pub(crate) async fn apply_saved_preset(
+    app: &AppHandle,
+    preset_id: &str,
+    source: HistorySource,
+    confirm: bool,
+) -> Result<(), String> {
+    let preset = app
+        .state::<AppState>()
+        .find_preset(preset_id)
+        .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+
+    let ApplyOutcome::Applied { undo_snapshot, .. } = apply_preset_config(app, &preset, source).await? else {
+        log::info!("[Presets] Skipped {}: a newer apply was queued", preset.name);
+        return Ok(());
+    };
+    if confirm {
+        rollback::arm(app, undo_snapshot);
+    }
+
+    log::info!("[Presets] Applied {}", preset.name);
+    let _ = app.emit("preset-applied", &preset.name);
+    Ok(())
+}
+
+/// Apply a preset in the background, reporting failures to the user.
+///
+/// Used by hotkeys and the tray, which have nowhere to return an error to;
+/// success is confirmed with a notification as well.
+pub fn spawn_apply_preset(app: &AppHandle, preset_id: String, source: HistorySource) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match apply_saved_preset(&app, &preset_id, source, false).await {
+            Ok(()) => {
+                if let Some(preset) = app.state::<AppState>().find_preset(&preset_id) {
+                    show_tray_notification("DPUI", &format!("Applied: {}", preset.name));
+                }
+            }
+            Err(e) => {
+                log::error!("[Presets] Failed to apply preset: {}", e);
+                let _ = app.emit("preset-apply-failed", &e);
+                show_tray_notification("DPUI", &format!("Failed to apply preset: {}", e));
+            }
+        }
+    });
+}
+
+/// Save the current display layout as a new preset
+///
+/// # Arguments
+/// * `name` - Name of the new preset
+///
+/// # Returns
+/// * `Ok(Preset)` - The saved preset
+/// * `Err(String)` - Error message if the displays cannot be read or the preset cannot be saved
 #[tauri::command]
-pub async fn add_preset(name: String, config: String, hotkey: Option<String>) -> Result<Preset, String> {
-    let mut store = load_presets().await?;
+pub async fn create_preset_from_current(state: State<'_, AppState>, name: String) -> Result<Preset, String> {
+    let current = get_displays().await?;
+    if current.displays.is_empty() {
+        return Err("No displays found".to_string());
+    }
+
+    insert_preset(&state, Preset::new(name, layout_command(&current)))
+}
+
+/// Create a preset from a pasted displayplacer command
+///
+/// # Arguments
+/// * `raw` - Command as copied from `displayplacer list` or saved notes
+/// * `name` - Name of the new preset (defaults to "Imported preset")
+///
+/// # Returns
+/// * `Ok(Preset)` - The saved preset, with the command in canonical form
+/// * `Err(String)` - Error message if the command cannot be parsed or the preset cannot be saved
+#[tauri::command]
+pub async fn import_from_command(
+    state: State<'_, AppState>,
+    raw: String,
+    name: Option<String>,
+) -> Result<Preset, String> {
+    let config = parse_command(&raw)?;
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| "Imported preset".to_string());
+
+    insert_preset(&state, Preset::new(name, config))
+}
 
+/// Display IDs a preset references that are not among `displays`.
+pub(crate) fn missing_displays(preset: &Preset, displays: &[Display]) -> Vec<String> {
+    validate_layout(&preset.config, displays)
+        .into_iter()
+        .filter(|w| w.issue == ConfigIssue::UnknownDisplay)
+        .filter_map(|w| w.display_id)
+        .collect()
+}
+
+/// Check which of a preset's displays are not currently connected.
+///
+/// # Arguments
+/// * `preset_id` - ID of the preset to check
+///
+/// # Returns
+/// * `Ok(PresetValidation)` - Missing display IDs and whether the preset can be applied
+/// * `Err(String)` - Error message if the preset doesn't exist or the displays cannot be read
+#[tauri::command]
+pub async fn validate_preset(state: State<'_, AppState>, preset_id: String) -> Result<PresetValidation, String> {
+    let preset = state
+        .find_preset(&preset_id)
+        .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+
+    let current = get_displays().await?;
+    let missing = missing_displays(&preset, &current.displays);
+
+    Ok(PresetValidation {
+        preset_id,
+        applicable: missing.is_empty(),
+        missing_displays: missing,
+    })
+}
+
+/// Show what applying a preset would change.
+///
+/// # Arguments
+/// * `preset_id` - ID of the preset to compare with the current layout
+///
+/// # Returns
+/// * `Ok(Vec<DisplayDifference>)` - Per-display differences; empty if the preset is already applied
+/// * `Err(String)` - Error message if the preset doesn't exist or the displays cannot be read
+#[tauri::command]
+pub async fn diff_preset(state: State<'_, AppState>, preset_id: String) -> Result<Vec<DisplayDifference>, String> {
+    let preset = state
+        .find_preset(&preset_id)
+        .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+
+    let current = get_displays().await?;
+    Ok(diff_layout(&preset.config, &current))
+}
+
+/// Trim tags, dropping empty and duplicate (case-insensitive) ones.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Trim a description, treating an empty one as none.
+fn normalize_description(description: Option<String>) -> Option<String> {
+    description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty())
+}
+
+/// Trim a folder name, treating an empty name as no folder.
+fn normalize_folder(folder: Option<String>) -> Option<String> {
+    folder.map(|f| f.trim().to_string()).filter(|f| !f.is_empty())
+}
+
+/// Check that every per-display brightness or volume is a percentage.
+fn validate_percentages(values: BTreeMap<String, u16>) -> Result<BTreeMap<String, u16>, String> {
+    match values.iter().find(|(_, value)| **value > 100) {
+        Some((id, value)) => Err(format!("Invalid value {} for display {}; expected 0-100", value, id)),
+        None => Ok(values),
+    }
+}
+
+/// Get all presets carrying a tag
+///
+/// # Arguments
+/// * `tag` - Tag to look for, compared case-insensitively
+///
+/// # Returns
+/// * `Ok(Vec<Preset>)` - Matching presets in store order
+/// * `Err(String)` - Error message if the presets cannot be loaded
+#[tauri::command]
+pub async fn get_presets_by_tag(state: State<'_, AppState>, tag: String) -> Result<Vec<Preset>, String> {
+    let store = state.presets();
+    let tag = tag.trim();
+
+    Ok(store
+        .presets
+        .into_iter()
+        .filter(|p| p.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .collect())
+}
+
+/// Add a new preset
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn add_preset(
+    state: State<'_, AppState>,
+    name: String,
+    config: String,
+    hotkey: Option<String>,
+    description: Option<String>,
+    pre_hook: Option<String>,
+    post_hook: Option<String>,
+    tags: Option<Vec<String>>,
+    folder: Option<String>,
+    brightness: Option<BTreeMap<String, u16>>,
+    volume: Option<BTreeMap<String, u16>>,
+    input_sources: Option<BTreeMap<String, InputSource>>,
+    night_shift: Option<bool>,
+    keep_awake: Option<bool>,
+) -> Result<Preset, String> {
     let preset = Preset {
-        id: uuid::Uuid::new_v4().to_string(),
-        name,
-        config,
         hotkey,
-        created_at: chrono::Utc::now().to_rfc3339(),
+        description: normalize_description(description),
+        pre_hook,
+        post_hook,
+        tags: normalize_tags(tags.unwrap_or_default()),
+        folder: normalize_folder(folder),
+        brightness: validate_percentages(brightness.unwrap_or_default())?,
+        volume: validate_percentages(volume.unwrap_or_default())?,
+        input_sources: input_sources.unwrap_or_default(),
+        night_shift,
+        keep_awake: keep_awake.unwrap_or(false),
+        ..Preset::new(name, config)
     };
 
-    store.presets.push(preset.clone());
-    save_presets(store).await?;
+    insert_preset(&state, preset)
+}
 
-    Ok(preset)
+/// Store a new preset after all existing ones.
+fn insert_preset(state: &AppState, mut preset: Preset) -> Result<Preset, String> {
+    state.update_presets(|store| {
+        preset.sort_order = store.presets.iter().map(|p| p.sort_order + 1).max().unwrap_or(0);
+        store.presets.push(preset.clone());
+        Ok(preset)
+    })
 }
 
-/// Delete a preset
+/// Reorder presets
+///
+/// Presets are moved into the order of `ids`; presets not listed keep their
+/// relative order after the listed ones. The tray menu follows this order.
+///
+/// # Arguments
+/// * `ids` - Preset IDs in the desired order
+///
+/// # Returns
+/// * `Ok(Vec<Preset>)` - All presets in their new order
+/// * `Err(String)` - Error message if an ID is unknown or the presets cannot be saved
 #[tauri::command]
-pub async fn delete_preset(id: String) -> Result<(), String> {
-    let mut store = load_presets().await?;
+pub async fn reorder_presets(state: State<'_, AppState>, ids: Vec<String>) -> Result<Vec<Preset>, String> {
+    state.update_presets(|store| {
+        if let Some(unknown) = ids.iter().find(|id| !store.presets.iter().any(|p| &p.id == *id)) {
+            return Err(format!("Preset {} not found", unknown));
+        }
 
-    store.presets.retain(|p| p.id != id);
-    save_presets(store).await?;
+        store
+            .presets
+            .sort_by_key(|p| ids.iter().position(|id| *id == p.id).unwrap_or(ids.len()));
+        for (index, preset) in store.presets.iter_mut().enumerate() {
+            preset.sort_order = index as u32;
+        }
 
-    Ok(())
+        Ok(store.presets.clone())
+    })
+}
+
+/// Delete a preset
+#[tauri::command]
+pub async fn delete_preset(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    state.update_presets(|store| {
+        store.presets.retain(|p| p.id != id);
+        Ok(())
+    })
 }
 
 /// Update a preset
+///
+/// An empty `description` clears it; an empty `folder` moves the preset out of its folder.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_preset(
+    state: State<'_, AppState>,
     id: String,
     name: Option<String>,
     config: Option<String>,
     hotkey: Option<String>,
+    description: Option<String>,
+    pre_hook: Option<String>,
+    post_hook: Option<String>,
+    tags: Option<Vec<String>>,
+    folder: Option<String>,
+    brightness: Option<BTreeMap<String, u16>>,
+    volume: Option<BTreeMap<String, u16>>,
+    input_sources: Option<BTreeMap<String, InputSource>>,
+    night_shift: Option<bool>,
+    keep_awake: Option<bool>,
 ) -> Result<Preset, String> {
-    let mut store = load_presets().await?;
+    state.update_presets(|store| {
+        let preset = store
+            .presets
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or("Preset not found")?;
 
-    let preset = store
-        .presets
-        .iter_mut()
-        .find(|p| p.id == id)
-        .ok_or("Preset not found")?;
-
-    if let Some(n) = name {
-        preset.name = n;
-    }
-    if let Some(c) = config {
-        preset.config = c;
-    }
-    if hotkey.is_some() {
-        preset.hotkey = hotkey;
-    }
-
-    let updated_preset = preset.clone();
-    save_presets(store).await?;
+        if let Some(n) = name {
+            preset.name = n;
+        }
+        if let Some(c) = config {
+            preset.config = c;
+        }
+        if hotkey.is_some() {
+            preset.hotkey = hotkey;
+        }
+        if description.is_some() {
+            preset.description = normalize_description(description);
+        }
+        if pre_hook.is_some() {
+            preset.pre_hook = pre_hook;
+        }
+        if post_hook.is_some() {
+            preset.post_hook = post_hook;
+        }
+        if let Some(t) = tags {
+            preset.tags = normalize_tags(t);
+        }
+        if folder.is_some() {
+            preset.folder = normalize_folder(folder);
+        }
+        if let Some(b) = brightness {
+            preset.brightness = validate_percentages(b)?;
+        }
+        if let Some(v) = volume {
+            preset.volume = validate_percentages(v)?;
+        }
+        if let Some(sources) = input_sources {
+            preset.input_sources = sources;
+        }
+        if night_shift.is_some() {
+            preset.night_shift = night_shift;
+        }
+        if let Some(k) = keep_awake {
+            preset.keep_awake = k;
+        }
 
-    Ok(updated_preset)
+        Ok(preset.clone())
+    })
 }
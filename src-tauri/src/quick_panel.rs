@@ -0,0 +1,95 @@
+//! Quick panel shown when the tray icon is left-clicked.
+//!
+//! A small undecorated window anchored below the tray icon with one-click
+//! preset buttons and display toggles. It loads the regular frontend with
+//! `?view=quick-panel`, is created on first use and hidden again as soon as
+//! it loses focus.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, Rect, Runtime, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+const PANEL_LABEL: &str = "quick-panel";
+
+/// Panel size in logical pixels
+const PANEL_WIDTH: f64 = 320.0;
+const PANEL_HEIGHT: f64 = 420.0;
+
+/// A click on the tray icon first takes focus from the panel, which hides it;
+/// a toggle this soon after is that same click and must not show it again
+const REOPEN_GUARD: Duration = Duration::from_millis(300);
+
+static LAST_HIDDEN: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Show the panel below the tray icon, or hide it if it is showing.
+///
+/// # Arguments
+/// * `app` - Application handle
+/// * `tray_rect` - Tray icon bounds, as reported by the click event
+pub fn toggle<R: Runtime>(app: &AppHandle<R>, tray_rect: Rect) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PANEL_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            hide(&window);
+            return Ok(());
+        }
+        let just_hidden = LAST_HIDDEN
+            .lock()
+            .ok()
+            .and_then(|t| *t)
+            .is_some_and(|t| t.elapsed() < REOPEN_GUARD);
+        if just_hidden {
+            return Ok(());
+        }
+        return show(app, &window, tray_rect);
+    }
+
+    let window = WebviewWindowBuilder::new(app, PANEL_LABEL, WebviewUrl::App("index.html?view=quick-panel".into()))
+        .title("DPUI")
+        .inner_size(PANEL_WIDTH, PANEL_HEIGHT)
+        .decorations(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .build()
+        .map_err(|e| format!("Failed to create quick panel: {}", e))?;
+
+    let panel = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            hide(&panel);
+        }
+    });
+
+    show(app, &window, tray_rect)
+}
+
+/// Position the panel centered below the tray icon and show it.
+fn show<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>, tray_rect: Rect) -> Result<(), String> {
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let position = tray_rect.position.to_physical::<f64>(scale);
+    let size = tray_rect.size.to_physical::<f64>(scale);
+
+    let x = position.x + size.width / 2.0 - PANEL_WIDTH * scale / 2.0;
+    let y = position.y + size.height;
+    window
+        .set_position(PhysicalPosition::new(x.max(0.0), y))
+        .map_err(|e| format!("Failed to position quick panel: {}", e))?;
+
+    window.show().map_err(|e| format!("Failed to show quick panel: {}", e))?;
+    let _ = window.set_focus();
+
+    // Displays or presets may have changed while it was hidden
+    let _ = app.emit_to(PANEL_LABEL, "quick-panel-shown", ());
+    Ok(())
+}
+
+fn hide<R: Runtime>(window: &WebviewWindow<R>) {
+    let _ = window.hide();
+    if let Ok(mut last) = LAST_HIDDEN.lock() {
+        *last = Some(Instant::now());
+    }
+}
@@ -0,0 +1,552 @@
+//! Remote control over the local network.
+//!
+//! When enabled, DPUI listens on a TCP port and advertises itself over Bonjour
+//! as `_dpui._tcp`, so a companion app or another DPUI instance can list and
+//! apply presets — e.g. to fix the layout of a Mac whose own screen is blank.
+//!
+//! The protocol is one JSON object per line in each direction. A device pairs
+//! once by sending the six-digit code shown in the UI and receives a key that
+//! must accompany every later request. Paired devices are stored in
+//! `paired-devices.json`; keys for hosts this instance controls are stored in
+//! `remote-hosts.json`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::atomic_file::write_atomic;
+use crate::conflicts::{self, IntegrationKind};
+use crate::history::HistorySource;
+use crate::displayplacer::run_blocking;
+use crate::presets::{apply_preset_config, get_config_dir};
+use crate::state::AppState;
+use crate::session;
+use crate::settings;
+
+/// Bonjour service type advertised by the server
+const SERVICE_TYPE: &str = "_dpui._tcp";
+
+/// How long a pairing code stays valid
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(120);
+
+/// Wrong guesses after which a pairing code is discarded
+const MAX_PAIRING_FAILURES: u32 = 5;
+
+/// How long a peer has to wait after a wrong pairing code
+const PAIRING_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How long discovery listens for Bonjour announcements
+const DISCOVERY_TIME: Duration = Duration::from_secs(2);
+
+/// Timeout for client connections and idle server connections
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pairing code currently shown in the UI
+static PAIRING_CODE: Mutex<Option<PairingCode>> = Mutex::new(None);
+
+/// Peers that recently sent a wrong pairing code, with the time they did
+static PAIRING_FAILURES: Mutex<Vec<(IpAddr, Instant)>> = Mutex::new(Vec::new());
+
+struct PairingCode {
+    code: String,
+    expires: Instant,
+    /// Wrong guesses so far
+    failures: u32,
+}
+
+/// A device allowed to control this instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    /// Secret the device sends with every request; never sent to the frontend
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    pub key: String,
+    pub paired_at: String,
+}
+
+/// A remote DPUI this instance has paired with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteHost {
+    pub host: String,
+    pub port: u16,
+    pub device_id: String,
+    pub key: String,
+}
+
+/// A DPUI instance found on the network.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredHost {
+    /// Bonjour instance name
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+}
+
+/// Preset as listed to remote devices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemotePreset {
+    pub id: String,
+    pub name: String,
+}
+
+/// Request sent by a remote device.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Pair { code: String, device_name: String },
+    ListPresets { key: String },
+    ApplyPreset { key: String, preset_id: String },
+}
+
+/// Response sent back for every request.
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    data: Value,
+}
+
+impl Response {
+    fn ok(data: Value) -> Self {
+        Self { ok: true, error: None, data }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self { ok: false, error: Some(message.into()), data: Value::Null }
+    }
+}
+
+fn get_paired_devices_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("paired-devices.json"))
+}
+
+fn get_remote_hosts_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("remote-hosts.json"))
+}
+
+fn read_json_list<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write_json_list<T: Serialize>(path: &Path, items: &[T]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(items).map_err(|e| format!("Failed to serialize: {}", e))?;
+    write_atomic(path, content.as_bytes())
+}
+
+/// Compare secrets without exiting early on the first mismatch.
+fn keys_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Find the paired device a key belongs to.
+fn authenticate(key: &str) -> Result<PairedDevice, String> {
+    read_json_list::<PairedDevice>(&get_paired_devices_path()?)?
+        .into_iter()
+        .find(|d| keys_match(&d.key, key))
+        .ok_or_else(|| "Unknown device; pair again".to_string())
+}
+
+/// Check a guess against the pending code.
+///
+/// The code is consumed when it matches, and discarded once it expires or has
+/// been guessed wrong `MAX_PAIRING_FAILURES` times, so it can't be brute-forced.
+fn check_pairing_code(pending: &mut Option<PairingCode>, code: &str, now: Instant) -> bool {
+    let Some(current) = pending.as_mut() else {
+        return false;
+    };
+    if now >= current.expires {
+        *pending = None;
+        return false;
+    }
+    if keys_match(&current.code, code) {
+        *pending = None;
+        return true;
+    }
+
+    current.failures += 1;
+    if current.failures >= MAX_PAIRING_FAILURES {
+        log::warn!("[Remote] Discarded the pairing code after {} wrong guesses", current.failures);
+        *pending = None;
+    }
+    false
+}
+
+/// Consume the pairing code if it matches, making `peer` wait after a wrong guess.
+fn take_pairing_code(code: &str, peer: IpAddr) -> Result<(), String> {
+    let now = Instant::now();
+    let mut failures = PAIRING_FAILURES.lock().map_err(|_| "Pairing lock poisoned".to_string())?;
+    failures.retain(|(_, at)| now.duration_since(*at) < PAIRING_RETRY_DELAY);
+    if failures.iter().any(|(ip, _)| *ip == peer) {
+        return Err("Too many pairing attempts; try again in a moment".to_string());
+    }
+
+    let mut pending = PAIRING_CODE.lock().map_err(|_| "Pairing lock poisoned".to_string())?;
+    if check_pairing_code(&mut pending, code, now) {
+        Ok(())
+    } else {
+        failures.push((peer, now));
+        Err("Invalid or expired pairing code".to_string())
+    }
+}
+
+fn handle_pair(app: &AppHandle, peer: IpAddr, code: &str, device_name: &str) -> Result<Value, String> {
+    take_pairing_code(code, peer)?;
+
+    let device = PairedDevice {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: device_name.to_string(),
+        key: format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+        paired_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let path = get_paired_devices_path()?;
+    let mut devices = read_json_list::<PairedDevice>(&path)?;
+    devices.push(device.clone());
+    write_json_list(&path, &devices)?;
+
+    log::info!("[Remote] Paired {}", device.name);
+    let _ = app.emit("remote-device-paired", &device.name);
+
+    Ok(serde_json::json!({ "device_id": device.id, "key": device.key }))
+}
+
+fn handle_request(app: &AppHandle, peer: IpAddr, request: Request) -> Result<Value, String> {
+    if !session::is_active() {
+        return Err(session::INACTIVE_ERROR.to_string());
+    }
+    match request {
+        Request::Pair { code, device_name } => handle_pair(app, peer, &code, &device_name),
+        Request::ListPresets { key } => {
+            authenticate(&key)?;
+            let store = app.state::<AppState>().presets();
+            let presets: Vec<RemotePreset> = store
+                .presets
+                .into_iter()
+                .map(|p| RemotePreset { id: p.id, name: p.name })
+                .collect();
+            serde_json::to_value(presets).map_err(|e| e.to_string())
+        }
+        Request::ApplyPreset { key, preset_id } => {
+            let device = authenticate(&key)?;
+            let preset = app
+                .state::<AppState>()
+                .find_preset(&preset_id)
+                .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+
+            log::info!("[Remote] {} applied preset {}", device.name, preset.name);
+            tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::Remote))?;
+            let _ = app.emit("refresh-displays", ());
+            Ok(Value::Null)
+        }
+    }
+}
+
+/// Serve one connection until the peer closes it.
+fn handle_connection(app: &AppHandle, stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let Ok(peer) = stream.peer_addr().map(|a| a.ip()) else {
+        return;
+    };
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => match handle_request(app, peer, request) {
+                Ok(data) => Response::ok(data),
+                Err(e) => Response::error(e),
+            },
+            Err(e) => Response::error(format!("Invalid request: {}", e)),
+        };
+
+        let Ok(json) = serde_json::to_string(&response) else {
+            break;
+        };
+        if writeln!(writer, "{}", json).is_err() {
+            break;
+        }
+    }
+}
+
+/// Advertise the server over Bonjour with `dns-sd`, which runs until killed.
+fn advertise(port: u16) {
+    let name = Command::new("scutil")
+        .args(["--get", "ComputerName"])
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "DPUI".to_string());
+
+    let result = Command::new("dns-sd")
+        .args(["-R", &name, SERVICE_TYPE, "local", &port.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    match result {
+        // The child is left running for the lifetime of the app
+        Ok(_) => log::info!("[Remote] Advertising {} as {}", SERVICE_TYPE, name),
+        Err(e) => log::error!("[Remote] Failed to advertise over Bonjour: {}", e),
+    }
+}
+
+/// Start the remote control server if it is enabled in settings.
+///
+/// Runs the usual pre-flight conflict checks first; if the port is taken the
+/// server is not started and the diagnostics are returned.
+pub fn start(app: AppHandle) -> Result<(), String> {
+    let remote = settings::current().remote;
+    if !remote.enabled {
+        return Ok(());
+    }
+
+    let report = conflicts::check(IntegrationKind::Remote, Some(remote.port), None);
+    report.into_result()?;
+
+    let listener = TcpListener::bind(("0.0.0.0", remote.port))
+        .map_err(|e| format!("Failed to bind port {}: {}", remote.port, e))?;
+
+    advertise(remote.port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            thread::spawn(move || handle_connection(&app, stream));
+        }
+    });
+
+    log::info!("[Remote] Listening on port {}", remote.port);
+    Ok(())
+}
+
+/// Generate a pairing code for a new device.
+///
+/// # Returns
+/// * `Ok(String)` - Six-digit code, valid for two minutes, one pairing and a few wrong guesses
+#[tauri::command]
+pub async fn start_remote_pairing() -> Result<String, String> {
+    let code = format!("{:06}", uuid::Uuid::new_v4().as_u128() % 1_000_000);
+
+    let mut pending = PAIRING_CODE.lock().map_err(|_| "Pairing lock poisoned".to_string())?;
+    *pending = Some(PairingCode {
+        code: code.clone(),
+        expires: Instant::now() + PAIRING_CODE_TTL,
+        failures: 0,
+    });
+
+    Ok(code)
+}
+
+/// List devices paired with this instance, without their keys.
+#[tauri::command]
+pub async fn get_paired_devices() -> Result<Vec<PairedDevice>, String> {
+    let devices = read_json_list::<PairedDevice>(&get_paired_devices_path()?)?;
+    Ok(devices
+        .into_iter()
+        .map(|d| PairedDevice { key: String::new(), ..d })
+        .collect())
+}
+
+/// Revoke a paired device's access.
+#[tauri::command]
+pub async fn unpair_device(id: String) -> Result<(), String> {
+    let path = get_paired_devices_path()?;
+    let mut devices = read_json_list::<PairedDevice>(&path)?;
+    devices.retain(|d| d.id != id);
+    write_json_list(&path, &devices)
+}
+
+/// Send one request to a remote instance and read its response.
+fn send(host: &str, port: u16, request: Value) -> Result<Value, String> {
+    let mut stream = TcpStream::connect((host, port)).map_err(|e| format!("Failed to connect to {}: {}", host, e))?;
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+
+    writeln!(stream, "{}", request).map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    let response: Response = serde_json::from_str(&line).map_err(|e| format!("Invalid response: {}", e))?;
+    if !response.ok {
+        return Err(response.error.unwrap_or_else(|| "Request failed".to_string()));
+    }
+    Ok(response.data)
+}
+
+/// Key previously obtained from a host.
+fn host_key(host: &str, port: u16) -> Result<String, String> {
+    read_json_list::<RemoteHost>(&get_remote_hosts_path()?)?
+        .into_iter()
+        .find(|h| h.host == host && h.port == port)
+        .map(|h| h.key)
+        .ok_or_else(|| format!("Not paired with {}", host))
+}
+
+/// Parse `dns-sd -L` output for the host and port a service can be reached at.
+///
+/// The relevant line looks like
+/// `... Mac-mini._dpui._tcp.local. can be reached at Mac-mini.local.:48620 (interface 4)`.
+fn parse_resolve_output(output: &str) -> Option<(String, u16)> {
+    let target = output.lines().find_map(|l| l.split("can be reached at ").nth(1))?;
+    let address = target.split_whitespace().next()?;
+    let (host, port) = address.rsplit_once(':')?;
+    Some((host.trim_end_matches('.').to_string(), port.parse().ok()?))
+}
+
+/// Run a command that never exits on its own for `duration` and return its stdout.
+fn run_for(program: &str, args: &[&str], duration: Duration) -> Result<String, String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    thread::sleep(duration);
+    let _ = child.kill();
+
+    let output = child.wait_with_output().map_err(|e| format!("Failed to read {} output: {}", program, e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Find other DPUI instances advertising on the local network.
+#[tauri::command]
+pub async fn discover_remote_hosts() -> Result<Vec<DiscoveredHost>, String> {
+    run_blocking(discover).await
+}
+
+/// Browse for and resolve `_dpui._tcp` services; blocks for a few seconds.
+fn discover() -> Result<Vec<DiscoveredHost>, String> {
+    let browse = run_for("dns-sd", &["-B", SERVICE_TYPE, "local"], DISCOVERY_TIME)?;
+
+    // Browse lines: "Timestamp A/R Flags if Domain Service Type Instance Name";
+    // the instance name may contain spaces and follows the service type
+    let names: Vec<String> = browse
+        .lines()
+        .filter(|l| l.contains(" Add "))
+        .filter_map(|l| l.split_once(&format!("{}.", SERVICE_TYPE)).map(|(_, name)| name.trim().to_string()))
+        .collect();
+
+    let mut hosts = Vec::new();
+    for name in names {
+        let resolved = run_for("dns-sd", &["-L", &name, SERVICE_TYPE, "local"], DISCOVERY_TIME)?;
+        if let Some((host, port)) = parse_resolve_output(&resolved) {
+            hosts.push(DiscoveredHost { name, host, port });
+        }
+    }
+
+    Ok(hosts)
+}
+
+/// Pair with a remote instance using the code shown on its screen.
+#[tauri::command]
+pub async fn pair_remote_host(host: String, port: u16, code: String, device_name: String) -> Result<(), String> {
+    let data = send(
+        &host,
+        port,
+        serde_json::json!({ "type": "pair", "code": code, "device_name": device_name }),
+    )?;
+
+    let remote = RemoteHost {
+        host: host.clone(),
+        port,
+        device_id: data["device_id"].as_str().unwrap_or_default().to_string(),
+        key: data["key"].as_str().ok_or("Pairing response has no key")?.to_string(),
+    };
+
+    let path = get_remote_hosts_path()?;
+    let mut hosts = read_json_list::<RemoteHost>(&path)?;
+    hosts.retain(|h| !(h.host == host && h.port == port));
+    hosts.push(remote);
+    write_json_list(&path, &hosts)
+}
+
+/// List the presets of a paired remote instance.
+#[tauri::command]
+pub async fn list_remote_presets(host: String, port: u16) -> Result<Vec<RemotePreset>, String> {
+    let key = host_key(&host, port)?;
+    let data = send(&host, port, serde_json::json!({ "type": "list_presets", "key": key }))?;
+    serde_json::from_value(data).map_err(|e| format!("Invalid preset list: {}", e))
+}
+
+/// Apply a preset on a paired remote instance.
+#[tauri::command]
+pub async fn apply_remote_preset(host: String, port: u16, preset_id: String) -> Result<(), String> {
+    let key = host_key(&host, port)?;
+    send(
+        &host,
+        port,
+        serde_json::json!({ "type": "apply_preset", "key": key, "preset_id": preset_id }),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolve_output() {
+        let output = "Lookup Mac mini._dpui._tcp.local\n\
+            DATE: ---Mon 01 Jan 2024---\n\
+            12:00:00.000  Mac\\032mini._dpui._tcp.local. can be reached at Mac-mini.local.:48620 (interface 4)\n";
+        assert_eq!(parse_resolve_output(output), Some(("Mac-mini.local".to_string(), 48620)));
+        assert_eq!(parse_resolve_output("Lookup nothing\n"), None);
+    }
+
+    #[test]
+    fn test_check_pairing_code() {
+        let now = Instant::now();
+        let code = |expires| {
+            Some(PairingCode {
+                code: "123456".to_string(),
+                expires,
+                failures: 0,
+            })
+        };
+
+        let mut pending = code(now + PAIRING_CODE_TTL);
+        assert!(!check_pairing_code(&mut pending, "000000", now));
+        assert!(check_pairing_code(&mut pending, "123456", now));
+        assert!(!check_pairing_code(&mut pending, "123456", now), "a code pairs only once");
+
+        let mut pending = code(now + PAIRING_CODE_TTL);
+        for _ in 0..MAX_PAIRING_FAILURES {
+            assert!(!check_pairing_code(&mut pending, "000000", now));
+        }
+        assert!(!check_pairing_code(&mut pending, "123456", now), "too many guesses discard the code");
+
+        let mut pending = code(now);
+        assert!(!check_pairing_code(&mut pending, "123456", now), "expired");
+    }
+
+    #[test]
+    fn test_keys_match() {
+        assert!(keys_match("abc123", "abc123"));
+        assert!(!keys_match("abc123", "abc124"));
+        assert!(!keys_match("abc", "abc123"));
+    }
+}
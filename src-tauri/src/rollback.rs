@@ -0,0 +1,169 @@
+//! Confirmation countdown for applied layouts.
+//!
+//! After an interactive apply the backend emits `confirm-layout` and starts a
+//! timer. If the frontend does not call `confirm_applied_config` before it
+//! runs out, the layout that apply replaced is restored from the undo stack,
+//! so a preset that blanks every screen fixes itself without any input.
+//!
+//! Any later change, or an undo, settles the pending layout: it is no longer
+//! the one on screen, so reverting it would undo something else.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::displayplacer::run_blocking;
+use crate::settings;
+use crate::undo;
+
+/// Payload of the `confirm-layout` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfirmRequest {
+    /// Identifies this apply; later applies supersede it
+    pub token: u64,
+    /// Seconds left before the layout is reverted
+    pub timeout_secs: u64,
+}
+
+/// Source of apply tokens
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+/// An apply awaiting confirmation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pending {
+    token: u64,
+    /// Undo snapshot of the layout the apply replaced
+    snapshot: u64,
+}
+
+/// The apply currently awaiting confirmation
+static PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+
+/// Take the pending apply if it is still `token` (or any pending apply for `None`).
+///
+/// # Returns
+/// * `Some(u64)` - Undo snapshot to revert to
+/// * `None` - Nothing matching was pending
+fn take_pending(token: Option<u64>) -> Option<u64> {
+    let mut pending = PENDING.lock().ok()?;
+    match (*pending, token) {
+        (Some(current), Some(token)) if current.token != token => None,
+        _ => pending.take().map(|p| p.snapshot),
+    }
+}
+
+/// Ask the user to keep the layout that was just applied.
+///
+/// Does nothing when the confirmation timeout is set to 0, or without a
+/// snapshot of the replaced layout to go back to. A newer apply replaces any
+/// pending one; only the newest is reverted on timeout.
+///
+/// # Arguments
+/// * `snapshot` - Undo snapshot the apply pushed, as returned by `UndoStack::push`
+pub fn arm(app: &AppHandle, snapshot: Option<u64>) {
+    let timeout_secs = settings::current().confirm_timeout_secs;
+    if timeout_secs == 0 {
+        return;
+    }
+    let Some(snapshot) = snapshot else {
+        log::info!("[Rollback] Previous layout unknown, not asking for confirmation");
+        return;
+    };
+
+    let token = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some(Pending { token, snapshot });
+    }
+
+    let _ = app.emit("confirm-layout", ConfirmRequest { token, timeout_secs });
+
+    let app = app.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(timeout_secs));
+
+        if let Some(snapshot) = take_pending(Some(token)) {
+            log::info!("[Rollback] Layout not confirmed within {}s, reverting", timeout_secs);
+            revert(&app, snapshot);
+        }
+    });
+}
+
+/// Settle the layout awaiting confirmation without reverting it.
+///
+/// Called when a later change or an undo replaces it. The frontend is told
+/// with `confirm-layout-cancelled` so it can close the prompt.
+pub(crate) fn cancel<R: Runtime>(app: &AppHandle<R>) {
+    if take_pending(None).is_some() {
+        log::info!("[Rollback] Layout replaced before it was confirmed");
+        let _ = app.emit("confirm-layout-cancelled", ());
+    }
+}
+
+fn revert(app: &AppHandle, snapshot: u64) {
+    match undo::revert_to(app, snapshot) {
+        Ok(()) => {
+            let _ = app.emit("layout-rolled-back", ());
+        }
+        Err(e) => log::error!("[Rollback] Failed to revert layout: {}", e),
+    }
+}
+
+/// Keep the layout that was just applied and cancel the rollback timer.
+///
+/// # Returns
+/// * `Ok(true)` - A pending layout was confirmed
+/// * `Ok(false)` - Nothing was awaiting confirmation (e.g. it already timed out)
+#[tauri::command]
+pub async fn confirm_applied_config() -> Result<bool, String> {
+    Ok(take_pending(None).is_some())
+}
+
+/// Revert the layout awaiting confirmation right away instead of waiting for the timer.
+///
+/// # Returns
+/// * `Ok(true)` - The previous layout was restored
+/// * `Ok(false)` - Nothing was awaiting confirmation
+#[tauri::command]
+pub async fn reject_applied_config(app: AppHandle) -> Result<bool, String> {
+    let Some(snapshot) = take_pending(None) else {
+        return Ok(false);
+    };
+    let handle = app.clone();
+    run_blocking(move || undo::revert_to(&handle, snapshot)).await?;
+    let _ = app.emit("layout-rolled-back", ());
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_pending(token: u64, snapshot: u64) {
+        *PENDING.lock().unwrap() = Some(Pending { token, snapshot });
+    }
+
+    #[test]
+    fn test_take_pending() {
+        // A timer for a superseded apply leaves the newer one pending
+        set_pending(2, 20);
+        assert_eq!(take_pending(Some(1)), None);
+        assert_eq!(PENDING.lock().unwrap().map(|p| p.token), Some(2));
+
+        // The newest apply's timer takes its own snapshot
+        assert_eq!(take_pending(Some(2)), Some(20));
+        assert_eq!(take_pending(Some(2)), None);
+
+        // Confirming, a later change or an undo takes whatever is pending, once
+        set_pending(3, 30);
+        assert_eq!(take_pending(None), Some(30));
+        assert_eq!(take_pending(None), None);
+
+        // A settled apply is not reverted when its timer runs out
+        set_pending(4, 40);
+        assert_eq!(take_pending(None), Some(40));
+        assert_eq!(take_pending(Some(4)), None);
+    }
+}
@@ -0,0 +1,480 @@
+//! Background scheduler for time-based display changes.
+//!
+//! A single thread ticks every few seconds and drives time-based features:
+//!
+//! - Scheduled presets, applied at a time of day on chosen weekdays and stored
+//!   in `schedules.json` next to the presets file.
+//! - The digital sunset: at the configured start time the secondary displays
+//!   are disabled one step at a time (or a minimal preset is applied), with a
+//!   snoozable notice, and the prior layout is restored at the configured wake
+//!   time or on demand.
+
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::atomic_file::write_atomic;
+use crate::displayplacer::{display_args, execute_displayplacer, get_displays, Display};
+use crate::history::HistorySource;
+use crate::presets::{apply_preset_config, get_config_dir};
+use crate::state::AppState;
+use crate::session;
+use crate::settings::{self, SunsetSettings};
+use crate::system_tray::show_tray_notification;
+use crate::undo;
+
+/// How often the scheduler wakes up to check its work
+const TICK: Duration = Duration::from_secs(15);
+
+/// Presets scheduled after this long past their time (e.g. while asleep) are skipped
+const MAX_SCHEDULE_LATENESS: chrono::Duration = chrono::Duration::minutes(5);
+
+/// A preset applied at a time of day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: String,
+    pub preset_id: String,
+    /// Local time to apply the preset, "HH:MM"
+    pub time: String,
+    /// Days to apply it on; every day if empty
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleStore {
+    pub version: String,
+    pub schedules: Vec<Schedule>,
+}
+
+impl Default for ScheduleStore {
+    fn default() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            schedules: Vec::new(),
+        }
+    }
+}
+
+/// When schedules were last checked, so each one fires once per day even
+/// though the scheduler ticks more often
+static LAST_SCHEDULE_CHECK: Mutex<Option<NaiveDateTime>> = Mutex::new(None);
+
+/// Phase of the digital sunset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SunsetPhase {
+    /// Outside the wind-down window or not started
+    #[default]
+    Idle,
+    /// Secondary displays are being disabled step by step
+    WindingDown,
+    /// Wind-down finished; waiting for the wake time
+    Done,
+}
+
+#[derive(Default)]
+struct SunsetState {
+    phase: SunsetPhase,
+    /// Layout captured before the wind-down started
+    saved_layout: Vec<Display>,
+    /// When the next display should be disabled
+    next_step: Option<Instant>,
+    /// Wind-down is postponed until this instant
+    snoozed_until: Option<Instant>,
+    /// User ended the wind-down; don't restart until the window closes
+    dismissed: bool,
+}
+
+/// Sunset status reported to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct SunsetStatus {
+    /// Current phase
+    pub phase: SunsetPhase,
+    /// Seconds remaining on an active snooze
+    pub snoozed_for_secs: Option<u64>,
+}
+
+static SUNSET: OnceLock<Mutex<SunsetState>> = OnceLock::new();
+
+fn sunset() -> &'static Mutex<SunsetState> {
+    SUNSET.get_or_init(|| Mutex::new(SunsetState::default()))
+}
+
+/// Start the scheduler thread.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        // Another user owns the displays; leave them alone
+        if session::is_active() {
+            tick_schedules(&app);
+            tick_sunset(&app);
+        }
+        std::thread::sleep(TICK);
+    });
+    log::info!("[Scheduler] Scheduler started");
+}
+
+/// Whether `time` falls in the window from `start` to `end`, which may cross midnight.
+fn in_window(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// Parse an "HH:MM" setting.
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+fn get_schedules_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("schedules.json"))
+}
+
+fn read_schedules() -> Result<ScheduleStore, String> {
+    let path = get_schedules_path()?;
+
+    if !path.exists() {
+        return Ok(ScheduleStore::default());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read schedules: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse schedules: {}", e))
+}
+
+fn write_schedules(store: &ScheduleStore) -> Result<(), String> {
+    let path = get_schedules_path()?;
+
+    let content =
+        serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize schedules: {}", e))?;
+
+    write_atomic(&path, content.as_bytes()).map_err(|e| format!("Failed to write schedules: {}", e))
+}
+
+/// Whether a schedule's time fell after `last` and no later than `now`, on one
+/// of its days and recently enough to still apply.
+fn is_due(schedule: &Schedule, last: NaiveDateTime, now: NaiveDateTime) -> bool {
+    let Some(time) = parse_time(&schedule.time) else {
+        return false;
+    };
+
+    // The window spans at most a day boundary between two ticks
+    [now.date(), now.date() - chrono::Duration::days(1)].into_iter().any(|date| {
+        let at = date.and_time(time);
+        at > last
+            && at <= now
+            && now - at <= MAX_SCHEDULE_LATENESS
+            && (schedule.days.is_empty() || schedule.days.contains(&date.weekday()))
+    })
+}
+
+/// Apply the presets of schedules whose time has come since the last tick.
+fn tick_schedules(app: &AppHandle) {
+    let now = Local::now().naive_local();
+    let last = match LAST_SCHEDULE_CHECK.lock() {
+        Ok(mut last) => last.replace(now),
+        Err(_) => return,
+    };
+    // The first tick only marks where checking starts
+    let Some(last) = last else {
+        return;
+    };
+
+    let store = match read_schedules() {
+        Ok(store) => store,
+        Err(e) => {
+            log::error!("[Schedule] {}", e);
+            return;
+        }
+    };
+
+    for schedule in store.schedules.iter().filter(|s| s.enabled && is_due(s, last, now)) {
+        let Some(preset) = app.state::<AppState>().find_preset(&schedule.preset_id) else {
+            log::error!("[Schedule] Preset {} not found", schedule.preset_id);
+            continue;
+        };
+
+        log::info!("[Schedule] Applying {} scheduled for {}", preset.name, schedule.time);
+        match tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::Automation)) {
+            Ok(_) => show_tray_notification("DPUI", &format!("Applied: {}", preset.name)),
+            Err(e) => {
+                log::error!("[Schedule] Failed to apply {}: {}", preset.name, e);
+                show_tray_notification("DPUI", &format!("Failed to apply {}: {}", preset.name, e));
+            }
+        }
+    }
+}
+
+/// List scheduled presets
+#[tauri::command]
+pub async fn list_schedules() -> Result<ScheduleStore, String> {
+    read_schedules()
+}
+
+/// Schedule a preset for a time of day
+///
+/// # Arguments
+/// * `schedule` - Schedule to add; its ID and creation time are assigned here
+///
+/// # Returns
+/// * `Ok(Schedule)` - The stored schedule
+/// * `Err(String)` - Error message if the time or preset is invalid or the schedule can't be saved
+#[tauri::command]
+pub async fn add_schedule(app: AppHandle, mut schedule: Schedule) -> Result<Schedule, String> {
+    if parse_time(&schedule.time).is_none() {
+        return Err(format!("Invalid time \"{}\", expected HH:MM", schedule.time));
+    }
+    if app.state::<AppState>().find_preset(&schedule.preset_id).is_none() {
+        return Err(format!("Preset {} not found", schedule.preset_id));
+    }
+
+    let mut store = read_schedules()?;
+    schedule.id = uuid::Uuid::new_v4().to_string();
+    schedule.created_at = chrono::Utc::now().to_rfc3339();
+
+    store.schedules.push(schedule.clone());
+    write_schedules(&store)?;
+
+    log::info!("[Schedule] Scheduled preset {} at {}", schedule.preset_id, schedule.time);
+    Ok(schedule)
+}
+
+/// Delete a scheduled preset
+#[tauri::command]
+pub async fn delete_schedule(id: String) -> Result<(), String> {
+    let mut store = read_schedules()?;
+
+    store.schedules.retain(|s| s.id != id);
+    write_schedules(&store)
+}
+
+/// Advance the digital sunset state machine.
+fn tick_sunset(app: &AppHandle) {
+    let config = settings::current().sunset;
+    let Ok(mut state) = sunset().lock() else {
+        return;
+    };
+
+    let (Some(start), Some(wake)) = (parse_time(&config.start), parse_time(&config.wake)) else {
+        return;
+    };
+    let active = config.enabled && in_window(Local::now().time(), start, wake);
+
+    if !active {
+        state.dismissed = false;
+        state.snoozed_until = None;
+        if state.phase != SunsetPhase::Idle {
+            restore_layout(app, &mut state);
+            notify(app, "Good morning — displays restored");
+        }
+        return;
+    }
+
+    if state.dismissed || state.snoozed_until.is_some_and(|until| Instant::now() < until) {
+        return;
+    }
+
+    match state.phase {
+        SunsetPhase::Idle => begin_wind_down(app, &config, &mut state),
+        SunsetPhase::WindingDown => {
+            if state.next_step.is_some_and(|at| Instant::now() >= at) {
+                step_wind_down(app, &config, &mut state);
+            }
+        }
+        SunsetPhase::Done => {}
+    }
+}
+
+/// Capture the current layout and start winding down.
+fn begin_wind_down(app: &AppHandle, config: &SunsetSettings, state: &mut SunsetState) {
+    let current = match tauri::async_runtime::block_on(get_displays()) {
+        Ok(current) => current,
+        Err(e) => {
+            log::error!("[Sunset] Failed to read displays: {}", e);
+            return;
+        }
+    };
+    state.saved_layout = current.displays;
+    state.snoozed_until = None;
+
+    if let Some(preset_id) = &config.preset_id {
+        let preset = app.state::<AppState>().find_preset(preset_id);
+
+        match preset {
+            Some(preset) => match tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::Preset)) {
+                Ok(_) => notify(app, &format!("Winding down: applied {}", preset.name)),
+                Err(e) => log::error!("[Sunset] Failed to apply wind-down preset: {}", e),
+            },
+            None => log::error!("[Sunset] Wind-down preset {} not found", preset_id),
+        }
+        state.phase = SunsetPhase::Done;
+        return;
+    }
+
+    state.phase = SunsetPhase::WindingDown;
+    notify(
+        app,
+        &format!("Winding down secondary displays every {} minutes — snooze to postpone", config.step_minutes),
+    );
+    step_wind_down(app, config, state);
+}
+
+/// Disable the next enabled secondary display.
+fn step_wind_down(app: &AppHandle, config: &SunsetSettings, state: &mut SunsetState) {
+    let current = match tauri::async_runtime::block_on(get_displays()) {
+        Ok(current) => current,
+        Err(e) => {
+            log::error!("[Sunset] Failed to read displays: {}", e);
+            return;
+        }
+    };
+
+    // The main display sits at the origin; turn off the farthest secondary first
+    let next = current
+        .displays
+        .iter()
+        .filter(|d| d.enabled && d.origin != (0, 0))
+        .max_by_key(|d| d.origin.0.abs() + d.origin.1.abs());
+
+    let Some(display) = next else {
+        state.phase = SunsetPhase::Done;
+        state.next_step = None;
+        return;
+    };
+
+    match execute_displayplacer(vec![format!("id:{} enabled:false", display.id)]) {
+        Ok(_) => {
+            undo::changed(None);
+            let name = display.name.clone().unwrap_or_else(|| display.id.clone());
+            log::info!("[Sunset] Disabled display {}", name);
+            let _ = app.emit("sunset-step", &display.id);
+        }
+        Err(e) => log::error!("[Sunset] Failed to disable display {}: {}", display.id, e),
+    }
+
+    state.next_step = Some(Instant::now() + Duration::from_secs(config.step_minutes * 60));
+}
+
+/// Restore the layout captured before the wind-down.
+fn restore_layout(app: &AppHandle, state: &mut SunsetState) {
+    if !state.saved_layout.is_empty() {
+        match execute_displayplacer(display_args(&state.saved_layout)) {
+            Ok(_) => {
+                undo::changed(None);
+                let _ = app.emit("sunset-ended", ());
+            }
+            Err(e) => log::error!("[Sunset] Failed to restore layout: {}", e),
+        }
+    }
+
+    state.phase = SunsetPhase::Idle;
+    state.saved_layout.clear();
+    state.next_step = None;
+}
+
+/// Show a sunset notification and forward it to the frontend.
+fn notify(app: &AppHandle, message: &str) {
+    show_tray_notification("Digital Sunset", message);
+    let _ = app.emit("sunset-notice", message);
+}
+
+/// Get the digital sunset status.
+#[tauri::command]
+pub async fn get_sunset_status() -> Result<SunsetStatus, String> {
+    let state = sunset().lock().map_err(|_| "Sunset state poisoned".to_string())?;
+
+    Ok(SunsetStatus {
+        phase: state.phase.clone(),
+        snoozed_for_secs: state
+            .snoozed_until
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .map(|d| d.as_secs()),
+    })
+}
+
+/// Postpone the wind-down, re-enabling any displays it already turned off.
+///
+/// # Arguments
+/// * `minutes` - Snooze length; defaults to the configured snooze length
+#[tauri::command]
+pub async fn snooze_sunset(app: AppHandle, minutes: Option<u64>) -> Result<(), String> {
+    let minutes = minutes.unwrap_or(settings::current().sunset.snooze_minutes);
+    let mut state = sunset().lock().map_err(|_| "Sunset state poisoned".to_string())?;
+
+    if state.phase != SunsetPhase::Idle {
+        restore_layout(&app, &mut state);
+    }
+    state.snoozed_until = Some(Instant::now() + Duration::from_secs(minutes * 60));
+
+    log::info!("[Sunset] Snoozed for {} minutes", minutes);
+    Ok(())
+}
+
+/// End the wind-down now and re-enable displays until the next evening.
+#[tauri::command]
+pub async fn end_sunset(app: AppHandle) -> Result<(), String> {
+    let mut state = sunset().lock().map_err(|_| "Sunset state poisoned".to_string())?;
+
+    if state.phase != SunsetPhase::Idle {
+        restore_layout(&app, &mut state);
+    }
+    state.dismissed = true;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_window_crossing_midnight() {
+        let t = |s| parse_time(s).unwrap();
+
+        assert!(in_window(t("23:00"), t("22:00"), t("07:00")));
+        assert!(in_window(t("06:59"), t("22:00"), t("07:00")));
+        assert!(!in_window(t("07:00"), t("22:00"), t("07:00")));
+        assert!(!in_window(t("12:00"), t("22:00"), t("07:00")));
+        assert!(in_window(t("13:00"), t("12:00"), t("14:00")));
+    }
+
+    #[test]
+    fn test_schedule_is_due() {
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let schedule = |days: Vec<Weekday>| Schedule {
+            id: "s".to_string(),
+            preset_id: "p".to_string(),
+            time: "19:00".to_string(),
+            days,
+            enabled: true,
+            created_at: String::new(),
+        };
+
+        // 2024-01-05 is a Friday
+        let every_day = schedule(Vec::new());
+        assert!(is_due(&every_day, at("2024-01-05 18:59:50"), at("2024-01-05 19:00:05")));
+        assert!(!is_due(&every_day, at("2024-01-05 19:00:05"), at("2024-01-05 19:00:20")));
+        // Slept through it
+        assert!(!is_due(&every_day, at("2024-01-05 18:00:00"), at("2024-01-05 19:30:00")));
+
+        let weekdays = schedule(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]);
+        assert!(is_due(&weekdays, at("2024-01-05 18:59:50"), at("2024-01-05 19:00:05")));
+        assert!(!is_due(&weekdays, at("2024-01-06 18:59:50"), at("2024-01-06 19:00:05")));
+
+        let midnight = Schedule { time: "00:00".to_string(), ..every_day };
+        assert!(is_due(&midnight, at("2024-01-05 23:59:55"), at("2024-01-06 00:00:10")));
+    }
+}
@@ -0,0 +1,137 @@
+//! AppleScript support.
+//!
+//! `DPUI.sdef` (bundled as the app's scripting definition) declares three
+//! commands, each handled by an `NSScriptCommand` subclass registered here:
+//!
+//! ```applescript
+//! tell application "DPUI" to apply preset "Office"
+//! tell application "DPUI" to toggle display "37D8832A-2D66-02CA-B9F7-8F30A301B230"
+//! tell application "DPUI" to current layout
+//! ```
+//!
+//! Applying and toggling run in the background like their `dpui://` URL
+//! counterparts; an unknown preset is reported back to the script.
+
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+use crate::displayplacer::{layout_command, read_displays};
+use crate::presets::find_preset_by_key;
+use crate::state::AppState;
+use crate::url_scheme::{run_command, UrlCommand};
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// `errAEEventFailed`, reported to scripts when a command fails
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const SCRIPT_ERROR: i64 = -10000;
+
+/// Register the command classes named in `DPUI.sdef`.
+///
+/// Must run before the first Apple event arrives, i.e. in setup.
+///
+/// # Returns
+/// * `Ok(())` - Commands registered
+/// * `Err(String)` - Error message if the classes could not be created
+pub fn start(app: AppHandle) -> Result<(), String> {
+    APP.set(app).map_err(|_| "Scripting already started".to_string())?;
+    ns::register_commands()?;
+
+    log::info!("[Scripting] AppleScript commands registered");
+    Ok(())
+}
+
+/// `apply preset "<name or ID>"`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn apply_preset(key: String) -> Result<Option<String>, String> {
+    let app = APP.get().ok_or("DPUI is still starting")?;
+    let preset = find_preset_by_key(&app.state::<AppState>().presets(), &key)
+        .cloned()
+        .ok_or_else(|| format!("Preset {} not found", key))?;
+
+    log::info!("[Scripting] Applying {}", preset.name);
+    run_command(app, UrlCommand::Apply(preset.id));
+    Ok(None)
+}
+
+/// `toggle display "<ID>"`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn toggle_display(display_id: String) -> Result<Option<String>, String> {
+    let app = APP.get().ok_or("DPUI is still starting")?;
+    run_command(app, UrlCommand::Toggle(display_id));
+    Ok(None)
+}
+
+/// `current layout`, as a displayplacer command.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn current_layout(_argument: String) -> Result<Option<String>, String> {
+    Ok(Some(layout_command(&read_displays()?)))
+}
+
+#[cfg(target_os = "macos")]
+mod ns {
+    use std::ffi::{c_void, CStr};
+
+    use crate::objc::{self, Id, Sel};
+
+    /// A scripting command: direct parameter in, optional text result out
+    type Handler = fn(String) -> Result<Option<String>, String>;
+
+    /// Run a handler for a command and translate its result for Cocoa scripting.
+    unsafe fn perform(command: Id, handler: Handler) -> Id {
+        let parameter = objc::to_string(objc::send(command, c"directParameter")).unwrap_or_default();
+        match handler(parameter) {
+            Ok(Some(result)) => objc::ns_string(&result),
+            Ok(None) => std::ptr::null_mut(),
+            Err(e) => {
+                log::error!("[Scripting] {}", e);
+                let set_number: unsafe extern "C" fn(Id, Sel, isize) = objc::msg_send();
+                let set_string: unsafe extern "C" fn(Id, Sel, Id) = objc::msg_send();
+                set_number(command, objc::sel(c"setScriptErrorNumber:"), super::SCRIPT_ERROR as isize);
+                set_string(command, objc::sel(c"setScriptErrorString:"), objc::ns_string(&e));
+                std::ptr::null_mut()
+            }
+        }
+    }
+
+    /// `-[DPUIApplyPresetCommand performDefaultImplementation]`
+    extern "C" fn perform_apply_preset(this: Id, _cmd: Sel) -> Id {
+        unsafe { perform(this, super::apply_preset) }
+    }
+
+    /// `-[DPUIToggleDisplayCommand performDefaultImplementation]`
+    extern "C" fn perform_toggle_display(this: Id, _cmd: Sel) -> Id {
+        unsafe { perform(this, super::toggle_display) }
+    }
+
+    /// `-[DPUICurrentLayoutCommand performDefaultImplementation]`
+    extern "C" fn perform_current_layout(this: Id, _cmd: Sel) -> Id {
+        unsafe { perform(this, super::current_layout) }
+    }
+
+    pub fn register_commands() -> Result<(), String> {
+        let commands: [(&CStr, extern "C" fn(Id, Sel) -> Id); 3] = [
+            (c"DPUIApplyPresetCommand", perform_apply_preset),
+            (c"DPUIToggleDisplayCommand", perform_toggle_display),
+            (c"DPUICurrentLayoutCommand", perform_current_layout),
+        ];
+
+        // Cocoa scripting is part of Foundation
+        for (name, implementation) in commands {
+            objc::define_class(
+                c"NSScriptCommand",
+                name,
+                &[(c"performDefaultImplementation", implementation as *const c_void, c"@@:")],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod ns {
+    pub fn register_commands() -> Result<(), String> {
+        Err("AppleScript is only available on macOS".to_string())
+    }
+}
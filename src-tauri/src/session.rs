@@ -0,0 +1,117 @@
+//! Fast user switching awareness.
+//!
+//! When another macOS user becomes active, a second DPUI instance in their
+//! session would otherwise fight this one over displayplacer. This module polls
+//! the console owner; while our session is inactive, hotkeys are suspended,
+//! background automation checks `is_active()` and holds off, and the remote,
+//! socket and Stream Deck servers turn requests away. On return the
+//! hotkeys are re-registered and the layout captured at switch-out is
+//! re-applied if it drifted while we were away.
+
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::displayplacer::{display_args, execute_displayplacer, get_displays, Display};
+use crate::hotkeys;
+use crate::undo;
+
+/// How often the console owner is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Error servers answer with while the session is inactive
+pub const INACTIVE_ERROR: &str = "Paused while another user's session is active";
+
+static ACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// Layout captured when the session became inactive
+static SAVED_LAYOUT: Mutex<Vec<Display>> = Mutex::new(Vec::new());
+
+/// Whether our login session currently owns the console.
+///
+/// Background tasks should skip work while this is false.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Start polling for session switches.
+pub fn start(app: AppHandle) {
+    let Some(user) = std::env::var("USER").ok().filter(|u| !u.is_empty()) else {
+        log::error!("[Session] USER is not set; fast user switching detection disabled");
+        return;
+    };
+
+    std::thread::spawn(move || loop {
+        if let Some(owner) = console_owner() {
+            let active = owner == user;
+            if active != is_active() {
+                on_session_change(&app, active);
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+/// Get the user that owns the console (the active GUI session).
+fn console_owner() -> Option<String> {
+    let output = Command::new("stat").args(["-f", "%Su", "/dev/console"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let owner = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!owner.is_empty()).then_some(owner)
+}
+
+/// Pause or resume everything that touches the displays.
+fn on_session_change(app: &AppHandle, active: bool) {
+    if active {
+        log::info!("[Session] Session became active, resuming");
+        ACTIVE.store(true, Ordering::SeqCst);
+        hotkeys::resume_all(app);
+        restore_layout_if_drifted();
+    } else {
+        log::info!("[Session] Session became inactive, pausing");
+        if let Ok(current) = tauri::async_runtime::block_on(get_displays()) {
+            if let Ok(mut saved) = SAVED_LAYOUT.lock() {
+                *saved = current.displays;
+            }
+        }
+        ACTIVE.store(false, Ordering::SeqCst);
+        if let Err(e) = hotkeys::suspend_all(app) {
+            log::error!("[Session] {}", e);
+        }
+    }
+
+    let _ = app.emit("session-changed", active);
+}
+
+/// Re-apply the layout captured at switch-out if the other session changed it.
+fn restore_layout_if_drifted() {
+    let saved = SAVED_LAYOUT.lock().map(|s| s.clone()).unwrap_or_default();
+    if saved.is_empty() {
+        return;
+    }
+
+    let current = match tauri::async_runtime::block_on(get_displays()) {
+        Ok(current) => current.displays,
+        Err(e) => {
+            log::error!("[Session] Failed to verify layout: {}", e);
+            return;
+        }
+    };
+
+    if display_args(&current) == display_args(&saved) {
+        return;
+    }
+
+    match execute_displayplacer(display_args(&saved)) {
+        Ok(_) => {
+            undo::changed(None);
+            log::info!("[Session] Restored layout changed while away");
+        }
+        Err(e) => log::error!("[Session] Failed to restore layout: {}", e),
+    }
+}
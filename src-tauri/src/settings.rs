@@ -0,0 +1,550 @@
+//! Application-level settings.
+//!
+//! Settings are stored in `settings.json` in the config directory and cached
+//! in memory so that hot paths (such as spawning displayplacer) can consult
+//! them without touching the disk. Writes are atomic and keep one backup
+//! generation; a corrupt file is moved aside and replaced by the backup or
+//! defaults instead of failing startup.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use crate::atomic_file::{backup_path, quarantine, write_atomic};
+use crate::{displayplacer, logging};
+use crate::dock::DockBinding;
+use crate::presets::get_config_dir;
+use crate::socket::SocketMethod;
+use crate::storage::StorageBackend;
+use crate::virtual_displays::VirtualDisplaySpec;
+use crate::window_state::WindowGeometry;
+
+/// Persisted application settings.
+///
+/// Unknown or missing fields fall back to their defaults so older files keep loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Record every spawned command, its output and parser decisions to the trace file
+    pub developer_mode: bool,
+    /// Treat displays that vanish and return within the KVM window as a KVM switch
+    pub kvm_mode: bool,
+    /// How long vanished displays are remembered in KVM mode, in seconds
+    pub kvm_window_secs: u64,
+    /// Seconds to confirm a newly applied layout before it is reverted; 0 disables
+    pub confirm_timeout_secs: u64,
+    /// Seconds before a hung displayplacer process is killed
+    pub command_timeout_secs: u64,
+    /// How long a display list is reused before displayplacer is asked again, in milliseconds; 0 disables
+    pub display_cache_ttl_ms: u64,
+    /// Longest interval between display checks when change callbacks are unavailable, in seconds
+    pub poll_interval_secs: u64,
+    /// Apply the preset made for the connected displays when a display is plugged in or removed
+    pub auto_apply_on_hotplug: bool,
+    /// Re-apply the last applied preset after waking from sleep if the layout drifted from it
+    pub reapply_after_wake: bool,
+    /// Move the other displays to close the gap a turned-off display leaves, and back when it is turned on
+    pub close_gaps_on_toggle: bool,
+    /// Show notifications for applied presets and errors
+    pub notifications_enabled: bool,
+    /// How much DPUI writes to its log file: "off", "error", "warn", "info", "debug" or "trace"
+    pub log_level: String,
+    /// Apply the preset mapped to a Wi-Fi network when the Mac joins it
+    pub wifi_triggers_enabled: bool,
+    /// Preset ID to apply per Wi-Fi network name (SSID)
+    pub wifi_presets: BTreeMap<String, String>,
+    /// Docks, matched by USB/Thunderbolt device, with the presets applied when they come and go
+    pub docks: Vec<DockBinding>,
+    /// Full path to the displayplacer binary; PATH and the Homebrew directories are searched if unset
+    pub displayplacer_path: Option<String>,
+    /// Scheduled wind-down of secondary displays
+    pub sunset: SunsetSettings,
+    /// Control from paired devices on the local network
+    pub remote: RemoteSettings,
+    /// JSON-RPC control socket for scripts and the CLI
+    pub socket: SocketSettings,
+    /// Localhost endpoint for the Stream Deck plugin
+    pub stream_deck: StreamDeckSettings,
+    /// Keep the main window above other apps' windows
+    pub always_on_top: bool,
+    /// Last position and size of the main and per-display windows, by window label.
+    /// Only changed through `store_window_geometry`, when windows close.
+    pub window_geometry: BTreeMap<String, WindowGeometry>,
+    /// Directory holding presets.json; the config directory if unset.
+    /// Only changed through `set_storage_path`, which migrates the file.
+    pub storage_path: Option<String>,
+    /// Where presets, hotkeys and history are kept.
+    /// Only changed through `set_storage_backend`, which copies the data.
+    pub storage_backend: StorageBackend,
+    /// Virtual displays recreated at startup.
+    /// Only changed through `create_virtual_display` and `remove_virtual_display`.
+    pub virtual_displays: Vec<VirtualDisplaySpec>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            developer_mode: false,
+            kvm_mode: false,
+            kvm_window_secs: 30,
+            confirm_timeout_secs: 15,
+            command_timeout_secs: 10,
+            display_cache_ttl_ms: 1000,
+            poll_interval_secs: 30,
+            auto_apply_on_hotplug: false,
+            reapply_after_wake: false,
+            close_gaps_on_toggle: false,
+            notifications_enabled: true,
+            log_level: "info".to_string(),
+            wifi_triggers_enabled: true,
+            wifi_presets: BTreeMap::new(),
+            docks: Vec::new(),
+            displayplacer_path: None,
+            sunset: SunsetSettings::default(),
+            remote: RemoteSettings::default(),
+            socket: SocketSettings::default(),
+            stream_deck: StreamDeckSettings::default(),
+            always_on_top: false,
+            window_geometry: BTreeMap::new(),
+            storage_path: None,
+            storage_backend: StorageBackend::default(),
+            virtual_displays: Vec::new(),
+        }
+    }
+}
+
+/// Settings for the digital sunset wind-down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SunsetSettings {
+    /// Whether the wind-down runs at all
+    pub enabled: bool,
+    /// Local time the wind-down starts, "HH:MM"
+    pub start: String,
+    /// Local time displays are re-enabled, "HH:MM"
+    pub wake: String,
+    /// Minutes between disabling successive secondary displays
+    pub step_minutes: u64,
+    /// Minutes a snooze postpones the wind-down
+    pub snooze_minutes: u64,
+    /// Preset to apply instead of disabling displays one by one
+    pub preset_id: Option<String>,
+}
+
+impl Default for SunsetSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            wake: "07:00".to_string(),
+            step_minutes: 10,
+            snooze_minutes: 15,
+            preset_id: None,
+        }
+    }
+}
+
+/// Settings for the remote control server.
+///
+/// Changes take effect on the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteSettings {
+    /// Whether to listen for paired devices and advertise over Bonjour
+    pub enabled: bool,
+    /// TCP port the server listens on
+    pub port: u16,
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 48620,
+        }
+    }
+}
+
+/// Settings for the JSON-RPC control socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SocketSettings {
+    /// Whether to listen on `dpui.sock` in the config directory
+    pub enabled: bool,
+    /// Methods clients may call; others are refused
+    pub allowed_methods: Vec<SocketMethod>,
+}
+
+impl Default for SocketSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_methods: SocketMethod::defaults(),
+        }
+    }
+}
+
+/// Settings for the Stream Deck endpoint.
+///
+/// Changes take effect on the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamDeckSettings {
+    /// Whether to serve the endpoint on localhost
+    pub enabled: bool,
+    /// TCP port on 127.0.0.1
+    pub port: u16,
+    /// Token the plugin sends as `X-DPUI-Token`; generated on first start
+    pub token: String,
+}
+
+impl Default for StreamDeckSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 48621,
+            token: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Check values that serde cannot validate on its own.
+    fn validate(&self) -> Result<(), String> {
+        for time in [&self.sunset.start, &self.sunset.wake] {
+            chrono::NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| format!("Invalid time \"{}\", expected HH:MM", time))?;
+        }
+        if self.command_timeout_secs == 0 {
+            return Err("Command timeout must be at least 1 second".to_string());
+        }
+        if self.poll_interval_secs < 2 {
+            return Err("Polling interval must be at least 2 seconds".to_string());
+        }
+        logging::validate_level(&self.log_level)?;
+        for dock in &self.docks {
+            dock.validate()?;
+        }
+        if let Some(path) = self.displayplacer_path.as_deref().filter(|p| !p.trim().is_empty()) {
+            displayplacer::validate_binary_path(path)?;
+        }
+        Ok(())
+    }
+}
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+
+/// Message describing a recovery performed while loading, shown once at startup
+static LOAD_NOTICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Get the path to the settings file
+pub(crate) fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("settings.json"))
+}
+
+/// Parse a settings file, rejecting values `update_settings` would refuse.
+fn parse_settings_file(path: &Path) -> Result<Settings, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read settings: {}", e))?;
+
+    let settings: Settings =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))?;
+    settings.validate().map_err(|e| format!("Invalid settings: {}", e))?;
+    Ok(settings)
+}
+
+/// Load settings from disk, recovering from a corrupt file.
+///
+/// Returns defaults if the file does not exist. If it cannot be parsed or
+/// holds invalid values, the corrupt file is moved aside and the backup
+/// generation is used if valid, otherwise defaults. The second value describes
+/// any recovery performed.
+fn load_settings() -> (Settings, Option<String>) {
+    match get_settings_path() {
+        Ok(path) => load_settings_from(&path),
+        Err(e) => (Settings::default(), Some(format!("Settings could not be loaded ({}); using defaults", e))),
+    }
+}
+
+/// [`load_settings`] for the settings file at `path`.
+fn load_settings_from(path: &Path) -> (Settings, Option<String>) {
+    if !path.exists() {
+        return (Settings::default(), None);
+    }
+
+    let error = match parse_settings_file(path) {
+        Ok(settings) => return (settings, None),
+        Err(e) => e,
+    };
+    log::error!("[Settings] {}", error);
+
+    let saved = match quarantine(path) {
+        Ok(target) => format!("backup saved to {}", target.display()),
+        Err(e) => format!("backup could not be saved: {}", e),
+    };
+
+    match parse_settings_file(&backup_path(path)) {
+        Ok(settings) => {
+            // Promote the backup so the next launch starts from a valid file
+            if let Err(e) = write_settings_to(path, &settings) {
+                log::error!("[Settings] {}", e);
+            }
+            (settings, Some(format!("Settings were corrupt and restored from the previous version ({})", saved)))
+        }
+        Err(_) => (Settings::default(), Some(format!("Settings were reset ({})", saved))),
+    }
+}
+
+/// Write settings to disk atomically, keeping the previous file as a backup.
+fn write_settings_file(settings: &Settings) -> Result<(), String> {
+    write_settings_to(&get_settings_path()?, settings)
+}
+
+fn write_settings_to(path: &Path, settings: &Settings) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    write_atomic(path, content.as_bytes()).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// In-memory settings cache, loaded from disk on first access.
+fn cache() -> &'static RwLock<Settings> {
+    SETTINGS.get_or_init(|| {
+        let (settings, notice) = load_settings();
+        if let Some(notice) = notice {
+            log::error!("[Settings] {}", notice);
+            if let Ok(mut pending) = LOAD_NOTICE.lock() {
+                *pending = Some(notice);
+            }
+        }
+        RwLock::new(settings)
+    })
+}
+
+/// Load settings and take the recovery notice, if any.
+///
+/// Called once from the setup hook so a reset can be reported to the user.
+pub fn init() -> Option<String> {
+    cache();
+    LOAD_NOTICE.lock().ok().and_then(|mut pending| pending.take())
+}
+
+/// Get a snapshot of the current settings.
+pub fn current() -> Settings {
+    cache().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Get the current application settings
+#[tauri::command]
+pub async fn get_settings() -> Result<Settings, String> {
+    Ok(current())
+}
+
+/// Replace the application settings and persist them
+///
+/// # Arguments
+/// * `settings` - Complete settings object to store
+///
+/// # Returns
+/// * `Ok(Settings)` - The stored settings
+/// * `Err(String)` - Error message if the settings could not be written
+///
+/// `storage_path` and `storage_backend` are kept as-is; use `set_storage_path`
+/// and `set_storage_backend` to move the data. Window geometry and virtual
+/// displays are kept too.
+#[tauri::command]
+pub async fn update_settings(mut settings: Settings) -> Result<Settings, String> {
+    settings.validate()?;
+
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    settings.storage_path = cached.storage_path.clone();
+    settings.storage_backend = cached.storage_backend;
+    settings.window_geometry = cached.window_geometry.clone();
+    settings.virtual_displays = cached.virtual_displays.clone();
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+    logging::apply_level(&settings.log_level)?;
+
+    Ok(settings)
+}
+
+/// Persist a new log level.
+///
+/// Callers are responsible for applying it to the logger.
+pub(crate) fn store_log_level(level: String) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    let settings = Settings {
+        log_level: level,
+        ..cached.clone()
+    };
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+/// Persist a newly generated Stream Deck token.
+pub(crate) fn store_stream_deck_token(token: String) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    let mut settings = cached.clone();
+    settings.stream_deck.token = token;
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+/// Persist whether the main window stays on top.
+///
+/// Callers are responsible for applying it to the window.
+pub(crate) fn store_always_on_top(enabled: bool) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    let settings = Settings {
+        always_on_top: enabled,
+        ..cached.clone()
+    };
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+/// Persist the geometry of a window.
+pub(crate) fn store_window_geometry(label: String, geometry: WindowGeometry) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    if cached.window_geometry.get(&label) == Some(&geometry) {
+        return Ok(cached.clone());
+    }
+    let mut settings = cached.clone();
+    settings.window_geometry.insert(label, geometry);
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+/// Persist the list of virtual displays.
+pub(crate) fn store_virtual_displays(specs: Vec<VirtualDisplaySpec>) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    let settings = Settings {
+        virtual_displays: specs,
+        ..cached.clone()
+    };
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+/// Persist a new preset storage directory.
+///
+/// Callers are responsible for migrating the presets file first.
+pub(crate) fn store_storage_path(path: Option<String>) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    let settings = Settings {
+        storage_path: path,
+        ..cached.clone()
+    };
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+/// Persist a new storage backend, used from the next launch.
+///
+/// Callers are responsible for copying the data first.
+pub(crate) fn store_storage_backend(backend: StorageBackend) -> Result<Settings, String> {
+    let mut cached = cache().write().map_err(|_| "Settings lock poisoned".to_string())?;
+    let settings = Settings {
+        storage_backend: backend,
+        ..cached.clone()
+    };
+    write_settings_file(&settings)?;
+    *cached = settings.clone();
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test's settings files.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dpui-settings-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Files in `dir` moved aside by `quarantine`.
+    fn quarantined(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(".corrupt-"))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = test_dir("missing");
+        let path = dir.join("settings.json");
+
+        let (settings, notice) = load_settings_from(&path);
+        assert!(notice.is_none());
+        assert!(!settings.developer_mode);
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_restores_backup() {
+        let dir = test_dir("backup");
+        let path = dir.join("settings.json");
+        fs::write(&path, "{ not json").unwrap();
+        fs::write(backup_path(&path), r#"{"developer_mode": true}"#).unwrap();
+
+        let (settings, notice) = load_settings_from(&path);
+        assert!(settings.developer_mode);
+        assert!(notice.unwrap().contains("restored from the previous version"));
+        assert_eq!(quarantined(&dir).len(), 1);
+        // The backup is promoted so the next launch reads a valid file
+        assert!(parse_settings_file(&path).unwrap().developer_mode);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_values() {
+        let dir = test_dir("invalid");
+        let path = dir.join("settings.json");
+        fs::write(&path, r#"{"developer_mode": true, "log_level": "loudest"}"#).unwrap();
+
+        let (settings, notice) = load_settings_from(&path);
+        assert!(!settings.developer_mode, "well-formed but invalid files are not used");
+        assert!(notice.unwrap().starts_with("Settings were reset"));
+        assert_eq!(quarantined(&dir).len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_resets_without_valid_backup() {
+        let dir = test_dir("reset");
+        let path = dir.join("settings.json");
+        fs::write(&path, "{ not json").unwrap();
+        fs::write(backup_path(&path), "also not json").unwrap();
+
+        let (settings, notice) = load_settings_from(&path);
+        assert!(!settings.developer_mode);
+        assert!(notice.unwrap().starts_with("Settings were reset"));
+        assert_eq!(quarantined(&dir).len(), 1);
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+//! Setup checks behind the "Setup Assistant…" tray item.
+//!
+//! Re-evaluates everything onboarding walks through — displayplacer, macOS
+//! permissions, launch at login and hotkey health — so the UI can jump
+//! straight to whichever step is failing. A missing displayplacer can be
+//! installed from here through Homebrew.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::displayplacer::{
+    displayplacer_binary, displayplacer_version, invalidate_display_cache, run_blocking, DisplayplacerVersion,
+    HOMEBREW_BIN_DIRS,
+};
+use crate::hotkeys;
+use crate::state::AppState;
+
+/// Onboarding step a check belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    Displayplacer,
+    Permissions,
+    LaunchAtLogin,
+    Hotkeys,
+}
+
+/// Result of a single setup check.
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupCheck {
+    pub step: SetupStep,
+    pub ok: bool,
+    /// What was found, or how to fix it
+    pub detail: String,
+}
+
+/// Result of all setup checks, in onboarding order.
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupReport {
+    pub checks: Vec<SetupCheck>,
+    /// First failing step, for the UI to deep-link to
+    pub first_failing: Option<SetupStep>,
+}
+
+/// Check that displayplacer is installed and runs.
+///
+/// Blocks for up to `command_timeout_secs` the first time a binary is probed.
+fn check_displayplacer() -> SetupCheck {
+    let binary = match displayplacer_binary() {
+        Ok(binary) => binary,
+        Err(e) => return SetupCheck { step: SetupStep::Displayplacer, ok: false, detail: e },
+    };
+
+    let (ok, detail) = match displayplacer_version() {
+        Some(DisplayplacerVersion(major, minor, patch)) => {
+            (true, format!("displayplacer v{}.{}.{} ({})", major, minor, patch, binary.display()))
+        }
+        None => (false, format!("{} did not run or report its version", binary.display())),
+    };
+
+    SetupCheck { step: SetupStep::Displayplacer, ok, detail }
+}
+
+/// Check that the app is trusted for Accessibility, which global shortcuts need
+/// to work reliably while other apps are focused.
+fn check_permissions() -> SetupCheck {
+    let ok = ax::is_process_trusted();
+    let detail = if ok {
+        "Accessibility access granted".to_string()
+    } else {
+        "Grant Accessibility access in System Settings → Privacy & Security → Accessibility".to_string()
+    };
+
+    SetupCheck { step: SetupStep::Permissions, ok, detail }
+}
+
+/// Check whether the app is registered as a login item.
+fn check_launch_at_login(app_name: &str) -> SetupCheck {
+    let output = Command::new("osascript")
+        .args(["-e", "tell application \"System Events\" to get the name of every login item"])
+        .output();
+
+    let (ok, detail) = match output {
+        Ok(output) if output.status.success() => {
+            let items = String::from_utf8_lossy(&output.stdout).to_string();
+            if items.split(',').any(|item| item.trim().eq_ignore_ascii_case(app_name)) {
+                (true, format!("{} opens at login", app_name))
+            } else {
+                (false, format!("Add {} in System Settings → General → Login Items", app_name))
+            }
+        }
+        Ok(output) => (false, String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        Err(e) => (false, format!("Failed to query login items: {}", e)),
+    };
+
+    SetupCheck { step: SetupStep::LaunchAtLogin, ok, detail }
+}
+
+/// Check that every preset hotkey is registered with the system.
+async fn check_hotkeys(app: &AppHandle) -> SetupCheck {
+    let mut problems = Vec::new();
+
+    for preset in &app.state::<AppState>().presets().presets {
+        let Some(shortcut) = &preset.hotkey else {
+            continue;
+        };
+        if !hotkeys::is_active(app, shortcut) {
+            problems.push(format!("{} ({})", shortcut, preset.name));
+        }
+    }
+
+    let ok = problems.is_empty();
+    let detail = if ok {
+        "All preset hotkeys are registered".to_string()
+    } else {
+        format!("Not registered: {}", problems.join(", "))
+    };
+
+    SetupCheck { step: SetupStep::Hotkeys, ok, detail }
+}
+
+/// Re-run every onboarding check.
+///
+/// # Returns
+/// * `Ok(SetupReport)` - Result of each check and the first failing step
+#[tauri::command]
+pub async fn run_setup_checks(app: AppHandle) -> Result<SetupReport, String> {
+    let app_name = app.package_info().name.clone();
+
+    let checks = vec![
+        run_blocking(|| Ok(check_displayplacer())).await?,
+        check_permissions(),
+        check_launch_at_login(&app_name),
+        check_hotkeys(&app).await,
+    ];
+    let first_failing = checks.iter().find(|c| !c.ok).map(|c| c.step);
+
+    Ok(SetupReport { checks, first_failing })
+}
+
+/// Event carrying each line of `brew install` output
+const INSTALL_PROGRESS_EVENT: &str = "displayplacer-install-progress";
+
+static INSTALLING: AtomicBool = AtomicBool::new(false);
+
+/// One line of output from the Homebrew installation.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallProgress {
+    pub line: String,
+    /// Whether the line was written to stderr (Homebrew also reports progress there)
+    pub stderr: bool,
+}
+
+/// Homebrew's `brew` binary, if Homebrew is installed.
+fn find_brew() -> Option<PathBuf> {
+    HOMEBREW_BIN_DIRS.iter().map(|dir| Path::new(dir).join("brew")).find(|path| path.is_file())
+}
+
+/// Emit every line read from `pipe` as an install progress event.
+async fn forward_lines(app: AppHandle, pipe: impl AsyncRead + Unpin, stderr: bool) {
+    let mut lines = BufReader::new(pipe).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = app.emit(INSTALL_PROGRESS_EVENT, InstallProgress { line, stderr });
+    }
+}
+
+/// Run `brew install displayplacer`, streaming its output.
+async fn run_brew_install(app: &AppHandle) -> Result<(), String> {
+    let brew = find_brew().ok_or("Homebrew is not installed. Install it from https://brew.sh and try again")?;
+    log::info!("[Setup] Installing displayplacer with {}", brew.display());
+
+    let mut child = tokio::process::Command::new(&brew)
+        .args(["install", "displayplacer"])
+        .env("NONINTERACTIVE", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run brew: {}", e))?;
+
+    let stdout = child.stdout.take().map(|pipe| tauri::async_runtime::spawn(forward_lines(app.clone(), pipe, false)));
+    let stderr = child.stderr.take().map(|pipe| tauri::async_runtime::spawn(forward_lines(app.clone(), pipe, true)));
+
+    let status = child.wait().await.map_err(|e| format!("Failed to wait for brew: {}", e))?;
+    for task in [stdout, stderr].into_iter().flatten() {
+        let _ = task.await;
+    }
+
+    if !status.success() {
+        return Err(format!("brew install displayplacer failed ({})", status));
+    }
+    Ok(())
+}
+
+/// Install displayplacer through Homebrew
+///
+/// Each line brew prints is emitted as a `displayplacer-install-progress`
+/// event while the installation runs.
+///
+/// # Returns
+/// * `Ok(String)` - Path of the installed (or already present) displayplacer
+/// * `Err(String)` - Error message if Homebrew is missing, the install failed,
+///   another install is running or displayplacer still can't be found afterwards
+#[tauri::command]
+pub async fn install_displayplacer(app: AppHandle) -> Result<String, String> {
+    if let Ok(binary) = displayplacer_binary() {
+        return Ok(binary.display().to_string());
+    }
+    if INSTALLING.swap(true, Ordering::SeqCst) {
+        return Err("displayplacer is already being installed".to_string());
+    }
+
+    let result = run_brew_install(&app).await;
+    INSTALLING.store(false, Ordering::SeqCst);
+    result?;
+
+    invalidate_display_cache();
+    let binary = displayplacer_binary()?;
+    log::info!("[Setup] displayplacer installed at {}", binary.display());
+    Ok(binary.display().to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod ax {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    pub fn is_process_trusted() -> bool {
+        unsafe { AXIsProcessTrusted() }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod ax {
+    pub fn is_process_trusted() -> bool {
+        true
+    }
+}
@@ -0,0 +1,335 @@
+//! JSON-RPC control socket.
+//!
+//! When enabled in settings, DPUI listens on `dpui.sock` in the config
+//! directory for JSON-RPC 2.0 requests, one per line, and answers each with
+//! one line. It is lighter than the remote control server and needs no
+//! pairing: the socket is only accessible to the current user. Each method
+//! still has to be allowed in settings, so e.g. scripts can be limited to
+//! reading the layout.
+//!
+//! ```text
+//! {"jsonrpc":"2.0","id":1,"method":"apply_preset","params":{"preset":"Office"}}
+//! {"jsonrpc":"2.0","id":1,"result":null}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::conflicts::{self, IntegrationKind};
+use crate::displayplacer::{get_displays, layout_command, toggle_display};
+use crate::history::HistorySource;
+use crate::paths;
+use crate::presets::{active_preset, apply_preset_config, create_preset_from_current, find_preset_by_key};
+use crate::session;
+use crate::settings;
+use crate::state::AppState;
+
+/// Timeout for idle connections and for clients waiting on a response
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The method failed, e.g. displayplacer returned an error
+const SERVER_ERROR: i64 = -32000;
+/// The method is not allowed in settings
+const NOT_ALLOWED: i64 = -32001;
+
+/// Methods the socket answers, each allowed or not in settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketMethod {
+    ListDisplays,
+    ListPresets,
+    GetStatus,
+    /// The current layout as a displayplacer command
+    GetLayout,
+    ApplyPreset,
+    ToggleDisplay,
+    Snapshot,
+}
+
+impl SocketMethod {
+    /// Methods allowed until the user changes it: everything but changing
+    /// displays directly and editing presets
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::ListDisplays,
+            Self::ListPresets,
+            Self::GetStatus,
+            Self::GetLayout,
+            Self::ApplyPreset,
+        ]
+    }
+}
+
+/// A JSON-RPC request.
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcResponse {
+    jsonrpc: String,
+    id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(value) => (Some(value), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+fn rpc_error(code: i64, message: impl Into<String>) -> RpcError {
+    RpcError {
+        code,
+        message: message.into(),
+    }
+}
+
+/// Where the socket is created.
+pub fn socket_path() -> Result<PathBuf, String> {
+    Ok(paths::config_dir()?.join("dpui.sock"))
+}
+
+/// A string parameter, by name.
+fn string_param(params: &Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| rpc_error(INVALID_PARAMS, format!("Missing string parameter \"{}\"", name)))
+}
+
+fn call_method(app: &AppHandle, method: SocketMethod, params: &Value) -> Result<Value, RpcError> {
+    let failed = |e: String| rpc_error(SERVER_ERROR, e);
+    let state = app.state::<AppState>();
+
+    match method {
+        SocketMethod::ListDisplays => {
+            let config = tauri::async_runtime::block_on(get_displays()).map_err(failed)?;
+            serde_json::to_value(&config.displays).map_err(|e| failed(e.to_string()))
+        }
+        SocketMethod::ListPresets => {
+            let presets: Vec<Value> = state
+                .presets()
+                .presets
+                .into_iter()
+                .map(|p| json!({ "id": p.id, "name": p.name, "folder": p.folder, "tags": p.tags }))
+                .collect();
+            Ok(Value::Array(presets))
+        }
+        SocketMethod::GetStatus => {
+            let config = tauri::async_runtime::block_on(get_displays()).map_err(failed)?;
+            let store = state.presets();
+            let active = active_preset(&store, &config).map(|p| json!({ "id": p.id, "name": p.name }));
+            Ok(json!({
+                "version": app.package_info().version.to_string(),
+                "display_count": config.displays.len(),
+                "enabled_display_count": config.displays.iter().filter(|d| d.enabled).count(),
+                "active_preset": active,
+            }))
+        }
+        SocketMethod::GetLayout => {
+            let config = tauri::async_runtime::block_on(get_displays()).map_err(failed)?;
+            Ok(json!({ "command": layout_command(&config) }))
+        }
+        SocketMethod::ApplyPreset => {
+            let key = string_param(params, "preset")?;
+            let preset = find_preset_by_key(&state.presets(), &key)
+                .cloned()
+                .ok_or_else(|| failed(format!("Preset {} not found", key)))?;
+            tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::External)).map_err(failed)?;
+            log::info!("[Socket] Applied {}", preset.name);
+            Ok(Value::Null)
+        }
+        SocketMethod::ToggleDisplay => {
+            let display_id = string_param(params, "display")?;
+            let enabled = tauri::async_runtime::block_on(toggle_display(&display_id, HistorySource::External)).map_err(failed)?;
+            Ok(json!({ "enabled": enabled }))
+        }
+        SocketMethod::Snapshot => {
+            let name = string_param(params, "name")?;
+            let preset =
+                tauri::async_runtime::block_on(create_preset_from_current(state, name)).map_err(failed)?;
+            Ok(json!({ "id": preset.id, "name": preset.name }))
+        }
+    }
+}
+
+/// Answer one request line; `None` for notifications.
+fn handle_line(app: &AppHandle, line: &str, allowed: &[SocketMethod]) -> Option<RpcResponse> {
+    let request: RpcRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            let code = if serde_json::from_str::<Value>(line).is_ok() { INVALID_REQUEST } else { PARSE_ERROR };
+            return Some(RpcResponse::new(Value::Null, Err(rpc_error(code, format!("Invalid request: {}", e)))));
+        }
+    };
+    let id = request.id.clone();
+
+    let result = authorize(&request, allowed).and_then(|method| {
+        if !session::is_active() {
+            return Err(rpc_error(SERVER_ERROR, session::INACTIVE_ERROR));
+        }
+        call_method(app, method, &request.params)
+    });
+    id.map(|id| RpcResponse::new(id, result))
+}
+
+/// Resolve the method of a request and check it is allowed.
+fn authorize(request: &RpcRequest, allowed: &[SocketMethod]) -> Result<SocketMethod, RpcError> {
+    if request.jsonrpc != "2.0" {
+        return Err(rpc_error(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported"));
+    }
+    let method: SocketMethod = serde_json::from_value(Value::String(request.method.clone()))
+        .map_err(|_| rpc_error(METHOD_NOT_FOUND, format!("Unknown method \"{}\"", request.method)))?;
+    if !allowed.contains(&method) {
+        return Err(rpc_error(NOT_ALLOWED, format!("Method \"{}\" is not allowed in settings", request.method)));
+    }
+    Ok(method)
+}
+
+/// Serve one connection until the peer closes it.
+fn handle_connection(app: &AppHandle, stream: UnixStream) {
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Read per request so permission changes apply to open connections too
+        let allowed = settings::current().socket.allowed_methods;
+        let Some(response) = handle_line(app, &line, &allowed) else {
+            continue;
+        };
+        let Ok(json) = serde_json::to_string(&response) else {
+            break;
+        };
+        if writeln!(writer, "{}", json).is_err() {
+            break;
+        }
+    }
+}
+
+/// Start the socket server if it is enabled in settings.
+///
+/// Runs the usual pre-flight conflict checks first; a stale socket file left
+/// by a crash is removed, a live one means another instance owns it.
+pub fn start(app: AppHandle) -> Result<(), String> {
+    if !settings::current().socket.enabled {
+        return Ok(());
+    }
+    let path = socket_path()?;
+
+    conflicts::check(IntegrationKind::Socket, None, Some(&path)).into_result()?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove stale socket: {}", e))?;
+    }
+
+    let listener = UnixListener::bind(&path).map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict socket permissions: {}", e))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            thread::spawn(move || handle_connection(&app, stream));
+        }
+    });
+
+    log::info!("[Socket] Listening on {}", path.display());
+    Ok(())
+}
+
+/// Connect to the socket of a running DPUI, if it is listening.
+pub fn connect() -> Option<UnixStream> {
+    let stream = UnixStream::connect(socket_path().ok()?).ok()?;
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    Some(stream)
+}
+
+/// Call a method over a connection and return its result.
+pub fn call(stream: &mut UnixStream, method: SocketMethod, params: Value) -> Result<Value, String> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    writeln!(stream, "{}", request).map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(&*stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    let response: RpcResponse = serde_json::from_str(&line).map_err(|e| format!("Invalid response: {}", e))?;
+    match response.error {
+        Some(error) => Err(error.message),
+        None => Ok(response.result.unwrap_or(Value::Null)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorize() {
+        let request = |line: &str| serde_json::from_str::<RpcRequest>(line).unwrap();
+        let allowed = SocketMethod::defaults();
+
+        let apply = request(r#"{"jsonrpc":"2.0","id":1,"method":"apply_preset","params":{"preset":"Office"}}"#);
+        assert_eq!(authorize(&apply, &allowed).unwrap(), SocketMethod::ApplyPreset);
+        assert_eq!(string_param(&apply.params, "preset").unwrap(), "Office");
+        assert_eq!(string_param(&apply.params, "name").unwrap_err().code, INVALID_PARAMS);
+
+        let toggle = request(r#"{"jsonrpc":"2.0","id":2,"method":"toggle_display","params":{"display":"1"}}"#);
+        assert_eq!(authorize(&toggle, &allowed).unwrap_err().code, NOT_ALLOWED);
+
+        let unknown = request(r#"{"jsonrpc":"2.0","id":3,"method":"reboot"}"#);
+        assert_eq!(authorize(&unknown, &allowed).unwrap_err().code, METHOD_NOT_FOUND);
+
+        let old = request(r#"{"jsonrpc":"1.0","id":4,"method":"get_status"}"#);
+        assert_eq!(authorize(&old, &allowed).unwrap_err().code, INVALID_REQUEST);
+    }
+}
@@ -0,0 +1,162 @@
+//! Shared in-memory application state.
+//!
+//! Presets are loaded once at startup and kept in a Tauri-managed `AppState`.
+//! Commands read and mutate that copy under a lock, so concurrent edits can't
+//! lose each other's changes, and a background thread persists snapshots to
+//! disk. Bursts of edits are coalesced into a single write.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use crate::presets::{Preset, PresetStore};
+use crate::storage;
+
+/// Work for the background writer.
+enum PersistRequest {
+    /// Write a snapshot, optionally backing up the previous file first
+    Save { store: PresetStore, backup: bool },
+    /// Signal once every earlier request has been written
+    Flush(Sender<()>),
+}
+
+/// Callback run after presets are edited.
+type ChangeHandler = Box<dyn Fn(&PresetStore) + Send + Sync>;
+
+/// State shared by all commands.
+pub struct AppState {
+    presets: RwLock<PresetStore>,
+    persist_tx: Mutex<Sender<PersistRequest>>,
+    on_change: OnceLock<ChangeHandler>,
+}
+
+impl AppState {
+    /// Load presets from disk and start the background writer.
+    ///
+    /// If the presets cannot be read, the app starts with an empty store; the
+    /// unreadable file is copied to the backups directory before it is first
+    /// overwritten.
+    pub fn load() -> Self {
+        let presets = storage::backend().load_presets().unwrap_or_else(|e| {
+            log::warn!("[State] {}; starting with no presets", e);
+            PresetStore::default()
+        });
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || run_writer(rx));
+
+        Self {
+            presets: RwLock::new(presets),
+            persist_tx: Mutex::new(tx),
+            on_change: OnceLock::new(),
+        }
+    }
+
+    /// Snapshot of all presets, in their user-defined order.
+    pub fn presets(&self) -> PresetStore {
+        self.presets.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Look up a preset by ID.
+    pub fn find_preset(&self, id: &str) -> Option<Preset> {
+        let store = self.presets.read().ok()?;
+        store.presets.iter().find(|p| p.id == id).cloned()
+    }
+
+    /// Set the callback run after every `update_presets`, e.g. to rebuild the tray menu.
+    ///
+    /// It runs outside the lock, so it may read the presets again. Only the
+    /// first handler is kept.
+    pub fn on_change(&self, handler: impl Fn(&PresetStore) + Send + Sync + 'static) {
+        let _ = self.on_change.set(Box::new(handler));
+    }
+
+    /// Mutate the presets and persist the result, backing up the previous file.
+    ///
+    /// Nothing is persisted if `f` returns an error.
+    pub fn update_presets<T>(&self, f: impl FnOnce(&mut PresetStore) -> Result<T, String>) -> Result<T, String> {
+        self.update(true, f)
+    }
+
+    /// Like `update_presets`, but without taking a backup; for bookkeeping such as usage counters.
+    pub fn update_presets_quietly<T>(
+        &self,
+        f: impl FnOnce(&mut PresetStore) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.update(false, f)
+    }
+
+    fn update<T>(&self, backup: bool, f: impl FnOnce(&mut PresetStore) -> Result<T, String>) -> Result<T, String> {
+        let mut store = self.presets.write().map_err(|_| "Presets lock poisoned".to_string())?;
+        let result = f(&mut store)?;
+        store.presets.sort_by_key(|p| p.sort_order);
+
+        let snapshot = store.clone();
+        drop(store);
+        self.persist(PersistRequest::Save {
+            store: snapshot.clone(),
+            backup,
+        });
+
+        // Bookkeeping updates don't change anything the handler shows
+        if backup {
+            if let Some(handler) = self.on_change.get() {
+                handler(&snapshot);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Replace the in-memory presets with `store` without writing it.
+    ///
+    /// Used when the file on disk is already up to date, e.g. after an external edit.
+    pub fn replace_presets(&self, store: PresetStore) {
+        if let Ok(mut presets) = self.presets.write() {
+            *presets = store;
+        }
+    }
+
+    /// Block until every pending write has reached the disk.
+    pub fn flush(&self) {
+        let (done_tx, done_rx) = mpsc::channel();
+        self.persist(PersistRequest::Flush(done_tx));
+        let _ = done_rx.recv();
+    }
+
+    fn persist(&self, request: PersistRequest) {
+        if let Ok(tx) = self.persist_tx.lock() {
+            if tx.send(request).is_err() {
+                log::error!("[State] Preset writer has stopped; changes will not be saved");
+            }
+        }
+    }
+}
+
+/// Write snapshots as they arrive, keeping only the latest of a burst.
+fn run_writer(rx: Receiver<PersistRequest>) {
+    while let Ok(first) = rx.recv() {
+        let mut pending: Option<(PresetStore, bool)> = None;
+        let mut flushes = Vec::new();
+
+        let mut next = Some(first);
+        while let Some(request) = next {
+            match request {
+                PersistRequest::Save { store, backup } => {
+                    let backup = backup || pending.as_ref().is_some_and(|(_, b)| *b);
+                    pending = Some((store, backup));
+                }
+                PersistRequest::Flush(done) => flushes.push(done),
+            }
+            next = rx.try_recv().ok();
+        }
+
+        if let Some((store, backup)) = pending {
+            if let Err(e) = storage::backend().save_presets(&store, backup) {
+                log::error!("[State] Failed to save presets: {}", e);
+            }
+        }
+        for done in flushes {
+            let _ = done.send(());
+        }
+    }
+}
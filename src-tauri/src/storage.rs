@@ -0,0 +1,329 @@
+//! Storage backends for presets, hotkey bindings and history.
+//!
+//! By default everything is kept in JSON files in the config directory. Builds
+//! with the `sqlite` feature can keep the same data in a single SQLite
+//! database instead, which scales better to hundreds of presets and a long
+//! history. The backend is chosen from settings at startup; switching copies
+//! the data across and takes effect on the next launch.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::atomic_file::write_atomic;
+use crate::history::{self, HistoryEntry};
+use crate::hotkeys::HotkeyBinding;
+use crate::presets::{get_config_dir, read_presets, write_presets, write_store, PresetStore};
+use crate::settings;
+
+/// Which backend stores the app's data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+/// Persistence for presets, hotkey bindings and history.
+pub trait Storage: Send + Sync {
+    fn kind(&self) -> StorageBackend;
+    fn load_presets(&self) -> Result<PresetStore, String>;
+    /// Replace all presets; `backup` asks for the previous version to be kept where supported
+    fn save_presets(&self, store: &PresetStore, backup: bool) -> Result<(), String>;
+    fn load_hotkeys(&self) -> Result<Vec<HotkeyBinding>, String>;
+    fn save_hotkeys(&self, bindings: &[HotkeyBinding]) -> Result<(), String>;
+    fn append_history(&self, entry: &HistoryEntry) -> Result<(), String>;
+    /// All history entries, oldest first
+    fn load_history(&self) -> Result<Vec<HistoryEntry>, String>;
+}
+
+static BACKEND: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+/// The storage backend selected in settings, opened on first use.
+///
+/// Falls back to JSON files if the selected backend can't be opened.
+pub fn backend() -> &'static dyn Storage {
+    BACKEND
+        .get_or_init(|| {
+            let selected = settings::current().storage_backend;
+            open(selected).unwrap_or_else(|e| {
+                log::error!("[Storage] {}; using JSON files", e);
+                Box::new(JsonStorage)
+            })
+        })
+        .as_ref()
+}
+
+/// Open a backend.
+fn open(backend: StorageBackend) -> Result<Box<dyn Storage>, String> {
+    match backend {
+        StorageBackend::Json => Ok(Box::new(JsonStorage)),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Ok(Box::new(sqlite::SqliteStorage::open()?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => Err("This build of DPUI does not include SQLite support".to_string()),
+    }
+}
+
+/// Switch to another storage backend
+///
+/// Copies presets, hotkey bindings and history from the current backend into
+/// the new one. The switch takes effect on the next launch.
+///
+/// # Arguments
+/// * `backend` - Backend to use from the next launch
+///
+/// # Returns
+/// * `Ok(())` - Data copied and setting saved
+/// * `Err(String)` - Error message if the backend is unavailable or the data cannot be copied
+#[tauri::command]
+pub async fn set_storage_backend(backend: StorageBackend) -> Result<(), String> {
+    let source = self::backend();
+    if source.kind() == backend {
+        return settings::store_storage_backend(backend).map(|_| ());
+    }
+
+    let target = open(backend)?;
+
+    target.save_presets(&source.load_presets()?, true)?;
+    target.save_hotkeys(&source.load_hotkeys()?)?;
+    for entry in source.load_history()? {
+        target.append_history(&entry)?;
+    }
+
+    settings::store_storage_backend(backend)?;
+    log::info!("[Storage] Copied data to {:?} backend; active after restart", backend);
+
+    Ok(())
+}
+
+/// Presets in `presets.json` (which may live in a synced folder), hotkey
+/// bindings in `hotkeys.json` and history in `history.jsonl`.
+pub struct JsonStorage;
+
+fn get_hotkeys_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("hotkeys.json"))
+}
+
+impl Storage for JsonStorage {
+    fn kind(&self) -> StorageBackend {
+        StorageBackend::Json
+    }
+
+    fn load_presets(&self) -> Result<PresetStore, String> {
+        read_presets()
+    }
+
+    fn save_presets(&self, store: &PresetStore, backup: bool) -> Result<(), String> {
+        if backup {
+            write_presets(store)
+        } else {
+            write_store(store)
+        }
+    }
+
+    fn load_hotkeys(&self) -> Result<Vec<HotkeyBinding>, String> {
+        let path = get_hotkeys_path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read hotkeys: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse hotkeys: {}", e))
+    }
+
+    fn save_hotkeys(&self, bindings: &[HotkeyBinding]) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(bindings).map_err(|e| format!("Failed to serialize hotkeys: {}", e))?;
+        write_atomic(&get_hotkeys_path()?, content.as_bytes()).map_err(|e| format!("Failed to write hotkeys: {}", e))
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> Result<(), String> {
+        history::append_to_file(entry)
+    }
+
+    fn load_history(&self) -> Result<Vec<HistoryEntry>, String> {
+        history::read_file_entries()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use rusqlite::{params, Connection};
+    use std::sync::Mutex;
+
+    use super::{Storage, StorageBackend};
+    use crate::history::HistoryEntry;
+    use crate::hotkeys::HotkeyBinding;
+    use crate::migrations::PRESETS_VERSION;
+    use crate::presets::{get_config_dir, PresetStore};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS presets (
+            id TEXT PRIMARY KEY,
+            position INTEGER NOT NULL,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS hotkeys (
+            shortcut TEXT PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS history (
+            id TEXT PRIMARY KEY,
+            timestamp TEXT NOT NULL,
+            data TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS history_timestamp ON history (timestamp);
+    ";
+
+    /// History rows kept, roughly what `history.jsonl` holds before it is rotated
+    const MAX_HISTORY_ROWS: usize = 5000;
+
+    /// Rows are stored as JSON so new fields don't need a schema change.
+    pub struct SqliteStorage {
+        conn: Mutex<Connection>,
+    }
+
+    fn db_error(e: rusqlite::Error) -> String {
+        format!("Database error: {}", e)
+    }
+
+    impl SqliteStorage {
+        /// Open (or create) `dpui.sqlite3` in the config directory.
+        pub fn open() -> Result<Self, String> {
+            let path = get_config_dir()?.join("dpui.sqlite3");
+            Self::with_connection(Connection::open(&path).map_err(db_error)?)
+        }
+
+        fn with_connection(conn: Connection) -> Result<Self, String> {
+            conn.execute_batch(SCHEMA).map_err(db_error)?;
+
+            Ok(Self { conn: Mutex::new(conn) })
+        }
+
+        /// Insert a history entry, then drop the oldest rows beyond `max_rows`.
+        fn insert_history(&self, entry: &HistoryEntry, max_rows: usize) -> Result<(), String> {
+            let data = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+            let mut conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+            let tx = conn.transaction().map_err(db_error)?;
+
+            tx.execute(
+                "INSERT OR REPLACE INTO history (id, timestamp, data) VALUES (?1, ?2, ?3)",
+                params![entry.id, entry.timestamp, data],
+            )
+            .map_err(db_error)?;
+            tx.execute(
+                "DELETE FROM history WHERE rowid NOT IN \
+                 (SELECT rowid FROM history ORDER BY timestamp DESC, rowid DESC LIMIT ?1)",
+                params![max_rows as i64],
+            )
+            .map_err(db_error)?;
+
+            tx.commit().map_err(db_error)
+        }
+
+        fn load_json<T: serde::de::DeserializeOwned>(&self, sql: &str) -> Result<Vec<T>, String> {
+            let conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+            let mut stmt = conn.prepare(sql).map_err(db_error)?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(db_error)?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(db_error)?;
+
+            rows.iter()
+                .map(|data| serde_json::from_str(data).map_err(|e| format!("Failed to parse stored row: {}", e)))
+                .collect()
+        }
+    }
+
+    impl Storage for SqliteStorage {
+        fn kind(&self) -> StorageBackend {
+            StorageBackend::Sqlite
+        }
+
+        fn load_presets(&self) -> Result<PresetStore, String> {
+            Ok(PresetStore {
+                version: PRESETS_VERSION.to_string(),
+                presets: self.load_json("SELECT data FROM presets ORDER BY position")?,
+            })
+        }
+
+        fn save_presets(&self, store: &PresetStore, _backup: bool) -> Result<(), String> {
+            let mut conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+            let tx = conn.transaction().map_err(db_error)?;
+
+            tx.execute("DELETE FROM presets", []).map_err(db_error)?;
+            for (position, preset) in store.presets.iter().enumerate() {
+                let data = serde_json::to_string(preset).map_err(|e| format!("Failed to serialize preset: {}", e))?;
+                tx.execute(
+                    "INSERT INTO presets (id, position, data) VALUES (?1, ?2, ?3)",
+                    params![preset.id, position as i64, data],
+                )
+                .map_err(db_error)?;
+            }
+
+            tx.commit().map_err(db_error)
+        }
+
+        fn load_hotkeys(&self) -> Result<Vec<HotkeyBinding>, String> {
+            self.load_json("SELECT data FROM hotkeys ORDER BY rowid")
+        }
+
+        fn save_hotkeys(&self, bindings: &[HotkeyBinding]) -> Result<(), String> {
+            let mut conn = self.conn.lock().map_err(|_| "Database lock poisoned".to_string())?;
+            let tx = conn.transaction().map_err(db_error)?;
+
+            tx.execute("DELETE FROM hotkeys", []).map_err(db_error)?;
+            for binding in bindings {
+                let data = serde_json::to_string(binding).map_err(|e| format!("Failed to serialize hotkey: {}", e))?;
+                tx.execute(
+                    "INSERT OR REPLACE INTO hotkeys (shortcut, data) VALUES (?1, ?2)",
+                    params![binding.shortcut, data],
+                )
+                .map_err(db_error)?;
+            }
+
+            tx.commit().map_err(db_error)
+        }
+
+        fn append_history(&self, entry: &HistoryEntry) -> Result<(), String> {
+            self.insert_history(entry, MAX_HISTORY_ROWS)
+        }
+
+        fn load_history(&self) -> Result<Vec<HistoryEntry>, String> {
+            self.load_json("SELECT data FROM history ORDER BY timestamp, rowid")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::history::HistorySource;
+
+        fn entry(id: &str, second: u32) -> HistoryEntry {
+            HistoryEntry {
+                id: id.to_string(),
+                timestamp: format!("2024-01-01T00:00:{:02}+00:00", second),
+                source: HistorySource::Manual,
+                config: String::new(),
+                success: true,
+                error: None,
+            }
+        }
+
+        #[test]
+        fn test_history_pruning() {
+            let storage = SqliteStorage::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+
+            for (second, id) in ["a", "b", "c", "d"].into_iter().enumerate() {
+                storage.insert_history(&entry(id, second as u32), 3).unwrap();
+            }
+
+            let ids: Vec<String> = storage.load_history().unwrap().into_iter().map(|e| e.id).collect();
+            assert_eq!(ids, ["b", "c", "d"], "the oldest rows beyond the cap are dropped");
+        }
+    }
+}
@@ -0,0 +1,415 @@
+//! Localhost endpoint for a Stream Deck plugin.
+//!
+//! When enabled in settings, DPUI serves a small HTTP API on `127.0.0.1`.
+//! Every request needs the `X-DPUI-Token` header with the token from
+//! settings, so web pages can't drive it.
+//!
+//! | Request                                | Answer                                 |
+//! |----------------------------------------|----------------------------------------|
+//! | `GET /v1/state`                        | Active preset, display counts, actions |
+//! | `GET /v1/actions`                      | Actions with stable IDs and titles     |
+//! | `POST /v1/actions/<action_id>`         | Runs the action                        |
+//! | `GET /v1/actions/<action_id>/icon.svg` | Key image drawn from the layout        |
+//! | `GET /v1/events`                       | Server-sent `state` events             |
+//!
+//! Action IDs stay the same across restarts and renames: `preset:<preset ID>`
+//! applies a preset and `display:<persistent ID>` toggles a display. Each
+//! action has a `state` of 1 while its preset is active or its display is on,
+//! for two-state keys. Subscribers of `/v1/events` get the full state again
+//! whenever the layout changes, so keys update without polling.
+
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::conflicts::{self, IntegrationKind};
+use crate::displayplacer::{display_size, get_displays, parse_config_displays, toggle_display, Display, DisplayConfig};
+use crate::history::HistorySource;
+use crate::presets::{active_preset, spawn_apply_preset, Preset, PresetStore};
+use crate::session;
+use crate::settings;
+use crate::state::AppState;
+
+/// Timeout for reading a request
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Header carrying the token
+const TOKEN_HEADER: &str = "x-dpui-token";
+
+/// Side length of generated key images (Stream Deck keys are 72pt, @2x)
+const ICON_SIZE: i32 = 144;
+
+/// How long a write to a `/v1/events` subscriber may block before it is dropped
+const EVENT_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Connections listening to `/v1/events`
+static SUBSCRIBERS: Mutex<Vec<TcpStream>> = Mutex::new(Vec::new());
+
+/// States waiting to be pushed to subscribers by the event thread
+static EVENTS: OnceLock<mpsc::Sender<DeckState>> = OnceLock::new();
+
+/// Something a Stream Deck key can do.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeckAction {
+    /// Stable ID, `preset:<preset ID>` or `display:<persistent ID>`
+    pub action_id: String,
+    #[serde(rename = "type")]
+    pub kind: DeckActionKind,
+    pub title: String,
+    /// 1 while the preset is active or the display is on, otherwise 0
+    pub state: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeckActionKind {
+    ApplyPreset,
+    ToggleDisplay,
+}
+
+/// Everything a plugin shows, sent for `/v1/state` and on every change.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeckState {
+    /// Action ID of the active preset
+    pub active_action_id: Option<String>,
+    pub active_preset_name: Option<String>,
+    pub display_count: usize,
+    pub enabled_display_count: usize,
+    pub actions: Vec<DeckAction>,
+}
+
+fn preset_action_id(preset: &Preset) -> String {
+    format!("preset:{}", preset.id)
+}
+
+fn display_action_id(display: &Display) -> String {
+    format!("display:{}", display.persistent_id.as_deref().unwrap_or(&display.id))
+}
+
+/// Build the state from presets and the current displays.
+fn build_state(store: &PresetStore, config: &DisplayConfig) -> DeckState {
+    let active = active_preset(store, config);
+
+    let presets = store.presets.iter().map(|preset| DeckAction {
+        action_id: preset_action_id(preset),
+        kind: DeckActionKind::ApplyPreset,
+        title: preset.name.clone(),
+        state: u8::from(active.is_some_and(|a| a.id == preset.id)),
+    });
+    let displays = config.displays.iter().map(|display| DeckAction {
+        action_id: display_action_id(display),
+        kind: DeckActionKind::ToggleDisplay,
+        title: display.name.clone().unwrap_or_else(|| display.id.clone()),
+        state: u8::from(display.enabled),
+    });
+
+    DeckState {
+        active_action_id: active.map(preset_action_id),
+        active_preset_name: active.map(|p| p.name.clone()),
+        display_count: config.displays.len(),
+        enabled_display_count: config.displays.iter().filter(|d| d.enabled).count(),
+        actions: presets.chain(displays).collect(),
+    }
+}
+
+fn current_state(app: &AppHandle) -> Result<DeckState, String> {
+    let config = tauri::async_runtime::block_on(get_displays())?;
+    Ok(build_state(&app.state::<AppState>().presets(), &config))
+}
+
+/// Draw displays as rectangles scaled to fit a key, highlighted if `active`.
+fn layout_svg(displays: &[Display], active: bool) -> String {
+    let rects: Vec<(i32, i32, i32, i32, bool)> = displays
+        .iter()
+        .map(|d| {
+            let (width, height) = display_size(d);
+            (d.origin.0, d.origin.1, width.max(1), height.max(1), d.enabled)
+        })
+        .collect();
+
+    let min_x = rects.iter().map(|r| r.0).min().unwrap_or(0);
+    let min_y = rects.iter().map(|r| r.1).min().unwrap_or(0);
+    let max_x = rects.iter().map(|r| r.0 + r.2).max().unwrap_or(1);
+    let max_y = rects.iter().map(|r| r.1 + r.3).max().unwrap_or(1);
+
+    let margin = 16.0;
+    let available = ICON_SIZE as f64 - 2.0 * margin;
+    let scale = available / f64::from((max_x - min_x).max(max_y - min_y).max(1));
+    // Center the layout on the key
+    let offset_x = margin + (available - f64::from(max_x - min_x) * scale) / 2.0;
+    let offset_y = margin + (available - f64::from(max_y - min_y) * scale) / 2.0;
+
+    let accent = if active { "#3b82f6" } else { "#9ca3af" };
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\
+         <rect width=\"{0}\" height=\"{0}\" fill=\"#111827\"/>",
+        ICON_SIZE
+    );
+    for (x, y, width, height, enabled) in rects {
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"3\" fill=\"{}\" stroke=\"#f9fafb\" stroke-width=\"2\"/>",
+            offset_x + f64::from(x - min_x) * scale,
+            offset_y + f64::from(y - min_y) * scale,
+            f64::from(width) * scale,
+            f64::from(height) * scale,
+            if enabled { accent } else { "#374151" }
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Key image for an action.
+fn action_icon(app: &AppHandle, action_id: &str) -> Result<String, String> {
+    let config = tauri::async_runtime::block_on(get_displays())?;
+    let store = app.state::<AppState>().presets();
+
+    if let Some(preset) = store.presets.iter().find(|p| preset_action_id(p) == action_id) {
+        let active = active_preset(&store, &config).is_some_and(|a| a.id == preset.id);
+        return Ok(layout_svg(&parse_config_displays(&preset.config), active));
+    }
+    if let Some(display) = config.displays.iter().find(|d| display_action_id(d) == action_id) {
+        let at_origin = Display {
+            origin: (0, 0),
+            ..display.clone()
+        };
+        return Ok(layout_svg(&[at_origin], display.enabled));
+    }
+    Err(format!("Unknown action {}", action_id))
+}
+
+/// Run an action in the background, as a key press.
+fn run_action(app: &AppHandle, action_id: &str) -> Result<(), String> {
+    if let Some(preset_id) = action_id.strip_prefix("preset:") {
+        app.state::<AppState>()
+            .find_preset(preset_id)
+            .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+        spawn_apply_preset(app, preset_id.to_string(), HistorySource::External);
+        return Ok(());
+    }
+    if let Some(display_id) = action_id.strip_prefix("display:") {
+        let display_id = display_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = toggle_display(&display_id, HistorySource::External).await {
+                log::error!("[StreamDeck] Failed to toggle display {}: {}", display_id, e);
+            }
+        });
+        return Ok(());
+    }
+    Err(format!("Unknown action {}", action_id))
+}
+
+/// Send a state to every `/v1/events` subscriber, dropping those that have gone away.
+fn send_state(subscribers: &mut Vec<TcpStream>, state: &DeckState) {
+    let Ok(json) = serde_json::to_string(state) else {
+        return;
+    };
+    let event = format!("event: state\ndata: {}\n\n", json);
+    subscribers.retain_mut(|stream| stream.write_all(event.as_bytes()).and_then(|_| stream.flush()).is_ok());
+}
+
+/// Push the state for `config` to Stream Deck subscribers.
+///
+/// Called when the layout or the active preset changes. The writes happen on
+/// the event thread, so a stalled subscriber can't hold up applies or the watcher.
+pub fn publish_state(app: &AppHandle, config: &DisplayConfig) {
+    let (Some(events), Ok(subscribers)) = (EVENTS.get(), SUBSCRIBERS.lock()) else {
+        return;
+    };
+    if subscribers.is_empty() {
+        return;
+    }
+    drop(subscribers);
+    let _ = events.send(build_state(&app.state::<AppState>().presets(), config));
+}
+
+/// A parsed HTTP request; only what the API needs.
+#[derive(Debug, PartialEq)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    token: Option<String>,
+}
+
+/// Read the request line and headers. Bodies are not used and not read.
+fn read_request(reader: &mut impl BufRead) -> Option<HttpRequest> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.split('?').next()?.to_string();
+
+    let mut token = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case(TOKEN_HEADER) {
+                token = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    Some(HttpRequest { method, path, token })
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+}
+
+fn write_json<T: Serialize>(stream: &mut TcpStream, result: Result<T, String>) {
+    match result.and_then(|value| serde_json::to_string(&value).map_err(|e| e.to_string())) {
+        Ok(json) => write_response(stream, "200 OK", "application/json", &json),
+        Err(e) => write_error(stream, "500 Internal Server Error", &e),
+    }
+}
+
+fn write_error(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = serde_json::json!({ "error": message }).to_string();
+    write_response(stream, status, "application/json", &body);
+}
+
+/// Compare the token without exiting early on the first mismatch.
+fn token_matches(expected: &str, given: Option<&str>) -> bool {
+    let given = given.unwrap_or_default();
+    !expected.is_empty()
+        && expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Serve one request; `/v1/events` connections are kept open.
+fn handle_connection(app: &AppHandle, mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let Some(request) = read_request(&mut BufReader::new(reader)) else {
+        return;
+    };
+
+    if !token_matches(&settings::current().stream_deck.token, request.token.as_deref()) {
+        write_error(&mut stream, "401 Unauthorized", "Missing or wrong X-DPUI-Token");
+        return;
+    }
+    if !session::is_active() {
+        write_error(&mut stream, "503 Service Unavailable", session::INACTIVE_ERROR);
+        return;
+    }
+
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["v1", "state"]) => write_json(&mut stream, current_state(app)),
+        ("GET", ["v1", "actions"]) => write_json(&mut stream, current_state(app).map(|s| s.actions)),
+        ("POST", ["v1", "actions", action_id]) => {
+            write_json(&mut stream, run_action(app, action_id).map(|_| serde_json::json!({ "ok": true })))
+        }
+        ("GET", ["v1", "actions", action_id, "icon.svg"]) => match action_icon(app, action_id) {
+            Ok(svg) => write_response(&mut stream, "200 OK", "image/svg+xml", &svg),
+            Err(e) => write_error(&mut stream, "404 Not Found", &e),
+        },
+        ("GET", ["v1", "events"]) => {
+            let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n";
+            if stream.set_write_timeout(Some(EVENT_WRITE_TIMEOUT)).is_err() || stream.write_all(headers.as_bytes()).is_err() {
+                return;
+            }
+            // Start the subscriber off with the current state
+            let mut subscriber = vec![stream];
+            match current_state(app) {
+                Ok(state) => send_state(&mut subscriber, &state),
+                Err(e) => log::error!("[StreamDeck] {}", e),
+            }
+            if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+                subscribers.append(&mut subscriber);
+            }
+        }
+        _ => write_error(&mut stream, "404 Not Found", "No such endpoint"),
+    }
+}
+
+/// Start the Stream Deck endpoint if it is enabled in settings.
+///
+/// A token is generated and stored on first start. Runs the usual pre-flight
+/// conflict checks first; if the port is taken the endpoint is not started.
+pub fn start(app: AppHandle) -> Result<(), String> {
+    let mut stream_deck = settings::current().stream_deck;
+    if !stream_deck.enabled {
+        return Ok(());
+    }
+    if stream_deck.token.is_empty() {
+        stream_deck.token = format!("{}", uuid::Uuid::new_v4().simple());
+        settings::store_stream_deck_token(stream_deck.token.clone())?;
+    }
+
+    conflicts::check(IntegrationKind::Http, Some(stream_deck.port), None).into_result()?;
+    let listener = TcpListener::bind(("127.0.0.1", stream_deck.port))
+        .map_err(|e| format!("Failed to bind port {}: {}", stream_deck.port, e))?;
+
+    let (events, pending) = mpsc::channel::<DeckState>();
+    if EVENTS.set(events).is_ok() {
+        thread::spawn(move || {
+            for state in pending {
+                if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+                    send_state(&mut subscribers, &state);
+                }
+            }
+        });
+    }
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            thread::spawn(move || handle_connection(&app, stream));
+        }
+    });
+
+    log::info!("[StreamDeck] Listening on 127.0.0.1:{}", stream_deck.port);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /v1/actions/preset:abc?x=1 HTTP/1.1\r\nHost: 127.0.0.1\r\nX-DPUI-Token: secret\r\n\r\n";
+        let request = read_request(&mut BufReader::new(raw.as_bytes())).unwrap();
+        assert_eq!(
+            request,
+            HttpRequest {
+                method: "POST".to_string(),
+                path: "/v1/actions/preset:abc".to_string(),
+                token: Some("secret".to_string()),
+            }
+        );
+
+        assert!(token_matches("secret", Some("secret")));
+        assert!(!token_matches("secret", Some("secreT")));
+        assert!(!token_matches("secret", None));
+        assert!(!token_matches("", Some("")));
+    }
+
+    #[test]
+    fn test_layout_svg() {
+        let displays = parse_config_displays(
+            r#""id:A res:2560x1440 enabled:true origin:(0,0) degree:0" "id:B res:1080x1920 enabled:true origin:(2560,-240) degree:0""#,
+        );
+        let svg = layout_svg(&displays, true);
+        assert_eq!(svg.matches("<rect").count(), 3);
+        assert!(svg.contains("#3b82f6"));
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+    }
+}
@@ -3,11 +3,37 @@
 //! Provides quick access to display presets and application controls
 //! through the macOS menu bar.
 
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 use tauri::{
-    menu::{Menu, PredefinedMenuItem, Submenu, MenuItemBuilder},
+    image::Image,
+    menu::{CheckMenuItem, CheckMenuItemBuilder, Menu, PredefinedMenuItem, Submenu, MenuItemBuilder},
     tray::{TrayIcon, TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState},
     App, AppHandle, Emitter, Manager, Runtime,
 };
+use tauri_plugin_notification::NotificationExt;
+
+use crate::display_sleep;
+use crate::headless;
+use crate::history::HistorySource;
+use crate::displayplacer::{
+    cached_displays, change_resolution, get_displays, mirror_all_displays, restore_from_mirroring,
+    turn_off_external_displays, Display, DisplayConfig,
+};
+use crate::hotkeys;
+use crate::login_item;
+use crate::quick_panel;
+use crate::presets::{active_preset, missing_displays, recent_presets, spawn_apply_preset, Preset};
+use crate::settings;
+use crate::state::AppState;
+use crate::workspaces::spawn_apply_workspace;
+
+/// Whether the most recent apply failed, shown as a badge on the tray icon
+static LAST_APPLY_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Handle notifications are posted through, set by `init_notifications`
+static APP: OnceLock<AppHandle> = OnceLock::new();
 
 /// Initialize the system tray icon and menu.
 ///
@@ -20,11 +46,11 @@ use tauri::{
 /// * `Ok(())` - Tray initialized successfully
 /// * `Err(String)` - Error message if initialization fails
 pub fn init_system_tray<R: Runtime>(app: &App<R>) -> Result<(), Box<dyn std::error::Error>> {
-    // Create the tray menu
-    let menu = create_tray_menu(app)?;
+    // Create the tray menu; displays are filled in once they have been read
+    let current = cached_displays();
+    let menu = create_tray_menu(app, current.as_ref())?;
 
-    // Create tray icon
-    // Note: You need to add an icon file in src-tauri/icons/tray-icon.png
+    // Create tray icon; the image and title are set by update_tray_status
     let _tray: TrayIcon<R> = TrayIconBuilder::<R>::with_id("main")
         .tooltip("DPUI - Display Manager")
         .menu(&menu)
@@ -33,13 +59,23 @@ pub fn init_system_tray<R: Runtime>(app: &App<R>) -> Result<(), Box<dyn std::err
             handle_tray_event(tray, event);
         })
         .build(app)?;
+    update_tray_status(app.handle());
+    if current.is_none() {
+        refresh_displays_in_background(app.handle());
+    }
 
-    println!("[Tray] System tray initialized");
+    log::info!("[Tray] System tray initialized");
     Ok(())
 }
 
 /// Create the tray menu structure.
-fn create_tray_menu<R: Runtime>(app: &impl Manager<R>) -> Result<Menu<R>, Box<dyn std::error::Error>> {
+///
+/// `current` is the cached display list; without it the display-dependent
+/// items are left out rather than running displayplacer on the main thread.
+fn create_tray_menu<R: Runtime>(
+    app: &impl Manager<R>,
+    current: Option<&DisplayConfig>,
+) -> Result<Menu<R>, Box<dyn std::error::Error>> {
     let menu = Menu::new(app)?;
 
     // Show/Hide Window
@@ -50,10 +86,24 @@ fn create_tray_menu<R: Runtime>(app: &impl Manager<R>) -> Result<Menu<R>, Box<dy
     // Separator
     menu.append(&PredefinedMenuItem::separator(app)?)?;
 
+    // Recently applied presets
+    if append_recent_presets(app, &menu)? {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
     // Quick Presets submenu
-    let presets_menu = create_presets_submenu(app)?;
+    let presets_menu = create_presets_submenu(app, current)?;
     menu.append(&presets_menu)?;
 
+    // Workspaces submenu
+    let workspaces_menu = create_workspaces_submenu(app)?;
+    menu.append(&workspaces_menu)?;
+
+    // Resolution submenu per display
+    if let Some(displays_menu) = create_displays_submenu(app, current)? {
+        menu.append(&displays_menu)?;
+    }
+
     // Separator
     menu.append(&PredefinedMenuItem::separator(app)?)?;
 
@@ -63,6 +113,49 @@ fn create_tray_menu<R: Runtime>(app: &impl Manager<R>) -> Result<Menu<R>, Box<dy
         .build(app)?;
     menu.append(&refresh)?;
 
+    // Disable All External Displays
+    let disable_external = MenuItemBuilder::with_id("disable_external", "Disable All External Displays").build(app)?;
+    menu.append(&disable_external)?;
+
+    // Sleep Displays, keeping the layout
+    let sleep_displays = MenuItemBuilder::with_id("sleep_displays", "Sleep Displays").build(app)?;
+    menu.append(&sleep_displays)?;
+
+    // Mirror Displays, offered once there is something to mirror
+    if let Some(current) = current {
+        let mirrored = !current.mirror_groups.is_empty();
+        if mirrored || current.displays.iter().filter(|d| d.enabled).count() > 1 {
+            let mirror = CheckMenuItemBuilder::with_id("mirror_displays", "Mirror Displays")
+                .checked(mirrored)
+                .build(app)?;
+            menu.append(&mirror)?;
+        }
+    }
+
+    // Undo Last Change
+    let revert = MenuItemBuilder::with_id("revert_last", "Undo Last Change")
+        .accelerator("Cmd+Z")
+        .build(app)?;
+    menu.append(&revert)?;
+
+    // Pause Hotkeys
+    let pause_hotkeys = CheckMenuItemBuilder::with_id("pause_hotkeys", "Pause Hotkeys")
+        .checked(!hotkeys::is_enabled())
+        .build(app)?;
+    menu.append(&pause_hotkeys)?;
+
+    // Launch at Login, left out where login items aren't supported
+    if let Ok(enabled) = login_item::is_enabled() {
+        let launch_at_login = CheckMenuItemBuilder::with_id("launch_at_login", "Launch at Login")
+            .checked(enabled)
+            .build(app)?;
+        menu.append(&launch_at_login)?;
+    }
+
+    // Setup Assistant
+    let setup = MenuItemBuilder::with_id("setup_assistant", "Setup Assistant…").build(app)?;
+    menu.append(&setup)?;
+
     // Separator
     menu.append(&PredefinedMenuItem::separator(app)?)?;
 
@@ -75,12 +168,71 @@ fn create_tray_menu<R: Runtime>(app: &impl Manager<R>) -> Result<Menu<R>, Box<dy
     Ok(menu)
 }
 
+/// Number of recently applied presets listed at the top of the menu
+const RECENT_PRESET_COUNT: usize = 3;
+
+/// Append the most recently applied presets under a "Recent Presets" header.
+///
+/// Returns whether anything was added; nothing is when no preset has been applied yet.
+fn append_recent_presets<R: Runtime>(
+    app: &impl Manager<R>,
+    menu: &Menu<R>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let store = app.state::<AppState>().presets();
+    let recent = recent_presets(&store, RECENT_PRESET_COUNT);
+    if recent.is_empty() {
+        return Ok(false);
+    }
+
+    let header = MenuItemBuilder::with_id("recent_presets", "Recent Presets")
+        .enabled(false)
+        .build(app)?;
+    menu.append(&header)?;
+    for preset in recent {
+        // Prefixed so the ID differs from the preset's item in the submenu
+        let item = MenuItemBuilder::with_id(format!("recent_preset_{}", preset.id), &preset.name).build(app)?;
+        menu.append(&item)?;
+    }
+
+    Ok(true)
+}
+
 /// Create the presets submenu.
-fn create_presets_submenu<R: Runtime>(app: &impl Manager<R>) -> Result<Submenu<R>, Box<dyn std::error::Error>> {
+///
+/// Presets in a folder are grouped into a nested submenu per folder, listed
+/// alphabetically before the presets without a folder.
+fn create_presets_submenu<R: Runtime>(
+    app: &impl Manager<R>,
+    current: Option<&DisplayConfig>,
+) -> Result<Submenu<R>, Box<dyn std::error::Error>> {
     let presets_menu = Submenu::with_id(app, "presets", "Quick Presets", true)?;
 
-    // Note: Presets will be loaded asynchronously and the menu will be updated
-    // via update_tray_menu() once presets are available
+    let store = app.state::<AppState>().presets();
+    let displays = current.map(|c| c.displays.as_slice());
+    let active_id = current
+        .and_then(|c| active_preset(&store, c))
+        .map(|p| p.id.clone());
+    let is_active = |preset: &Preset| active_id.as_deref() == Some(preset.id.as_str());
+
+    let mut folders: BTreeMap<&str, Vec<&Preset>> = BTreeMap::new();
+    for preset in &store.presets {
+        if let Some(folder) = preset.folder.as_deref() {
+            folders.entry(folder).or_default().push(preset);
+        }
+    }
+    for (folder, presets) in &folders {
+        let folder_menu = Submenu::with_id(app, format!("preset_folder_{}", folder), *folder, true)?;
+        for preset in presets {
+            folder_menu.append(&create_preset_item(app, preset, displays, is_active(preset))?)?;
+        }
+        presets_menu.append(&folder_menu)?;
+    }
+    for preset in store.presets.iter().filter(|p| p.folder.is_none()) {
+        presets_menu.append(&create_preset_item(app, preset, displays, is_active(preset))?)?;
+    }
+    if !store.presets.is_empty() {
+        presets_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
 
     // Add "Manage Presets" option
     let manage_presets = MenuItemBuilder::with_id("manage_presets", "Manage Presets...")
@@ -90,16 +242,151 @@ fn create_presets_submenu<R: Runtime>(app: &impl Manager<R>) -> Result<Submenu<R
     Ok(presets_menu)
 }
 
+/// Create the menu item that applies a preset.
+///
+/// The active preset is checked and the preset's hotkey is shown as
+/// accelerator text. Presets whose displays aren't connected are greyed out;
+/// if the displays can't be read (`displays` is `None`), the item is left
+/// enabled rather than blocking the menu.
+fn create_preset_item<R: Runtime>(
+    app: &impl Manager<R>,
+    preset: &Preset,
+    displays: Option<&[Display]>,
+    active: bool,
+) -> Result<CheckMenuItem<R>, Box<dyn std::error::Error>> {
+    let applicable = displays.is_none_or(|d| missing_displays(preset, d).is_empty());
+    let label = if applicable {
+        preset.name.clone()
+    } else {
+        format!("{} (display not connected)", preset.name)
+    };
+
+    let builder = || {
+        CheckMenuItemBuilder::with_id(format!("preset_{}", preset.id), &label)
+            .enabled(applicable)
+            .checked(active)
+    };
+
+    // Sequences have no accelerator form, and a hotkey the menu can't parse
+    // shouldn't cost the preset its item
+    let hotkey = preset
+        .hotkey
+        .clone()
+        .or_else(|| hotkeys::shortcut_for(hotkeys::HotkeyTarget::Preset, &preset.id));
+    if let Some(hotkey) = hotkey.filter(|h| hotkeys::parse_strokes(h).is_ok_and(|s| s.len() == 1)) {
+        if let Ok(item) = builder().accelerator(hotkey).build(app) {
+            return Ok(item);
+        }
+    }
+
+    Ok(builder().build(app)?)
+}
+
+/// Most resolutions listed per display in the tray
+const MAX_TRAY_RESOLUTIONS: usize = 12;
+
+/// Create the displays submenu with a resolution submenu per display.
+///
+/// Returns `None` if the displays haven't been read or none reports its modes.
+fn create_displays_submenu<R: Runtime>(
+    app: &impl Manager<R>,
+    current: Option<&DisplayConfig>,
+) -> Result<Option<Submenu<R>>, Box<dyn std::error::Error>> {
+    let Some(current) = current else {
+        return Ok(None);
+    };
+
+    let displays_menu = Submenu::with_id(app, "displays", "Displays", true)?;
+    let mut any = false;
+    for display in current.displays.iter().filter(|d| d.enabled) {
+        let resolutions = common_resolutions(display);
+        if resolutions.is_empty() {
+            continue;
+        }
+
+        let name = display.name.clone().unwrap_or_else(|| display.id.clone());
+        let display_menu = Submenu::with_id(app, format!("display_{}", display.id), name, true)?;
+        for resolution in resolutions {
+            let label = resolution.replace('x', " × ");
+            let item = CheckMenuItemBuilder::with_id(format!("resolution_{}_{}", display.id, resolution), label)
+                .checked(resolution == display.resolution)
+                .build(app)?;
+            display_menu.append(&item)?;
+        }
+        displays_menu.append(&display_menu)?;
+        any = true;
+    }
+
+    Ok(any.then_some(displays_menu))
+}
+
+/// Resolutions offered in the tray for a display, largest first.
+///
+/// Only modes with the display's current scaling are listed, since a HiDPI
+/// display also advertises every unscaled mode. The current resolution is
+/// always included.
+fn common_resolutions(display: &Display) -> Vec<String> {
+    let scaling = display
+        .scaling
+        .or_else(|| display.modes.iter().find(|m| m.current).map(|m| m.scaling));
+    let area = |resolution: &str| {
+        resolution
+            .split_once('x')
+            .and_then(|(w, h)| Some(w.parse::<u64>().ok()? * h.parse::<u64>().ok()?))
+            .unwrap_or(0)
+    };
+
+    let mut resolutions: Vec<String> = Vec::new();
+    for mode in &display.modes {
+        if scaling.is_some_and(|s| s != mode.scaling) || resolutions.contains(&mode.resolution) {
+            continue;
+        }
+        resolutions.push(mode.resolution.clone());
+    }
+    resolutions.sort_by_key(|r| std::cmp::Reverse(area(r)));
+    resolutions.truncate(MAX_TRAY_RESOLUTIONS);
+
+    if !display.resolution.is_empty() && !resolutions.contains(&display.resolution) {
+        resolutions.push(display.resolution.clone());
+        resolutions.sort_by_key(|r| std::cmp::Reverse(area(r)));
+    }
+    resolutions
+}
+
+/// Create the workspaces submenu from the saved workspaces.
+fn create_workspaces_submenu<R: Runtime>(app: &impl Manager<R>) -> Result<Submenu<R>, Box<dyn std::error::Error>> {
+    let workspaces_menu = Submenu::with_id(app, "workspaces", "Workspaces", true)?;
+
+    let store = crate::workspaces::read_workspaces().unwrap_or_default();
+    for workspace in &store.workspaces {
+        let item = MenuItemBuilder::with_id(format!("workspace_{}", workspace.id), &workspace.name).build(app)?;
+        workspaces_menu.append(&item)?;
+    }
+
+    if store.workspaces.is_empty() {
+        let empty = MenuItemBuilder::with_id("workspaces_empty", "No Workspaces")
+            .enabled(false)
+            .build(app)?;
+        workspaces_menu.append(&empty)?;
+    }
+
+    Ok(workspaces_menu)
+}
+
 /// Handle tray icon events.
-fn handle_tray_event<R: Runtime>(_tray: &TrayIcon<R>, event: TrayIconEvent) {
+fn handle_tray_event<R: Runtime>(tray: &TrayIcon<R>, event: TrayIconEvent) {
     match event {
         TrayIconEvent::Click {
             button: MouseButton::Left,
             button_state: MouseButtonState::Down,
+            rect,
             ..
         } => {
-            // Left click - could show a quick status popup
-            println!("[Tray] Left click");
+            // Left click - show or hide the quick panel
+            log::info!("[Tray] Left click");
+            if let Err(e) = quick_panel::toggle(tray.app_handle(), rect) {
+                log::error!("[Tray] {}", e);
+            }
         }
         TrayIconEvent::Click {
             button: MouseButton::Right,
@@ -107,7 +394,7 @@ fn handle_tray_event<R: Runtime>(_tray: &TrayIcon<R>, event: TrayIconEvent) {
             ..
         } => {
             // Right click - show the menu (this is default behavior)
-            println!("[Tray] Right click - showing menu");
+            log::info!("[Tray] Right click - showing menu");
         }
         _ => {}
     }
@@ -120,7 +407,7 @@ fn handle_tray_event<R: Runtime>(_tray: &TrayIcon<R>, event: TrayIconEvent) {
 /// # Arguments
 /// * `app` - Application handle
 /// * `id` - Menu item ID that was clicked
-pub fn handle_tray_menu_event<R: Runtime>(app: &AppHandle<R>, id: &str) {
+pub fn handle_tray_menu_event(app: &AppHandle, id: &str) {
     match id {
         "show_hide" => {
             toggle_window_visibility(app);
@@ -128,6 +415,43 @@ pub fn handle_tray_menu_event<R: Runtime>(app: &AppHandle<R>, id: &str) {
         "refresh" => {
             refresh_displays(app);
         }
+        "revert_last" => {
+            revert_from_tray(app);
+        }
+        "disable_external" => {
+            disable_external_from_tray(app);
+        }
+        "sleep_displays" => {
+            tauri::async_runtime::spawn(async {
+                if let Err(e) = display_sleep::sleep_displays().await {
+                    log::error!("[Tray] Failed to sleep displays: {}", e);
+                    show_tray_notification("DPUI", &format!("Failed to sleep displays: {}", e));
+                }
+            });
+        }
+        "mirror_displays" => {
+            toggle_mirroring_from_tray(app);
+        }
+        "pause_hotkeys" => {
+            if let Err(e) = hotkeys::set_enabled(app, !hotkeys::is_enabled()) {
+                log::error!("[Tray] Failed to pause hotkeys: {}", e);
+            }
+        }
+        "launch_at_login" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let enabled = login_item::is_enabled().unwrap_or(false);
+                if let Err(e) = login_item::set_launch_at_login(app.clone(), !enabled).await {
+                    log::error!("[Tray] {}", e);
+                    show_tray_notification("DPUI", &e);
+                    let _ = update_tray_menu(&app);
+                }
+            });
+        }
+        "setup_assistant" => {
+            show_main_window(app);
+            let _ = app.emit("open-setup-assistant", ());
+        }
         "manage_presets" => {
             show_main_window(app);
         }
@@ -137,33 +461,52 @@ pub fn handle_tray_menu_event<R: Runtime>(app: &AppHandle<R>, id: &str) {
         id if id.starts_with("preset_") => {
             apply_preset_from_tray(app, id);
         }
+        id if id.starts_with("recent_preset_") => {
+            apply_preset_from_tray(app, &id["recent_".len()..]);
+        }
+        id if id.starts_with("resolution_") => {
+            set_resolution_from_tray(app, id);
+        }
+        id if id.starts_with("workspace_") => {
+            if let Some(workspace_id) = id.strip_prefix("workspace_") {
+                log::info!("[Tray] Apply workspace: {}", workspace_id);
+                spawn_apply_workspace(app, workspace_id.to_string());
+            }
+        }
         _ => {
-            println!("[Tray] Unknown menu item: {}", id);
+            log::info!("[Tray] Unknown menu item: {}", id);
         }
     }
 }
 
 /// Toggle main window visibility.
-fn toggle_window_visibility<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        if window.is_visible().unwrap_or(false) {
+///
+/// In headless mode the window is created the first time it's shown.
+fn toggle_window_visibility(app: &AppHandle) {
+    match headless::main_window(app) {
+        Ok(window) if window.is_visible().unwrap_or(false) => {
             let _ = window.hide();
-            println!("[Tray] Window hidden");
-        } else {
+            log::info!("[Tray] Window hidden");
+        }
+        Ok(window) => {
             let _ = window.show();
             let _ = window.set_focus();
-            println!("[Tray] Window shown");
+            log::info!("[Tray] Window shown");
         }
+        Err(e) => log::error!("[Tray] {}", e),
     }
 }
 
 /// Show the main window and bring it to front.
-fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.set_focus();
-        let _ = window.unminimize();
-        println!("[Tray] Main window shown");
+fn show_main_window(app: &AppHandle) {
+    match headless::main_window(app) {
+        Ok(window) => {
+            let _ = window.show();
+            let _ = window.set_focus();
+            let _ = window.unminimize();
+            log::info!("[Tray] Main window shown");
+        }
+        Err(e) => log::error!("[Tray] {}", e),
     }
 }
 
@@ -171,24 +514,93 @@ fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
 fn refresh_displays<R: Runtime>(app: &AppHandle<R>) {
     // Emit event to frontend to refresh displays
     let _ = app.emit("refresh-displays", ());
-    println!("[Tray] Refresh displays requested");
+    log::info!("[Tray] Refresh displays requested");
+}
+
+/// Revert the last applied configuration.
+fn revert_from_tray<R: Runtime>(app: &AppHandle<R>) {
+    match crate::undo::revert_last(app) {
+        Ok(()) => {
+            let _ = app.emit("refresh-displays", ());
+            log::info!("[Tray] Reverted last apply");
+        }
+        Err(e) => log::error!("[Tray] Failed to revert last apply: {}", e),
+    }
 }
 
 /// Apply a preset from the tray menu.
-fn apply_preset_from_tray<R: Runtime>(app: &AppHandle<R>, menu_id: &str) {
+fn apply_preset_from_tray(app: &AppHandle, menu_id: &str) {
     // Extract preset ID from menu ID (format: "preset_<uuid>")
     if let Some(preset_id) = menu_id.strip_prefix("preset_") {
-        // Emit event to frontend to apply the preset
-        let _ = app.emit("apply-preset-from-tray", preset_id);
-        println!("[Tray] Apply preset: {}", preset_id);
+        log::info!("[Tray] Apply preset: {}", preset_id);
+        spawn_apply_preset(app, preset_id.to_string(), HistorySource::Tray);
+    }
+}
 
-        // Show a native notification (optional)
-        #[cfg(target_os = "macos")]
-        {
-            // You could add native notification here
-            println!("[Tray] Preset applied via tray: {}", preset_id);
+/// Turn off every external display from the tray, reporting failures to the user.
+fn disable_external_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match turn_off_external_displays(HistorySource::Tray).await {
+            Ok(count) => log::info!("[Tray] Turned off {} external displays", count),
+            Err(e) => {
+                log::error!("[Tray] Failed to turn off external displays: {}", e);
+                show_tray_notification("DPUI", &format!("Failed to turn off external displays: {}", e));
+            }
         }
-    }
+        let _ = app.emit("refresh-displays", ());
+    });
+}
+
+/// Mirror all displays, or restore the previous arrangement if they are mirrored.
+fn toggle_mirroring_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mirrored = get_displays().await.is_ok_and(|c| !c.mirror_groups.is_empty());
+        let result = if mirrored {
+            restore_from_mirroring(&app).await
+        } else {
+            mirror_all_displays().await
+        };
+
+        if let Err(e) = result {
+            log::error!("[Tray] Failed to change mirroring: {}", e);
+            show_tray_notification("DPUI", &format!("Failed to change mirroring: {}", e));
+        }
+        // Sync the checkbox with the actual state
+        if let Err(e) = update_tray_menu(&app) {
+            log::error!("[Tray] Failed to update tray menu: {}", e);
+        }
+        let _ = app.emit("refresh-displays", ());
+    });
+}
+
+/// Switch a display's resolution from the tray, reporting failures to the user.
+fn set_resolution_from_tray(app: &AppHandle, menu_id: &str) {
+    // Menu ID format: "resolution_<display id>_<width>x<height>"
+    let Some((display_id, resolution)) = menu_id.strip_prefix("resolution_").and_then(|r| r.rsplit_once('_')) else {
+        return;
+    };
+    let Some((width, height)) = resolution
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+    else {
+        return;
+    };
+
+    log::info!("[Tray] Set display {} to {}", display_id, resolution);
+    let app = app.clone();
+    let display_id = display_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = change_resolution(&display_id, width, height, None, HistorySource::Tray).await {
+            log::error!("[Tray] Failed to set resolution: {}", e);
+            show_tray_notification("DPUI", &format!("Failed to set resolution: {}", e));
+        }
+        // Move the checkmark, or put it back after a failure
+        if let Err(e) = update_tray_menu(&app) {
+            log::error!("[Tray] Failed to update tray menu: {}", e);
+        }
+    });
 }
 
 /// Update the tray menu with new presets.
@@ -198,35 +610,152 @@ fn apply_preset_from_tray<R: Runtime>(app: &AppHandle<R>, menu_id: &str) {
 /// # Arguments
 /// * `app` - Application handle
 pub fn update_tray_menu<R: Runtime>(app: &AppHandle<R>) -> Result<(), Box<dyn std::error::Error>> {
-    // Recreate the menu with updated presets
-    let menu = create_tray_menu(app)?;
+    // Recreate the menu with updated presets, from the cached displays only:
+    // this runs on the main thread from menu handlers
+    let current = cached_displays();
+    let menu = create_tray_menu(app, current.as_ref())?;
 
     // Update the tray icon's menu
     if let Some(tray) = app.tray_by_id("main") {
         tray.set_menu(Some(menu))?;
-        println!("[Tray] Menu updated");
+        log::info!("[Tray] Menu updated");
+    }
+    update_tray_status(app);
+    if current.is_none() {
+        refresh_displays_in_background(app);
     }
 
     Ok(())
 }
 
-/// Show tray notification.
+/// Read the displays off the main thread, then rebuild the menu with them.
+fn refresh_displays_in_background<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Only rebuild with a cached list, or the rebuild would land back here
+        if get_displays().await.is_ok() && cached_displays().is_some() {
+            if let Err(e) = update_tray_menu(&app) {
+                log::error!("[Tray] Failed to update tray menu: {}", e);
+            }
+        }
+    });
+}
+
+/// Record whether the most recent apply failed and refresh the tray icon.
+pub fn set_last_apply_failed<R: Runtime>(app: &AppHandle<R>, failed: bool) {
+    if LAST_APPLY_FAILED.swap(failed, Ordering::SeqCst) != failed {
+        update_tray_status(app);
+    }
+}
+
+/// Update the tray icon, title and tooltip to reflect the display state.
 ///
-/// Displays a temporary notification near the tray icon.
+/// The title shows "enabled/total" while some connected displays are turned
+/// off, the icon gets a red dot while the last apply has failed, and the
+/// tooltip summarizes the layout.
+fn update_tray_status<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+
+    let current = cached_displays();
+    let displays = current.as_ref().map(|c| c.displays.as_slice()).unwrap_or_default();
+    let enabled = displays.iter().filter(|d| d.enabled).count();
+    let title = (enabled < displays.len()).then(|| format!("{}/{}", enabled, displays.len()));
+    if let Err(e) = tray.set_title(title) {
+        log::error!("[Tray] Failed to set title: {}", e);
+    }
+
+    let active = current.as_ref().zip(app.try_state::<AppState>()).and_then(|(current, state)| {
+        let store = state.presets();
+        active_preset(&store, current).map(|p| p.name.clone())
+    });
+    if let Err(e) = tray.set_tooltip(Some(layout_summary(displays, active.as_deref()))) {
+        log::error!("[Tray] Failed to set tooltip: {}", e);
+    }
+
+    let icon = app.default_window_icon().map(|icon| {
+        if LAST_APPLY_FAILED.load(Ordering::SeqCst) {
+            let rgba = with_failure_badge(icon.rgba(), icon.width(), icon.height());
+            Image::new_owned(rgba, icon.width(), icon.height())
+        } else {
+            icon.clone().to_owned()
+        }
+    });
+    if let Err(e) = tray.set_icon(icon) {
+        log::error!("[Tray] Failed to set icon: {}", e);
+    }
+}
+
+/// Tooltip text such as "3 displays · Main 2560x1440 · Preset: Office".
+///
+/// The main display is the one at the origin; the preset part is left out
+/// when no preset matches the layout.
+fn layout_summary(displays: &[Display], active_preset: Option<&str>) -> String {
+    let enabled: Vec<&Display> = displays.iter().filter(|d| d.enabled).collect();
+    if enabled.is_empty() {
+        return "DPUI - Display Manager".to_string();
+    }
+
+    let mut parts = vec![match enabled.len() {
+        1 => "1 display".to_string(),
+        n => format!("{} displays", n),
+    }];
+    if let Some(main) = enabled.iter().find(|d| d.origin == (0, 0)).or(enabled.first()) {
+        let name = main.name.as_deref().unwrap_or("Main");
+        parts.push(format!("{} {}", name, main.resolution));
+    }
+    if let Some(preset) = active_preset {
+        parts.push(format!("Preset: {}", preset));
+    }
+    parts.join(" · ")
+}
+
+/// Copy of an RGBA icon with a red dot in its bottom-right corner.
+fn with_failure_badge(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let mut badged = rgba.to_vec();
+    let radius = width.min(height) as f32 / 4.0;
+    let (cx, cy) = (width as f32 - radius, height as f32 - radius);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                if let Some(pixel) = badged.get_mut(i..i + 4) {
+                    pixel.copy_from_slice(&[0xE5, 0x3E, 0x3E, 0xFF]);
+                }
+            }
+        }
+    }
+    badged
+}
+
+/// Let `show_tray_notification` post through the notification plugin.
+pub fn init_notifications(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Post a notification to Notification Center.
+///
+/// Used for results of actions without a window to report to, such as
+/// hotkeys and tray items. Does nothing when notifications are turned off in
+/// settings. Posted through the notification plugin, which delivers it in the
+/// background so callers never wait for it.
 ///
 /// # Arguments
 /// * `title` - Notification title
 /// * `message` - Notification message
-#[allow(dead_code)]
 pub fn show_tray_notification(title: &str, message: &str) {
-    // This would typically use native notification APIs
-    // For now, just log it
-    println!("[Tray Notification] {}: {}", title, message);
+    if !settings::current().notifications_enabled {
+        return;
+    }
+    log::info!("[Tray Notification] {}: {}", title, message);
 
-    // On macOS, you could use:
-    #[cfg(target_os = "macos")]
-    {
-        // Implementation would go here using native macOS notification center
-        // or a third-party crate like notify-rust
+    let Some(app) = APP.get() else {
+        return;
+    };
+    if let Err(e) = app.notification().builder().title(title).body(message).show() {
+        log::error!("[Tray Notification] Failed to post notification: {}", e);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,130 @@
+//! Verbose command tracing for developer mode.
+//!
+//! When `developer_mode` is enabled in settings, every spawned command is
+//! recorded with its full arguments, raw output, timing and any parser
+//! decisions to `command-trace.jsonl` in the config directory. The most recent
+//! trace is also kept in memory so it can be attached to bug reports.
+
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::Output;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::presets::get_config_dir;
+use crate::settings;
+
+/// Rotate the trace file once it grows beyond this size
+const MAX_TRACE_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+static LAST_TRACE: Mutex<Option<CommandTrace>> = Mutex::new(None);
+
+/// A complete record of one spawned command.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandTrace {
+    /// RFC 3339 timestamp of when the command was spawned
+    pub started_at: String,
+    /// Program that was executed
+    pub program: String,
+    /// Full argument list, one entry per argv element
+    pub args: Vec<String>,
+    /// Process exit code, if the process ran to completion
+    pub exit_code: Option<i32>,
+    /// Raw stdout
+    pub stdout: String,
+    /// Raw stderr
+    pub stderr: String,
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: u64,
+    /// Decisions made while parsing the output
+    pub parser_notes: Vec<String>,
+    /// Error reported to the caller, if any
+    pub error: Option<String>,
+    #[serde(skip)]
+    started: Instant,
+}
+
+impl CommandTrace {
+    /// Begin tracing a command that is about to be spawned.
+    pub fn start(program: &str, args: &[String]) -> Self {
+        Self {
+            started_at: chrono::Utc::now().to_rfc3339(),
+            program: program.to_string(),
+            args: args.to_vec(),
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 0,
+            parser_notes: Vec::new(),
+            error: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Capture the output of the finished process.
+    pub fn finish(&mut self, output: &Output) {
+        self.duration_ms = self.started.elapsed().as_millis() as u64;
+        self.exit_code = output.status.code();
+        self.stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        self.stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    }
+
+    /// Record the error returned to the caller.
+    pub fn fail(&mut self, error: &str) {
+        self.duration_ms = self.started.elapsed().as_millis() as u64;
+        self.error = Some(error.to_string());
+    }
+}
+
+/// Whether developer mode tracing is enabled.
+pub fn is_enabled() -> bool {
+    settings::current().developer_mode
+}
+
+/// Store a finished trace if developer mode is enabled.
+///
+/// Failures to write the trace file are logged but never surfaced to the caller.
+pub fn record(trace: CommandTrace) {
+    if !is_enabled() {
+        return;
+    }
+
+    if let Err(e) = append_to_file(&trace) {
+        log::error!("[Trace] Failed to write trace file: {}", e);
+    }
+
+    if let Ok(mut last) = LAST_TRACE.lock() {
+        *last = Some(trace);
+    }
+}
+
+/// Append a trace as one JSON line, rotating the file when it grows too large.
+fn append_to_file(trace: &CommandTrace) -> Result<(), String> {
+    let path = get_config_dir()?.join("command-trace.jsonl");
+
+    if fs::metadata(&path).map(|m| m.len() > MAX_TRACE_FILE_BYTES).unwrap_or(false) {
+        fs::rename(&path, path.with_extension("jsonl.old"))
+            .map_err(|e| format!("Failed to rotate trace file: {}", e))?;
+    }
+
+    let line = serde_json::to_string(trace).map_err(|e| format!("Failed to serialize trace: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open trace file: {}", e))?;
+
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to append trace: {}", e))
+}
+
+/// Get the trace of the most recently spawned command.
+///
+/// # Returns
+/// * `Some(CommandTrace)` - Last recorded trace
+/// * `None` - If developer mode is off or no command has run yet
+#[tauri::command]
+pub async fn get_last_command_trace() -> Option<CommandTrace> {
+    LAST_TRACE.lock().ok().and_then(|last| last.clone())
+}
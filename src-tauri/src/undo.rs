@@ -0,0 +1,224 @@
+//! Undo support for applied configurations.
+//!
+//! Every successful `apply_config` pushes the layout it replaced, mirror groups
+//! included, onto a bounded stack held in Tauri-managed state.
+//! `revert_last_apply` pops the most recent snapshot and restores it, so
+//! several levels of undo work.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::displayplacer::{execute_displayplacer, layout_command, run_blocking, tokenize_config, DisplayConfig};
+use crate::rollback;
+
+/// Maximum number of layouts kept for undo
+const MAX_DEPTH: usize = 20;
+
+/// Used to reach the managed stack from changes made outside a command
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Stack of layouts replaced by previous applies, most recent last.
+#[derive(Default)]
+pub struct UndoStack {
+    /// Snapshots with the ID `push` returned for them
+    snapshots: Mutex<Vec<(u64, DisplayConfig)>>,
+    next_id: AtomicU64,
+}
+
+impl UndoStack {
+    /// Remember a layout that is about to be replaced.
+    ///
+    /// Consecutive identical layouts are stored once.
+    ///
+    /// # Returns
+    /// * `Some(u64)` - ID of the snapshot holding `layout`, for `take`
+    /// * `None` - The stack is unusable
+    pub fn push(&self, layout: DisplayConfig) -> Option<u64> {
+        let mut snapshots = self.snapshots.lock().ok()?;
+
+        if let Some((id, top)) = snapshots.last() {
+            if layout_command(top) == layout_command(&layout) {
+                return Some(*id);
+            }
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        snapshots.push((id, layout));
+        if snapshots.len() > MAX_DEPTH {
+            snapshots.remove(0);
+        }
+        Some(id)
+    }
+
+    /// Take the most recently replaced layout.
+    pub fn pop(&self) -> Option<DisplayConfig> {
+        self.snapshots.lock().ok()?.pop().map(|(_, layout)| layout)
+    }
+
+    /// Take the snapshot `push` returned `id` for, wherever it is in the stack.
+    pub fn take(&self, id: u64) -> Option<DisplayConfig> {
+        let mut snapshots = self.snapshots.lock().ok()?;
+        let index = snapshots.iter().position(|(snapshot_id, _)| *snapshot_id == id)?;
+        Some(snapshots.remove(index).1)
+    }
+
+    /// Number of layouts available to revert to.
+    pub fn depth(&self) -> usize {
+        self.snapshots.lock().map(|s| s.len()).unwrap_or(0)
+    }
+}
+
+/// Keep the app handle so `remember` can reach the undo stack.
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Note a successful change that doesn't have an `AppHandle` at hand.
+///
+/// Pushes the layout it replaced, if known, and keeps any layout awaiting
+/// confirmation, as `apply_layout_now` does.
+pub(crate) fn changed(replaced: Option<DisplayConfig>) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    rollback::cancel(app);
+    if let Some(layout) = replaced {
+        app.state::<UndoStack>().push(layout);
+    }
+}
+
+/// displayplacer arguments that restore a snapshot, mirror groups included.
+fn restore_args(layout: &DisplayConfig) -> Result<Vec<String>, String> {
+    tokenize_config(&layout_command(layout))
+}
+
+/// Restore the layout that was active before the last apply.
+///
+/// Shared by the command and the tray menu item.
+///
+/// A layout awaiting confirmation is the one reverted here, so its rollback
+/// timer is cancelled rather than left to revert a second time.
+pub fn revert_last<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    rollback::cancel(app);
+    let stack = app.state::<UndoStack>();
+    let layout = stack.pop().ok_or("Nothing to revert")?;
+    restore(app, &stack, layout)
+}
+
+/// Restore the snapshot `push` returned `id` for, e.g. the layout an
+/// unconfirmed apply replaced.
+pub fn revert_to<R: Runtime>(app: &AppHandle<R>, id: u64) -> Result<(), String> {
+    let stack = app.state::<UndoStack>();
+    let layout = stack.take(id).ok_or("The previous layout is no longer available")?;
+    restore(app, &stack, layout)
+}
+
+fn restore<R: Runtime>(app: &AppHandle<R>, stack: &UndoStack, layout: DisplayConfig) -> Result<(), String> {
+    if let Err(e) = restore_args(&layout).and_then(execute_displayplacer) {
+        // Keep the snapshot so the user can try again
+        stack.push(layout);
+        return Err(e);
+    }
+
+    let _ = app.emit("config-reverted", stack.depth());
+    log::info!("[Undo] Reverted to previous layout ({} more available)", stack.depth());
+    Ok(())
+}
+
+/// Revert the last applied configuration.
+///
+/// # Returns
+/// * `Ok(())` - Previous layout restored
+/// * `Err(String)` - Error message if there is nothing to revert or displayplacer fails
+#[tauri::command]
+pub async fn revert_last_apply(app: AppHandle) -> Result<(), String> {
+    run_blocking(move || revert_last(&app)).await
+}
+
+/// Get how many levels of undo are available.
+#[tauri::command]
+pub async fn get_undo_depth(stack: State<'_, UndoStack>) -> Result<usize, String> {
+    Ok(stack.depth())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayplacer::{parse_config_displays, MirrorGroup};
+
+    fn layout(x: i32) -> DisplayConfig {
+        DisplayConfig {
+            displays: parse_config_displays(&format!(
+                r#""id:A res:1728x1117 origin:(0,0) degree:0" "id:B res:2560x1440 origin:({},0) degree:0""#,
+                x
+            )),
+            mirror_groups: Vec::new(),
+            lid_closed: None,
+            builtin_active: false,
+            raw_command: String::new(),
+        }
+    }
+
+    fn origin(layout: Option<DisplayConfig>) -> Option<(i32, i32)> {
+        layout.map(|l| l.displays[1].origin)
+    }
+
+    #[test]
+    fn test_undo_stack() {
+        let stack = UndoStack::default();
+        assert!(stack.pop().is_none());
+
+        stack.push(layout(1728));
+        stack.push(layout(1728));
+        assert_eq!(stack.depth(), 1, "identical layouts are stored once");
+
+        stack.push(layout(-2560));
+        assert_eq!(origin(stack.pop()), Some((-2560, 0)));
+        assert_eq!(origin(stack.pop()), Some((1728, 0)));
+        assert_eq!(stack.depth(), 0);
+
+        for x in 0..(MAX_DEPTH as i32 + 5) {
+            stack.push(layout(x));
+        }
+        assert_eq!(stack.depth(), MAX_DEPTH);
+        assert_eq!(origin(stack.pop()), Some((MAX_DEPTH as i32 + 4, 0)));
+    }
+
+    #[test]
+    fn test_take_snapshot() {
+        let stack = UndoStack::default();
+        let guarded = stack.push(layout(1728)).unwrap();
+        assert_eq!(stack.push(layout(1728)), Some(guarded), "an identical layout shares the snapshot");
+
+        // A later change pushes its own snapshot on top
+        let later = stack.push(layout(-2560)).unwrap();
+        assert_ne!(later, guarded);
+
+        // Reverting the guarded apply restores its snapshot, not the top one
+        assert_eq!(origin(stack.take(guarded)), Some((1728, 0)));
+        assert!(stack.take(guarded).is_none());
+        assert_eq!(origin(stack.pop()), Some((-2560, 0)));
+    }
+
+    #[test]
+    fn test_restore_mirrored_layout() {
+        let mut mirrored = layout(0);
+        mirrored.mirror_groups = vec![MirrorGroup {
+            primary: "A".to_string(),
+            mirrors: vec!["B".to_string()],
+        }];
+
+        let stack = UndoStack::default();
+        stack.push(layout(0));
+        stack.push(mirrored.clone());
+        assert_eq!(stack.depth(), 2, "mirroring alone makes a different layout");
+
+        let args = restore_args(&stack.pop().unwrap()).unwrap();
+        assert_eq!(args, vec!["id:A+B res:1728x1117 enabled:true origin:(0,0) degree:0"]);
+
+        let args = restore_args(&stack.pop().unwrap()).unwrap();
+        assert_eq!(args.len(), 2);
+        assert!(args[1].starts_with("id:B "));
+    }
+}
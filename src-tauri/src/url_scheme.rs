@@ -0,0 +1,183 @@
+//! `dpui://` URL scheme.
+//!
+//! Lets Shortcuts, scripts and other apps drive DPUI with `open`:
+//!
+//! - `dpui://apply/<preset>` applies a preset, named by ID or name
+//! - `dpui://toggle/<display>` turns a display on or off
+//! - `dpui://snapshot/<name>` saves the current layout as a new preset
+//!
+//! macOS hands the URLs to the running app (launching it first if needed),
+//! which passes them to `handle_url`. The scheme itself is declared in
+//! `Info.plist`.
+
+// URLs only reach the app on macOS
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use tauri::{AppHandle, Manager};
+
+use crate::displayplacer::toggle_display;
+use crate::history::HistorySource;
+use crate::presets::{create_preset_from_current, find_preset_by_key, spawn_apply_preset};
+use crate::state::AppState;
+use crate::system_tray::show_tray_notification;
+
+const SCHEME: &str = "dpui";
+
+/// What a `dpui://` URL asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrlCommand {
+    /// Apply the preset with this ID or name
+    Apply(String),
+    /// Toggle the display with this ID
+    Toggle(String),
+    /// Save the current layout as a preset with this name
+    Snapshot(String),
+}
+
+impl UrlCommand {
+    /// The `dpui://` URL that runs this command.
+    pub fn to_url(&self) -> String {
+        let (action, target) = match self {
+            Self::Apply(target) => ("apply", target),
+            Self::Toggle(target) => ("toggle", target),
+            Self::Snapshot(target) => ("snapshot", target),
+        };
+        format!("{}://{}/{}", SCHEME, action, percent_encode(target))
+    }
+}
+
+/// Escape everything but unreserved characters as `%XX`.
+pub(crate) fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, e.g. "Home%20Office" to "Home Office".
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parse a `dpui://<action>/<target>` URL.
+pub fn parse_url(url: &str) -> Result<UrlCommand, String> {
+    let rest = url
+        .split_once("://")
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(SCHEME))
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| format!("Not a {}:// URL: {}", SCHEME, url))?;
+    // Query strings and fragments carry nothing
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+
+    let (action, target) = rest.split_once('/').unwrap_or((rest, ""));
+    let target = percent_decode(target.trim_end_matches('/'));
+    if target.trim().is_empty() {
+        return Err(format!("Missing target in {}", url));
+    }
+
+    match action.to_ascii_lowercase().as_str() {
+        "apply" => Ok(UrlCommand::Apply(target)),
+        "toggle" => Ok(UrlCommand::Toggle(target)),
+        "snapshot" => Ok(UrlCommand::Snapshot(target)),
+        _ => Err(format!("Unknown action \"{}\", expected apply, toggle or snapshot", action)),
+    }
+}
+
+/// Run the command of a URL DPUI was opened with, reporting failures to the user.
+pub fn handle_url(app: &AppHandle, url: &str) {
+    log::info!("[URL] Opened with {}", url);
+
+    let command = match parse_url(url) {
+        Ok(command) => command,
+        Err(e) => {
+            log::error!("[URL] {}", e);
+            show_tray_notification("DPUI", &e);
+            return;
+        }
+    };
+    run_command(app, command);
+}
+
+/// Carry out a command in the background, reporting failures to the user.
+///
+/// Shared with the AppleScript commands, which name the same actions.
+pub fn run_command(app: &AppHandle, command: UrlCommand) {
+    match command {
+        UrlCommand::Apply(key) => {
+            let preset_id = find_preset_by_key(&app.state::<AppState>().presets(), &key).map(|p| p.id.clone());
+            match preset_id {
+                Some(id) => spawn_apply_preset(app, id, HistorySource::External),
+                None => {
+                    log::error!("[URL] Preset {} not found", key);
+                    show_tray_notification("DPUI", &format!("Preset {} not found", key));
+                }
+            }
+        }
+        UrlCommand::Toggle(display_id) => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = toggle_display(&display_id, HistorySource::External).await {
+                    log::error!("[URL] Failed to toggle display {}: {}", display_id, e);
+                    show_tray_notification("DPUI", &format!("Failed to toggle display: {}", e));
+                }
+            });
+        }
+        UrlCommand::Snapshot(name) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                match create_preset_from_current(app.state::<AppState>(), name).await {
+                    Ok(preset) => show_tray_notification("DPUI", &format!("Saved: {}", preset.name)),
+                    Err(e) => {
+                        log::error!("[URL] Failed to save snapshot: {}", e);
+                        show_tray_notification("DPUI", &format!("Failed to save snapshot: {}", e));
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(parse_url("dpui://apply/Office"), Ok(UrlCommand::Apply("Office".to_string())));
+        assert_eq!(
+            parse_url("DPUI://Apply/Home%20Office/?source=shortcuts"),
+            Ok(UrlCommand::Apply("Home Office".to_string()))
+        );
+        assert_eq!(
+            parse_url("dpui://toggle/37D8832A-2D66-02CA-B9F7-8F30A301B230"),
+            Ok(UrlCommand::Toggle("37D8832A-2D66-02CA-B9F7-8F30A301B230".to_string()))
+        );
+        assert_eq!(parse_url("dpui://snapshot/Desk"), Ok(UrlCommand::Snapshot("Desk".to_string())));
+        let command = UrlCommand::Apply("Home Office/2".to_string());
+        assert_eq!(command.to_url(), "dpui://apply/Home%20Office%2F2");
+        assert_eq!(parse_url(&command.to_url()), Ok(command));
+        assert!(parse_url("dpui://apply/").is_err());
+        assert!(parse_url("dpui://rotate/1").is_err());
+        assert!(parse_url("https://apply/Office").is_err());
+    }
+}
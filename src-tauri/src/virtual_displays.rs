@@ -0,0 +1,329 @@
+//! Virtual displays without a monitor attached.
+//!
+//! macOS can host displays that exist only in software (`CGVirtualDisplay`,
+//! the private API BetterDummy and BetterDisplay use), e.g. for screen
+//! sharing or streaming from a headless Mac mini. They show up in
+//! displayplacer like any other display, so presets can arrange them.
+//!
+//! A virtual display lives only as long as DPUI keeps it, so the ones created
+//! are stored in settings and recreated at startup. Each gets a fixed serial
+//! number, which keeps its persistent ID stable across launches and presets
+//! referring to it valid.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::displayplacer::{invalidate_display_cache, Display};
+use crate::settings;
+
+/// Serial number of the first virtual display; later ones count up
+const FIRST_SERIAL: u32 = 0x4450_0001;
+
+const MIN_SIZE: (u32, u32) = (640, 480);
+const MAX_SIZE: (u32, u32) = (7680, 4320);
+
+/// A virtual display kept in settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VirtualDisplaySpec {
+    pub name: String,
+    /// Width in points
+    pub width: u32,
+    /// Height in points
+    pub height: u32,
+    /// Render at twice the resolution, like a Retina display
+    pub hidpi: bool,
+    /// Serial number the display reports, unique among virtual displays
+    pub serial: u32,
+}
+
+/// Virtual displays created in this session, by serial number
+static ACTIVE: Mutex<Vec<(u32, native::VirtualDisplay)>> = Mutex::new(Vec::new());
+
+fn validate_size(width: u32, height: u32) -> Result<(), String> {
+    if !(MIN_SIZE.0..=MAX_SIZE.0).contains(&width) || !(MIN_SIZE.1..=MAX_SIZE.1).contains(&height) {
+        return Err(format!(
+            "Invalid size {}x{}; expected between {}x{} and {}x{}",
+            width, height, MIN_SIZE.0, MIN_SIZE.1, MAX_SIZE.0, MAX_SIZE.1
+        ));
+    }
+    Ok(())
+}
+
+/// The serial number for a new virtual display.
+fn next_serial(specs: &[VirtualDisplaySpec]) -> u32 {
+    specs.iter().map(|s| s.serial + 1).max().unwrap_or(FIRST_SERIAL)
+}
+
+/// Flag the virtual displays in a display list and name them.
+pub(crate) fn mark(displays: &mut [Display], specs: &[VirtualDisplaySpec]) {
+    for display in displays.iter_mut() {
+        let serial = display
+            .serial_id
+            .as_deref()
+            .and_then(|s| s.trim_start_matches('s').parse::<u32>().ok());
+        if let Some(spec) = specs.iter().find(|s| Some(s.serial) == serial) {
+            display.virtual_display = true;
+            display.name = Some(spec.name.clone());
+            display.vendor = Some("DPUI".to_string());
+        }
+    }
+}
+
+fn activate(spec: &VirtualDisplaySpec) -> Result<(), String> {
+    let display = native::create(spec)?;
+    ACTIVE
+        .lock()
+        .map_err(|_| "Virtual display lock poisoned".to_string())?
+        .push((spec.serial, display));
+    invalidate_display_cache();
+
+    log::info!("[VirtualDisplays] Created {} ({}x{})", spec.name, spec.width, spec.height);
+    Ok(())
+}
+
+/// Recreate the virtual displays stored in settings; called once at startup.
+pub fn start() -> Result<(), String> {
+    let specs = settings::current().virtual_displays;
+    let failures: Vec<String> = specs
+        .iter()
+        .filter_map(|spec| activate(spec).err().map(|e| format!("{}: {}", spec.name, e)))
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("; "))
+    }
+}
+
+/// Create a virtual display and keep it across launches
+///
+/// Not `async`, so it runs on the main thread, whose dispatch queue the
+/// display is attached to.
+///
+/// # Arguments
+/// * `name` - Name shown for the display
+/// * `width` - Width in points
+/// * `height` - Height in points
+/// * `hidpi` - Render at twice the resolution; defaults to `true`
+///
+/// # Returns
+/// * `Ok(VirtualDisplaySpec)` - The new display
+/// * `Err(String)` - Error message if the size is out of range or macOS refused the display
+#[tauri::command]
+pub fn create_virtual_display(
+    name: String,
+    width: u32,
+    height: u32,
+    hidpi: Option<bool>,
+) -> Result<VirtualDisplaySpec, String> {
+    validate_size(width, height)?;
+    let name = name.trim();
+    let mut specs = settings::current().virtual_displays;
+    let spec = VirtualDisplaySpec {
+        name: if name.is_empty() { "DPUI Virtual Display".to_string() } else { name.to_string() },
+        width,
+        height,
+        hidpi: hidpi.unwrap_or(true),
+        serial: next_serial(&specs),
+    };
+
+    activate(&spec)?;
+    specs.push(spec.clone());
+    settings::store_virtual_displays(specs)?;
+    Ok(spec)
+}
+
+/// Remove a virtual display
+///
+/// Not `async`, so the display is released on the main thread it was created on.
+///
+/// # Arguments
+/// * `serial` - Serial number of the virtual display
+///
+/// # Returns
+/// * `Ok(())` - Display removed, or it didn't exist
+/// * `Err(String)` - Error message if settings couldn't be saved
+#[tauri::command]
+pub fn remove_virtual_display(serial: u32) -> Result<(), String> {
+    if let Ok(mut active) = ACTIVE.lock() {
+        // Dropping the display takes it away
+        active.retain(|(s, _)| *s != serial);
+    }
+    invalidate_display_cache();
+
+    let mut specs = settings::current().virtual_displays;
+    specs.retain(|s| s.serial != serial);
+    settings::store_virtual_displays(specs)?;
+
+    log::info!("[VirtualDisplays] Removed display with serial {}", serial);
+    Ok(())
+}
+
+/// List the virtual displays DPUI keeps
+///
+/// # Returns
+/// * `Ok(Vec<VirtualDisplaySpec>)` - Virtual displays from settings
+#[tauri::command]
+pub async fn list_virtual_displays() -> Result<Vec<VirtualDisplaySpec>, String> {
+    Ok(settings::current().virtual_displays)
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::ffi::{c_char, c_void, CStr};
+
+    use super::VirtualDisplaySpec;
+    use crate::objc::{self, msg_send, sel, Id, Sel};
+
+    /// Vendor ID virtual displays report
+    const VENDOR_ID: u32 = 0x4450;
+
+    /// Pixel density used to derive a plausible physical size
+    const POINTS_PER_INCH: f64 = 110.0;
+
+    #[repr(C)]
+    struct CGSize {
+        width: f64,
+        height: f64,
+    }
+
+    extern "C" {
+        /// What `dispatch_get_main_queue()` returns
+        static _dispatch_main_q: c_void;
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {}
+
+    /// A `CGVirtualDisplay`; the display disappears when this is dropped.
+    pub struct VirtualDisplay(Id);
+
+    // Only retained and released, which Objective-C allows from any thread
+    unsafe impl Send for VirtualDisplay {}
+
+    impl Drop for VirtualDisplay {
+        fn drop(&mut self) {
+            unsafe { objc::release(self.0) };
+        }
+    }
+
+    /// `[[class alloc] init]`
+    fn new(class: &CStr) -> Result<Id, String> {
+        let class_ptr = objc::class(class).ok_or("Virtual displays require macOS 11 or later")?;
+        unsafe { objc::new(class_ptr) }.ok_or_else(|| format!("Failed to create {}", class.to_string_lossy()))
+    }
+
+    fn alloc(class: &CStr) -> Result<Id, String> {
+        let class_ptr = objc::class(class).ok_or_else(|| format!("{} is unavailable", class.to_string_lossy()))?;
+        Ok(unsafe { objc::alloc(class_ptr) })
+    }
+
+    pub fn create(spec: &VirtualDisplaySpec) -> Result<VirtualDisplay, String> {
+        let scale = if spec.hidpi { 2 } else { 1 };
+        let name = std::ffi::CString::new(spec.name.as_str()).map_err(|e| e.to_string())?;
+
+        unsafe {
+            let set_id: unsafe extern "C" fn(Id, Sel, Id) = msg_send();
+            let set_u32: unsafe extern "C" fn(Id, Sel, u32) = msg_send();
+            let set_size: unsafe extern "C" fn(Id, Sel, CGSize) = msg_send();
+            let init_with_id: unsafe extern "C" fn(Id, Sel, Id) -> Id = msg_send();
+
+            let descriptor = new(c"CGVirtualDisplayDescriptor")?;
+            // Created rather than autoreleased; commands don't run inside a pool
+            let init_string: unsafe extern "C" fn(Id, Sel, *const c_char) -> Id = msg_send();
+            let ns_name = init_string(alloc(c"NSString")?, sel(c"initWithUTF8String:"), name.as_ptr());
+            set_id(descriptor, sel(c"setName:"), ns_name);
+            objc::release(ns_name);
+            set_id(descriptor, sel(c"setQueue:"), &raw const _dispatch_main_q as Id);
+            set_u32(descriptor, sel(c"setMaxPixelsWide:"), spec.width * scale);
+            set_u32(descriptor, sel(c"setMaxPixelsHigh:"), spec.height * scale);
+            let millimeters = |points: u32| f64::from(points) / POINTS_PER_INCH * 25.4;
+            set_size(
+                descriptor,
+                sel(c"setSizeInMillimeters:"),
+                CGSize {
+                    width: millimeters(spec.width),
+                    height: millimeters(spec.height),
+                },
+            );
+            set_u32(descriptor, sel(c"setVendorID:"), VENDOR_ID);
+            set_u32(descriptor, sel(c"setProductID:"), 1);
+            set_u32(descriptor, sel(c"setSerialNum:"), spec.serial);
+
+            let display = init_with_id(alloc(c"CGVirtualDisplay")?, sel(c"initWithDescriptor:"), descriptor);
+            objc::release(descriptor);
+            if display.is_null() {
+                return Err("macOS refused to create the virtual display".to_string());
+            }
+            let display = VirtualDisplay(display);
+
+            let init_mode: unsafe extern "C" fn(Id, Sel, u32, u32, f64) -> Id = msg_send();
+            let mode = init_mode(
+                alloc(c"CGVirtualDisplayMode")?,
+                sel(c"initWithWidth:height:refreshRate:"),
+                spec.width,
+                spec.height,
+                60.0,
+            );
+            let init_array: unsafe extern "C" fn(Id, Sel, *const Id, usize) -> Id = msg_send();
+            let modes = init_array(alloc(c"NSArray")?, sel(c"initWithObjects:count:"), &mode, 1);
+            objc::release(mode);
+
+            let settings = new(c"CGVirtualDisplaySettings")?;
+            let set_bool: unsafe extern "C" fn(Id, Sel, bool) = msg_send();
+            set_bool(settings, sel(c"setHiDPI:"), spec.hidpi);
+            set_id(settings, sel(c"setModes:"), modes);
+            objc::release(modes);
+
+            let apply: unsafe extern "C" fn(Id, Sel, Id) -> bool = msg_send();
+            let applied = apply(display.0, sel(c"applySettings:"), settings);
+            objc::release(settings);
+            if !applied {
+                return Err("macOS refused the virtual display's mode".to_string());
+            }
+            Ok(display)
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod native {
+    use super::VirtualDisplaySpec;
+
+    pub struct VirtualDisplay;
+
+    pub fn create(_spec: &VirtualDisplaySpec) -> Result<VirtualDisplay, String> {
+        Err("Virtual displays are only available on macOS".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark() {
+        let spec = |serial| VirtualDisplaySpec {
+            name: "Stream".to_string(),
+            width: 1920,
+            height: 1080,
+            hidpi: false,
+            serial,
+        };
+        assert_eq!(next_serial(&[]), FIRST_SERIAL);
+        assert_eq!(next_serial(&[spec(FIRST_SERIAL), spec(FIRST_SERIAL + 4)]), FIRST_SERIAL + 5);
+
+        let mut displays = crate::displayplacer::parse_config_displays(
+            r#""id:A res:1728x1117 origin:(0,0) degree:0" "id:B res:1920x1080 origin:(1728,0) degree:0""#,
+        );
+        displays[1].serial_id = Some(format!("s{}", FIRST_SERIAL));
+        mark(&mut displays, &[spec(FIRST_SERIAL)]);
+        assert!(!displays[0].virtual_display);
+        assert!(displays[1].virtual_display);
+        assert_eq!(displays[1].name.as_deref(), Some("Stream"));
+
+        assert!(validate_size(1920, 1080).is_ok());
+        assert!(validate_size(320, 1080).is_err());
+    }
+}
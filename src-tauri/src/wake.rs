@@ -0,0 +1,131 @@
+//! Recovery of global shortcuts and the layout after sleep and session switches.
+//!
+//! macOS occasionally drops global shortcut registrations when the Mac wakes
+//! or when this user's session becomes active again, while the plugin still
+//! believes they are registered. An observer for the NSWorkspace wake and
+//! session notifications re-registers every binding once things settle.
+//!
+//! Waking also tends to scramble the display arrangement. With
+//! `reapply_after_wake` on, the last applied preset is applied again if the
+//! layout no longer matches it.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::displayplacer::{invalidate_display_cache, read_displays};
+use crate::history::HistorySource;
+use crate::hotkeys;
+use crate::presets::{apply_preset_config, drifted_preset};
+use crate::session;
+use crate::settings;
+use crate::state::AppState;
+use crate::system_tray::show_tray_notification;
+
+/// Delay after a notification before re-registering, so the window server
+/// and session state have caught up
+const SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Start observing wake and session notifications.
+///
+/// # Returns
+/// * `Ok(())` - Observer registered
+/// * `Err(String)` - Error message if the observer could not be registered
+pub fn start(app: AppHandle) -> Result<(), String> {
+    APP.set(app).map_err(|_| "Wake observer already started".to_string())?;
+    ns::register_observer()?;
+
+    log::info!("[Wake] Observing wake and session notifications");
+    Ok(())
+}
+
+/// Called on the main thread for every observed notification; `woke` is
+/// `false` for session switches.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn on_notification(woke: bool) {
+    let Some(app) = APP.get().cloned() else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        std::thread::sleep(SETTLE_DELAY);
+
+        // Shortcuts stay suspended while another user is active
+        if !session::is_active() {
+            return;
+        }
+        log::info!("[Wake] Woke or session became active, re-registering shortcuts");
+        hotkeys::reregister_all(&app);
+
+        if woke && settings::current().reapply_after_wake {
+            reapply_last_preset(&app);
+        }
+    });
+}
+
+/// Apply the last applied preset again if the layout drifted from it.
+fn reapply_last_preset(app: &AppHandle) {
+    // The cached list may predate sleep
+    invalidate_display_cache();
+    let current = match read_displays() {
+        Ok(current) => current,
+        Err(e) => {
+            log::error!("[Wake] Failed to read displays: {}", e);
+            return;
+        }
+    };
+    let state = app.state::<AppState>();
+    let Some(preset) = drifted_preset(&state.presets(), &current).cloned() else {
+        return;
+    };
+
+    log::info!("[Wake] Layout drifted from {} during sleep, re-applying", preset.name);
+    match tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::Automation)) {
+        Ok(_) => show_tray_notification("DPUI", &format!("Restored: {}", preset.name)),
+        Err(e) => {
+            log::error!("[Wake] Failed to re-apply {}: {}", preset.name, e);
+            show_tray_notification("DPUI", &format!("Failed to restore {}: {}", preset.name, e));
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod ns {
+    use crate::objc::{self, Id, Sel};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSWorkspaceDidWakeNotification: Id;
+        static NSWorkspaceSessionDidBecomeActiveNotification: Id;
+    }
+
+    /// `-[DPUIWakeObserver handleWake:]`
+    extern "C" fn handle_wake(_this: Id, _cmd: Sel, _notification: Id) {
+        super::on_notification(true);
+    }
+
+    /// `-[DPUIWakeObserver handleSessionActive:]`
+    extern "C" fn handle_session_active(_this: Id, _cmd: Sel, _notification: Id) {
+        super::on_notification(false);
+    }
+
+    pub fn register_observer() -> Result<(), String> {
+        let (wake, session) = unsafe { (NSWorkspaceDidWakeNotification, NSWorkspaceSessionDidBecomeActiveNotification) };
+        objc::observe_workspace(
+            c"DPUIWakeObserver",
+            &[
+                (c"handleWake:", wake, handle_wake),
+                (c"handleSessionActive:", session, handle_session_active),
+            ],
+        )
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod ns {
+    pub fn register_observer() -> Result<(), String> {
+        Err("Wake notifications are only available on macOS".to_string())
+    }
+}
@@ -0,0 +1,222 @@
+//! Display topology watcher.
+//!
+//! Registers a CoreGraphics display reconfiguration callback. A single hotplug
+//! produces a burst of callbacks, so changes are debounced on a background
+//! thread; once things settle the display list is re-read, subsystems that
+//! track topology are notified and a `displays-changed` event is emitted.
+//!
+//! If the callback cannot be registered (sandboxed or headless sessions), an
+//! adaptive polling loop hashes `displayplacer list` output instead, backing
+//! off while the configuration is stable so events still fire, just later.
+//!
+//! With `auto_apply_on_hotplug` set, plugging in or removing a display applies
+//! the preset made for exactly the displays now connected. Automation rules
+//! are evaluated after every change.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::automation;
+use crate::display_windows;
+use crate::displayplacer::{get_displays, invalidate_display_cache, read_displays_uncached, DisplayConfig};
+use crate::headless;
+use crate::history::HistorySource;
+use crate::kvm;
+use crate::presets::{apply_preset_config, preset_for_displays};
+use crate::session;
+use crate::settings;
+use crate::state::AppState;
+use crate::streamdeck;
+use crate::system_tray::{show_tray_notification, update_tray_menu};
+
+/// Quiet period after the last reconfiguration callback before reacting
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Polling interval right after a change is seen
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+static CHANGE_TX: OnceLock<Mutex<Sender<()>>> = OnceLock::new();
+
+/// Sorted IDs of the displays connected at the last change, to tell a hotplug
+/// from a rearrangement
+static LAST_DISPLAY_IDS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Start watching for display topology changes.
+///
+/// # Arguments
+/// * `app` - Application handle used to emit events
+///
+/// # Returns
+/// * `Ok(())` - Watcher started, via callback or polling
+/// * `Err(String)` - Error message if the watcher was already started
+pub fn start(app: AppHandle) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel();
+    CHANGE_TX
+        .set(Mutex::new(tx))
+        .map_err(|_| "Display watcher already started".to_string())?;
+
+    match cg::register_reconfiguration_callback() {
+        Ok(()) => log::info!("[Watcher] Display watcher started"),
+        Err(e) => {
+            log::warn!("[Watcher] {}; falling back to polling", e);
+            std::thread::spawn(run_polling_loop);
+        }
+    }
+
+    std::thread::spawn(move || run_dispatch_loop(app, rx));
+
+    Ok(())
+}
+
+/// Poll `displayplacer list` and signal a change whenever its output changes.
+///
+/// The interval doubles while nothing changes, up to the `poll_interval_secs`
+/// setting, and drops back to `MIN_POLL_INTERVAL` as soon as a change is seen.
+fn run_polling_loop() {
+    let mut last_hash = None;
+    let mut interval = MIN_POLL_INTERVAL;
+
+    loop {
+        std::thread::sleep(interval);
+
+        let hash = match read_displays_uncached() {
+            Ok(config) => {
+                let mut hasher = DefaultHasher::new();
+                config.raw_command.hash(&mut hasher);
+                hasher.finish()
+            }
+            Err(_) => 0,
+        };
+
+        if last_hash.is_some_and(|last| last != hash) {
+            notify_change();
+            interval = MIN_POLL_INTERVAL;
+        } else {
+            let max_interval = Duration::from_secs(settings::current().poll_interval_secs).max(MIN_POLL_INTERVAL);
+            interval = (interval * 2).min(max_interval);
+        }
+        last_hash = Some(hash);
+    }
+}
+
+/// Signal that the display configuration changed.
+fn notify_change() {
+    invalidate_display_cache();
+    if let Some(tx) = CHANGE_TX.get() {
+        if let Ok(tx) = tx.lock() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Debounce change signals and handle each settled change.
+fn run_dispatch_loop(app: AppHandle, rx: Receiver<()>) {
+    // Seed subsystems with the layout present at startup
+    tauri::async_runtime::block_on(handle_topology_change(&app));
+
+    while rx.recv().is_ok() {
+        // Coalesce the burst of callbacks a single hotplug produces
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+        tauri::async_runtime::block_on(handle_topology_change(&app));
+    }
+}
+
+/// Re-read the display list and notify subsystems and the frontend.
+async fn handle_topology_change(app: &AppHandle) {
+    // Changes made in another user's session are not ours to react to
+    if !session::is_active() {
+        return;
+    }
+
+    // Reads made while the change was still settling may have been cached
+    invalidate_display_cache();
+    match get_displays().await {
+        Ok(config) => {
+            kvm::on_topology_change(app, &config.displays);
+            // Headless mode has no windows to keep on the displays
+            if !headless::is_headless() {
+                if let Err(e) = display_windows::sync(app, &config.displays) {
+                    log::error!("[Watcher] Failed to update display windows: {}", e);
+                }
+            }
+            let _ = app.emit("displays-changed", &config);
+
+            let mut ids: Vec<String> = config.displays.iter().map(|d| d.id.clone()).collect();
+            ids.sort();
+            let previous = LAST_DISPLAY_IDS.lock().ok().and_then(|mut last| last.replace(ids.clone()));
+            if previous.is_some_and(|previous| previous != ids) && settings::current().auto_apply_on_hotplug {
+                auto_apply(app, &config).await;
+            }
+            automation::evaluate(app, &config).await;
+            streamdeck::publish_state(app, &config);
+
+            // Presets may have become (un)available
+            if let Err(e) = update_tray_menu(app) {
+                log::error!("[Watcher] Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => log::error!("[Watcher] Failed to read displays after change: {}", e),
+    }
+}
+
+/// Apply the preset made for the connected displays, unless it's already in effect.
+///
+/// Holds off while displays are away on a KVM, whose own restore would be undone.
+async fn auto_apply(app: &AppHandle, config: &DisplayConfig) {
+    if kvm::is_suppressing() {
+        return;
+    }
+    let store = app.state::<AppState>().presets();
+    let Some(preset) = preset_for_displays(&store, config) else {
+        return;
+    };
+
+    log::info!("[Watcher] Displays changed, applying preset {}", preset.name);
+    if let Err(e) = apply_preset_config(app, preset, HistorySource::Preset).await {
+        log::error!("[Watcher] Failed to apply preset {}: {}", preset.name, e);
+        show_tray_notification("DPUI", &format!("Failed to apply {}: {}", preset.name, e));
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod cg {
+    use std::ffi::c_void;
+
+    /// `kCGDisplayBeginConfigurationFlag`: sent before the change is applied
+    const BEGIN_CONFIGURATION_FLAG: u32 = 1 << 0;
+
+    type ReconfigurationCallback = extern "C" fn(display: u32, flags: u32, user_info: *mut c_void);
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGDisplayRegisterReconfigurationCallback(callback: ReconfigurationCallback, user_info: *mut c_void) -> i32;
+    }
+
+    extern "C" fn on_reconfiguration(_display: u32, flags: u32, _user_info: *mut c_void) {
+        // Only react once the change has been applied
+        if flags & BEGIN_CONFIGURATION_FLAG == 0 {
+            super::notify_change();
+        }
+    }
+
+    pub fn register_reconfiguration_callback() -> Result<(), String> {
+        let error = unsafe { CGDisplayRegisterReconfigurationCallback(on_reconfiguration, std::ptr::null_mut()) };
+
+        if error != 0 {
+            return Err(format!("CGDisplayRegisterReconfigurationCallback failed with error {}", error));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod cg {
+    pub fn register_reconfiguration_callback() -> Result<(), String> {
+        Err("Display reconfiguration callbacks are only available on macOS".to_string())
+    }
+}
@@ -0,0 +1,140 @@
+//! Presets triggered by the Wi-Fi network.
+//!
+//! Polls the current Wi-Fi network name (SSID) and, when it changes to one
+//! mapped in the `wifi_presets` setting, applies that preset — so joining the
+//! office network sets up the office desk. Like other triggers, the network
+//! present at launch is only recorded. Turned off with `wifi_triggers_enabled`.
+
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::history::HistorySource;
+use crate::presets::apply_preset_config;
+use crate::session;
+use crate::settings;
+use crate::state::AppState;
+use crate::system_tray::show_tray_notification;
+
+/// How often the network is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Interface the built-in Wi-Fi uses on every current Mac
+const WIFI_INTERFACE: &str = "en0";
+
+/// Network seen at the last check; the outer `None` until the first check
+static LAST_SSID: Mutex<Option<Option<String>>> = Mutex::new(None);
+
+/// Network name from `ipconfig getsummary` output (" SSID : Office-WiFi").
+fn parse_ipconfig_summary(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("SSID : "))
+        .map(|ssid| ssid.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+}
+
+/// Network name from `networksetup -getairportnetwork` output
+/// ("Current Wi-Fi Network: Office-WiFi").
+fn parse_networksetup(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Current Wi-Fi Network: "))
+        .map(|ssid| ssid.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Name of the Wi-Fi network the Mac is joined to, if any.
+///
+/// `networksetup` no longer reports the name on recent macOS releases, so
+/// `ipconfig getsummary` is asked first.
+pub fn current_ssid() -> Option<String> {
+    command_stdout("ipconfig", &["getsummary", WIFI_INTERFACE])
+        .and_then(|output| parse_ipconfig_summary(&output))
+        .or_else(|| {
+            command_stdout("networksetup", &["-getairportnetwork", WIFI_INTERFACE])
+                .and_then(|output| parse_networksetup(&output))
+        })
+}
+
+/// Start polling the Wi-Fi network.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        if session::is_active() {
+            check_network(&app);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+/// Apply the mapped preset if the network changed since the last check.
+fn check_network(app: &AppHandle) {
+    let settings = settings::current();
+    if !settings.wifi_triggers_enabled {
+        // Start over from whatever network is joined when turned back on
+        if let Ok(mut last) = LAST_SSID.lock() {
+            *last = None;
+        }
+        return;
+    }
+
+    let ssid = current_ssid();
+    let previous = match LAST_SSID.lock() {
+        Ok(mut last) => last.replace(ssid.clone()),
+        Err(_) => return,
+    };
+    let Some(previous) = previous else {
+        return;
+    };
+    if previous == ssid {
+        return;
+    }
+    log::info!("[WiFi] Network changed from {:?} to {:?}", previous, ssid);
+
+    let Some(preset_id) = ssid.as_ref().and_then(|ssid| settings.wifi_presets.get(ssid)) else {
+        return;
+    };
+    let Some(preset) = app.state::<AppState>().find_preset(preset_id) else {
+        log::error!("[WiFi] Preset {} not found", preset_id);
+        return;
+    };
+
+    match tauri::async_runtime::block_on(apply_preset_config(app, &preset, HistorySource::Automation)) {
+        Ok(_) => show_tray_notification("DPUI", &format!("Applied: {}", preset.name)),
+        Err(e) => {
+            log::error!("[WiFi] Failed to apply {}: {}", preset.name, e);
+            show_tray_notification("DPUI", &format!("Failed to apply {}: {}", preset.name, e));
+        }
+    }
+}
+
+/// Get the name of the current Wi-Fi network
+///
+/// # Returns
+/// * `Ok(Some(String))` - Network name, for mapping it to a preset
+/// * `Ok(None)` - Not joined to a Wi-Fi network, or the name can't be read
+#[tauri::command]
+pub async fn get_wifi_network() -> Result<Option<String>, String> {
+    Ok(current_ssid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ssid() {
+        let summary = "<dictionary> {\n  BSSID : <redacted>\n  InterfaceType : WiFi\n  SSID : Office-WiFi\n}";
+        assert_eq!(parse_ipconfig_summary(summary).as_deref(), Some("Office-WiFi"));
+        assert_eq!(parse_ipconfig_summary("<dictionary> {\n  InterfaceType : WiFi\n}"), None);
+
+        assert_eq!(parse_networksetup("Current Wi-Fi Network: Home 5G\n").as_deref(), Some("Home 5G"));
+        assert_eq!(parse_networksetup("You are not associated with an AirPort network.\n"), None);
+    }
+}
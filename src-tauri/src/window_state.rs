@@ -0,0 +1,196 @@
+//! Window positions and sizes across launches.
+//!
+//! The main and per-display windows store their geometry in settings when
+//! they are closed and when DPUI quits, and get it back when they are created.
+//! Displays may have moved or gone in the meantime, so restored geometry is
+//! clamped to the monitors that are available then: a window whose title bar
+//! would be off-screen is centered on the primary display instead.
+//!
+//! The main window can also be pinned above other apps' windows, e.g. to
+//! arrange displays while looking at another app.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, WebviewWindow, WindowEvent};
+
+use crate::settings;
+
+/// Height of the strip along the top of a window that must stay on a display
+const TITLE_BAR_HEIGHT: f64 = 28.0;
+
+/// Position and size of a window in points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WindowGeometry {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Label of the main window in tauri.conf.json
+const MAIN_WINDOW: &str = "main";
+
+/// Whether a window's geometry is remembered; the quick panel follows the tray icon.
+fn is_tracked(label: &str) -> bool {
+    label == MAIN_WINDOW || label.starts_with("display_")
+}
+
+/// Fit `geometry` onto `monitors`, the first being the primary.
+///
+/// Returns `None` without monitors, in which case the window stays put.
+fn clamp(geometry: WindowGeometry, monitors: &[WindowGeometry]) -> Option<WindowGeometry> {
+    let title_bar = (geometry.x + geometry.width / 2.0, geometry.y + TITLE_BAR_HEIGHT / 2.0);
+    let (monitor, on_screen) = match monitors.iter().find(|m| m.contains(title_bar.0, title_bar.1)) {
+        Some(monitor) => (monitor, true),
+        None => (monitors.first()?, false),
+    };
+
+    let width = geometry.width.min(monitor.width);
+    let height = geometry.height.min(monitor.height);
+    let (x, y) = if on_screen {
+        // Keep the whole window on its display where it fits
+        (
+            geometry.x.clamp(monitor.x, monitor.x + monitor.width - width),
+            geometry.y.clamp(monitor.y, monitor.y + monitor.height - height),
+        )
+    } else {
+        (
+            monitor.x + (monitor.width - width) / 2.0,
+            monitor.y + (monitor.height - height) / 2.0,
+        )
+    };
+
+    Some(WindowGeometry { x, y, width, height })
+}
+
+/// Available monitors in points, primary first.
+fn monitor_geometries(app: &AppHandle) -> Vec<WindowGeometry> {
+    app.available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| {
+            let scale = monitor.scale_factor();
+            let position = monitor.position().to_logical::<f64>(scale);
+            let size = monitor.size().to_logical::<f64>(scale);
+            WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            }
+        })
+        .collect()
+}
+
+fn current_geometry(window: &WebviewWindow) -> Result<WindowGeometry, String> {
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let position = window.outer_position().map_err(|e| e.to_string())?.to_logical::<f64>(scale);
+    let size = window.inner_size().map_err(|e| e.to_string())?.to_logical::<f64>(scale);
+    Ok(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+/// Store a window's current geometry in settings.
+pub fn save(window: &WebviewWindow) {
+    if !is_tracked(window.label()) {
+        return;
+    }
+    let result = current_geometry(window)
+        .and_then(|geometry| settings::store_window_geometry(window.label().to_string(), geometry));
+    if let Err(e) = result {
+        log::error!("[WindowState] Failed to save geometry of {}: {}", window.label(), e);
+    }
+}
+
+/// Store the geometry of every open window, e.g. when DPUI quits.
+pub fn save_all(app: &AppHandle) {
+    for window in app.webview_windows().values() {
+        save(window);
+    }
+}
+
+/// Restore a new window's geometry and save it again when the window closes.
+///
+/// The main window is also pinned on top if settings say so.
+pub fn track(window: &WebviewWindow) {
+    if !is_tracked(window.label()) {
+        return;
+    }
+    if window.label() == MAIN_WINDOW && settings::current().always_on_top {
+        let _ = window.set_always_on_top(true);
+    }
+
+    let stored = settings::current().window_geometry.get(window.label()).copied();
+    if let Some(geometry) = stored.and_then(|g| clamp(g, &monitor_geometries(window.app_handle()))) {
+        let _ = window.set_size(LogicalSize::new(geometry.width, geometry.height));
+        let _ = window.set_position(LogicalPosition::new(geometry.x, geometry.y));
+        log::info!("[WindowState] Restored {} at ({}, {})", window.label(), geometry.x, geometry.y);
+    }
+
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::CloseRequested { .. } = event {
+            save(&handle);
+        }
+    });
+}
+
+/// Keep the main window above other windows, or stop doing so
+///
+/// # Arguments
+/// * `app` - Application handle
+/// * `enabled` - `true` to pin the window on top
+///
+/// # Returns
+/// * `Ok(())` - Setting stored and applied to the open window
+/// * `Err(String)` - Error message if the setting couldn't be stored or applied
+#[tauri::command]
+pub async fn set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), String> {
+    settings::store_always_on_top(enabled)?;
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        window
+            .set_always_on_top(enabled)
+            .map_err(|e| format!("Failed to pin window: {}", e))?;
+    }
+
+    log::info!("[WindowState] Always on top {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        let geometry = |x, y, width, height| WindowGeometry { x, y, width, height };
+        let monitors = [geometry(0.0, 0.0, 1728.0, 1117.0), geometry(1728.0, -323.0, 2560.0, 1440.0)];
+
+        // On the secondary display: kept
+        let window = geometry(2000.0, 100.0, 600.0, 700.0);
+        assert_eq!(clamp(window, &monitors), Some(window));
+
+        // Hanging off the bottom of the primary: pulled up
+        assert_eq!(
+            clamp(geometry(100.0, 900.0, 600.0, 700.0), &monitors),
+            Some(geometry(100.0, 417.0, 600.0, 700.0))
+        );
+
+        // On a display that is gone: centered on the primary
+        assert_eq!(
+            clamp(geometry(-2000.0, 0.0, 600.0, 700.0), &monitors),
+            Some(geometry(564.0, 208.5, 600.0, 700.0))
+        );
+
+        assert_eq!(clamp(window, &[]), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Workspaces: a display preset bundled with the rest of the desk setup.
+//!
+//! A workspace composes a display preset with an audio output device, Dock
+//! settings and a window layout script, and is applied as one unit from the
+//! UI, the tray or its own hotkey. Each component is applied independently and
+//! reported separately, so one failing piece doesn't hide what did change.
+//!
+//! Workspaces are stored in `workspaces.json` next to the presets file.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use tauri::{AppHandle, Emitter};
+
+use crate::atomic_file::write_atomic;
+use crate::hotkeys::{self, HotkeyTarget};
+use crate::history::HistorySource;
+use crate::presets::{apply_saved_preset, get_config_dir};
+use crate::system_tray::{show_tray_notification, update_tray_menu};
+
+/// Dock preferences applied by a workspace. Unset fields are left alone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockSettings {
+    pub autohide: Option<bool>,
+    /// "left", "bottom" or "right"
+    pub orientation: Option<String>,
+    /// Icon size in points
+    pub tile_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    /// Display preset to apply
+    #[serde(default)]
+    pub preset_id: Option<String>,
+    /// Name of the audio output device to switch to
+    #[serde(default)]
+    pub audio_device: Option<String>,
+    #[serde(default)]
+    pub dock: Option<DockSettings>,
+    /// AppleScript that arranges windows, run after the displays settle
+    #[serde(default)]
+    pub window_layout: Option<String>,
+    #[serde(default)]
+    pub hotkey: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStore {
+    pub version: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+impl Default for WorkspaceStore {
+    fn default() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            workspaces: Vec::new(),
+        }
+    }
+}
+
+/// Part of a workspace that is applied on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceComponent {
+    Displays,
+    Audio,
+    Dock,
+    Windows,
+}
+
+/// Outcome of applying one component.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentResult {
+    pub component: WorkspaceComponent,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// Per-component outcome of applying a workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceReport {
+    pub workspace_id: String,
+    pub results: Vec<ComponentResult>,
+}
+
+impl WorkspaceReport {
+    /// Whether every component was applied.
+    pub fn ok(&self) -> bool {
+        self.results.iter().all(|r| r.ok)
+    }
+}
+
+fn get_workspaces_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("workspaces.json"))
+}
+
+/// Read workspaces from disk.
+///
+/// Synchronous so the tray menu can list workspaces while it is built.
+pub fn read_workspaces() -> Result<WorkspaceStore, String> {
+    let path = get_workspaces_path()?;
+
+    if !path.exists() {
+        return Ok(WorkspaceStore::default());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read workspaces: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse workspaces: {}", e))
+}
+
+fn write_workspaces(store: &WorkspaceStore) -> Result<(), String> {
+    let path = get_workspaces_path()?;
+
+    let content =
+        serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize workspaces: {}", e))?;
+
+    write_atomic(&path, content.as_bytes()).map_err(|e| format!("Failed to write workspaces: {}", e))
+}
+
+/// Rebuild the tray menu so it lists the current workspaces.
+fn refresh_tray(app: &AppHandle) {
+    if let Err(e) = update_tray_menu(app) {
+        log::error!("[Workspace] Failed to update tray menu: {}", e);
+    }
+}
+
+/// Load workspaces from file
+#[tauri::command]
+pub async fn load_workspaces() -> Result<WorkspaceStore, String> {
+    read_workspaces()
+}
+
+/// Add a new workspace, registering its hotkey if it has one
+#[tauri::command]
+pub async fn add_workspace(app: AppHandle, mut workspace: Workspace) -> Result<Workspace, String> {
+    let mut store = read_workspaces()?;
+
+    workspace.id = uuid::Uuid::new_v4().to_string();
+    workspace.created_at = chrono::Utc::now().to_rfc3339();
+
+    if let Some(hotkey) = &workspace.hotkey {
+        hotkeys::register_target(&app, HotkeyTarget::Workspace, &workspace.id, hotkey)?;
+    }
+
+    store.workspaces.push(workspace.clone());
+    write_workspaces(&store)?;
+    refresh_tray(&app);
+
+    Ok(workspace)
+}
+
+/// Replace an existing workspace, moving its hotkey if it changed
+#[tauri::command]
+pub async fn update_workspace(app: AppHandle, workspace: Workspace) -> Result<(), String> {
+    let mut store = read_workspaces()?;
+
+    let existing = store
+        .workspaces
+        .iter_mut()
+        .find(|w| w.id == workspace.id)
+        .ok_or("Workspace not found")?;
+
+    if existing.hotkey != workspace.hotkey {
+        if let Some(old) = &existing.hotkey {
+            hotkeys::unregister_target(&app, old);
+        }
+        if let Some(new) = &workspace.hotkey {
+            hotkeys::register_target(&app, HotkeyTarget::Workspace, &workspace.id, new)?;
+        }
+    }
+
+    *existing = Workspace {
+        created_at: existing.created_at.clone(),
+        ..workspace
+    };
+    write_workspaces(&store)?;
+    refresh_tray(&app);
+    Ok(())
+}
+
+/// Delete a workspace and its hotkey
+#[tauri::command]
+pub async fn delete_workspace(app: AppHandle, id: String) -> Result<(), String> {
+    let mut store = read_workspaces()?;
+
+    if let Some(hotkey) = store.workspaces.iter().find(|w| w.id == id).and_then(|w| w.hotkey.clone()) {
+        hotkeys::unregister_target(&app, &hotkey);
+    }
+
+    store.workspaces.retain(|w| w.id != id);
+    write_workspaces(&store)?;
+    refresh_tray(&app);
+    Ok(())
+}
+
+/// Register the hotkeys of all saved workspaces.
+///
+/// Called once from the setup hook; failures are logged and skipped.
+pub fn register_hotkeys(app: &AppHandle) {
+    let store = match read_workspaces() {
+        Ok(store) => store,
+        Err(e) => {
+            log::error!("[Workspace] {}", e);
+            return;
+        }
+    };
+
+    for workspace in &store.workspaces {
+        let Some(hotkey) = &workspace.hotkey else {
+            continue;
+        };
+        if let Err(e) = hotkeys::register_target(app, HotkeyTarget::Workspace, &workspace.id, hotkey) {
+            log::error!("[Workspace] Failed to register hotkey for {}: {}", workspace.name, e);
+        }
+    }
+}
+
+/// Run a command, turning a non-zero exit into its stderr.
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+async fn apply_displays(app: &AppHandle, preset_id: &str) -> Result<String, String> {
+    apply_saved_preset(app, preset_id, HistorySource::Preset, false).await?;
+    Ok("Applied display preset".to_string())
+}
+
+/// Switch the default output device with `SwitchAudioSource` (switchaudio-osx).
+fn apply_audio(device: &str) -> Result<String, String> {
+    run("SwitchAudioSource", &["-t", "output", "-s", device])
+        .map_err(|e| format!("{} (is switchaudio-osx installed?)", e))?;
+    Ok(format!("Switched audio output to {}", device))
+}
+
+/// Write Dock preferences and restart the Dock to pick them up.
+fn apply_dock(dock: &DockSettings) -> Result<String, String> {
+    if let Some(autohide) = dock.autohide {
+        let value = if autohide { "true" } else { "false" };
+        run("defaults", &["write", "com.apple.dock", "autohide", "-bool", value])?;
+    }
+    if let Some(orientation) = &dock.orientation {
+        if !matches!(orientation.as_str(), "left" | "bottom" | "right") {
+            return Err(format!("Invalid Dock orientation \"{}\"", orientation));
+        }
+        run("defaults", &["write", "com.apple.dock", "orientation", "-string", orientation])?;
+    }
+    if let Some(tile_size) = dock.tile_size {
+        run("defaults", &["write", "com.apple.dock", "tilesize", "-int", &tile_size.to_string()])?;
+    }
+
+    run("killall", &["Dock"])?;
+    Ok("Updated Dock".to_string())
+}
+
+fn apply_windows(script: &str) -> Result<String, String> {
+    run("osascript", &["-e", script])?;
+    Ok("Arranged windows".to_string())
+}
+
+fn component_result(component: WorkspaceComponent, result: Result<String, String>) -> ComponentResult {
+    match result {
+        Ok(message) => ComponentResult { component, ok: true, message },
+        Err(message) => ComponentResult { component, ok: false, message },
+    }
+}
+
+/// Apply every component of a workspace.
+///
+/// Components are applied in order (displays, audio, Dock, windows) and a
+/// failure in one does not stop the rest. The report is also emitted as a
+/// `workspace-applied` event.
+///
+/// # Returns
+/// * `Ok(WorkspaceReport)` - Outcome of each configured component
+/// * `Err(String)` - Error message if the workspace cannot be found
+#[tauri::command]
+pub async fn apply_workspace(app: AppHandle, id: String) -> Result<WorkspaceReport, String> {
+    let store = read_workspaces()?;
+    let workspace = store
+        .workspaces
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("Workspace {} not found", id))?;
+
+    let mut results = Vec::new();
+
+    if let Some(preset_id) = &workspace.preset_id {
+        results.push(component_result(WorkspaceComponent::Displays, apply_displays(&app, preset_id).await));
+    }
+    if let Some(device) = &workspace.audio_device {
+        results.push(component_result(WorkspaceComponent::Audio, apply_audio(device)));
+    }
+    if let Some(dock) = &workspace.dock {
+        results.push(component_result(WorkspaceComponent::Dock, apply_dock(dock)));
+    }
+    if let Some(script) = &workspace.window_layout {
+        results.push(component_result(WorkspaceComponent::Windows, apply_windows(script)));
+    }
+
+    let report = WorkspaceReport { workspace_id: workspace.id, results };
+    for failed in report.results.iter().filter(|r| !r.ok) {
+        log::error!("[Workspace] {} failed: {:?}: {}", workspace.name, failed.component, failed.message);
+    }
+    if report.ok() {
+        log::info!("[Workspace] Applied {}", workspace.name);
+    }
+
+    let _ = app.emit("workspace-applied", &report);
+    Ok(report)
+}
+
+/// Apply a workspace in the background, reporting failures to the user.
+///
+/// Used by hotkeys and the tray, which have nowhere to return an error to.
+pub fn spawn_apply_workspace(app: &AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match apply_workspace(app.clone(), id).await {
+            Ok(report) if !report.ok() => show_tray_notification("DPUI", "Workspace applied with errors"),
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("[Workspace] {}", e);
+                show_tray_notification("DPUI", &format!("Failed to apply workspace: {}", e));
+            }
+        }
+    });
+}
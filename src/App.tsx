@@ -3,13 +3,30 @@ import { DisplayCanvas } from './components/DisplayCanvas';
 import { PresetManager } from './components/PresetManager';
 import { HotkeyManager } from './components/HotkeyManager';
 import { ErrorDialog } from './components/ErrorDialog';
+import { ConfirmDialog } from './components/ConfirmDialog';
+import { SetupAssistant } from './components/SetupAssistant';
 import { useDisplayStore } from './store/useDisplayStore';
 import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import type { WorkspaceReport } from './types/display';
 
 function App() {
-  const { fetchDisplays, fetchPresets, loading, error, presets, applyConfig } = useDisplayStore();
+  const { fetchDisplays, fetchPresets, loading, error } = useDisplayStore();
   const [showError, setShowError] = React.useState(false);
   const [errorContext, setErrorContext] = React.useState<string>('');
+  const [layoutCountdown, setLayoutCountdown] = React.useState<number | null>(null);
+  const [showSetup, setShowSetup] = React.useState(false);
+  const [alwaysOnTop, setAlwaysOnTop] = React.useState(false);
+
+  React.useEffect(() => {
+    invoke<{ always_on_top: boolean }>('get_settings').then((settings) => setAlwaysOnTop(settings.always_on_top));
+  }, []);
+
+  // Keep the window above other apps while arranging displays
+  const toggleAlwaysOnTop = async () => {
+    await invoke('set_always_on_top', { enabled: !alwaysOnTop });
+    setAlwaysOnTop(!alwaysOnTop);
+  };
 
   // Track error changes
   React.useEffect(() => {
@@ -18,6 +35,67 @@ function App() {
     }
   }, [error]);
 
+  // Count down while a newly applied layout awaits confirmation.
+  // The backend owns the timer and reverts on its own when it runs out.
+  React.useEffect(() => {
+    if (layoutCountdown === null || layoutCountdown <= 0) return;
+    const timer = setTimeout(() => setLayoutCountdown(layoutCountdown - 1), 1000);
+    return () => clearTimeout(timer);
+  }, [layoutCountdown]);
+
+  React.useEffect(() => {
+    const unlistenConfirm = listen('confirm-layout', (event) => {
+      const { timeout_secs } = event.payload as { token: number; timeout_secs: number };
+      setLayoutCountdown(timeout_secs);
+    });
+
+    const unlistenRolledBack = listen('layout-rolled-back', () => {
+      setLayoutCountdown(null);
+      fetchDisplays();
+    });
+
+    // A later change or an undo replaced the layout before it was confirmed
+    const unlistenCancelled = listen('confirm-layout-cancelled', () => {
+      setLayoutCountdown(null);
+    });
+
+    const unlistenSetup = listen('open-setup-assistant', () => {
+      setShowSetup(true);
+    });
+
+    // Workspaces are applied by the backend, which reports each component
+    const unlistenWorkspace = listen('workspace-applied', (event) => {
+      const report = event.payload as WorkspaceReport;
+      const failed = report.results.filter((r) => !r.ok);
+      if (failed.length > 0) {
+        setErrorContext('워크스페이스 적용');
+        useDisplayStore.setState({
+          error: failed.map((r) => `${r.component}: ${r.message}`).join('\n'),
+        });
+      }
+      fetchDisplays();
+    });
+
+    return () => {
+      unlistenConfirm.then(fn => fn());
+      unlistenRolledBack.then(fn => fn());
+      unlistenCancelled.then(fn => fn());
+      unlistenSetup.then(fn => fn());
+      unlistenWorkspace.then(fn => fn());
+    };
+  }, [fetchDisplays]);
+
+  const handleKeepLayout = async () => {
+    setLayoutCountdown(null);
+    await invoke('confirm_applied_config');
+  };
+
+  const handleRevertLayout = async () => {
+    setLayoutCountdown(null);
+    await invoke('reject_applied_config');
+    await fetchDisplays();
+  };
+
   React.useEffect(() => {
     // Load initial data
     setErrorContext('초기 데이터 로드');
@@ -30,21 +108,23 @@ function App() {
       fetchDisplays();
     });
 
-    const unlistenApplyPreset = listen('apply-preset-from-tray', (event) => {
-      const presetId = event.payload as string;
-      const preset = presets.find(p => p.id === presetId);
-      if (preset) {
-        setErrorContext('트레이에서 프리셋 적용');
-        applyConfig(preset.config);
-      }
+    const unlistenApplyFailed = listen('preset-apply-failed', (event) => {
+      setErrorContext('프리셋 적용');
+      useDisplayStore.setState({ error: event.payload as string });
+    });
+
+    // presets.json was edited outside the app or synced from another Mac
+    const unlistenPresetsChanged = listen('presets-changed', () => {
+      fetchPresets();
     });
 
     // Cleanup listeners
     return () => {
       unlistenRefresh.then(fn => fn());
-      unlistenApplyPreset.then(fn => fn());
+      unlistenApplyFailed.then(fn => fn());
+      unlistenPresetsChanged.then(fn => fn());
     };
-  }, [fetchDisplays, fetchPresets, presets, applyConfig]);
+  }, [fetchDisplays, fetchPresets]);
 
   const handleRetry = () => {
     setErrorContext('재시도');
@@ -69,6 +149,15 @@ function App() {
                 Display Manager
               </p>
             </div>
+            <button
+              onClick={toggleAlwaysOnTop}
+              className={`ml-auto rounded-lg px-3 py-1.5 text-sm font-medium shadow-sm ${
+                alwaysOnTop ? 'bg-blue-600 text-white' : 'bg-white text-gray-600 hover:bg-gray-50'
+              }`}
+              title="다른 앱 창 위에 항상 표시"
+            >
+              📌 {alwaysOnTop ? '고정됨' : '맨 위에 고정'}
+            </button>
           </div>
           <p className="text-gray-600 text-sm ml-16">
             macOS 디스플레이 레이아웃을 간편하게 관리하세요
@@ -114,6 +203,20 @@ function App() {
         </footer>
       </div>
 
+      {/* Layout Confirmation */}
+      <ConfirmDialog
+        isOpen={layoutCountdown !== null}
+        title="이 레이아웃을 유지하시겠습니까?"
+        message="새 디스플레이 레이아웃이 적용되었습니다."
+        countdown={layoutCountdown ?? 0}
+        confirmLabel="유지"
+        onConfirm={handleKeepLayout}
+        onCancel={handleRevertLayout}
+      />
+
+      {/* Setup Assistant */}
+      <SetupAssistant isOpen={showSetup} onClose={() => setShowSetup(false)} />
+
       {/* Error Dialog */}
       <ErrorDialog
         isOpen={showError}
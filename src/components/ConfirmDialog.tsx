@@ -12,6 +12,8 @@ interface ConfirmDialogProps {
   message: string;
   /** Current countdown value in seconds */
   countdown: number;
+  /** Label of the confirm button */
+  confirmLabel?: string;
   /** Callback when user confirms the action */
   onConfirm: () => void;
   /** Callback when user cancels or timeout occurs */
@@ -47,6 +49,7 @@ export const ConfirmDialog: React.FC<ConfirmDialogProps> = ({
   title,
   message,
   countdown,
+  confirmLabel = '확인 (화면 끄기)',
   onConfirm,
   onCancel,
 }) => {
@@ -79,7 +82,7 @@ export const ConfirmDialog: React.FC<ConfirmDialogProps> = ({
           <p className="text-gray-700 text-base">{message}</p>
           <div className="mt-4 p-3 bg-gradient-to-r from-yellow-50 to-orange-50 border-l-4 border-yellow-400 rounded-lg shadow-sm">
             <p className="text-sm text-yellow-800 font-medium">
-              ⏰ 15초 내에 확인하지 않으면 자동으로 원복됩니다.
+              ⏰ {countdown}초 내에 확인하지 않으면 자동으로 원복됩니다.
             </p>
           </div>
         </div>
@@ -104,7 +107,7 @@ export const ConfirmDialog: React.FC<ConfirmDialogProps> = ({
             onClick={onConfirm}
             className="flex-1 px-4 py-2.5 bg-gradient-to-r from-red-600 to-rose-600 text-white rounded-lg hover:from-red-700 hover:to-rose-700 transition-all duration-200 font-medium shadow-md hover:shadow-lg active:scale-95"
           >
-            확인 (화면 끄기)
+            {confirmLabel}
           </button>
         </div>
       </div>
@@ -1,9 +1,10 @@
 import React from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { DisplayCard } from './DisplayCard';
 import { useDisplayStore } from '../store/useDisplayStore';
 
 export const DisplayCanvas: React.FC = () => {
-  const { displays, updateDisplayPosition, toggleDisplayEnabled } = useDisplayStore();
+  const { displays, updateDisplayPosition, arrangeDisplays, toggleDisplayEnabled } = useDisplayStore();
 
   // Count enabled displays
   const enabledCount = React.useMemo(() => {
@@ -51,12 +52,21 @@ export const DisplayCanvas: React.FC = () => {
             </p>
           </div>
         </div>
-        <button
-          onClick={() => window.location.reload()}
-          className="px-4 py-2 bg-gray-100 hover:bg-gray-200 text-gray-700 rounded-lg transition-colors text-sm font-medium flex items-center gap-2"
-        >
-          <span>🔄</span> 새로고침
-        </button>
+        <div className="flex gap-2">
+          <button
+            onClick={() => invoke('identify_displays')}
+            className="px-4 py-2 bg-gray-100 hover:bg-gray-200 text-gray-700 rounded-lg transition-colors text-sm font-medium flex items-center gap-2"
+            title="각 디스플레이에 번호 표시"
+          >
+            <span>🔢</span> 식별
+          </button>
+          <button
+            onClick={() => window.location.reload()}
+            className="px-4 py-2 bg-gray-100 hover:bg-gray-200 text-gray-700 rounded-lg transition-colors text-sm font-medium flex items-center gap-2"
+          >
+            <span>🔄</span> 새로고침
+          </button>
+        </div>
       </div>
 
       <div className="flex justify-center">
@@ -70,7 +80,7 @@ export const DisplayCanvas: React.FC = () => {
             minHeight: '400px',
           }}
         >
-          {displays.map((display) => (
+          {displays.map((display, index) => (
             <DisplayCard
               key={display.id}
               display={{
@@ -88,8 +98,10 @@ export const DisplayCanvas: React.FC = () => {
                 ];
                 updateDisplayPosition(display.id, actualOrigin);
               }}
+              onDragEnd={() => arrangeDisplays(display.id)}
               onToggleEnabled={toggleDisplayEnabled}
               enabledCount={enabledCount}
+              number={index + 1}
             />
           ))}
         </div>
@@ -13,10 +13,14 @@ interface DisplayCardProps {
   scale?: number;
   /** Callback when display position changes via drag */
   onPositionChange?: (origin: [number, number]) => void;
+  /** Callback when a drag ends, e.g. to snap the layout */
+  onDragEnd?: () => void;
   /** Callback when display enabled state changes */
   onToggleEnabled?: (id: string, enabled: boolean) => void;
   /** Total number of currently enabled displays */
   enabledCount?: number;
+  /** Position in the display list, matching the "identify displays" overlay */
+  number?: number;
 }
 
 /**
@@ -46,8 +50,10 @@ export const DisplayCard: React.FC<DisplayCardProps> = ({
   display,
   scale = 0.1,
   onPositionChange,
+  onDragEnd,
   onToggleEnabled,
   enabledCount = 1,
+  number,
 }) => {
   const [width, height] = display.resolution.split('x').map(Number);
   const [isDragging, setIsDragging] = React.useState(false);
@@ -93,6 +99,7 @@ export const DisplayCard: React.FC<DisplayCardProps> = ({
 
     const handleMouseUp = () => {
       setIsDragging(false);
+      onDragEnd?.();
     };
 
     document.addEventListener('mousemove', handleMouseMove);
@@ -102,7 +109,7 @@ export const DisplayCard: React.FC<DisplayCardProps> = ({
       document.removeEventListener('mousemove', handleMouseMove);
       document.removeEventListener('mouseup', handleMouseUp);
     };
-  }, [isDragging, dragOffset, scale, onPositionChange]);
+  }, [isDragging, dragOffset, scale, onPositionChange, onDragEnd]);
 
   /**
    * Handle toggle button click for turning display ON/OFF.
@@ -229,7 +236,10 @@ export const DisplayCard: React.FC<DisplayCardProps> = ({
         {/* Display Info */}
         <div className="flex-1 flex items-center justify-center text-center p-3 pt-10">
           <div>
-            <div className="font-bold text-xl text-gray-900 mb-1">Display {display.id}</div>
+            <div className="font-bold text-xl text-gray-900 mb-1">
+              {number !== undefined && <span className="mr-1 text-blue-600">#{number}</span>}
+              Display {display.id}
+            </div>
             <div className="text-sm text-gray-700 font-semibold bg-white bg-opacity-50 px-3 py-1 rounded-full mb-2">
               {display.resolution}
             </div>
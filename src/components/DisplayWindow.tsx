@@ -0,0 +1,80 @@
+import React from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { useDisplayStore } from '../store/useDisplayStore';
+import type { Display } from '../types/display';
+
+/**
+ * DisplayWindow component - Controls for the display a secondary window sits on
+ *
+ * Rendered in the windows opened on each secondary display
+ * (`?view=display&display=<ID>`). Asks the backend which display the window
+ * is on, and asks again when the window is moved or displays change, so the
+ * controls always apply to the screen the user is looking at.
+ *
+ * @component
+ */
+export const DisplayWindow: React.FC = () => {
+  const { displays, loading, fetchDisplays, toggleDisplayEnabled } = useDisplayStore();
+  const [display, setDisplay] = React.useState<Display | null>(null);
+
+  const locate = React.useCallback(async () => {
+    const found = await invoke<Display | null>('get_window_display', {
+      windowLabel: getCurrentWindow().label,
+    });
+    setDisplay(found);
+  }, []);
+
+  React.useEffect(() => {
+    fetchDisplays();
+    locate();
+
+    const unlistenChanged = listen('displays-changed', () => {
+      fetchDisplays();
+      locate();
+    });
+    const unlistenMoved = getCurrentWindow().onMoved(() => locate());
+
+    return () => {
+      unlistenChanged.then(fn => fn());
+      unlistenMoved.then(fn => fn());
+    };
+  }, [fetchDisplays, locate]);
+
+  if (!display) {
+    return <div className="p-6 text-sm text-gray-500">이 창이 있는 디스플레이를 찾을 수 없습니다</div>;
+  }
+
+  // The last enabled display can't be turned off
+  const locked = displays.filter((d) => d.enabled).length <= 1;
+
+  return (
+    <div className="min-h-screen bg-gradient-to-br from-blue-50 via-indigo-50 to-purple-50 p-6">
+      <h1 className="mb-1 text-2xl font-bold text-gray-800">{display.name ?? display.id}</h1>
+      <p className="mb-6 text-sm text-gray-500">{display.persistent_id ?? display.id}</p>
+
+      <dl className="mb-6 grid grid-cols-2 gap-2 text-sm">
+        <dt className="font-semibold text-gray-600">해상도</dt>
+        <dd>
+          {display.resolution}
+          {display.hz ? ` @ ${display.hz}Hz` : ''}
+        </dd>
+        <dt className="font-semibold text-gray-600">위치</dt>
+        <dd>
+          ({display.origin[0]}, {display.origin[1]})
+        </dd>
+        <dt className="font-semibold text-gray-600">회전</dt>
+        <dd>{display.rotation}°</dd>
+      </dl>
+
+      <button
+        onClick={() => toggleDisplayEnabled(display.id, false)}
+        disabled={loading || locked}
+        className="rounded-lg bg-red-50 px-4 py-2 text-sm font-semibold text-red-700 hover:bg-red-100 disabled:opacity-50"
+      >
+        이 디스플레이 끄기
+      </button>
+    </div>
+  );
+};
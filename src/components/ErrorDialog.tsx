@@ -43,6 +43,7 @@ const getErrorDetails = (errorType: ErrorType, errorMessage: string) => {
         hints: [
           'Homebrew를 사용하여 설치: brew install displayplacer',
           '또는 직접 다운로드: https://github.com/jakehilborn/displayplacer',
+          '다른 위치에 설치했다면 설정에서 displayplacer 경로를 지정하세요',
           '설치 후 앱을 재시작하세요',
         ],
         canRetry: false,
@@ -133,6 +134,10 @@ const getErrorDetails = (errorType: ErrorType, errorMessage: string) => {
 const determineErrorType = (errorMessage: string): ErrorType => {
   const message = errorMessage.toLowerCase();
 
+  // The backend prefixes this error with its code
+  if (message.startsWith(ErrorType.DISPLAYPLACER_NOT_FOUND)) {
+    return ErrorType.DISPLAYPLACER_NOT_FOUND;
+  }
   if (message.includes('displayplacer') && (message.includes('not found') || message.includes('command not found'))) {
     return ErrorType.DISPLAYPLACER_NOT_FOUND;
   }
@@ -25,7 +25,7 @@ interface HotkeyBinding {
  * @component
  */
 export const HotkeyManager: React.FC = () => {
-  const { presets, applyConfig } = useDisplayStore();
+  const { presets, fetchDisplays } = useDisplayStore();
   const [hotkeys, setHotkeys] = useState<Map<string, string>>(new Map());
   const [editingPreset, setEditingPreset] = useState<string | null>(null);
   const [newShortcut, setNewShortcut] = useState('');
@@ -37,10 +37,10 @@ export const HotkeyManager: React.FC = () => {
   useEffect(() => {
     loadHotkeys();
 
-    // Listen for hotkey activation events
-    const unlistenHotkey = listen('apply-preset-hotkey', (event) => {
-      const presetId = event.payload as string;
-      handlePresetActivation(presetId);
+    // Presets are applied by the backend; just report it
+    const unlistenApplied = listen('preset-applied', (event) => {
+      showToastMessage(`✅ 프리셋 "${event.payload as string}" 적용됨`);
+      fetchDisplays();
     });
 
     // Listen for hotkey registration confirmations
@@ -50,7 +50,7 @@ export const HotkeyManager: React.FC = () => {
     });
 
     return () => {
-      unlistenHotkey.then(fn => fn());
+      unlistenApplied.then(fn => fn());
       unlistenRegistered.then(fn => fn());
     };
   }, []);
@@ -71,17 +71,6 @@ export const HotkeyManager: React.FC = () => {
     }
   };
 
-  /**
-   * Handle preset activation via hotkey.
-   */
-  const handlePresetActivation = (presetId: string) => {
-    const preset = presets.find(p => p.id === presetId);
-    if (preset) {
-      applyConfig(preset.config);
-      showToastMessage(`✅ 프리셋 "${preset.name}" 적용됨`);
-    }
-  };
-
   /**
    * Show toast notification.
    */
@@ -0,0 +1,22 @@
+import React from 'react';
+
+/**
+ * IdentifyOverlay component - Big display number shown on each screen
+ *
+ * Rendered in the borderless windows opened by `identify_displays`
+ * (`?view=identify&number=<N>&name=<name>`). The number matches the
+ * `#N` badges in the layout canvas; the backend closes the window after a
+ * few seconds.
+ *
+ * @component
+ */
+export const IdentifyOverlay: React.FC = () => {
+  const params = new URLSearchParams(window.location.search);
+
+  return (
+    <div className="flex h-screen flex-col items-center justify-center bg-gradient-to-br from-blue-600 to-indigo-600 text-white select-none">
+      <div className="text-8xl font-bold leading-none">{params.get('number')}</div>
+      <div className="mt-3 max-w-full truncate px-4 text-lg font-medium">{params.get('name')}</div>
+    </div>
+  );
+};
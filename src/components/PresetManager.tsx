@@ -1,13 +1,14 @@
 import React from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { useDisplayStore } from '../store/useDisplayStore';
-import type { Preset } from '../types/display';
+import type { Preset, PresetValidation } from '../types/display';
 
 export const PresetManager: React.FC = () => {
   const {
     presets,
     displays,
-    applyConfig,
-    addPreset,
+    applyPreset,
+    savePresetFromCurrent,
     deletePreset,
     fetchDisplays,
   } = useDisplayStore();
@@ -15,9 +16,23 @@ export const PresetManager: React.FC = () => {
   const [isAddingPreset, setIsAddingPreset] = React.useState(false);
   const [presetName, setPresetName] = React.useState('');
   const [presetHotkey, setPresetHotkey] = React.useState('');
+  const [missingDisplays, setMissingDisplays] = React.useState<Record<string, string[]>>({});
+
+  // Re-check which presets can be applied whenever the displays change
+  React.useEffect(() => {
+    Promise.all(
+      presets.map((p) => invoke<PresetValidation>('validate_preset', { presetId: p.id }).catch(() => null))
+    ).then((results) => {
+      const missing: Record<string, string[]> = {};
+      results.forEach((r) => {
+        if (r && !r.applicable) missing[r.preset_id] = r.missing_displays;
+      });
+      setMissingDisplays(missing);
+    });
+  }, [presets, displays]);
 
   const handleApplyPreset = async (preset: Preset) => {
-    await applyConfig(preset.config);
+    await applyPreset(preset.id, 'preset');
   };
 
   const handleSaveCurrentLayout = async () => {
@@ -26,9 +41,8 @@ export const PresetManager: React.FC = () => {
       return;
     }
 
-    // Generate displayplacer command from current layout
-    const config = generateDisplayplacerCommand(displays);
-    await addPreset(presetName, config, presetHotkey || undefined);
+    // The backend reads the current layout and builds the command
+    await savePresetFromCurrent(presetName, presetHotkey || undefined);
 
     setPresetName('');
     setPresetHotkey('');
@@ -39,19 +53,6 @@ export const PresetManager: React.FC = () => {
     await fetchDisplays();
   };
 
-  const generateDisplayplacerCommand = (displays: Array<{
-    id: string;
-    resolution: string;
-    origin: [number, number];
-    rotation: number;
-  }>) => {
-    const configs = displays.map((d) => {
-      return `"id:${d.id} res:${d.resolution} origin:(${d.origin[0]},${d.origin[1]}) degree:${d.rotation}"`;
-    });
-
-    return `displayplacer ${configs.join(' ')}`;
-  };
-
   return (
     <div className="bg-white rounded-xl shadow-lg p-6 border border-gray-100">
       <div className="flex items-center justify-between mb-6">
@@ -87,6 +88,11 @@ export const PresetManager: React.FC = () => {
             >
               <div className="flex-1">
                 <div className="font-semibold text-gray-900 text-lg">{preset.name}</div>
+                {missingDisplays[preset.id] && (
+                  <div className="text-xs text-amber-600 mt-1">
+                    ⚠️ 연결되지 않은 디스플레이: {missingDisplays[preset.id].join(', ')}
+                  </div>
+                )}
                 {preset.hotkey && (
                   <div className="flex items-center gap-2 mt-1">
                     <span className="text-xs bg-white px-2 py-1 rounded border border-gray-300 text-gray-600 font-mono">
@@ -98,7 +104,8 @@ export const PresetManager: React.FC = () => {
               <div className="flex gap-2">
                 <button
                   onClick={() => handleApplyPreset(preset)}
-                  className="px-5 py-2.5 bg-gradient-to-r from-blue-600 to-indigo-600 hover:from-blue-700 hover:to-indigo-700 text-white rounded-lg transition-all duration-200 font-medium shadow-md hover:shadow-lg active:scale-95"
+                  disabled={!!missingDisplays[preset.id]}
+                  className="disabled:opacity-40 disabled:cursor-not-allowed px-5 py-2.5 bg-gradient-to-r from-blue-600 to-indigo-600 hover:from-blue-700 hover:to-indigo-700 text-white rounded-lg transition-all duration-200 font-medium shadow-md hover:shadow-lg active:scale-95"
                 >
                   적용
                 </button>
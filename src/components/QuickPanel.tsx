@@ -0,0 +1,88 @@
+import React from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { useDisplayStore } from '../store/useDisplayStore';
+
+/**
+ * QuickPanel component - Compact panel opened from the tray icon
+ *
+ * Rendered in its own small window (`?view=quick-panel`) anchored below the
+ * tray icon. Offers one-click preset buttons and display on/off toggles.
+ * The backend hides the window when it loses focus and emits
+ * `quick-panel-shown` each time it is shown again, so the lists are refreshed.
+ *
+ * @component
+ */
+export const QuickPanel: React.FC = () => {
+  const { displays, presets, loading, error, fetchDisplays, fetchPresets, applyPreset, toggleDisplayEnabled } =
+    useDisplayStore();
+
+  React.useEffect(() => {
+    fetchDisplays();
+    fetchPresets();
+
+    const unlistenShown = listen('quick-panel-shown', () => {
+      fetchDisplays();
+      fetchPresets();
+    });
+
+    return () => {
+      unlistenShown.then(fn => fn());
+    };
+  }, [fetchDisplays, fetchPresets]);
+
+  const enabledCount = displays.filter((d) => d.enabled).length;
+
+  return (
+    <div className="h-screen overflow-y-auto bg-white/95 p-3 text-sm select-none">
+      <section className="mb-3">
+        <h2 className="mb-2 text-xs font-semibold uppercase tracking-wide text-gray-500">프리셋</h2>
+        {presets.length === 0 ? (
+          <p className="text-gray-400">저장된 프리셋이 없습니다</p>
+        ) : (
+          <div className="grid grid-cols-2 gap-2">
+            {presets.map((preset) => (
+              <button
+                key={preset.id}
+                onClick={() => applyPreset(preset.id, 'tray')}
+                disabled={loading}
+                className="truncate rounded-lg bg-blue-50 px-3 py-2 text-left font-medium text-blue-800 hover:bg-blue-100 disabled:opacity-50"
+                title={preset.hotkey ? `${preset.name} (${preset.hotkey})` : preset.name}
+              >
+                {preset.name}
+              </button>
+            ))}
+          </div>
+        )}
+      </section>
+
+      <section>
+        <h2 className="mb-2 text-xs font-semibold uppercase tracking-wide text-gray-500">디스플레이</h2>
+        <ul className="space-y-1">
+          {displays.map((display) => {
+            // The last enabled display can't be turned off
+            const locked = display.enabled && enabledCount <= 1;
+            return (
+              <li key={display.id} className="flex items-center justify-between rounded-lg px-2 py-1 hover:bg-gray-50">
+                <span className="truncate">
+                  {display.name ?? display.id}
+                  <span className="ml-2 text-gray-400">{display.resolution}</span>
+                </span>
+                <button
+                  onClick={() => toggleDisplayEnabled(display.id, !display.enabled)}
+                  disabled={loading || locked}
+                  className={`rounded-full px-3 py-0.5 text-xs font-semibold disabled:opacity-50 ${
+                    display.enabled ? 'bg-green-100 text-green-800' : 'bg-gray-200 text-gray-600'
+                  }`}
+                >
+                  {display.enabled ? 'ON' : 'OFF'}
+                </button>
+              </li>
+            );
+          })}
+        </ul>
+      </section>
+
+      {error && <p className="mt-3 text-xs text-red-600">{error}</p>}
+    </div>
+  );
+};
@@ -0,0 +1,162 @@
+import React, { useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { SetupReport, SetupStep } from '../types/display';
+
+/**
+ * Props for the SetupAssistant component.
+ */
+interface SetupAssistantProps {
+  /** Controls dialog visibility */
+  isOpen: boolean;
+  /** Callback when dialog is closed */
+  onClose: () => void;
+}
+
+/** Lines of `brew install` output kept on screen */
+const MAX_INSTALL_LINES = 6;
+
+const STEP_LABELS: Record<SetupStep, string> = {
+  displayplacer: 'displayplacer 설치',
+  permissions: '손쉬운 사용 권한',
+  launch_at_login: '로그인 시 실행',
+  hotkeys: '단축키 등록',
+};
+
+/**
+ * SetupAssistant component - Re-runs onboarding checks
+ *
+ * Shows the result of `run_setup_checks` and highlights the first failing step.
+ * A missing displayplacer can be installed through Homebrew, with brew's
+ * output streamed below the step. Opened from the "Setup Assistant…" tray item.
+ *
+ * @component
+ * @example
+ * ```tsx
+ * <SetupAssistant isOpen={showSetup} onClose={() => setShowSetup(false)} />
+ * ```
+ */
+export const SetupAssistant: React.FC<SetupAssistantProps> = ({ isOpen, onClose }) => {
+  const [report, setReport] = React.useState<SetupReport | null>(null);
+  const [checking, setChecking] = React.useState(false);
+  const [installing, setInstalling] = React.useState(false);
+  const [installLog, setInstallLog] = React.useState<string[]>([]);
+  const [installError, setInstallError] = React.useState<string | null>(null);
+
+  const runChecks = React.useCallback(async () => {
+    setChecking(true);
+    try {
+      setReport(await invoke<SetupReport>('run_setup_checks'));
+    } finally {
+      setChecking(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    if (isOpen) {
+      runChecks();
+    }
+  }, [isOpen, runChecks]);
+
+  useEffect(() => {
+    const unlisten = listen<{ line: string; stderr: boolean }>('displayplacer-install-progress', (event) => {
+      setInstallLog((lines) => [...lines, event.payload.line].slice(-MAX_INSTALL_LINES));
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const installDisplayplacer = async () => {
+    setInstalling(true);
+    setInstallLog([]);
+    setInstallError(null);
+    try {
+      await invoke<string>('install_displayplacer');
+    } catch (error) {
+      setInstallError(String(error));
+    } finally {
+      setInstalling(false);
+      runChecks();
+    }
+  };
+
+  useEffect(() => {
+    const handleEscape = (e: KeyboardEvent) => {
+      if (e.key === 'Escape' && isOpen) {
+        onClose();
+      }
+    };
+
+    document.addEventListener('keydown', handleEscape);
+    return () => document.removeEventListener('keydown', handleEscape);
+  }, [isOpen, onClose]);
+
+  if (!isOpen) return null;
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center bg-black bg-opacity-50 backdrop-blur-sm animate-fadeIn">
+      <div className="bg-white rounded-xl shadow-2xl p-6 max-w-md w-full mx-4 animate-scaleIn">
+        <div className="mb-4 flex items-center gap-3">
+          <div className="w-12 h-12 bg-gradient-to-br from-blue-500 to-indigo-600 rounded-full flex items-center justify-center shadow-lg">
+            <span className="text-2xl">🧭</span>
+          </div>
+          <h3 className="text-xl font-bold text-gray-900">설정 도우미</h3>
+        </div>
+
+        <div className="space-y-3 mb-6">
+          {report?.checks.map((check) => (
+            <div
+              key={check.step}
+              className={`p-3 rounded-lg border-l-4 ${
+                check.ok
+                  ? 'bg-green-50 border-green-400'
+                  : check.step === report.first_failing
+                    ? 'bg-red-50 border-red-500 shadow-md'
+                    : 'bg-yellow-50 border-yellow-400'
+              }`}
+            >
+              <div className="font-semibold text-gray-900">
+                {check.ok ? '✅' : '⚠️'} {STEP_LABELS[check.step]}
+              </div>
+              <p className="text-sm text-gray-600 mt-1">{check.detail}</p>
+              {check.step === 'displayplacer' && !check.ok && (
+                <div className="mt-2">
+                  <button
+                    onClick={installDisplayplacer}
+                    disabled={installing}
+                    className="px-3 py-1.5 text-sm bg-blue-600 text-white rounded-lg hover:bg-blue-700 disabled:opacity-50"
+                  >
+                    {installing ? '설치 중...' : 'Homebrew로 설치'}
+                  </button>
+                  {installLog.length > 0 && (
+                    <pre className="mt-2 max-h-32 overflow-y-auto rounded bg-gray-900 p-2 text-xs text-gray-100 whitespace-pre-wrap">
+                      {installLog.join('\n')}
+                    </pre>
+                  )}
+                  {installError && <p className="mt-2 text-xs text-red-600">{installError}</p>}
+                </div>
+              )}
+            </div>
+          ))}
+          {checking && <p className="text-sm text-gray-500">확인 중...</p>}
+        </div>
+
+        <div className="flex gap-3">
+          <button
+            onClick={runChecks}
+            className="flex-1 px-4 py-2.5 bg-gray-100 text-gray-800 rounded-lg hover:bg-gray-200 transition-all duration-200 font-medium shadow-sm hover:shadow active:scale-95"
+          >
+            다시 확인
+          </button>
+          <button
+            onClick={onClose}
+            className="flex-1 px-4 py-2.5 bg-gradient-to-r from-blue-600 to-indigo-600 text-white rounded-lg hover:from-blue-700 hover:to-indigo-700 transition-all duration-200 font-medium shadow-md hover:shadow-lg active:scale-95"
+          >
+            닫기
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+};
@@ -1,10 +1,23 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import { QuickPanel } from "./components/QuickPanel";
+import { DisplayWindow } from "./components/DisplayWindow";
+import { IdentifyOverlay } from "./components/IdentifyOverlay";
 import "./index.css";
 
+const view = new URLSearchParams(window.location.search).get("view");
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {view === "quick-panel" ? (
+      <QuickPanel />
+    ) : view === "display" ? (
+      <DisplayWindow />
+    ) : view === "identify" ? (
+      <IdentifyOverlay />
+    ) : (
+      <App />
+    )}
   </React.StrictMode>,
 );
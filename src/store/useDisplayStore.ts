@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { Display, DisplayConfig, Preset, PresetStore } from '../types/display';
+import type { Display, DisplayConfig, HistorySource, Preset, PresetStore } from '../types/display';
 
 /**
  * Zustand store interface for display management.
@@ -21,14 +21,20 @@ interface DisplayState {
   // Actions
   /** Fetch current display configuration from displayplacer */
   fetchDisplays: () => Promise<void>;
-  /** Apply a displayplacer configuration string */
-  applyConfig: (config: string) => Promise<void>;
+  /** Apply a displayplacer configuration string, recording what triggered it */
+  applyConfig: (config: string, source?: HistorySource) => Promise<void>;
+  /** Apply a saved preset in the backend, running its hooks */
+  applyPreset: (id: string, source?: HistorySource) => Promise<void>;
   /** Toggle a display's enabled/disabled state */
   toggleDisplayEnabled: (id: string, enabled: boolean) => Promise<void>;
   /** Load saved presets from storage */
   fetchPresets: () => Promise<void>;
   /** Add a new preset configuration */
   addPreset: (name: string, config: string, hotkey?: string) => Promise<void>;
+  /** Save the current display layout as a new preset */
+  savePresetFromCurrent: (name: string, hotkey?: string) => Promise<void>;
+  /** Create a preset from a pasted displayplacer command */
+  importPresetFromCommand: (raw: string, name?: string) => Promise<void>;
   /** Delete a preset by ID */
   deletePreset: (id: string) => Promise<void>;
   /** Update an existing preset */
@@ -37,6 +43,8 @@ interface DisplayState {
   selectPreset: (preset: Preset | null) => void;
   /** Update display position locally (for drag operations) */
   updateDisplayPosition: (id: string, origin: [number, number]) => void;
+  /** Snap the layout after a drag and remove overlaps and gaps */
+  arrangeDisplays: (movedId: string) => Promise<void>;
 }
 
 /**
@@ -90,10 +98,10 @@ export const useDisplayStore = create<DisplayState>((set, get) => ({
     }
   },
 
-  applyConfig: async (config: string) => {
+  applyConfig: async (config: string, source?: HistorySource) => {
     set({ loading: true, error: null });
     try {
-      await invoke('apply_config', { config });
+      await invoke('apply_config', { config, source });
       // Refresh displays after applying config
       await get().fetchDisplays();
       set({ loading: false });
@@ -102,6 +110,17 @@ export const useDisplayStore = create<DisplayState>((set, get) => ({
     }
   },
 
+  applyPreset: async (id: string, source?: HistorySource) => {
+    set({ loading: true, error: null });
+    try {
+      await invoke('apply_preset', { presetId: id, source });
+      await get().fetchDisplays();
+      set({ loading: false });
+    } catch (error) {
+      set({ error: String(error), loading: false });
+    }
+  },
+
   toggleDisplayEnabled: async (id: string, enabled: boolean) => {
     set({ loading: true, error: null });
     try {
@@ -144,6 +163,35 @@ export const useDisplayStore = create<DisplayState>((set, get) => ({
     }
   },
 
+  savePresetFromCurrent: async (name: string, hotkey?: string) => {
+    set({ loading: true, error: null });
+    try {
+      const preset = await invoke<Preset>('create_preset_from_current', { name });
+      if (hotkey) {
+        await invoke('update_preset', { id: preset.id, hotkey });
+      }
+      await get().fetchPresets();
+      // Update system tray menu
+      await invoke('update_tray_presets');
+      set({ loading: false });
+    } catch (error) {
+      set({ error: String(error), loading: false });
+    }
+  },
+
+  importPresetFromCommand: async (raw: string, name?: string) => {
+    set({ loading: true, error: null });
+    try {
+      await invoke<Preset>('import_from_command', { raw, name });
+      await get().fetchPresets();
+      // Update system tray menu
+      await invoke('update_tray_presets');
+      set({ loading: false });
+    } catch (error) {
+      set({ error: String(error), loading: false });
+    }
+  },
+
   deletePreset: async (id: string) => {
     set({ loading: true, error: null });
     try {
@@ -179,4 +227,16 @@ export const useDisplayStore = create<DisplayState>((set, get) => ({
       ),
     }));
   },
+
+  arrangeDisplays: async (movedId: string) => {
+    try {
+      const displays = await invoke<Display[]>('arrange_layout', {
+        displays: get().displays,
+        movedId,
+      });
+      set({ displays });
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
 }));
@@ -1,25 +1,242 @@
 export interface Display {
   id: string;
+  persistent_id?: string;
+  contextual_id?: string;
+  serial_id?: string;
   resolution: string;
+  hz?: number;
+  color_depth?: number;
+  scaling?: boolean;
   origin: [number, number];
   rotation: number;
   enabled: boolean;
+  name?: string;
+  vendor?: string;
+  builtin?: boolean;
+  virtual_display?: boolean;
+}
+
+export interface MirrorGroup {
+  primary: string;
+  mirrors: string[];
 }
 
 export interface DisplayConfig {
   displays: Display[];
+  mirror_groups?: MirrorGroup[];
+  lid_closed?: boolean | null;
+  builtin_active?: boolean;
   raw_command: string;
 }
 
+export type InputSource = 'hdmi1' | 'hdmi2' | 'dp1' | 'dp2' | 'usbc';
+
 export interface Preset {
   id: string;
   name: string;
   config: string;
   hotkey?: string;
   created_at: string;
+  description?: string;
+  pre_hook?: string;
+  post_hook?: string;
+  tags?: string[];
+  folder?: string;
+  sort_order?: number;
+  last_applied_at?: string;
+  apply_count?: number;
+  brightness?: Record<string, number>;
+  volume?: Record<string, number>;
+  input_sources?: Record<string, InputSource>;
+  night_shift?: boolean;
+  keep_awake?: boolean;
+}
+
+export interface PresetStats {
+  preset_id: string;
+  name: string;
+  apply_count: number;
+  last_applied_at?: string;
 }
 
 export interface PresetStore {
   version: string;
   presets: Preset[];
 }
+
+export type SetupStep = 'displayplacer' | 'permissions' | 'launch_at_login' | 'hotkeys';
+
+export interface SetupCheck {
+  step: SetupStep;
+  ok: boolean;
+  detail: string;
+}
+
+export interface SetupReport {
+  checks: SetupCheck[];
+  first_failing: SetupStep | null;
+}
+
+export type HistorySource = 'preset' | 'hotkey' | 'tray' | 'manual' | 'automation' | 'external' | 'remote';
+
+export interface HistoryEntry {
+  id: string;
+  timestamp: string;
+  source: HistorySource;
+  config: string;
+  success: boolean;
+  error?: string | null;
+}
+
+export interface DockSettings {
+  autohide?: boolean | null;
+  orientation?: 'left' | 'bottom' | 'right' | null;
+  tile_size?: number | null;
+}
+
+export interface Workspace {
+  id: string;
+  name: string;
+  preset_id?: string | null;
+  audio_device?: string | null;
+  dock?: DockSettings | null;
+  window_layout?: string | null;
+  hotkey?: string | null;
+  created_at: string;
+}
+
+export type WorkspaceComponent = 'displays' | 'audio' | 'dock' | 'windows';
+
+export interface WorkspaceReport {
+  workspace_id: string;
+  results: { component: WorkspaceComponent; ok: boolean; message: string }[];
+}
+
+export type Comparison = 'eq' | 'ne' | 'lt' | 'le' | 'gt' | 'ge';
+
+export type RuleCondition =
+  | { type: 'display_count'; op: Comparison; count: number }
+  | { type: 'display_connected'; display: string }
+  | { type: 'power_source'; ac: boolean }
+  | { type: 'lid'; closed: boolean }
+  | { type: 'frontmost_app'; app: string };
+
+export type RuleAction =
+  | { type: 'apply_preset'; preset_id: string }
+  | { type: 'apply_workspace'; workspace_id: string }
+  | { type: 'disable_external_displays' }
+  | { type: 'set_refresh_rate'; display_id: string; hz: number };
+
+export interface Rule {
+  id: string;
+  name: string;
+  enabled: boolean;
+  conditions: RuleCondition[];
+  action: RuleAction;
+  created_at: string;
+}
+
+export interface RuleStore {
+  version: string;
+  rules: Rule[];
+}
+
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
+export interface Schedule {
+  id: string;
+  preset_id: string;
+  /** Local time, "HH:MM" */
+  time: string;
+  /** Every day if empty */
+  days: Weekday[];
+  enabled: boolean;
+  created_at: string;
+}
+
+export interface ScheduleStore {
+  version: string;
+  schedules: Schedule[];
+}
+
+export interface FrontmostApp {
+  bundle_id?: string | null;
+  name?: string | null;
+}
+
+export interface DockDevice {
+  bus: 'usb' | 'thunderbolt';
+  name?: string | null;
+  vendor_id?: number | null;
+  product_id?: number | null;
+}
+
+/** Every given field has to match a connected device */
+export interface DockBinding {
+  name: string;
+  vendor_id?: number | null;
+  product_id?: number | null;
+  device_name?: string | null;
+  preset_id: string;
+  undock_preset_id?: string | null;
+}
+
+export interface PairedDevice {
+  id: string;
+  name: string;
+  paired_at: string;
+}
+
+export interface DiscoveredHost {
+  name: string;
+  host: string;
+  port: number;
+}
+
+export interface RemotePreset {
+  id: string;
+  name: string;
+}
+
+export type DisplayChange =
+  | { kind: 'missing' }
+  | { kind: 'not_in_config' }
+  | { kind: 'resolution_changed'; from: string; to: string }
+  | { kind: 'refresh_rate_changed'; from: number | null; to: number }
+  | { kind: 'origin_changed'; from: [number, number]; to: [number, number] }
+  | { kind: 'rotation_changed'; from: number; to: number }
+  | { kind: 'enabled_changed'; from: boolean; to: boolean }
+  | { kind: 'mirroring_changed'; from: string[]; to: string[] };
+
+export interface DisplayDifference {
+  display_id: string;
+  name?: string | null;
+  change: DisplayChange;
+}
+
+/** Result of `apply_config`; in each mismatch `from` is the actual value and `to` the requested one */
+export interface ApplyVerification {
+  mismatches: DisplayDifference[];
+}
+
+export type ConfigIssue = 'unparsable' | 'unknown_display' | 'unsupported_mode' | 'overlap';
+
+export interface ConfigWarning {
+  issue: ConfigIssue;
+  display_id?: string | null;
+  message: string;
+}
+
+export interface PresetValidation {
+  preset_id: string;
+  missing_displays: string[];
+  applicable: boolean;
+}
+
+export interface VirtualDisplaySpec {
+  name: string;
+  width: number;
+  height: number;
+  hidpi: boolean;
+  serial: number;
+}